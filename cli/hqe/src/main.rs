@@ -2,17 +2,29 @@
 
 use clap::{Parser, Subcommand};
 use console::style;
+use hqe_core::encrypted_db::EncryptedDb;
+use hqe_core::events::ScanEvent;
 use hqe_core::models::*;
-use hqe_core::scan::ScanPipeline;
-use hqe_openai::profile::ProfileManager;
+use hqe_core::osv::OsvClient;
+use hqe_core::persistence::{CacheFilter, CacheKind, LocalDb};
+use hqe_core::redaction::{contains_placeholder, RedactionMap, REDACTION_MAP_FILE};
+use hqe_core::scan::{ScanPipeline, ScanResult};
+use hqe_openai::profile::{
+    DefaultProfilesStore, KeychainStore, ProfileManager, ProviderProfile, ProviderProfileExt,
+};
 use hqe_openai::prompts::sanitize_for_prompt;
 use hqe_openai::provider_discovery::is_local_or_private_base_url;
-use hqe_openai::{ClientConfig, OpenAIAnalyzer, OpenAIClient};
+use hqe_openai::{
+    ClientConfig, CostLedger, OpenAIAnalyzer, OpenAIClient, PricingTable, RepoTools,
+    RoutingStrategy,
+};
 use indicatif::{ProgressBar, ProgressStyle};
 use secrecy::SecretString;
 use serde_json::json;
+use std::collections::{BTreeMap, HashSet};
 use std::path::PathBuf;
 use std::sync::Arc;
+use tokio_util::sync::CancellationToken;
 use tracing::Level;
 
 #[derive(Parser)]
@@ -46,6 +58,10 @@ enum Commands {
         /// Disable local semantic caching
         #[arg(long)]
         no_cache: bool,
+
+        /// Fail when a template placeholder has no value instead of sending it literally
+        #[arg(long)]
+        strict: bool,
     },
 
     /// Scan a repository
@@ -82,9 +98,133 @@ enum Commands {
         #[arg(long, value_name = "BOOL")]
         parallel_tool_calls: Option<bool>,
 
+        /// Let the model read files and list directories of the repository while analyzing
+        #[arg(long)]
+        repo_tools: bool,
+
+        /// Maximum number of files analyzed by the LLM at once
+        #[arg(long, default_value = "4")]
+        concurrency: usize,
+
+        /// TOML or JSON file with custom secret detection rules
+        #[arg(long, value_name = "PATH")]
+        secret_rules: Option<PathBuf>,
+
+        /// Fail the scan if any secret is still detected after redaction
+        #[arg(long)]
+        strict_redaction: bool,
+
+        /// Also replace emails, IP addresses, phone and card numbers before sending content to the LLM
+        #[arg(long)]
+        redact_pii: bool,
+
+        /// Run detected linters (cargo clippy, eslint, ruff) and include their findings; runs code from the repository
+        #[arg(long)]
+        run_external_tools: bool,
+
+        /// JSON advisory feed for dependency checks (defaults to the bundled list)
+        #[arg(long, value_name = "PATH")]
+        advisories: Option<PathBuf>,
+
+        /// Look up dependency versions in the OSV vulnerability database (sends package names and versions to api.osv.dev)
+        #[arg(long)]
+        osv: bool,
+
+        /// Also scan files matched by .gitignore, .git/info/exclude and .hqeignore
+        #[arg(long)]
+        no_ignore: bool,
+
+        /// Only scan files matching this glob, e.g. 'src/**' (repeatable)
+        #[arg(long, value_name = "GLOB", action = clap::ArgAction::Append)]
+        include: Vec<String>,
+
+        /// Skip files and directories matching this glob, even if included (repeatable)
+        #[arg(long, value_name = "GLOB", action = clap::ArgAction::Append)]
+        exclude: Vec<String>,
+
+        /// Stop sending LLM requests once their estimated cost reaches this many USD
+        #[arg(long, value_name = "USD")]
+        budget_usd: Option<f64>,
+
+        /// Stop sending LLM requests once they have used this many tokens
+        #[arg(long, value_name = "TOKENS")]
+        budget_tokens: Option<u64>,
+
+        /// Also write report.sarif for CI code scanning
+        #[arg(long)]
+        sarif: bool,
+
+        /// Also write a standalone report.html
+        #[arg(long)]
+        html: bool,
+
+        /// Also write the TODO backlog as backlog.csv
+        #[arg(long)]
+        backlog_csv: bool,
+
+        /// Also write findings as junit.xml for CI test dashboards
+        #[arg(long)]
+        junit: bool,
+
+        /// Also write the detected dependencies as a CycloneDX SBOM (sbom.cdx.json)
+        #[arg(long)]
+        sbom: bool,
+
+        /// Render report.md (and report.html) from this MiniJinja template
+        #[arg(long, value_name = "PATH")]
+        report_template: Option<PathBuf>,
+
+        /// Sign run-manifest.json with the artifact signing key from the OS keychain
+        #[arg(long)]
+        sign: bool,
+
+        /// Mark findings recorded in this baseline file as suppressed
+        #[arg(long, value_name = "PATH")]
+        baseline: Option<PathBuf>,
+
+        /// Accept every current finding by writing its fingerprint to this file
+        #[arg(long, value_name = "PATH")]
+        write_baseline: Option<PathBuf>,
+
+        /// Exit with code 2 when a finding is at or above this severity
+        #[arg(long, value_enum, value_name = "SEVERITY")]
+        fail_on: Option<FailOn>,
+
+        /// Only print errors
+        #[arg(short, long)]
+        quiet: bool,
+
+        /// How the scan result is printed; json and sarif print a single document to stdout
+        #[arg(long, value_enum, default_value_t = OutputFormat::Text)]
+        format: OutputFormat,
+
         /// Disable local semantic caching
         #[arg(long)]
         no_cache: bool,
+
+        /// Only re-analyze files changed since the previous scan in the output directory
+        #[arg(long)]
+        incremental: bool,
+
+        /// Only check files changed since this git ref (e.g. origin/main) and untracked files
+        #[arg(long, value_name = "REF", conflicts_with_all = ["incremental", "resume"])]
+        changed_since: Option<String>,
+
+        /// Continue an interrupted scan from its checkpoint in the output directory
+        #[arg(long, value_name = "RUN_ID", conflicts_with = "incremental")]
+        resume: Option<String>,
+
+        /// Write scan progress events to this file as NDJSON
+        #[arg(long, value_name = "PATH")]
+        events: Option<PathBuf>,
+
+        /// Provider profile to fall back to when --profile keeps failing (repeatable)
+        #[arg(long, value_name = "PROFILE", action = clap::ArgAction::Append)]
+        fallback_profile: Vec<String>,
+
+        /// How requests are spread over --profile and the fallback profiles
+        #[arg(long, value_enum, default_value_t = Routing::Failover)]
+        routing: Routing,
     },
 
     /// Export a specific run
@@ -100,6 +240,21 @@ enum Commands {
         /// Source directory to search for run (artifacts)
         #[arg(long)]
         from: Option<PathBuf>,
+
+        /// What to export
+        #[arg(long, value_enum, default_value_t = ExportFormat::Artifacts)]
+        format: ExportFormat,
+    },
+
+    /// Check that a run's artifacts match the checksums and signature in its manifest
+    Verify {
+        /// Run directory (hqe_run_<RUN_ID>)
+        #[arg(value_name = "RUN_DIR")]
+        run_dir: PathBuf,
+
+        /// Hex public key the manifest must be signed with (default: this machine's key)
+        #[arg(long, value_name = "HEX")]
+        public_key: Option<String>,
     },
 
     /// Generate or apply patches
@@ -126,8 +281,124 @@ enum Commands {
         #[command(subcommand)]
         command: ConfigCommands,
     },
+
+    /// Inspect or clear the local response cache
+    Cache {
+        #[command(subcommand)]
+        command: CacheCommands,
+    },
+
+    /// Inspect the prompt tools available to `hqe prompt`
+    Tools {
+        #[command(subcommand)]
+        command: ToolsCommands,
+    },
+}
+
+#[derive(Subcommand)]
+enum ToolsCommands {
+    /// List registered tools with their callable names
+    List {
+        /// Also call each side-effect-free tool and report failing handlers
+        #[arg(long)]
+        check: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum CacheCommands {
+    /// Show cache size and age
+    Stats,
+
+    /// Drop expired entries and evict least recently used ones over the size limits
+    Prune,
+
+    /// List cached entries (metadata only, never prompts or responses)
+    List {
+        /// Only list entries produced by this model
+        #[arg(long, value_name = "MODEL")]
+        model: Option<String>,
+    },
+
+    /// Remove cached responses and file analyses
+    Clear {
+        /// Only remove entries older than this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than_days: Option<u64>,
+
+        /// Only remove entries produced by this model
+        #[arg(long, value_name = "MODEL")]
+        model: Option<String>,
+    },
+}
+
+/// Request routing across `--profile` and `--fallback-profile`
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum Routing {
+    /// Use the first profile; move on only when it fails
+    Failover,
+    /// Rotate the starting profile per request, failing over from there
+    RoundRobin,
+}
+
+impl From<Routing> for RoutingStrategy {
+    fn from(routing: Routing) -> Self {
+        match routing {
+            Routing::Failover => RoutingStrategy::Failover,
+            Routing::RoundRobin => RoutingStrategy::RoundRobin,
+        }
+    }
+}
+
+/// Lowest finding severity that fails `hqe scan --fail-on`
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+enum FailOn {
+    Critical,
+    High,
+    Medium,
+    Low,
+    Info,
+}
+
+impl From<FailOn> for Severity {
+    fn from(fail_on: FailOn) -> Self {
+        match fail_on {
+            FailOn::Critical => Severity::Critical,
+            FailOn::High => Severity::High,
+            FailOn::Medium => Severity::Medium,
+            FailOn::Low => Severity::Low,
+            FailOn::Info => Severity::Info,
+        }
+    }
 }
 
+/// Output format of the `hqe scan` summary
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum OutputFormat {
+    /// Decorated, human-readable summary
+    Text,
+    /// One JSON document on stdout, nothing else
+    Json,
+    /// The SARIF 2.1.0 log on stdout, nothing else; implies `--sarif`
+    Sarif,
+}
+
+/// What `hqe export` writes to the output directory
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum ExportFormat {
+    /// Copy the run's artifacts as they are
+    Artifacts,
+    /// backlog.csv and findings.csv, generated from the run's report.json
+    Csv,
+}
+
+/// `hqe scan` exit code when a finding reaches the `--fail-on` severity
+const EXIT_FINDINGS: i32 = 2;
+/// `hqe scan` exit code when the scan could not complete
+const EXIT_SCAN_ERROR: i32 = 3;
+
+// Parsed once per invocation, so the size of `Add` does not matter
+#[allow(clippy::large_enum_variant)]
 #[derive(Subcommand)]
 enum ConfigCommands {
     /// List configured profiles
@@ -138,17 +409,21 @@ enum ConfigCommands {
         /// Profile name
         name: String,
 
-        /// Base URL
-        #[arg(short, long)]
-        url: String,
+        /// Base URL (defaults to the spec's URL with --from-spec)
+        #[arg(short, long, required_unless_present = "from_spec")]
+        url: Option<String>,
 
         /// API key (will be stored in keychain)
         #[arg(short, long)]
         key: Option<String>,
 
-        /// Default model
-        #[arg(short, long, default_value = "gpt-4o-mini")]
-        model: String,
+        /// Default model (defaults to the spec's model, or gpt-4o-mini)
+        #[arg(short, long)]
+        model: Option<String>,
+
+        /// Prefill the profile from a provider spec (openai, anthropic, venice, openrouter, xai, kimi, azure, gemini, ollama, lmstudio)
+        #[arg(long, value_name = "SPEC_ID")]
+        from_spec: Option<String>,
 
         /// Additional header (repeatable). Format: "Header-Name: value"
         #[arg(long, value_name = "HEADER", action = clap::ArgAction::Append)]
@@ -162,9 +437,17 @@ enum ConfigCommands {
         #[arg(long)]
         project: Option<String>,
 
-        /// Request timeout in seconds
-        #[arg(long, default_value_t = 60)]
-        timeout: u64,
+        /// Request timeout in seconds (defaults to the spec's recommendation, or 60)
+        #[arg(long)]
+        timeout: Option<u64>,
+
+        /// Azure OpenAI deployment name
+        #[arg(long, value_name = "NAME")]
+        azure_deployment: Option<String>,
+
+        /// Azure OpenAI api-version (used with --azure-deployment)
+        #[arg(long, value_name = "VERSION", requires = "azure_deployment")]
+        azure_api_version: Option<String>,
     },
 
     /// Test a provider connection
@@ -178,12 +461,35 @@ enum ConfigCommands {
         /// Profile name
         name: String,
     },
+
+    /// Export profiles (without API keys) as a shareable JSON bundle
+    Export {
+        /// Profiles to export (all when omitted)
+        names: Vec<String>,
+
+        /// Write the bundle to this file instead of stdout
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Import profiles from a bundle created by `hqe config export`
+    Import {
+        /// Bundle file
+        file: PathBuf,
+
+        /// Replace existing profiles with the same name
+        #[arg(long)]
+        overwrite: bool,
+    },
 }
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
     // Setup logging
-    tracing_subscriber::fmt().with_max_level(Level::INFO).init();
+    tracing_subscriber::fmt()
+        .with_max_level(Level::INFO)
+        .with_writer(std::io::stderr)
+        .init();
 
     let cli = Cli::parse();
 
@@ -195,7 +501,8 @@ async fn main() -> anyhow::Result<()> {
             profile,
 
             no_cache,
-        } => handle_prompt(name, args, profile, no_cache).await,
+            strict,
+        } => handle_prompt(name, args, profile, no_cache, strict).await,
         Commands::Scan {
             repo,
             profile,
@@ -205,30 +512,111 @@ async fn main() -> anyhow::Result<()> {
             timeout,
             venice_parameters,
             parallel_tool_calls,
+            repo_tools,
+            concurrency,
+            secret_rules,
+            strict_redaction,
+            redact_pii,
+            run_external_tools,
+            advisories,
+            osv,
+            no_ignore,
+            include,
+            exclude,
+            budget_usd,
+            budget_tokens,
+            sarif,
+            html,
+            backlog_csv,
+            junit,
+            sbom,
+            report_template,
+            sign,
+            baseline,
+            write_baseline,
             no_cache,
+            incremental,
+            changed_since,
+            resume,
+            events,
+            fallback_profile,
+            routing,
+            fail_on,
+            quiet,
+            format,
         } => {
-            let venice_params = match venice_parameters {
-                Some(raw) => Some(
-                    serde_json::from_str(&raw)
-                        .map_err(|e| anyhow::anyhow!("Invalid venice_parameters JSON: {e}"))?,
-                ),
-                None => None,
+            let scan = async {
+                let venice_params = match venice_parameters {
+                    Some(raw) => Some(
+                        serde_json::from_str(&raw)
+                            .map_err(|e| anyhow::anyhow!("Invalid venice_parameters JSON: {e}"))?,
+                    ),
+                    None => None,
+                };
+                scan_repo(ScanRepoArgs {
+                    repo,
+                    profile,
+                    local_only,
+                    out,
+                    max_files,
+                    timeout,
+                    venice_parameters: venice_params,
+                    parallel_tool_calls,
+                    repo_tools,
+                    concurrency,
+                    secret_rules,
+                    strict_redaction,
+                    redact_pii,
+                    run_external_tools,
+                    advisories,
+                    osv,
+                    no_ignore,
+                    include,
+                    exclude,
+                    budget_usd,
+                    budget_tokens,
+                    sarif,
+                    html,
+                    backlog_csv,
+                    junit,
+                    sbom,
+                    report_template,
+                    sign,
+                    baseline,
+                    write_baseline,
+                    no_cache,
+                    incremental,
+                    changed_since,
+                    resume,
+                    events,
+                    fallback_profiles: fallback_profile,
+                    routing: routing.into(),
+                    fail_on: fail_on.map(Severity::from),
+                    quiet: quiet || format != OutputFormat::Text,
+                    format,
+                })
+                .await
             };
-            scan_repo(ScanRepoArgs {
-                repo,
-                profile,
-                local_only,
-                out,
-                max_files,
-                timeout,
-                venice_parameters: venice_params,
-                parallel_tool_calls,
-
-                no_cache,
-            })
-            .await
+            // CI relies on these codes, so scan errors are not left to `main`
+            match scan.await {
+                Ok(summary) if summary.gate_failed => std::process::exit(EXIT_FINDINGS),
+                Ok(_) => Ok(()),
+                Err(e) => {
+                    eprintln!("Error: {e:?}");
+                    std::process::exit(EXIT_SCAN_ERROR);
+                }
+            }
         }
-        Commands::Export { run_id, out, from } => export_run(run_id, out, from).await,
+        Commands::Export {
+            run_id,
+            out,
+            from,
+            format,
+        } => export_run(run_id, out, from, format).await,
+        Commands::Verify {
+            run_dir,
+            public_key,
+        } => verify_run(run_dir, public_key).await,
         Commands::Patch {
             run_id,
             todo,
@@ -236,9 +624,14 @@ async fn main() -> anyhow::Result<()> {
             apply,
         } => handle_patch(run_id, todo, preview, apply).await,
         Commands::Config { command } => handle_config(command).await,
+        Commands::Tools { command } => handle_tools(command).await,
+        Commands::Cache { command } => handle_cache(command),
     }
 }
 
+/// Analyzed files between two scan checkpoint saves
+const SCAN_CHECKPOINT_INTERVAL: usize = 5;
+
 struct ScanRepoArgs {
     repo: PathBuf,
     profile: Option<String>,
@@ -248,8 +641,164 @@ struct ScanRepoArgs {
     timeout: u64,
     venice_parameters: Option<serde_json::Value>,
     parallel_tool_calls: Option<bool>,
+    repo_tools: bool,
+    concurrency: usize,
+    secret_rules: Option<PathBuf>,
+    strict_redaction: bool,
+    redact_pii: bool,
+    run_external_tools: bool,
+    advisories: Option<PathBuf>,
+    osv: bool,
+    no_ignore: bool,
+    include: Vec<String>,
+    exclude: Vec<String>,
+    budget_usd: Option<f64>,
+    budget_tokens: Option<u64>,
+    sarif: bool,
+    html: bool,
+    backlog_csv: bool,
+    junit: bool,
+    sbom: bool,
+    report_template: Option<PathBuf>,
+    sign: bool,
+    baseline: Option<PathBuf>,
+    write_baseline: Option<PathBuf>,
+    no_cache: bool,
+    incremental: bool,
+    changed_since: Option<String>,
+    resume: Option<String>,
+    events: Option<PathBuf>,
+    fallback_profiles: Vec<String>,
+    routing: RoutingStrategy,
+    fail_on: Option<Severity>,
+    /// Suppress everything but errors (also set for `--format json`)
+    quiet: bool,
+    format: OutputFormat,
+}
 
+/// Build the LLM client for a scan from a stored provider profile
+fn scan_client(
+    manager: &ProfileManager<DefaultProfilesStore, KeychainStore>,
+    profile_name: &str,
+    timeout: u64,
     no_cache: bool,
+) -> anyhow::Result<(ProviderProfile, OpenAIClient)> {
+    let (profile, api_key) = manager
+        .get_profile_with_key(profile_name)?
+        .ok_or_else(|| anyhow::anyhow!("Profile not found: {profile_name}"))?;
+    let allow_missing_key = is_local_or_private_base_url(&profile.base_url).unwrap_or(false);
+    let api_key = match api_key {
+        Some(key) => key,
+        None if allow_missing_key => SecretString::new(String::new().into_boxed_str()),
+        None => {
+            return Err(anyhow::anyhow!(
+                "No API key stored for profile {profile_name}"
+            ))
+        }
+    };
+
+    let client = OpenAIClient::new(ClientConfig {
+        base_url: profile.base_url.clone(),
+        api_key,
+        default_model: profile.default_model.clone(),
+        headers: profile.headers.clone(),
+        organization: profile.organization.clone(),
+        project: profile.project.clone(),
+        disable_system_proxy: false,
+        proxy: None,
+        timeout_seconds: timeout,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
+        max_retries: 2,
+        rate_limit_config: None,
+        cache_enabled: !no_cache,
+        daily_budget: 1.0,
+        provider_kind: profile.provider_kind,
+        auth: profile.auth_placement(),
+        azure: profile.azure.clone(),
+        unsupported_params: profile.unsupported_params.clone(),
+    })?
+    .with_cost_ledger(CostLedger::new(PricingTable::load_default()));
+    Ok((profile, client))
+}
+
+/// Drive the progress bar from scan events and optionally mirror them to an NDJSON file
+async fn report_scan_progress(
+    mut rx: tokio::sync::mpsc::Receiver<ScanEvent>,
+    pb: ProgressBar,
+    events_file: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let writer = match events_file {
+        Some(path) => {
+            let file = tokio::fs::File::create(&path).await.map_err(|e| {
+                anyhow::anyhow!("Failed to create events file {}: {e}", path.display())
+            })?;
+            let (tx, rx) = tokio::sync::mpsc::channel(64);
+            Some((tx, tokio::spawn(hqe_core::events::write_ndjson(rx, file))))
+        }
+        None => None,
+    };
+
+    // Sets, because a failed LLM pass re-queues every file for local analysis
+    let mut queued = HashSet::new();
+    let mut analyzed = HashSet::new();
+    let mut cached = 0;
+    let mut tokens = 0;
+    while let Some(event) = rx.recv().await {
+        match &event {
+            ScanEvent::PhaseStarted { phase } => pb.set_message(format!("Phase: {phase}...")),
+            ScanEvent::FileQueued { path } => {
+                if queued.is_empty() {
+                    pb.set_style(
+                        ProgressStyle::default_bar()
+                            .template("{spinner:.cyan} [{bar:30.cyan/blue}] {pos}/{len} {msg}")
+                            .unwrap()
+                            .progress_chars("=> "),
+                    );
+                }
+                queued.insert(path.clone());
+                pb.set_length(queued.len() as u64);
+            }
+            ScanEvent::FileAnalyzed {
+                path, cached: hit, ..
+            } => {
+                if analyzed.insert(path.clone()) && *hit {
+                    cached += 1;
+                }
+                pb.set_position(analyzed.len() as u64);
+                let mut usage = Vec::new();
+                if cached > 0 {
+                    usage.push(format!("{cached} cached"));
+                }
+                if tokens > 0 {
+                    usage.push(format!("{tokens} tokens"));
+                }
+                let usage = if usage.is_empty() {
+                    String::new()
+                } else {
+                    format!(" [{}]", usage.join(", "))
+                };
+                pb.set_message(format!("{path}{usage}"));
+            }
+            ScanEvent::LlmCallCompleted {
+                tokens: Some(used), ..
+            } => tokens += used,
+            ScanEvent::Warning { message } => {
+                pb.println(format!("{}", style(format!("⚠️  {message}")).yellow()))
+            }
+            ScanEvent::LlmCallCompleted { .. } | ScanEvent::PhaseCompleted { .. } => {}
+        }
+        pb.tick();
+        if let Some((tx, _)) = &writer {
+            // A failed writer is reported when its task is joined
+            let _ = tx.send(event).await;
+        }
+    }
+    if let Some((tx, task)) = writer {
+        drop(tx);
+        task.await??;
+    }
+    Ok(())
 }
 
 async fn handle_prompt(
@@ -258,6 +807,7 @@ async fn handle_prompt(
     profile_name: Option<String>,
 
     no_cache: bool,
+    strict: bool,
 ) -> anyhow::Result<()> {
     println!(
         "{}",
@@ -289,7 +839,7 @@ async fn handle_prompt(
                 is_local_or_private_base_url(&profile.base_url).unwrap_or(false);
             let api_key = match entry.get_password() {
                 Ok(key) => SecretString::new(key.into_boxed_str()),
-                Err(err) if allow_missing_key => SecretString::new(String::new().into_boxed_str()),
+                Err(_) if allow_missing_key => SecretString::new(String::new().into_boxed_str()),
                 Err(err) => return Err(err.into()),
             };
 
@@ -301,11 +851,18 @@ async fn handle_prompt(
                 organization: profile.organization.clone(),
                 project: profile.project.clone(),
                 disable_system_proxy: false,
+                proxy: None,
                 timeout_seconds: 120, // Longer timeout for detailed prompts
+                connect_timeout_secs: None,
+                read_timeout_secs: None,
                 max_retries: 1,
                 rate_limit_config: None,
                 cache_enabled: !no_cache,
                 daily_budget: 1.0,
+                provider_kind: profile.provider_kind,
+                auth: profile.auth_placement(),
+                azure: profile.azure.clone(),
+                unsupported_params: profile.unsupported_params.clone(),
             };
             Some(hqe_openai::OpenAIClient::new(config)?)
         } else {
@@ -321,27 +878,7 @@ async fn handle_prompt(
     let client = std::sync::Arc::new(client);
 
     // 2. Locate Prompts Directory
-    let mut prompts_dir = PathBuf::from("./prompts");
-    if !prompts_dir.exists() {
-        if let Some(exe_dir) = std::env::current_exe()
-            .ok()
-            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
-        {
-            let try_path = exe_dir.join("prompts");
-            if try_path.exists() {
-                prompts_dir = try_path;
-            } else if let Some(parent) = exe_dir.parent() {
-                let try_path_parent = parent.join("prompts");
-                if try_path_parent.exists() {
-                    prompts_dir = try_path_parent;
-                }
-            }
-        }
-    }
-
-    if !prompts_dir.exists() {
-        return Err(anyhow::anyhow!("Could not locate 'prompts' directory."));
-    }
+    let prompts_dir = locate_prompts_dir()?;
     println!("  Prompts Dir: {}", prompts_dir.display());
 
     // 3. Load and Register Tools
@@ -349,7 +886,7 @@ async fn handle_prompt(
     let loaded_tools = loader.load()?;
     let registry = hqe_mcp::ToolRegistry::new();
 
-    for tool in loaded_tools {
+    for tool in loaded_tools.iter().cloned() {
         let template = tool.template.clone();
         let client_clone = client.clone();
 
@@ -362,44 +899,21 @@ async fn handle_prompt(
                 let client_clone = client_clone.clone();
 
                 Box::pin(async move {
-                    let prompt_text = substitute_template(&template, &args);
+                    let prompt_text =
+                        hqe_mcp::substitute_template(&template, &args, sanitize_for_prompt);
 
                     let response = client_clone
                         .chat(hqe_openai::ChatRequest {
-                            model: client_clone.default_model().to_string(),
-                            messages: vec![hqe_openai::Message {
-                                role: hqe_openai::Role::User,
-                                content: Some(prompt_text.into()),
-                                tool_calls: None,
-                            }],
-                            frequency_penalty: None,
-                            presence_penalty: None,
-                            repetition_penalty: None,
-                            logprobs: None,
-                            top_logprobs: None,
                             temperature: Some(0.2),
-                            min_temp: None,
-                            max_temp: None,
-                            top_p: None,
-                            top_k: None,
-                            max_tokens: None,
-                            max_completion_tokens: None,
-                            n: None,
-                            stop: None,
-                            stop_token_ids: None,
-                            seed: None,
-                            user: None,
-                            prompt_cache_key: None,
-                            prompt_cache_retention: None,
-                            reasoning_effort: None,
-                            reasoning: None,
-                            stream: None,
-                            stream_options: None,
-                            tool_choice: None,
-                            tools: None,
-                            venice_parameters: None,
-                            parallel_tool_calls: None,
-                            response_format: None,
+                            ..hqe_openai::ChatRequest::new(
+                                client_clone.default_model(),
+                                vec![hqe_openai::Message {
+                                    role: hqe_openai::Role::User,
+                                    content: Some(prompt_text.into()),
+                                    tool_calls: None,
+                                    tool_call_id: None,
+                                }],
+                            )
                         })
                         .await?;
 
@@ -427,18 +941,22 @@ async fn handle_prompt(
     let args_val: serde_json::Value = serde_json::from_str(&args_json)
         .map_err(|e| anyhow::anyhow!("Invalid JSON args: {}", e))?;
 
-    // The registry expects tool names like "topic__name" or just "name" if loaded that way.
-    // Our loader registers them under topic "prompts", so the key is "prompts__tool_name".
-    // We should try both or standardized format.
-    // Registry implementation: key = format!("{}__{}", topic_id, def.name);
-    let lookup_name = if tool_name.contains("__") {
-        tool_name
-    } else {
-        format!("prompts__{}", tool_name)
+    // Accepts "prompts__name" as well as the bare "name"
+    let Some(definition) = registry.describe_tool(&tool_name).await else {
+        return Err(anyhow::anyhow!(
+            "Prompt tool '{}' not found. Run 'hqe tools list' to see the available tools.",
+            tool_name
+        ));
     };
+    if let Some(tool) = loaded_tools
+        .iter()
+        .find(|t| t.definition.name == definition.name)
+    {
+        hqe_mcp::PromptLoader::validate_args(tool, &args_val, strict)?;
+    }
 
-    println!("  Running tool: {}...", lookup_name);
-    let result: serde_json::Value = registry.call_tool(&lookup_name, args_val).await?;
+    println!("  Running tool: {}...", tool_name);
+    let result: serde_json::Value = registry.call_tool(&tool_name, args_val).await?;
 
     println!("\n{}", style("📝 Result:").bold().green());
     if let Some(text) = result.get("result").and_then(|v| v.as_str()) {
@@ -450,21 +968,101 @@ async fn handle_prompt(
     Ok(())
 }
 
-fn substitute_template(template: &str, args: &serde_json::Value) -> String {
-    let mut result = template.to_string();
-
-    if let Some(obj) = args.as_object() {
-        for (k, v) in obj {
-            let key = format!("{{{{{}}}}}", k); // {{key}}
-            let val = v
-                .as_str()
-                .map(sanitize_for_prompt) // Validate string values
-                .unwrap_or_else(|| sanitize_for_prompt(&v.to_string())); // Validate non-string values
-            result = result.replace(&key, &val);
+/// `./prompts`, or `prompts` next to (or one level above) the executable
+fn locate_prompts_dir() -> anyhow::Result<PathBuf> {
+    let mut prompts_dir = PathBuf::from("./prompts");
+    if !prompts_dir.exists() {
+        if let Some(exe_dir) = std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(|p| p.to_path_buf()))
+        {
+            let try_path = exe_dir.join("prompts");
+            if try_path.exists() {
+                prompts_dir = try_path;
+            } else if let Some(parent) = exe_dir.parent() {
+                let try_path_parent = parent.join("prompts");
+                if try_path_parent.exists() {
+                    prompts_dir = try_path_parent;
+                }
+            }
         }
     }
 
-    result
+    if !prompts_dir.exists() {
+        return Err(anyhow::anyhow!("Could not locate 'prompts' directory."));
+    }
+    Ok(prompts_dir)
+}
+
+async fn handle_tools(command: ToolsCommands) -> anyhow::Result<()> {
+    let ToolsCommands::List { check } = command;
+
+    let prompts_dir = locate_prompts_dir()?;
+    let registry = hqe_mcp::ToolRegistry::new();
+    for tool in hqe_mcp::PromptLoader::new(&prompts_dir).load()? {
+        let tool_name = tool.definition.name.clone();
+        // Prompt tools call the LLM, so listing registers them without a client
+        let handler: hqe_mcp::ToolHandler = Box::new(|_| {
+            Box::pin(async { Err(anyhow::anyhow!("run prompt tools with 'hqe prompt'")) })
+        });
+        if let Err(e) = registry
+            .register_tool("prompts", tool.definition, handler)
+            .await
+        {
+            println!("  {} {}: {}", style("✗").red(), style(&tool_name).bold(), e);
+        }
+    }
+
+    let tools = registry.list_callable_tools().await;
+    println!(
+        "{}",
+        style(format!(
+            "🧰 {} tool(s) from {}",
+            tools.len(),
+            prompts_dir.display()
+        ))
+        .bold()
+    );
+    for (key, def) in &tools {
+        let summary = def.description.lines().next().unwrap_or_default();
+        println!("  {}  {}", style(key).cyan(), summary);
+    }
+    if !tools.is_empty() {
+        println!(
+            "\nRun one with {} (the {} prefix is optional)",
+            style("hqe prompt <NAME>").bold(),
+            style("prompts__").bold()
+        );
+    }
+
+    if check {
+        println!("\n{}", style("🩺 Health check").bold());
+        let mut failed = 0;
+        for (key, health) in registry.health_check().await {
+            match health {
+                hqe_mcp::ToolHealth::Healthy => println!("  {} {}", style("✓").green(), key),
+                hqe_mcp::ToolHealth::Skipped => {
+                    println!(
+                        "  {} {} (not idempotent, not called)",
+                        style("-").dim(),
+                        key
+                    )
+                }
+                hqe_mcp::ToolHealth::Failed(reason) => {
+                    failed += 1;
+                    println!("  {} {}: {}", style("✗").red(), key, reason);
+                }
+            }
+        }
+        if failed > 0 {
+            return Err(anyhow::anyhow!(
+                "{} tool(s) failed the health check",
+                failed
+            ));
+        }
+    }
+
+    Ok(())
 }
 
 // Embed protocol files at compile time for standalone binary distribution
@@ -645,7 +1243,7 @@ async fn validate_protocol() -> anyhow::Result<()> {
     }
 }
 
-async fn scan_repo(args: ScanRepoArgs) -> anyhow::Result<()> {
+async fn scan_repo(args: ScanRepoArgs) -> anyhow::Result<ScanSummary> {
     let ScanRepoArgs {
         repo,
         profile,
@@ -655,24 +1253,73 @@ async fn scan_repo(args: ScanRepoArgs) -> anyhow::Result<()> {
         timeout,
         venice_parameters,
         parallel_tool_calls,
+        repo_tools,
+        concurrency,
+        secret_rules,
+        strict_redaction,
+        redact_pii,
+        run_external_tools,
+        advisories,
+        osv,
+        no_ignore,
+        include,
+        exclude,
+        budget_usd,
+        budget_tokens,
+        sarif,
+        html,
+        backlog_csv,
+        junit,
+        sbom,
+        report_template,
+        sign,
+        baseline,
+        write_baseline,
         no_cache,
+        incremental,
+        changed_since,
+        resume,
+        events,
+        fallback_profiles,
+        routing,
+        fail_on,
+        quiet,
+        format,
     } = args;
-    println!("{}", style("🔍 HQE Repository Scan").bold().cyan());
-    println!("  Repository: {}", repo.display());
-    let mode_str = if local_only {
-        style("local-only").yellow().to_string()
+    // A broken template fails before the scan spends any provider budget
+    let report_renderer = report_template
+        .map(hqe_artifacts::TemplateRenderer::from_path)
+        .transpose()?;
+    let signing_key = if sign {
+        Some(load_signing_key(true)?.expect("created when missing"))
     } else {
-        style(format!("LLM ({})", profile.as_deref().unwrap_or("default")))
-            .green()
-            .to_string()
+        None
     };
-    println!("  Mode: {}", mode_str);
-    println!("  Timeout: {}s", timeout);
-    println!("  Output: {}", out.display());
-    println!();
+    if !quiet {
+        println!("{}", style("🔍 HQE Repository Scan").bold().cyan());
+        println!("  Repository: {}", repo.display());
+        let mode_str = if local_only {
+            style("local-only").yellow().to_string()
+        } else {
+            style(format!("LLM ({})", profile.as_deref().unwrap_or("default")))
+                .green()
+                .to_string()
+        };
+        println!("  Mode: {}", mode_str);
+        println!("  Timeout: {}s", timeout);
+        println!("  Output: {}", out.display());
+        if let Some(run_id) = &resume {
+            println!("  Resuming: {}", run_id);
+        }
+        println!();
+    }
 
     // Setup progress bar
-    let pb = ProgressBar::new_spinner();
+    let pb = if quiet {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new_spinner()
+    };
     pb.set_style(
         ProgressStyle::default_spinner()
             .template("{spinner:.cyan} {msg}")
@@ -684,6 +1331,9 @@ async fn scan_repo(args: ScanRepoArgs) -> anyhow::Result<()> {
     if let Some(max) = max_files {
         limits.max_files_sent = max;
     }
+    limits.respect_ignore_files = !no_ignore;
+    limits.include_globs = include;
+    limits.exclude_globs = exclude;
 
     let config = ScanConfig {
         llm_enabled: !local_only,
@@ -693,72 +1343,303 @@ async fn scan_repo(args: ScanRepoArgs) -> anyhow::Result<()> {
         timeout_seconds: timeout,
         venice_parameters: venice_parameters.clone(),
         parallel_tool_calls,
+        concurrency,
+        secret_rules_file: secret_rules,
+        strict_redaction,
+        redact_pii,
+        run_external_tools,
+        advisory_file: advisories,
+        osv_enabled: osv,
+        budget_usd,
+        budget_tokens,
+        changed_since,
     };
 
     // Run scan
     pb.set_message("Initializing scan pipeline...");
-    let mut pipeline = ScanPipeline::new(&repo, config.clone())?;
+    let cancel = CancellationToken::new();
+    let mut pipeline = ScanPipeline::new(&repo, config.clone())?.with_cancellation(cancel.clone());
+    if let Some(path) = &baseline {
+        pipeline = pipeline.with_baseline(Baseline::load(path)?);
+    }
+    if config.osv_enabled && !no_cache {
+        match LocalDb::init() {
+            Ok(db) => pipeline = pipeline.with_osv_client(OsvClient::new().with_cache(db)),
+            Err(e) => tracing::warn!("OSV lookup cache unavailable: {}", e),
+        }
+    }
     if config.llm_enabled && !config.local_only {
         let profile_name = config
             .provider_profile
             .clone()
             .ok_or_else(|| anyhow::anyhow!("Provider profile required for LLM scans"))?;
         let manager = ProfileManager::default();
-        let (profile, api_key) = manager
-            .get_profile_with_key(&profile_name)?
-            .ok_or_else(|| anyhow::anyhow!("Profile not found"))?;
-        let allow_missing_key = is_local_or_private_base_url(&profile.base_url).unwrap_or(false);
-        let api_key = match api_key {
-            Some(key) => key,
-            None if allow_missing_key => SecretString::new(String::new().into_boxed_str()),
-            None => return Err(anyhow::anyhow!("No API key stored for profile")),
-        };
-
-        let base_url = profile.base_url.clone();
-        let default_model = profile.default_model.clone();
+        let (profile, llm_client) = scan_client(&manager, &profile_name, timeout, no_cache)?;
 
         pipeline.set_provider_info(ProviderInfo {
             name: profile.name.clone(),
-            base_url: Some(base_url.clone()),
-            model: Some(default_model.clone()),
+            base_url: Some(profile.base_url.clone()),
+            model: Some(profile.default_model.clone()),
             llm_enabled: true,
         });
 
-        let llm_client = OpenAIClient::new(ClientConfig {
-            base_url,
-            api_key,
-            default_model,
-            headers: profile.headers.clone(),
-            organization: profile.organization.clone(),
-            project: profile.project.clone(),
-            disable_system_proxy: false,
-            timeout_seconds: timeout,
-            max_retries: 2,
-            rate_limit_config: None,
-            cache_enabled: !no_cache,
-            daily_budget: 1.0,
-        })?;
-        let analyzer = OpenAIAnalyzer::new(llm_client)
+        let fallbacks = fallback_profiles
+            .iter()
+            .map(|name| scan_client(&manager, name, timeout, no_cache).map(|(_, client)| client))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let mut analyzer = OpenAIAnalyzer::new(llm_client)
+            .with_fallbacks(fallbacks, routing)
             .with_venice_parameters(venice_parameters)
-            .with_parallel_tool_calls(parallel_tool_calls);
+            .with_parallel_tool_calls(parallel_tool_calls)
+            .with_cancellation(cancel.clone());
+        let budget = ScanBudget {
+            usd: budget_usd,
+            tokens: budget_tokens,
+        };
+        if budget.is_set() {
+            analyzer = analyzer.with_budget(budget);
+        }
+        if repo_tools {
+            let tools = RepoTools::new(&repo)?
+                .with_scanner(pipeline.repo_scanner())
+                .with_pii_redaction(redact_pii)
+                .with_strict_redaction(strict_redaction);
+            analyzer = analyzer.with_repo_tools(tools);
+        }
         pipeline = pipeline.with_llm_analyzer(Arc::new(analyzer));
+
+        if !no_cache {
+            match LocalDb::init() {
+                Ok(db) => pipeline = pipeline.with_analysis_cache(db),
+                Err(e) => tracing::warn!("File analysis cache unavailable: {}", e),
+            }
+        }
     }
 
-    pb.set_message("Phase: Ingestion...");
-    let result = pipeline.run().await?;
+    // First Ctrl-C stops the scan and keeps the partial report, a second one exits
+    {
+        let cancel = cancel.clone();
+        let pb = pb.clone();
+        tokio::spawn(async move {
+            if tokio::signal::ctrl_c().await.is_ok() {
+                pb.set_message("Cancelling scan (press Ctrl-C again to quit)...");
+                cancel.cancel();
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    std::process::exit(130);
+                }
+            }
+        });
+    }
+
+    // Checkpoints live in the run directory the artifacts are written to
+    let resume_dir = match &resume {
+        Some(run_id) => {
+            let dir = out.join(format!("hqe_run_{run_id}"));
+            if !dir.join(hqe_core::CHECKPOINT_FILE).exists() {
+                return Err(anyhow::anyhow!(
+                    "No checkpoint for run {run_id} in {}",
+                    out.display()
+                ));
+            }
+            Some(dir)
+        }
+        None => {
+            let run_dir = out.join(format!("hqe_run_{}", pipeline.run_id()));
+            pipeline = pipeline.with_checkpoints(run_dir, SCAN_CHECKPOINT_INTERVAL);
+            None
+        }
+    };
+
+    let (tx, rx) = tokio::sync::mpsc::channel(64);
+    let progress = tokio::spawn(report_scan_progress(rx, pb.clone(), events));
+    let result = if let Some(run_dir) = &resume_dir {
+        pipeline.resume_with_events(run_dir, tx).await
+    } else if incremental {
+        pipeline
+            .run_incremental_with_events(out.join(hqe_core::SCAN_CACHE_FILE), tx)
+            .await
+    } else {
+        pipeline.run_with_events(tx).await
+    };
+    progress.await??;
+    let result = result?;
 
-    pb.finish_with_message("Scan complete!");
+    if result.manifest.cancelled {
+        pb.finish_with_message("Scan cancelled, writing partial results");
+    } else {
+        pb.finish_with_message("Scan complete!");
+    }
 
     // Write artifacts
-    println!("\n{}", style("📁 Writing artifacts...").bold());
+    if !quiet {
+        println!("\n{}", style("📁 Writing artifacts...").bold());
+    }
 
     let run_dir = out.join(format!("hqe_run_{}", result.manifest.run_id));
     std::fs::create_dir_all(&run_dir)?;
 
-    let writer = hqe_artifacts::ArtifactWriter::new(&run_dir);
+    let mut writer = hqe_artifacts::ArtifactWriter::new(&run_dir)
+        .with_sarif(sarif || format == OutputFormat::Sarif)
+        .with_html(html)
+        .with_backlog_csv(backlog_csv)
+        .with_junit(junit)
+        .with_sbom(sbom);
+    if let Some(renderer) = report_renderer {
+        writer = writer.with_renderer(renderer);
+    }
+    if let Some(key) = signing_key {
+        writer = writer.with_signing_key(key);
+    }
     let paths = writer.write_all(&result).await?;
 
-    // Print summary
+    // Kept out of the artifacts: lets `hqe patch` restore redacted values in diffs
+    let redaction_map = pipeline.redaction_map();
+    if !redaction_map.is_empty() {
+        let saved = EncryptedDb::redaction_map_key().and_then(|key| {
+            EncryptedDb::save_redaction_map(&redaction_map, &run_dir.join(REDACTION_MAP_FILE), &key)
+        });
+        if let Err(e) = saved {
+            tracing::warn!(
+                "Redaction map not saved, patches will keep redaction placeholders: {}",
+                e
+            );
+        }
+    }
+
+    if let Some(path) = &write_baseline {
+        Baseline::from_report(&result.report).save(path)?;
+    }
+
+    let mut artifacts = vec![paths.manifest_json, paths.report_json, paths.report_md];
+    artifacts.extend(paths.manifest_signature);
+    artifacts.extend(paths.report_html);
+    artifacts.extend(paths.report_sarif.clone());
+    artifacts.extend(paths.backlog_csv);
+    artifacts.extend(paths.junit_xml);
+    artifacts.extend(paths.sbom_cyclonedx);
+    artifacts.extend(write_baseline);
+    let summary = ScanSummary::new(&result, fail_on, artifacts);
+
+    match format {
+        OutputFormat::Json => println!("{}", serde_json::to_string(&summary)?),
+        OutputFormat::Sarif => {
+            if let Some(path) = &paths.report_sarif {
+                println!("{}", std::fs::read_to_string(path)?);
+            }
+        }
+        OutputFormat::Text if !quiet => print_scan_summary(&result, &summary),
+        OutputFormat::Text => {}
+    }
+
+    // The partial report is on disk; still fail so scripts notice the cut-off
+    if let Some(reason) = result.manifest.budget_exceeded {
+        if !quiet {
+            println!(
+                "\n{}",
+                style("⚠️  Scan budget exhausted, report is partial").yellow()
+            );
+        }
+        return Err(hqe_core::HqeError::BudgetExceeded(reason).into());
+    }
+
+    if !quiet {
+        if summary.gate_failed {
+            println!(
+                "\n{}",
+                style(format!(
+                    "❌ Findings at or above --fail-on severity (highest: {})",
+                    summary
+                        .highest_severity
+                        .as_ref()
+                        .map_or_else(String::new, |s| s.to_string())
+                ))
+                .red()
+                .bold()
+            );
+        } else {
+            println!("\n{}", style("✅ Done!").green().bold());
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Machine-readable outcome of `hqe scan`, printed by `--format json`
+#[derive(Debug, serde::Serialize)]
+struct ScanSummary {
+    run_id: String,
+    health_score: u8,
+    /// Reported findings per severity, excluding ones suppressed by a baseline
+    findings: BTreeMap<String, usize>,
+    suppressed_findings: usize,
+    todo_items: usize,
+    /// Highest severity among reported and local findings
+    highest_severity: Option<Severity>,
+    fail_on: Option<Severity>,
+    gate_failed: bool,
+    cancelled: bool,
+    artifacts: Vec<PathBuf>,
+}
+
+impl ScanSummary {
+    fn new(result: &ScanResult, fail_on: Option<Severity>, artifacts: Vec<PathBuf>) -> Self {
+        let report = &result.report;
+        let mut findings = BTreeMap::new();
+        for finding in report.deep_scan_results.iter() {
+            *findings
+                .entry(finding.severity.to_string().to_lowercase())
+                .or_default() += 1;
+        }
+        let highest_severity = highest_severity(result);
+        let gate_failed = match (&fail_on, &highest_severity) {
+            (Some(threshold), Some(highest)) => highest.rank() >= threshold.rank(),
+            _ => false,
+        };
+
+        Self {
+            run_id: result.manifest.run_id.clone(),
+            health_score: report.executive_summary.health_score,
+            findings,
+            suppressed_findings: report.suppressed_findings.len(),
+            todo_items: report.master_todo_backlog.len(),
+            highest_severity,
+            fail_on,
+            gate_failed,
+            cancelled: result.manifest.cancelled,
+            artifacts,
+        }
+    }
+}
+
+/// Highest severity the `--fail-on` gate sees.
+///
+/// Local heuristics count even when an LLM produced the report, unless the
+/// baseline suppressed the finding they were turned into.
+fn highest_severity(result: &ScanResult) -> Option<Severity> {
+    let suppressed: HashSet<(&str, Option<&str>)> = result
+        .report
+        .suppressed_findings
+        .iter()
+        .map(|f| (f.title.as_str(), f.file()))
+        .collect();
+    let local = result
+        .local_findings
+        .iter()
+        .filter(|l| !suppressed.contains(&(l.description.as_str(), Some(l.file_path.as_str()))))
+        .map(|l| &l.severity);
+
+    result
+        .report
+        .deep_scan_results
+        .iter()
+        .map(|f| &f.severity)
+        .chain(local)
+        .max_by_key(|severity| severity.rank())
+        .cloned()
+}
+
+/// Decorated summary printed after a text-format scan
+fn print_scan_summary(result: &ScanResult, summary: &ScanSummary) {
     println!("\n{}", style("📊 Scan Summary").bold().green());
     println!("  Run ID: {}", result.manifest.run_id);
     println!(
@@ -766,6 +1647,57 @@ async fn scan_repo(args: ScanRepoArgs) -> anyhow::Result<()> {
         result.report.executive_summary.health_score
     );
     println!("  TODO Items: {}", result.report.master_todo_backlog.len());
+    if let Some(status) = &result.report.baseline {
+        println!(
+            "  Baseline: {} suppressed, {} stale",
+            status.suppressed,
+            status.stale.len()
+        );
+    }
+    if let Some(summary) = &result.manifest.incremental {
+        println!(
+            "  Incremental: {} reused, {} rescanned, {} removed",
+            summary.reused, summary.rescanned, summary.removed
+        );
+    }
+    if let Some(scope) = &result.manifest.changed_since {
+        println!(
+            "  Partial scan: {} file(s) changed since {}",
+            scope.changed_files, scope.base_ref
+        );
+    }
+    if let Some(skipped) = &result.manifest.skipped_files {
+        println!(
+            "  Skipped Files: {} binary, {} minified, {} generated",
+            skipped.binary, skipped.minified, skipped.generated
+        );
+    }
+    if let Some(truncation) = &result.manifest.prompt_truncation {
+        println!(
+            "  Prompt trimmed: {} -> {} tokens (context window {}, {} file(s) cut)",
+            truncation.original_tokens,
+            truncation.sent_tokens,
+            truncation.context_window,
+            truncation.trimmed_files.len()
+        );
+    }
+    if let Some(stats) = &result.manifest.analysis_cache {
+        println!(
+            "  Analysis Cache: {} hits, {} misses",
+            stats.hits, stats.misses
+        );
+    }
+    if let Some(cost) = &result.manifest.cost_estimate {
+        let usd = match cost.total_usd {
+            Some(usd) => format!("${usd:.4}"),
+            None if cost.unpriced_models.is_empty() => "unknown pricing".to_string(),
+            None => format!("unknown pricing for {}", cost.unpriced_models.join(", ")),
+        };
+        println!(
+            "  LLM Usage: {} prompt + {} completion tokens ({})",
+            cost.prompt_tokens, cost.completion_tokens, usd
+        );
+    }
 
     if !result.report.executive_summary.blockers.is_empty() {
         println!("\n{}", style("⚠️  Blockers:").yellow());
@@ -775,12 +1707,107 @@ async fn scan_repo(args: ScanRepoArgs) -> anyhow::Result<()> {
     }
 
     println!("\n{}", style("📄 Artifacts:").bold());
-    println!("  {}", paths.manifest_json.display());
-    println!("  {}", paths.report_json.display());
-    println!("  {}", paths.report_md.display());
+    for path in &summary.artifacts {
+        println!("  {}", path.display());
+    }
+}
+
+/// Keychain account holding the hex ed25519 key that signs run manifests
+const SIGNING_KEY_ACCOUNT: &str = "artifact-signing-key";
+
+/// The artifact signing key from the OS keychain, generated and stored on
+/// first use when `create` is set
+fn load_signing_key(create: bool) -> anyhow::Result<Option<hqe_artifacts::SigningKey>> {
+    let entry = keyring::Entry::new("hqe-workbench", SIGNING_KEY_ACCOUNT)?;
+    match entry.get_password() {
+        Ok(secret) => Ok(Some(hqe_artifacts::signing_key_from_hex(&secret)?)),
+        Err(keyring::Error::NoEntry) if create => {
+            let key = hqe_artifacts::generate_signing_key();
+            entry.set_password(&hex::encode(key.to_bytes()))?;
+            Ok(Some(key))
+        }
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+async fn verify_run(run_dir: PathBuf, public_key: Option<String>) -> anyhow::Result<()> {
+    println!(
+        "{}",
+        style(format!("🔏 Verifying {}", run_dir.display())).bold()
+    );
+    let report = hqe_artifacts::verify_run_dir(&run_dir).await?;
 
-    println!("\n{}", style("✅ Done!").green().bold());
+    for path in &report.verified {
+        println!("  {} {}", style("✓").green(), path);
+    }
+    for path in &report.modified {
+        println!("  {} {} (modified)", style("✗").red(), path);
+    }
+    for path in &report.missing {
+        println!("  {} {} (missing)", style("✗").red(), path);
+    }
+    for path in &report.unlisted {
+        println!("  {} {} (not in manifest)", style("-").dim(), path);
+    }
 
+    let trusted = match public_key {
+        Some(key) => Some(key.trim().to_lowercase()),
+        None => load_signing_key(false)
+            .ok()
+            .flatten()
+            .map(|key| hex::encode(key.verifying_key().as_bytes())),
+    };
+    let mut signature_ok = true;
+    match &report.signature {
+        hqe_artifacts::SignatureStatus::Unsigned => {
+            println!("\n  Signature: {}", style("none").yellow())
+        }
+        hqe_artifacts::SignatureStatus::Invalid(reason) => {
+            signature_ok = false;
+            println!("\n  Signature: {} ({})", style("invalid").red(), reason);
+        }
+        hqe_artifacts::SignatureStatus::Valid(key) => match &trusted {
+            Some(trusted) if trusted == key => {
+                println!("\n  Signature: {} ({})", style("valid").green(), key)
+            }
+            Some(_) => {
+                signature_ok = false;
+                println!(
+                    "\n  Signature: {} (signed by {}, not the trusted key)",
+                    style("untrusted key").red(),
+                    key
+                );
+            }
+            None => println!(
+                "\n  Signature: {} (signed by {}; pass --public-key to check the signer)",
+                style("valid").green(),
+                key
+            ),
+        },
+    }
+
+    let diverged = report.modified.len() + report.missing.len();
+    match (diverged, signature_ok) {
+        (0, true) => {}
+        (0, false) => return Err(anyhow::anyhow!("Manifest signature check failed")),
+        (n, true) => {
+            return Err(anyhow::anyhow!(
+                "{} artifact(s) diverge from the manifest",
+                n
+            ))
+        }
+        (n, false) => {
+            return Err(anyhow::anyhow!(
+                "{} artifact(s) diverge from the manifest and the signature check failed",
+                n
+            ))
+        }
+    }
+    println!(
+        "\n{}",
+        style("✅ All artifacts match the manifest").green().bold()
+    );
     Ok(())
 }
 
@@ -788,6 +1815,7 @@ async fn export_run(
     run_id: String,
     out_dir: PathBuf,
     from_dir: Option<PathBuf>,
+    format: ExportFormat,
 ) -> anyhow::Result<()> {
     println!(
         "{}",
@@ -803,9 +1831,33 @@ async fn export_run(
 
     std::fs::create_dir_all(&out_dir)?;
 
+    if format == ExportFormat::Csv {
+        let report_path = source.join("report.json");
+        if !report_path.exists() {
+            return Err(anyhow::anyhow!(
+                "Report not found at {}",
+                report_path.display()
+            ));
+        }
+        let content = tokio::fs::read_to_string(&report_path).await?;
+        let report: HqeReport = serde_json::from_str(&content)?;
+
+        let writer = hqe_artifacts::ArtifactWriter::new(&out_dir);
+        for path in [
+            writer.write_backlog_csv(&report).await?,
+            writer.write_findings_csv(&report).await?,
+        ] {
+            println!("  {}", path.display());
+        }
+        println!("\n{}", style("✅ Export complete").green().bold());
+        println!("  Source: {}", source.display());
+        return Ok(());
+    }
+
     for entry in std::fs::read_dir(&source)? {
         let entry = entry?;
-        if entry.file_type()?.is_dir() {
+        // The redaction map holds the secrets kept from the LLM
+        if entry.file_type()?.is_dir() || entry.file_name() == REDACTION_MAP_FILE {
             continue;
         }
         let target_file = out_dir.join(entry.file_name());
@@ -889,6 +1941,22 @@ async fn handle_patch(
         .iter()
         .find(|p| p.todo_id == todo_id);
 
+    // Diffs were written against redacted content; restore the real values
+    let map_path = run_dir.join(REDACTION_MAP_FILE);
+    let redaction_map = if map_path.exists() {
+        match EncryptedDb::redaction_map_key()
+            .and_then(|key| EncryptedDb::load_redaction_map(&map_path, &key))
+        {
+            Ok(map) => map,
+            Err(e) => {
+                tracing::warn!("Could not read redaction map: {}", e);
+                RedactionMap::default()
+            }
+        }
+    } else {
+        RedactionMap::default()
+    };
+
     match patch {
         Some(p) => {
             println!("  Found patch: {}", style(&p.title).bold());
@@ -901,16 +1969,25 @@ async fn handle_patch(
                     style(format!("File: {}", diff.file_path)).underlined()
                 );
 
+                let diff_content = redaction_map.rehydrate(&diff.diff_content);
+
                 if preview {
-                    println!("{}", style(&diff.diff_content).dim());
+                    println!("{}", style(&diff_content).dim());
                 }
 
                 if apply {
+                    if contains_placeholder(&diff_content) {
+                        println!(
+                            "  {}",
+                            style("Skipped: diff still contains redaction placeholders").red()
+                        );
+                        continue;
+                    }
                     println!("  Applying patch...");
 
                     // Create temp file for the diff
                     let mut temp = tempfile::Builder::new().suffix(".patch").tempfile()?;
-                    std::io::Write::write_all(&mut temp, diff.diff_content.as_bytes())?;
+                    std::io::Write::write_all(&mut temp, diff_content.as_bytes())?;
 
                     let status = std::process::Command::new("patch")
                         .arg("-p1")
@@ -951,6 +2028,75 @@ async fn handle_patch(
     Ok(())
 }
 
+fn handle_cache(command: CacheCommands) -> anyhow::Result<()> {
+    let db = LocalDb::init()?;
+
+    match command {
+        CacheCommands::Stats => {
+            let stats = db.cache_stats()?;
+            println!("{}", style("🗄️  Local Cache").bold());
+            println!("  Entries: {}", stats.entries);
+            println!(
+                "  Size: {:.1} MiB",
+                stats.total_bytes as f64 / (1024.0 * 1024.0)
+            );
+            match stats.oldest_entry {
+                Some(oldest) => println!("  Oldest entry: {}", oldest.format("%Y-%m-%d %H:%M UTC")),
+                None => println!("  Oldest entry: -"),
+            }
+        }
+        CacheCommands::Prune => {
+            let removed = db.prune()?;
+            println!(
+                "{}",
+                style(format!("✅ Pruned {} cache entries", removed)).green()
+            );
+        }
+        CacheCommands::List { model } => {
+            let entries = db.list_cached(&CacheFilter {
+                model,
+                ..CacheFilter::default()
+            })?;
+            println!("{}", style("🗄️  Cached Entries").bold());
+            if entries.is_empty() {
+                println!("  (none)");
+            }
+            for entry in entries {
+                let kind = match entry.kind {
+                    CacheKind::Response => "response",
+                    CacheKind::FileAnalysis => "file",
+                };
+                let created = entry
+                    .created_at
+                    .map(|t| t.format("%Y-%m-%d %H:%M UTC").to_string())
+                    .unwrap_or_else(|| "-".to_string());
+                println!(
+                    "  {:<8} {:<12} {:<20} {:>10} B  {}",
+                    kind,
+                    entry.key.get(..12).unwrap_or(&entry.key),
+                    created,
+                    entry.size_bytes,
+                    entry.model
+                );
+            }
+        }
+        CacheCommands::Clear {
+            older_than_days,
+            model,
+        } => {
+            let older_than =
+                older_than_days.map(|days| std::time::Duration::from_secs(days * 24 * 60 * 60));
+            let removed = db.clear_cached(&CacheFilter { model, older_than })?;
+            println!(
+                "{}",
+                style(format!("✅ Removed {} cache entries", removed)).green()
+            );
+        }
+    }
+
+    Ok(())
+}
+
 async fn handle_config(command: ConfigCommands) -> anyhow::Result<()> {
     let config_dir = dirs::data_local_dir()
         .map(|d| d.join("hqe-workbench"))
@@ -991,17 +2137,70 @@ async fn handle_config(command: ConfigCommands) -> anyhow::Result<()> {
             url,
             key,
             model,
+            from_spec,
             header,
             organization,
             project,
             timeout,
+            azure_deployment,
+            azure_api_version,
         } => {
             println!(
                 "{}",
                 style(format!("➕ Adding profile: {}", name)).bold().green()
             );
 
-            let allow_missing_key = is_local_or_private_base_url(&url).unwrap_or(false);
+            let spec = match from_spec.as_deref() {
+                Some(id) => Some(hqe_openai::prefilled::get_spec(id).ok_or_else(|| {
+                    let known: Vec<String> = hqe_openai::prefilled::spec_list()
+                        .into_iter()
+                        .map(|(id, _)| id)
+                        .collect();
+                    anyhow::anyhow!(
+                        "Unknown provider spec '{}'. Available: {}",
+                        id,
+                        known.join(", ")
+                    )
+                })?),
+                None => None,
+            };
+
+            let mut profile = match &spec {
+                Some(spec) => spec.to_profile(name.clone()),
+                None => hqe_openai::ProviderProfile::new(name.clone(), String::new()),
+            };
+            if let Some(url) = url {
+                profile.base_url = url;
+            }
+            if let Some(model) = model {
+                profile.default_model = model;
+            }
+            if let Some(timeout) = timeout {
+                profile.timeout_s = timeout;
+            }
+            profile.organization = organization;
+            profile.project = project;
+            if let Some(deployment) = azure_deployment {
+                let api_version = azure_api_version
+                    .unwrap_or_else(|| hqe_openai::AZURE_DEFAULT_API_VERSION.to_string());
+                profile = profile.with_azure_deployment(deployment, api_version);
+                if profile.provider_kind.is_none() {
+                    profile.provider_kind = Some(hqe_openai::ProviderKind::Azure);
+                }
+            }
+
+            if let Some(spec) = &spec {
+                println!(
+                    "  Using spec: {} ({} auth)",
+                    spec.display_name, spec.auth_scheme
+                );
+                for quirk in &spec.quirks {
+                    println!("  {}", style(format!("⚠ {}", quirk)).yellow());
+                }
+            }
+
+            let allow_missing_key =
+                is_local_or_private_base_url(&profile.base_url).unwrap_or(false);
             let key_value = key.as_deref().map(|k| k.trim()).filter(|k| !k.is_empty());
 
             if let Some(key_value) = key_value {
@@ -1035,13 +2234,9 @@ async fn handle_config(command: ConfigCommands) -> anyhow::Result<()> {
                 vec![]
             };
 
-            let mut profile = hqe_openai::ProviderProfile::new(name.clone(), url).with_model(model);
-            profile.timeout_s = timeout;
-            profile.organization = organization;
-            profile.project = project;
-
+            // Explicit headers override the spec's defaults
             if !header.is_empty() {
-                let mut headers = std::collections::HashMap::new();
+                let headers = profile.headers.get_or_insert_with(Default::default);
                 for raw in header {
                     let (name, value) = raw
                         .split_once(':')
@@ -1051,7 +2246,6 @@ async fn handle_config(command: ConfigCommands) -> anyhow::Result<()> {
                         })?;
                     headers.insert(name.trim().to_string(), value.trim().to_string());
                 }
-                profile.headers = Some(headers);
             }
 
             profile.validate_headers().map_err(|e| anyhow::anyhow!(e))?;
@@ -1094,7 +2288,7 @@ async fn handle_config(command: ConfigCommands) -> anyhow::Result<()> {
                     is_local_or_private_base_url(&profile.base_url).unwrap_or(false);
                 let api_key = match entry.get_password() {
                     Ok(key) => SecretString::new(key.into_boxed_str()),
-                    Err(err) if allow_missing_key => {
+                    Err(_) if allow_missing_key => {
                         SecretString::new(String::new().into_boxed_str())
                     }
                     Err(err) => return Err(err.into()),
@@ -1109,11 +2303,18 @@ async fn handle_config(command: ConfigCommands) -> anyhow::Result<()> {
                     organization: profile.organization.clone(),
                     project: profile.project.clone(),
                     disable_system_proxy: false,
+                    proxy: None,
                     timeout_seconds: 30,
+                    connect_timeout_secs: None,
+                    read_timeout_secs: None,
                     max_retries: 1,
                     rate_limit_config: None,
                     cache_enabled: true,
                     daily_budget: 1.0,
+                    provider_kind: profile.provider_kind,
+                    auth: profile.auth_placement(),
+                    azure: profile.azure.clone(),
+                    unsupported_params: profile.unsupported_params.clone(),
                 };
 
                 let client = hqe_openai::OpenAIClient::new(config)?;
@@ -1152,6 +2353,48 @@ async fn handle_config(command: ConfigCommands) -> anyhow::Result<()> {
                 println!("{}", style("✅ Profile removed").green());
             }
         }
+        ConfigCommands::Export { names, out } => {
+            let bundle = hqe_openai::ProfileManager::default().export_bundle(&names)?;
+            match out {
+                Some(path) => {
+                    tokio::fs::write(&path, bundle).await?;
+                    println!(
+                        "{}",
+                        style(format!("✅ Profiles exported to {}", path.display())).green()
+                    );
+                }
+                None => println!("{}", bundle),
+            }
+        }
+        ConfigCommands::Import { file, overwrite } => {
+            println!(
+                "{}",
+                style(format!("📥 Importing profiles from {}", file.display())).bold()
+            );
+
+            let json = tokio::fs::read_to_string(&file).await?;
+            let outcome = hqe_openai::ProfileManager::default().import_bundle(&json, overwrite)?;
+
+            for name in &outcome.imported {
+                println!("  • {}", name);
+            }
+            for name in &outcome.skipped {
+                println!(
+                    "  {}",
+                    style(format!("Skipped {} (exists; use --overwrite)", name)).yellow()
+                );
+            }
+            for name in &outcome.missing_keys {
+                println!(
+                    "  {}",
+                    style(format!("No API key stored for {} yet", name)).yellow()
+                );
+            }
+            println!(
+                "{}",
+                style(format!("✅ Imported {} profile(s)", outcome.imported.len())).green()
+            );
+        }
     }
 
     Ok(())
@@ -0,0 +1,273 @@
+//! Standalone HTML report.
+//!
+//! The HTML is rendered from the Markdown report so both formats always share
+//! the same sections and ordering. On top of the Markdown it adds a colored
+//! health score badge, collapsible `<details>` blocks for every section and
+//! category heading, highlighted diff blocks and an inline SVG chart of the
+//! findings per severity.
+//!
+//! Report content comes from the scanned repository and from model output, so
+//! none of it may become markup: raw HTML is escaped rather than passed
+//! through, links are limited to safe schemes and images are shown as their
+//! alt text, keeping the page free of scripts and external assets.
+
+use hqe_core::models::{HqeReport, Severity};
+use pulldown_cmark::{
+    html, CodeBlockKind, CowStr, Event, HeadingLevel, Options, Parser, Tag, TagEnd,
+};
+
+const STYLE: &str = r#"
+body { font-family: -apple-system, BlinkMacSystemFont, "Segoe UI", Helvetica, Arial, sans-serif; margin: 0 auto; max-width: 960px; padding: 2rem; color: #1f2328; line-height: 1.5; }
+header { display: flex; align-items: center; justify-content: space-between; border-bottom: 1px solid #d0d7de; margin-bottom: 1.5rem; }
+.run-id { color: #59636e; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; }
+.badge { border-radius: 999px; color: #fff; font-weight: 600; padding: 0.35rem 0.9rem; white-space: nowrap; }
+.badge.good { background: #1a7f37; }
+.badge.fair { background: #9a6700; }
+.badge.poor { background: #cf222e; }
+details { border: 1px solid #d0d7de; border-radius: 6px; margin: 0.75rem 0; padding: 0 1rem; }
+details > summary { cursor: pointer; font-weight: 600; padding: 0.6rem 0; }
+details.h2 > summary { font-size: 1.4rem; }
+details.h3 > summary { font-size: 1.15rem; }
+table { border-collapse: collapse; margin: 1rem 0; }
+th, td { border: 1px solid #d0d7de; padding: 0.3rem 0.6rem; text-align: left; }
+code { background: #f6f8fa; border-radius: 4px; font-family: ui-monospace, SFMono-Regular, Menlo, monospace; padding: 0.1rem 0.3rem; }
+pre { background: #f6f8fa; border-radius: 6px; overflow-x: auto; padding: 0.75rem; }
+pre code { background: none; padding: 0; }
+pre.diff span { display: block; }
+pre.diff .add { background: #dafbe1; color: #116329; }
+pre.diff .del { background: #ffebe9; color: #82071e; }
+pre.diff .hunk { color: #0550ae; }
+pre.diff .meta { color: #59636e; font-weight: 600; }
+.severity-chart { margin: 0 0 1.5rem; }
+.severity-chart text { fill: #1f2328; font-size: 13px; }
+"#;
+
+/// Chart rows: label and bar color per severity, most severe first
+const SEVERITY_BARS: [(Severity, &str, &str); 5] = [
+    (Severity::Critical, "Critical", "#82071e"),
+    (Severity::High, "High", "#cf222e"),
+    (Severity::Medium, "Medium", "#9a6700"),
+    (Severity::Low, "Low", "#0969da"),
+    (Severity::Info, "Info", "#59636e"),
+];
+
+/// Render the report as a single self-contained HTML document.
+///
+/// `markdown` is the output of the Markdown renderer for the same report; its
+/// title and run ID lines are replaced by the HTML header.
+pub(crate) fn render_html(report: &HqeReport, markdown: &str) -> String {
+    let score = report.executive_summary.health_score;
+    let badge_class = match score {
+        8.. => "good",
+        5..=7 => "fair",
+        _ => "poor",
+    };
+
+    let mut out = String::new();
+    out.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    out.push_str("<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\n");
+    out.push_str(&format!(
+        "<title>HQE Engineer Report - {}</title>\n",
+        escape_html(&report.run_id)
+    ));
+    out.push_str(&format!("<style>{STYLE}</style>\n</head>\n<body>\n"));
+    out.push_str("<header>\n<div>\n<h1>HQE Engineer Report</h1>\n");
+    out.push_str(&format!(
+        "<p class=\"run-id\">Run ID: {}</p>\n</div>\n",
+        escape_html(&report.run_id)
+    ));
+    out.push_str(&format!(
+        "<span class=\"badge {badge_class}\">Health Score: {score}/10</span>\n</header>\n"
+    ));
+    out.push_str("<main>\n");
+    render_severity_chart(&mut out, report);
+    render_body(&mut out, markdown);
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+/// Horizontal bar chart of the active findings per severity.
+fn render_severity_chart(out: &mut String, report: &HqeReport) {
+    const ROW: usize = 26;
+    const LABEL: usize = 80;
+    const BAR: usize = 360;
+
+    let counts: Vec<usize> = SEVERITY_BARS
+        .iter()
+        .map(|(severity, _, _)| {
+            report
+                .deep_scan_results
+                .iter()
+                .filter(|f| f.severity.rank() == severity.rank())
+                .count()
+        })
+        .collect();
+    let max = counts.iter().copied().max().unwrap_or(0).max(1);
+    let height = ROW * SEVERITY_BARS.len();
+
+    out.push_str(&format!(
+        "<figure class=\"severity-chart\">\n<svg xmlns=\"http://www.w3.org/2000/svg\" role=\"img\" \
+         width=\"{}\" height=\"{height}\" viewBox=\"0 0 {} {height}\">\n<title>Findings by severity</title>\n",
+        LABEL + BAR + 40,
+        LABEL + BAR + 40,
+    ));
+    for (row, ((_, label, color), count)) in SEVERITY_BARS.iter().zip(&counts).enumerate() {
+        let y = row * ROW;
+        let width = count * BAR / max;
+        out.push_str(&format!(
+            "<text x=\"0\" y=\"{}\">{label}</text>\
+             <rect x=\"{LABEL}\" y=\"{}\" width=\"{width}\" height=\"{}\" rx=\"3\" fill=\"{color}\"/>\
+             <text x=\"{}\" y=\"{}\">{count}</text>\n",
+            y + 17,
+            y + 4,
+            ROW - 8,
+            LABEL + width + 6,
+            y + 17,
+        ));
+    }
+    out.push_str("</svg>\n</figure>\n");
+}
+
+/// Convert the Markdown sections (from the first `##` heading on) to HTML.
+fn render_body(out: &mut String, markdown: &str) {
+    let body = match markdown.find("\n## ") {
+        Some(idx) => &markdown[idx + 1..],
+        None => markdown,
+    };
+    let mut events = Parser::new_ext(body, Options::ENABLE_TABLES | Options::ENABLE_TASKLISTS);
+
+    // Heading levels of the currently open <details> blocks
+    let mut open: Vec<HeadingLevel> = Vec::new();
+    let mut pending: Vec<Event> = Vec::new();
+
+    while let Some(event) = events.next() {
+        match event {
+            Event::Start(Tag::Heading { level, .. })
+                if matches!(level, HeadingLevel::H2 | HeadingLevel::H3) =>
+            {
+                flush(out, &mut pending);
+                while open.last().is_some_and(|l| *l >= level) {
+                    open.pop();
+                    out.push_str("</details>\n");
+                }
+                let title: Vec<Event> = events
+                    .by_ref()
+                    .take_while(|e| !matches!(e, Event::End(TagEnd::Heading(_))))
+                    .map(sanitize)
+                    .collect();
+                let class = if level == HeadingLevel::H2 {
+                    "h2"
+                } else {
+                    "h3"
+                };
+                out.push_str(&format!("<details class=\"{class}\" open>\n<summary>"));
+                html::push_html(out, title.into_iter());
+                out.push_str("</summary>\n");
+                open.push(level);
+            }
+            Event::Start(Tag::CodeBlock(CodeBlockKind::Fenced(lang))) if &*lang == "diff" => {
+                flush(out, &mut pending);
+                let mut code = String::new();
+                for e in events.by_ref() {
+                    match e {
+                        Event::End(TagEnd::CodeBlock) => break,
+                        Event::Text(text) => code.push_str(&text),
+                        _ => {}
+                    }
+                }
+                render_diff(out, &code);
+            }
+            other => pending.push(sanitize(other)),
+        }
+    }
+
+    flush(out, &mut pending);
+    for _ in open {
+        out.push_str("</details>\n");
+    }
+}
+
+fn flush(out: &mut String, pending: &mut Vec<Event>) {
+    html::push_html(out, pending.drain(..));
+}
+
+/// Report content comes partly from model output; never emit it as markup.
+///
+/// Raw HTML other than `<br>` becomes text, links with other than web or mail schemes lose their
+/// target, and images become their (emphasized) alt text.
+fn sanitize(event: Event) -> Event {
+    match event {
+        // Line breaks inside table cells
+        Event::InlineHtml(raw) if raw.eq_ignore_ascii_case("<br>") => Event::HardBreak,
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        Event::Start(Tag::Link {
+            link_type,
+            dest_url,
+            title,
+            id,
+        }) => Event::Start(Tag::Link {
+            link_type,
+            dest_url: if is_safe_url(&dest_url) {
+                dest_url
+            } else {
+                CowStr::Borrowed("#")
+            },
+            title,
+            id,
+        }),
+        Event::Start(Tag::Image { .. }) => Event::Start(Tag::Emphasis),
+        Event::End(TagEnd::Image) => Event::End(TagEnd::Emphasis),
+        other => other,
+    }
+}
+
+/// Relative links, fragments and http(s)/mailto URLs
+fn is_safe_url(url: &str) -> bool {
+    let url = url.trim().to_ascii_lowercase();
+    match url.find([':', '/', '?', '#']) {
+        Some(idx) if url[idx..].starts_with(':') => {
+            ["http", "https", "mailto"].contains(&&url[..idx])
+        }
+        _ => true,
+    }
+}
+
+fn render_diff(out: &mut String, diff: &str) {
+    out.push_str("<pre class=\"diff\"><code>");
+    for line in diff.lines() {
+        let class = if line.starts_with("+++") || line.starts_with("---") {
+            "meta"
+        } else if line.starts_with('+') {
+            "add"
+        } else if line.starts_with('-') {
+            "del"
+        } else if line.starts_with("@@") {
+            "hunk"
+        } else {
+            "ctx"
+        };
+        // Keep blank lines visible as empty block spans collapse
+        let text = if line.is_empty() {
+            " ".to_string()
+        } else {
+            escape_html(line)
+        };
+        out.push_str(&format!("<span class=\"{class}\">{text}</span>"));
+    }
+    out.push_str("</code></pre>\n");
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -0,0 +1,214 @@
+//! Artifact integrity for audits.
+//!
+//! [`ArtifactWriter::write_all`](crate::ArtifactWriter::write_all) records the
+//! size and SHA-256 of every file it writes in `run-manifest.json`, which is
+//! written last. With a signing key it also writes `run-manifest.json.sig`, an
+//! ed25519 signature over the exact manifest bytes. [`verify_run_dir`] checks
+//! both and names every file that no longer matches.
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use hqe_core::models::{ArtifactDigest, RunManifest};
+use hqe_core::HqeError;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+
+/// Manifest file name inside a run directory
+pub const MANIFEST_FILE: &str = "run-manifest.json";
+/// Detached signature of [`MANIFEST_FILE`]
+pub const SIGNATURE_FILE: &str = "run-manifest.json.sig";
+/// Value of [`ManifestSignature::algorithm`]
+const ALGORITHM: &str = "ed25519";
+
+/// Contents of [`SIGNATURE_FILE`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ManifestSignature {
+    /// Always `ed25519`
+    pub algorithm: String,
+    /// Hex verifying key of the signer
+    pub public_key: String,
+    /// Hex signature over the manifest bytes
+    pub signature: String,
+}
+
+impl ManifestSignature {
+    /// Sign the manifest bytes exactly as written to disk
+    pub fn sign(manifest: &[u8], key: &SigningKey) -> Self {
+        Self {
+            algorithm: ALGORITHM.to_string(),
+            public_key: hex::encode(key.verifying_key().as_bytes()),
+            signature: hex::encode(key.sign(manifest).to_bytes()),
+        }
+    }
+
+    /// Check the signature against the embedded public key.
+    ///
+    /// This only proves the manifest is unchanged since it was signed by that
+    /// key; callers compare [`ManifestSignature::public_key`] with a key they
+    /// trust.
+    pub fn verify(&self, manifest: &[u8]) -> Result<(), String> {
+        if self.algorithm != ALGORITHM {
+            return Err(format!("unsupported algorithm '{}'", self.algorithm));
+        }
+        let public_key: [u8; 32] = decode_hex(&self.public_key, "public key")?;
+        let signature: [u8; 64] = decode_hex(&self.signature, "signature")?;
+        VerifyingKey::from_bytes(&public_key)
+            .map_err(|e| format!("invalid public key: {e}"))?
+            .verify(manifest, &Signature::from_bytes(&signature))
+            .map_err(|_| "signature does not match the manifest".to_string())
+    }
+}
+
+fn decode_hex<const N: usize>(text: &str, what: &str) -> Result<[u8; N], String> {
+    hex::decode(text.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| format!("invalid {what}"))
+}
+
+/// A new random signing key
+pub fn generate_signing_key() -> SigningKey {
+    SigningKey::from_bytes(&rand::random())
+}
+
+/// Parse a hex-encoded 32-byte ed25519 secret key, as stored in the keychain
+pub fn signing_key_from_hex(text: &str) -> Result<SigningKey, HqeError> {
+    decode_hex(text, "signing key")
+        .map(|secret| SigningKey::from_bytes(&secret))
+        .map_err(HqeError::Config)
+}
+
+/// Size and SHA-256 of `path`, recorded relative to `run_dir`
+pub(crate) async fn digest_file(run_dir: &Path, path: &Path) -> std::io::Result<ArtifactDigest> {
+    let bytes = tokio::fs::read(path).await?;
+    let relative = path.strip_prefix(run_dir).unwrap_or(path);
+    Ok(ArtifactDigest {
+        path: relative.to_string_lossy().replace('\\', "/"),
+        size: bytes.len() as u64,
+        sha256: hex::encode(Sha256::digest(&bytes)),
+    })
+}
+
+/// Outcome of checking [`SIGNATURE_FILE`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// The run was not signed
+    Unsigned,
+    /// The signature matches the manifest; hex key of the signer
+    Valid(String),
+    /// The signature file is unreadable or does not match the manifest
+    Invalid(String),
+}
+
+/// Result of [`verify_run_dir`]
+#[derive(Debug, Clone)]
+pub struct VerifyReport {
+    /// Files whose size and checksum still match
+    pub verified: Vec<String>,
+    /// Files whose contents changed
+    pub modified: Vec<String>,
+    /// Files listed in the manifest that no longer exist
+    pub missing: Vec<String>,
+    /// Files in the run directory the manifest does not cover, such as
+    /// checkpoints or later exports
+    pub unlisted: Vec<String>,
+    /// Signature check
+    pub signature: SignatureStatus,
+}
+
+impl VerifyReport {
+    /// No artifact diverges and any signature is valid
+    pub fn is_intact(&self) -> bool {
+        self.modified.is_empty()
+            && self.missing.is_empty()
+            && !matches!(self.signature, SignatureStatus::Invalid(_))
+    }
+}
+
+/// Recompute the checksums recorded in a run directory's manifest and check
+/// its signature, if any.
+///
+/// Fails when the manifest is missing, unparsable or records no checksums
+/// (runs written before checksums were added).
+pub async fn verify_run_dir(run_dir: impl AsRef<Path>) -> Result<VerifyReport, HqeError> {
+    let run_dir = run_dir.as_ref();
+    let manifest_path = run_dir.join(MANIFEST_FILE);
+    let manifest_bytes = tokio::fs::read(&manifest_path).await.map_err(|e| {
+        HqeError::Artifacts(format!("Cannot read {}: {e}", manifest_path.display()))
+    })?;
+    let manifest: RunManifest = serde_json::from_slice(&manifest_bytes).map_err(|e| {
+        HqeError::Artifacts(format!("Invalid manifest {}: {e}", manifest_path.display()))
+    })?;
+    if manifest.artifacts.is_empty() {
+        return Err(HqeError::Artifacts(format!(
+            "{} records no artifact checksums",
+            manifest_path.display()
+        )));
+    }
+
+    let mut report = VerifyReport {
+        verified: Vec::new(),
+        modified: Vec::new(),
+        missing: Vec::new(),
+        unlisted: Vec::new(),
+        signature: SignatureStatus::Unsigned,
+    };
+    for expected in &manifest.artifacts {
+        let path = run_dir.join(&expected.path);
+        // Paths come from the manifest, which may itself be tampered with
+        if Path::new(&expected.path).is_absolute() || expected.path.split('/').any(|c| c == "..") {
+            report.modified.push(expected.path.clone());
+            continue;
+        }
+        match digest_file(run_dir, &path).await {
+            Ok(actual) if actual == *expected => report.verified.push(expected.path.clone()),
+            Ok(_) => report.modified.push(expected.path.clone()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                report.missing.push(expected.path.clone())
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    let listed: Vec<&str> = manifest.artifacts.iter().map(|a| a.path.as_str()).collect();
+    for file in files_under(run_dir)? {
+        let relative = file.strip_prefix(run_dir).unwrap_or(&file);
+        let relative = relative.to_string_lossy().replace('\\', "/");
+        if relative != MANIFEST_FILE && relative != SIGNATURE_FILE && !listed.contains(&&*relative)
+        {
+            report.unlisted.push(relative);
+        }
+    }
+    report.unlisted.sort();
+
+    let signature_path = run_dir.join(SIGNATURE_FILE);
+    report.signature = match tokio::fs::read(&signature_path).await {
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => SignatureStatus::Unsigned,
+        Err(e) => SignatureStatus::Invalid(format!("cannot read {SIGNATURE_FILE}: {e}")),
+        Ok(bytes) => match serde_json::from_slice::<ManifestSignature>(&bytes) {
+            Err(e) => SignatureStatus::Invalid(format!("invalid {SIGNATURE_FILE}: {e}")),
+            Ok(signature) => match signature.verify(&manifest_bytes) {
+                Ok(()) => SignatureStatus::Valid(signature.public_key),
+                Err(reason) => SignatureStatus::Invalid(reason),
+            },
+        },
+    };
+    Ok(report)
+}
+
+fn files_under(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                pending.push(entry.path());
+            } else if file_type.is_file() {
+                files.push(entry.path());
+            }
+        }
+    }
+    Ok(files)
+}
@@ -0,0 +1,165 @@
+//! JUnit XML export for CI dashboards (Jenkins, GitLab).
+//!
+//! Each report category becomes a `<testsuite>`. Every finding is a failing
+//! `<testcase>` whose `<failure>` carries severity, location and
+//! recommendation; a category without findings gets one passing testcase so
+//! dashboards show it as green rather than missing. Baseline-suppressed
+//! findings are reported as skipped.
+//!
+//! Titles and recommendations come from the LLM, so all text is escaped and
+//! characters XML 1.0 cannot represent are dropped.
+
+use hqe_core::models::{Evidence, Finding};
+use hqe_core::scan::ScanResult;
+use std::fmt::Write;
+
+/// Name of the passing testcase of a category without findings
+const CLEAN_TESTCASE: &str = "No findings";
+
+struct Suite<'a> {
+    name: &'static str,
+    findings: &'a [Finding],
+    skipped: bool,
+}
+
+/// Builds the JUnit XML document for a scan
+pub(crate) fn build_junit_xml(result: &ScanResult) -> String {
+    let report = &result.report;
+    let scan = &report.deep_scan_results;
+    let mut suites = vec![
+        Suite {
+            name: "Security",
+            findings: &scan.security,
+            skipped: false,
+        },
+        Suite {
+            name: "Code Quality",
+            findings: &scan.code_quality,
+            skipped: false,
+        },
+        Suite {
+            name: "Frontend",
+            findings: &scan.frontend,
+            skipped: false,
+        },
+        Suite {
+            name: "Backend",
+            findings: &scan.backend,
+            skipped: false,
+        },
+        Suite {
+            name: "Testing",
+            findings: &scan.testing,
+            skipped: false,
+        },
+    ];
+    if !report.suppressed_findings.is_empty() {
+        suites.push(Suite {
+            name: "Baseline",
+            findings: &report.suppressed_findings,
+            skipped: true,
+        });
+    }
+
+    let timestamps = &result.manifest.timestamps;
+    let timestamp = timestamps.started.format("%Y-%m-%dT%H:%M:%S").to_string();
+    let time = timestamps
+        .ended
+        .map(|ended| (ended - timestamps.started).num_milliseconds().max(0) as f64 / 1000.0)
+        .unwrap_or(0.0);
+
+    let count = |suite: &Suite| suite.findings.len().max(1);
+    let tests: usize = suites.iter().map(count).sum();
+    let failures: usize = suites
+        .iter()
+        .filter(|s| !s.skipped)
+        .map(|s| s.findings.len())
+        .sum();
+    let skipped = report.suppressed_findings.len();
+
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<testsuites name=\"HQE Workbench\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"{skipped}\" time=\"{time:.3}\" timestamp=\"{timestamp}\">"
+    );
+
+    for suite in &suites {
+        let (suite_failures, suite_skipped) = if suite.skipped {
+            (0, suite.findings.len())
+        } else {
+            (suite.findings.len(), 0)
+        };
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{}\" failures=\"{suite_failures}\" errors=\"0\" skipped=\"{suite_skipped}\" time=\"0\" timestamp=\"{timestamp}\">",
+            xml_escape(suite.name),
+            count(suite),
+        );
+
+        if suite.findings.is_empty() {
+            let _ = writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{CLEAN_TESTCASE}\" time=\"0\"/>",
+                xml_escape(suite.name)
+            );
+        }
+        for finding in suite.findings {
+            let _ = writeln!(
+                xml,
+                "    <testcase classname=\"{}\" name=\"{}\" time=\"0\">",
+                xml_escape(&finding.category),
+                xml_escape(&format!("{}: {}", finding.id, finding.title)),
+            );
+            if suite.skipped {
+                xml.push_str("      <skipped message=\"Accepted in baseline\"/>\n");
+            } else {
+                let _ = writeln!(
+                    xml,
+                    "      <failure message=\"{}\" type=\"{}\">{}</failure>",
+                    xml_escape(&format!("[{}] {}", finding.severity, finding.title)),
+                    finding.severity,
+                    xml_escape(&failure_text(finding)),
+                );
+            }
+            xml.push_str("    </testcase>\n");
+        }
+        xml.push_str("  </testsuite>\n");
+    }
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Body of a `<failure>` element
+fn failure_text(finding: &Finding) -> String {
+    let mut text = format!("Severity: {}\nRisk: {}\n", finding.severity, finding.risk);
+    match &finding.evidence {
+        Evidence::FileLine { file, line, .. } => {
+            let _ = write!(text, "File: {file}\nLine: {line}\n");
+        }
+        Evidence::FileFunction { file, function, .. } => {
+            let _ = write!(text, "File: {file}\nFunction: {function}\n");
+        }
+        Evidence::Reproduction { .. } => {}
+    }
+    let _ = write!(text, "Recommendation: {}", finding.recommendation);
+    text
+}
+
+/// Escapes text for XML attributes and content, dropping characters XML 1.0
+/// does not allow
+fn xml_escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' || c == '\u{FFFE}' || c == '\u{FFFF}' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
@@ -2,6 +2,22 @@
 
 #![warn(missing_docs)]
 
+mod html;
+mod integrity;
+mod junit;
+mod render;
+mod sarif;
+mod sbom;
+mod template;
+
+pub use ed25519_dalek::SigningKey;
+pub use integrity::{
+    generate_signing_key, signing_key_from_hex, verify_run_dir, ManifestSignature, SignatureStatus,
+    VerifyReport, MANIFEST_FILE, SIGNATURE_FILE,
+};
+pub use render::{escape_table_cell, DefaultRenderer, ReportRenderer, DEFAULT_MAX_CELL_CHARS};
+pub use template::{TemplateRenderer, DEFAULT_REPORT_TEMPLATE};
+
 use hqe_core::models::*;
 use hqe_core::scan::ScanResult;
 use std::collections::HashMap;
@@ -11,6 +27,13 @@ use tracing::{info, instrument};
 /// Artifact writer handles saving reports and manifests to disk
 pub struct ArtifactWriter {
     output_dir: PathBuf,
+    sarif: bool,
+    html: bool,
+    backlog_csv: bool,
+    junit: bool,
+    sbom: bool,
+    renderer: Box<dyn ReportRenderer>,
+    signing_key: Option<SigningKey>,
 }
 
 impl ArtifactWriter {
@@ -18,9 +41,50 @@ impl ArtifactWriter {
     pub fn new(output_dir: impl AsRef<Path>) -> Self {
         Self {
             output_dir: output_dir.as_ref().to_path_buf(),
+            sarif: false,
+            html: false,
+            backlog_csv: false,
+            junit: false,
+            sbom: false,
+            renderer: Box::new(TemplateRenderer::default()),
+            signing_key: None,
         }
     }
 
+    /// Render `report.md` and `report.html` with a custom [`ReportRenderer`]
+    pub fn with_renderer(mut self, renderer: impl ReportRenderer + 'static) -> Self {
+        self.renderer = Box::new(renderer);
+        self
+    }
+
+    /// Render `report.md` and `report.html` from a MiniJinja template file
+    /// instead of [`DEFAULT_REPORT_TEMPLATE`].
+    ///
+    /// Fails with [`hqe_core::HqeError::Artifacts`] when the file cannot be
+    /// read or does not parse.
+    pub fn with_template(self, path: impl AsRef<Path>) -> hqe_core::Result<Self> {
+        Ok(self.with_renderer(TemplateRenderer::from_path(path)?))
+    }
+
+    /// Also write `report.sarif` from [`ArtifactWriter::write_all`]
+    pub fn with_sarif(mut self, enabled: bool) -> Self {
+        self.sarif = enabled;
+        self
+    }
+
+    /// Also write `junit.xml` from [`ArtifactWriter::write_all`]
+    pub fn with_junit(mut self, enabled: bool) -> Self {
+        self.junit = enabled;
+        self
+    }
+
+    /// Sign `run-manifest.json` with `key` in [`ArtifactWriter::write_all`],
+    /// writing the signature to [`SIGNATURE_FILE`]
+    pub fn with_signing_key(mut self, key: SigningKey) -> Self {
+        self.signing_key = Some(key);
+        self
+    }
+
     /// Ensure output directory exists
     fn ensure_dir(&self) -> anyhow::Result<()> {
         std::fs::create_dir_all(&self.output_dir)?;
@@ -54,12 +118,168 @@ impl ArtifactWriter {
     pub async fn write_report_md(&self, report: &HqeReport) -> anyhow::Result<PathBuf> {
         self.ensure_dir()?;
         let path = self.output_dir.join("report.md");
-        let md = self.render_markdown(report)?;
+        let md = self.renderer.try_render(report)?;
         tokio::fs::write(&path, md).await?;
         info!("Wrote report Markdown: {}", path.display());
         Ok(path)
     }
 
+    /// Also write `report.html` from [`ArtifactWriter::write_all`]
+    pub fn with_html(mut self, enabled: bool) -> Self {
+        self.html = enabled;
+        self
+    }
+
+    /// Also write `backlog.csv` from [`ArtifactWriter::write_all`]
+    pub fn with_backlog_csv(mut self, enabled: bool) -> Self {
+        self.backlog_csv = enabled;
+        self
+    }
+
+    /// Write the master TODO backlog as CSV, most severe items first
+    #[instrument(skip(self, report))]
+    pub async fn write_backlog_csv(&self, report: &HqeReport) -> anyhow::Result<PathBuf> {
+        self.ensure_dir()?;
+        let path = self.output_dir.join("backlog.csv");
+
+        let mut todos: Vec<&TodoItem> = report.master_todo_backlog.iter().collect();
+        // Stable sort keeps report order within equal severity and risk
+        todos.sort_by(|a, b| {
+            (b.severity.rank(), b.risk.rank()).cmp(&(a.severity.rank(), a.risk.rank()))
+        });
+
+        let rows = todos.into_iter().map(|todo| {
+            let (file, line) = evidence_location(&todo.evidence);
+            [
+                todo.id.clone(),
+                todo.severity.to_string(),
+                todo.risk.to_string(),
+                todo.category.to_string(),
+                todo.title.clone(),
+                file,
+                line,
+                todo.fix_approach.clone(),
+            ]
+        });
+
+        tokio::fs::write(&path, csv_document(rows)).await?;
+        info!("Wrote backlog CSV: {}", path.display());
+        Ok(path)
+    }
+
+    /// Write the deep scan findings as CSV, most severe first.
+    ///
+    /// Findings suppressed by a baseline are left out, as in the report.
+    #[instrument(skip(self, report))]
+    pub async fn write_findings_csv(&self, report: &HqeReport) -> anyhow::Result<PathBuf> {
+        self.ensure_dir()?;
+        let path = self.output_dir.join("findings.csv");
+
+        let mut findings: Vec<&Finding> = report.deep_scan_results.iter().collect();
+        findings.sort_by(|a, b| {
+            (b.severity.rank(), b.risk.rank()).cmp(&(a.severity.rank(), a.risk.rank()))
+        });
+
+        let rows = findings.into_iter().map(|finding| {
+            let (file, line) = evidence_location(&finding.evidence);
+            [
+                finding.id.clone(),
+                finding.severity.to_string(),
+                finding.risk.to_string(),
+                finding.category.clone(),
+                finding.title.clone(),
+                file,
+                line,
+                finding.recommendation.clone(),
+            ]
+        });
+
+        tokio::fs::write(&path, csv_document(rows)).await?;
+        info!("Wrote findings CSV: {}", path.display());
+        Ok(path)
+    }
+
+    /// Write report as a standalone HTML page (same sections as the Markdown)
+    #[instrument(skip(self, report))]
+    pub async fn write_report_html(&self, report: &HqeReport) -> anyhow::Result<PathBuf> {
+        self.ensure_dir()?;
+        let path = self.output_dir.join("report.html");
+        let markdown = self.renderer.try_render(report)?;
+        let page = html::render_html(report, &markdown);
+        tokio::fs::write(&path, page).await?;
+        info!("Wrote report HTML: {}", path.display());
+        Ok(path)
+    }
+
+    /// Write report as SARIF 2.1.0 for CI code scanning
+    #[instrument(skip(self, report))]
+    pub async fn write_report_sarif(&self, report: &HqeReport) -> anyhow::Result<PathBuf> {
+        self.write_sarif_log(report, &[], None).await
+    }
+
+    /// Write `report.sarif` for a scan, including its local heuristic findings.
+    ///
+    /// Locations are made relative to the scanned repository, as GitHub code
+    /// scanning requires.
+    #[instrument(skip(self, result))]
+    pub async fn write_sarif(&self, result: &ScanResult) -> anyhow::Result<PathBuf> {
+        self.write_sarif_log(
+            &result.report,
+            &result.local_findings,
+            Some(&result.manifest.repo.path),
+        )
+        .await
+    }
+
+    async fn write_sarif_log(
+        &self,
+        report: &HqeReport,
+        local_findings: &[LocalFinding],
+        repo_root: Option<&str>,
+    ) -> anyhow::Result<PathBuf> {
+        self.ensure_dir()?;
+        let path = self.output_dir.join("report.sarif");
+        let log = sarif::build_sarif_log(report, local_findings, repo_root);
+        let json = serde_json::to_string_pretty(&log)?;
+        tokio::fs::write(&path, json).await?;
+        info!("Wrote report SARIF: {}", path.display());
+        Ok(path)
+    }
+
+    /// Write the findings of a scan as JUnit XML for CI dashboards.
+    ///
+    /// Each finding is a failing testcase in the suite of its category;
+    /// categories without findings get one passing testcase.
+    #[instrument(skip(self, result))]
+    pub async fn write_junit_xml(&self, result: &ScanResult) -> anyhow::Result<PathBuf> {
+        self.ensure_dir()?;
+        let path = self.output_dir.join("junit.xml");
+        tokio::fs::write(&path, junit::build_junit_xml(result)).await?;
+        info!("Wrote JUnit XML: {}", path.display());
+        Ok(path)
+    }
+
+    /// Also write `sbom.cdx.json` in `write_all`
+    pub fn with_sbom(mut self, enabled: bool) -> Self {
+        self.sbom = enabled;
+        self
+    }
+
+    /// Write the dependencies the scan found as a CycloneDX 1.5 JSON SBOM,
+    /// `sbom.cdx.json`.
+    ///
+    /// Each component has its name, version and purl, with the manifests and
+    /// lockfiles it was read from as evidence.
+    #[instrument(skip(self, result))]
+    pub async fn write_sbom_cyclonedx(&self, result: &ScanResult) -> anyhow::Result<PathBuf> {
+        self.ensure_dir()?;
+        let path = self.output_dir.join("sbom.cdx.json");
+        let json = serde_json::to_string_pretty(&sbom::build_bom(result))?;
+        tokio::fs::write(&path, json).await?;
+        info!("Wrote CycloneDX SBOM: {}", path.display());
+        Ok(path)
+    }
+
     /// Write session log
     #[instrument(skip(self, session_log))]
     pub async fn write_session_log(&self, session_log: &SessionLog) -> anyhow::Result<PathBuf> {
@@ -78,16 +298,33 @@ impl ArtifactWriter {
         let path = self.output_dir.join("redaction-log.json");
 
         #[derive(serde::Serialize)]
-        struct RedactionLog {
+        struct RedactionLog<'a> {
             total_redactions: usize,
             by_type: HashMap<String, usize>,
+            #[serde(skip_serializing_if = "<[LeakWarning]>::is_empty")]
+            leak_warnings: &'a [LeakWarning],
+            #[serde(skip_serializing_if = "is_zero")]
+            suppressed_findings: usize,
+            #[serde(skip_serializing_if = "HashMap::is_empty")]
+            pii_by_type: HashMap<String, usize>,
             note: &'static str,
         }
 
         let log = RedactionLog {
             total_redactions: summary.total_redactions,
             by_type: summary.by_type.clone(),
-            note: "Secret values removed before LLM transmission",
+            leak_warnings: &summary.leak_warnings,
+            suppressed_findings: summary.suppressed_findings,
+            pii_by_type: summary.pii_by_type.clone(),
+            note: match (
+                summary.pii_by_type.is_empty(),
+                summary.leak_warnings.is_empty(),
+            ) {
+                (true, true) => "Secret values removed before LLM transmission",
+                (true, false) => "Secret values removed before LLM transmission; leak_warnings lists secrets still detected after redaction",
+                (false, true) => "Secret values and personal data removed before LLM transmission",
+                (false, false) => "Secret values and personal data removed before LLM transmission; leak_warnings lists secrets still detected after redaction",
+            },
         };
 
         let json = serde_json::to_string_pretty(&log)?;
@@ -96,223 +333,99 @@ impl ArtifactWriter {
         Ok(path)
     }
 
-    /// Write all artifacts (manifest, report JSON/MD, logs)
+    /// Write all artifacts (manifest, report JSON/MD, logs, and HTML/SARIF/CSV/JUnit/SBOM if enabled).
+    ///
+    /// The manifest is written last and records the size and SHA-256 of every
+    /// other file; see [`verify_run_dir`].
     pub async fn write_all(&self, result: &ScanResult) -> anyhow::Result<ArtifactPaths> {
-        let manifest = self.write_manifest(&result.manifest).await?;
         let report_json = self.write_report_json(&result.report).await?;
         let report_md = self.write_report_md(&result.report).await?;
-        self.write_session_log(&result.report.session_log).await?;
+        let report_html = if self.html {
+            Some(self.write_report_html(&result.report).await?)
+        } else {
+            None
+        };
+        let backlog_csv = if self.backlog_csv {
+            Some(self.write_backlog_csv(&result.report).await?)
+        } else {
+            None
+        };
+        let report_sarif = if self.sarif {
+            Some(self.write_sarif(result).await?)
+        } else {
+            None
+        };
+        let junit_xml = if self.junit {
+            Some(self.write_junit_xml(result).await?)
+        } else {
+            None
+        };
+        let sbom_cyclonedx = if self.sbom {
+            Some(self.write_sbom_cyclonedx(result).await?)
+        } else {
+            None
+        };
+        let session_log = self.write_session_log(&result.report.session_log).await?;
+        let redaction_log = if result.redaction.total_redactions > 0
+            || !result.redaction.leak_warnings.is_empty()
+            || result.redaction.suppressed_findings > 0
+            || !result.redaction.pii_by_type.is_empty()
+        {
+            Some(self.write_redaction_log(&result.redaction).await?)
+        } else {
+            None
+        };
+
+        // The manifest goes last so it can vouch for everything else
+        let mut manifest = result.manifest.clone();
+        let written = [&report_json, &report_md, &session_log]
+            .into_iter()
+            .chain(&report_html)
+            .chain(&backlog_csv)
+            .chain(&report_sarif)
+            .chain(&junit_xml)
+            .chain(&sbom_cyclonedx)
+            .chain(&redaction_log);
+        for path in written {
+            manifest
+                .artifacts
+                .push(integrity::digest_file(&self.output_dir, path).await?);
+        }
+        manifest.artifacts.sort_by(|a, b| a.path.cmp(&b.path));
+        let manifest_json = self.write_manifest(&manifest).await?;
+        let manifest_signature = self.write_manifest_signature(&manifest_json).await?;
 
         Ok(ArtifactPaths {
-            manifest_json: manifest,
+            manifest_json,
+            manifest_signature,
             report_json,
             report_md,
+            report_html,
+            report_sarif,
+            backlog_csv,
+            junit_xml,
+            sbom_cyclonedx,
         })
     }
 
-    /// Render report as Markdown (HQE v3 format)
-    fn render_markdown(&self, report: &HqeReport) -> anyhow::Result<String> {
-        let mut md = String::new();
-
-        // Header
-        md.push_str("# HQE Engineer Report\n\n");
-        md.push_str(&format!("Run ID: `{}`\n\n", report.run_id));
-
-        // Section 1: Executive Summary
-        md.push_str("## 1. Executive Summary\n\n");
-        md.push_str(&format!(
-            "**Health Score:** {}/10\n\n",
-            report.executive_summary.health_score
-        ));
-
-        if !report.executive_summary.critical_findings.is_empty() {
-            md.push_str("### Critical Findings\n\n");
-            for finding in &report.executive_summary.critical_findings {
-                md.push_str(&format!("- 🚨 {}\n", finding));
-            }
-            md.push('\n');
-        }
-
-        if !report.executive_summary.top_priorities.is_empty() {
-            md.push_str("### Top Priorities\n\n");
-            for priority in &report.executive_summary.top_priorities {
-                md.push_str(&format!("- {}\n", priority));
-            }
-            md.push('\n');
-        }
-
-        if !report.executive_summary.blockers.is_empty() {
-            md.push_str("### Blockers\n\n");
-            for blocker in &report.executive_summary.blockers {
-                md.push_str(&format!("- **{}**\n", blocker.description));
-                md.push_str(&format!("  - Reason: {}\n", blocker.reason));
-                md.push_str(&format!("  - How to obtain: {}\n", blocker.how_to_obtain));
-            }
-            md.push('\n');
-        }
-
-        // Section 2: Project Map
-        md.push_str("## 2. Project Map\n\n");
-
-        md.push_str("### Architecture\n\n");
-        md.push_str(&format!(
-            "**Languages:** {}\n\n",
-            report.project_map.architecture.languages.join(", ")
-        ));
-
-        if !report.project_map.entrypoints.is_empty() {
-            md.push_str("### Entrypoints\n\n");
-            md.push_str("| File | Type | Description |\n");
-            md.push_str("|------|------|-------------|\n");
-            for ep in &report.project_map.entrypoints {
-                md.push_str(&format!(
-                    "| `{}` | {} | {} |\n",
-                    ep.file_path, ep.entry_type, ep.description
-                ));
-            }
-            md.push('\n');
-        }
-
-        if !report.project_map.tech_stack.detected.is_empty() {
-            md.push_str("### Tech Stack\n\n");
-            for tech in &report.project_map.tech_stack.detected {
-                md.push_str(&format!(
-                    "- **{}** (evidence: {})\n",
-                    tech.name, tech.evidence
-                ));
-            }
-            md.push('\n');
-        }
-
-        // Section 3: PR Harvest (if present)
-        if let Some(pr_harvest) = &report.pr_harvest {
-            md.push_str("## 3. PR Harvest\n\n");
-            if !pr_harvest.inventory.is_empty() {
-                md.push_str("| PR | Title | Status | Recommendation |\n");
-                md.push_str("|----|-------|--------|----------------|\n");
-                for pr in &pr_harvest.inventory {
-                    md.push_str(&format!(
-                        "| {} | {} | {} | {:?} |\n",
-                        pr.pr_id, pr.title, pr.status, pr.recommendation
-                    ));
-                }
-                md.push('\n');
-            }
-        }
-
-        // Section 4: Deep Scan Results
-        md.push_str("## 4. Deep Scan Results\n\n");
-
-        if !report.deep_scan_results.security.is_empty() {
-            md.push_str("### Security\n\n");
-            self.render_findings(&mut md, &report.deep_scan_results.security);
-        }
-
-        if !report.deep_scan_results.code_quality.is_empty() {
-            md.push_str("### Code Quality\n\n");
-            self.render_findings(&mut md, &report.deep_scan_results.code_quality);
-        }
-
-        // Section 5: Master TODO Backlog
-        md.push_str("## 5. Master TODO Backlog\n\n");
-        md.push_str("| ID | Severity | Risk | Category | Title |\n");
-        md.push_str("|----|----------|------|----------|-------|\n");
-        for todo in &report.master_todo_backlog {
-            md.push_str(&format!(
-                "| {} | {} | {} | {} | {} |\n",
-                todo.id, todo.severity, todo.risk, todo.category, todo.title
-            ));
-        }
-        md.push('\n');
-
-        // Section 6: Implementation Plan
-        md.push_str("## 6. Implementation Plan\n\n");
-
-        if !report.implementation_plan.immediate.is_empty() {
-            md.push_str("### Immediate (Do Now)\n\n");
-            for item in &report.implementation_plan.immediate {
-                md.push_str(&format!("- [ ] {}\n", item));
-            }
-            md.push('\n');
-        }
-
-        if !report.implementation_plan.short_term.is_empty() {
-            md.push_str("### Short-term (This Week)\n\n");
-            for item in &report.implementation_plan.short_term {
-                md.push_str(&format!("- [ ] {}\n", item));
-            }
-            md.push('\n');
-        }
-
-        // Section 7: Immediate Actions
-        md.push_str("## 7. Immediate Actions\n\n");
-        if report.immediate_actions.is_empty() {
-            md.push_str("No immediate actions generated.\n\n");
-        } else {
-            for action in &report.immediate_actions {
-                md.push_str(&format!("### {}: {}\n\n", action.todo_id, action.title));
-                md.push_str(&format!("**Problem:** {}\n\n", action.problem));
-                md.push_str(&format!("**Risk:** {}\n\n", action.risk));
-                if action.behavior_change {
-                    md.push_str("⚠️ **BEHAVIOR CHANGE**\n\n");
-                }
-                for diff in &action.diffs {
-                    md.push_str(&format!("#### File: `{}`\n\n", diff.file_path));
-                    md.push_str("```diff\n");
-                    md.push_str(&diff.diff_content);
-                    md.push_str("\n```\n\n");
-                }
-                md.push_str("**Verification:**\n");
-                for step in &action.verification {
-                    md.push_str(&format!("1. Run: `{}`\n", step.command));
-                    md.push_str(&format!("   Expected: {}\n", step.expected_output));
-                }
-                md.push('\n');
-            }
-        }
-
-        // Section 8: Session Log
-        md.push_str("## 8. Session Log\n\n");
-
-        if !report.session_log.completed.is_empty() {
-            md.push_str("### Completed\n\n");
-            for item in &report.session_log.completed {
-                md.push_str(&format!("- ✅ {}\n", item));
-            }
-            md.push('\n');
-        }
-
-        if !report.session_log.in_progress.is_empty() {
-            md.push_str("### In Progress\n\n");
-            for item in &report.session_log.in_progress {
-                md.push_str(&format!("- 🔄 {}\n", item));
-            }
-            md.push('\n');
-        }
-
-        if !report.session_log.discovered.is_empty() {
-            md.push_str("### Discovered\n\n");
-            for item in &report.session_log.discovered {
-                md.push_str(&format!("- 🆕 {}\n", item));
+    /// Sign the manifest as written, or remove a signature left over from an
+    /// earlier write so it cannot vouch for the new manifest
+    async fn write_manifest_signature(
+        &self,
+        manifest_path: &Path,
+    ) -> anyhow::Result<Option<PathBuf>> {
+        let path = self.output_dir.join(SIGNATURE_FILE);
+        let Some(key) = &self.signing_key else {
+            match tokio::fs::remove_file(&path).await {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => return Ok(None),
             }
-            md.push('\n');
-        }
-
-        Ok(md)
-    }
-
-    fn render_findings(&self, md: &mut String, findings: &[Finding]) {
-        for finding in findings {
-            md.push_str(&format!("#### {}: {}\n\n", finding.id, finding.title));
-            md.push_str(&format!("- **Severity:** {}\n", finding.severity));
-            md.push_str(&format!("- **Risk:** {}\n", finding.risk));
-            md.push_str(&format!("- **Impact:** {}\n", finding.impact));
-            md.push_str(&format!(
-                "- **Recommendation:** {}\n",
-                finding.recommendation
-            ));
-            md.push('\n');
-        }
+        };
+        let manifest = tokio::fs::read(manifest_path).await?;
+        let signature = ManifestSignature::sign(&manifest, key);
+        tokio::fs::write(&path, serde_json::to_string_pretty(&signature)?).await?;
+        info!("Wrote manifest signature: {}", path.display());
+        Ok(Some(path))
     }
 }
 
@@ -321,15 +434,82 @@ impl ArtifactWriter {
 pub struct ArtifactPaths {
     /// Path to manifest.json
     pub manifest_json: PathBuf,
+    /// Path to the manifest signature, if a signing key was set
+    pub manifest_signature: Option<PathBuf>,
     /// Path to report.json
     pub report_json: PathBuf,
     /// Path to report.md
     pub report_md: PathBuf,
+    /// Path to report.html, if HTML export was enabled
+    pub report_html: Option<PathBuf>,
+    /// Path to report.sarif, if SARIF export was enabled
+    pub report_sarif: Option<PathBuf>,
+    /// Path to backlog.csv, if CSV export was enabled
+    pub backlog_csv: Option<PathBuf>,
+    /// Path to junit.xml, if JUnit export was enabled
+    pub junit_xml: Option<PathBuf>,
+    /// Path to sbom.cdx.json, if SBOM export was enabled
+    pub sbom_cyclonedx: Option<PathBuf>,
+}
+
+fn is_zero(n: &usize) -> bool {
+    *n == 0
+}
+
+/// Columns of `backlog.csv` and `findings.csv`
+const CSV_COLUMNS: [&str; 8] = [
+    "id",
+    "severity",
+    "risk",
+    "category",
+    "title",
+    "file",
+    "line",
+    "recommendation",
+];
+
+/// Header plus one CRLF-terminated record per row
+fn csv_document(rows: impl Iterator<Item = [String; 8]>) -> String {
+    let mut csv = CSV_COLUMNS.join(",");
+    csv.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = row.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// File and line columns for a piece of evidence; empty when unknown
+fn evidence_location(evidence: &Evidence) -> (String, String) {
+    match evidence {
+        Evidence::FileLine { file, line, .. } => (file.clone(), line.to_string()),
+        Evidence::FileFunction { file, .. } => (file.clone(), String::new()),
+        Evidence::Reproduction { .. } => (String::new(), String::new()),
+    }
+}
+
+/// Quote a CSV field (RFC 4180) when it contains a delimiter, quote or line break.
+///
+/// Values starting with a formula trigger are prefixed with `'` so spreadsheets
+/// do not evaluate model-generated text.
+fn csv_field(value: &str) -> String {
+    let value = if value.starts_with(['=', '+', '-', '@']) {
+        format!("'{value}")
+    } else {
+        value.to_string()
+    };
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use hqe_core::advisories::Ecosystem;
     use tempfile::TempDir;
 
     fn create_test_report() -> HqeReport {
@@ -352,6 +532,20 @@ mod tests {
                 entrypoints: vec![],
                 data_flow: None,
                 tech_stack: TechStack::default(),
+                code_stats: CodeStats {
+                    languages: [(
+                        "Rust".to_string(),
+                        LanguageStats {
+                            files: 3,
+                            code_lines: 120,
+                            comment_lines: 30,
+                            blank_lines: 12,
+                        },
+                    )]
+                    .into(),
+                },
+                licenses: vec![],
+                hygiene: RepoHygiene::default(),
             },
             pr_harvest: None,
             deep_scan_results: DeepScanResults::default(),
@@ -359,6 +553,8 @@ mod tests {
             implementation_plan: ImplementationPlan::default(),
             immediate_actions: vec![],
             session_log: SessionLog::default(),
+            suppressed_findings: vec![],
+            baseline: None,
         }
     }
 
@@ -389,6 +585,583 @@ mod tests {
         assert!(content.contains("HQE Engineer Report"));
         assert!(content.contains("Executive Summary"));
         assert!(content.contains("Project Map"));
+        assert!(content.contains("| Rust | 3 | 120 | 30 | 12 |"));
+        Ok(())
+    }
+
+    struct BrandedRenderer;
+
+    impl ReportRenderer for BrandedRenderer {
+        fn render_header(&self, md: &mut String, report: &HqeReport) {
+            md.push_str(
+                "# ACME Compliance Review
+
+",
+            );
+            DefaultRenderer.render_header(md, report);
+        }
+
+        fn render_session_log(&self, _md: &mut String, _report: &HqeReport) {}
+    }
+
+    #[tokio::test]
+    async fn test_write_report_md_custom_renderer() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path()).with_renderer(BrandedRenderer);
+
+        let report = create_test_report();
+        let content = tokio::fs::read_to_string(writer.write_report_md(&report).await?).await?;
+        assert!(content.starts_with("# ACME Compliance Review\n\n# HQE Engineer Report"));
+        assert!(content.contains("## 7. Immediate Actions"));
+        assert!(!content.contains("Session Log"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_report_md_with_template() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let template = temp.path().join("summary.md.j2");
+        std::fs::write(
+            &template,
+            "# {{ report.run_id }}\n\nHealth: {{ report.executive_summary.health_score }}/10\n",
+        )?;
+        let writer = ArtifactWriter::new(temp.path().join("out")).with_template(&template)?;
+
+        let report = create_test_report();
+        let content = tokio::fs::read_to_string(writer.write_report_md(&report).await?).await?;
+        assert_eq!(content, "# test-123\n\nHealth: 7/10\n");
+
+        std::fs::write(&template, "{% for x in %}\n")?;
+        let err = ArtifactWriter::new(temp.path())
+            .with_template(&template)
+            .err()
+            .expect("syntax error");
+        assert!(matches!(err, hqe_core::HqeError::Artifacts(_)));
+        assert!(err.to_string().contains("summary.md.j2:1:"), "{err}");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_report_md_escapes_table_cells() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path()).with_html(true);
+
+        let mut report = create_test_report();
+        report.project_map.entrypoints.push(Entrypoint {
+            file_path: "src/`odd`|name.rs".to_string(),
+            entry_type: "cli".to_string(),
+            description: "Parses a|b\r\nthen runs".to_string(),
+        });
+        report.pr_harvest = Some(PrHarvest {
+            inventory: vec![PrInfo {
+                pr_id: "#7".to_string(),
+                title: "Use `a | b` pipes\nin shell".to_string(),
+                status: "open".to_string(),
+                intent: String::new(),
+                files_touched: vec![],
+                risk: RiskLevel::Low,
+                recommendation: PrRecommendation::Accept,
+            }],
+            conflicts: vec![],
+        });
+        report.master_todo_backlog = vec![todo(
+            "BUG-1",
+            Severity::High,
+            RiskLevel::Low,
+            "Split | on\nnewlines",
+        )];
+
+        let md = tokio::fs::read_to_string(writer.write_report_md(&report).await?).await?;
+        assert!(md.contains("| `` src/`odd`\\|name.rs `` | cli | Parses a\\|b<br>then runs |\n"));
+        assert!(
+            md.contains("| #7 | Use `a \\| b` pipes<br>in shell | open | Low | Accept |  |  |\n")
+        );
+        assert!(md.contains("| BUG-1 | High | Low | BUG | Split \\| on<br>newlines |\n"));
+        assert!(!md.contains("report.json"));
+
+        let html = tokio::fs::read_to_string(writer.write_report_html(&report).await?).await?;
+        assert!(html.contains("<td>Split | on<br />\nnewlines</td>"));
+        Ok(())
+    }
+
+    struct NarrowRenderer;
+
+    impl ReportRenderer for NarrowRenderer {
+        fn max_cell_chars(&self) -> Option<usize> {
+            Some(12)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_report_md_truncates_long_cells() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let mut report = create_test_report();
+        let title = "Refactor the | retry loop in the HTTP client";
+        report.master_todo_backlog = vec![todo("BUG-1", Severity::Low, RiskLevel::Low, title)];
+
+        let narrow = ArtifactWriter::new(temp.path()).with_renderer(NarrowRenderer);
+        let md = tokio::fs::read_to_string(narrow.write_report_md(&report).await?).await?;
+        assert!(md.contains("| BUG-1 | Low | Low | BUG | Refactor th… |\n"));
+        assert!(md.contains("full text is in [report.json](report.json)"));
+
+        let default = ArtifactWriter::new(temp.path());
+        let md = tokio::fs::read_to_string(default.write_report_md(&report).await?).await?;
+        assert!(md.contains("Refactor the \\| retry loop in the HTTP client |"));
+        assert!(!md.contains("report.json"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_report_md_lists_baseline_results() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path());
+
+        let mut report = create_test_report();
+        report.suppressed_findings.push(Finding {
+            id: "SEC-002".to_string(),
+            severity: Severity::Critical,
+            risk: RiskLevel::High,
+            category: "Security".to_string(),
+            title: "Legacy hardcoded password".to_string(),
+            evidence: Evidence::FileLine {
+                file: "src/legacy.rs".to_string(),
+                line: 12,
+                snippet: String::new(),
+            },
+            impact: String::new(),
+            recommendation: String::new(),
+            suppressed: true,
+        });
+        report.baseline = Some(BaselineStatus {
+            suppressed: 1,
+            stale: vec![BaselineEntry {
+                fingerprint: "9f2c4e1a7b3d5f60".to_string(),
+                title: "Removed debug endpoint".to_string(),
+                file: Some("src/debug.rs".to_string()),
+            }],
+        });
+
+        let content = tokio::fs::read_to_string(writer.write_report_md(&report).await?).await?;
+        assert!(content.contains(
+            "### Suppressed by Baseline\n\n\
+             1 finding(s) matched the baseline and are not counted in the summary.\n\n\
+             #### SEC-002: Legacy hardcoded password"
+        ));
+        assert!(content.contains("- `9f2c4e1a7b3d5f60` Removed debug endpoint (src/debug.rs)"));
+        Ok(())
+    }
+
+    fn todo(id: &str, severity: Severity, risk: RiskLevel, title: &str) -> TodoItem {
+        TodoItem {
+            id: id.to_string(),
+            severity,
+            risk,
+            category: TodoCategory::Bug,
+            title: title.to_string(),
+            root_cause: String::new(),
+            evidence: Evidence::Reproduction {
+                steps: vec![],
+                observed: String::new(),
+            },
+            fix_approach: String::new(),
+            verify: String::new(),
+            blocked_by: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_write_backlog_csv() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path());
+
+        let mut report = create_test_report();
+        let mut parse = todo(
+            "BUG-2",
+            Severity::High,
+            RiskLevel::Low,
+            "Parse \"a, b\" inputs,\nthen retry",
+        );
+        parse.evidence = Evidence::FileLine {
+            file: "src/parse.rs".to_string(),
+            line: 42,
+            snippet: String::new(),
+        };
+        parse.fix_approach = "Split on commas, not spaces".to_string();
+        report.master_todo_backlog = vec![
+            todo("BUG-1", Severity::Low, RiskLevel::High, "Typo in log"),
+            parse,
+            todo("BUG-3", Severity::High, RiskLevel::High, "=HYPERLINK(x)"),
+        ];
+
+        let path = writer.write_backlog_csv(&report).await?;
+        assert_eq!(path, temp.path().join("backlog.csv"));
+        let csv = tokio::fs::read_to_string(&path).await?;
+
+        assert_eq!(
+            csv,
+            "id,severity,risk,category,title,file,line,recommendation\r\n\
+             BUG-3,High,High,BUG,'=HYPERLINK(x),,,\r\n\
+             BUG-2,High,Low,BUG,\"Parse \"\"a, b\"\" inputs,\nthen retry\",src/parse.rs,42,\"Split on commas, not spaces\"\r\n\
+             BUG-1,Low,High,BUG,Typo in log,,,\r\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_findings_csv() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path());
+
+        let finding = |id: &str, severity, evidence| Finding {
+            id: id.to_string(),
+            severity,
+            risk: RiskLevel::Medium,
+            category: "Security".to_string(),
+            title: format!("{id} title"),
+            evidence,
+            impact: String::new(),
+            recommendation: "Rotate it,\nthen audit".to_string(),
+            suppressed: false,
+        };
+        let mut report = create_test_report();
+        report.deep_scan_results.code_quality.push(finding(
+            "DX-001",
+            Severity::Low,
+            Evidence::FileFunction {
+                file: "src/lib.rs".to_string(),
+                function: "run".to_string(),
+                snippet: String::new(),
+            },
+        ));
+        report.deep_scan_results.security.push(finding(
+            "SEC-001",
+            Severity::Critical,
+            Evidence::FileLine {
+                file: "src/main.rs".to_string(),
+                line: 3,
+                snippet: String::new(),
+            },
+        ));
+
+        let path = writer.write_findings_csv(&report).await?;
+        assert_eq!(path, temp.path().join("findings.csv"));
+        assert_eq!(
+            tokio::fs::read_to_string(&path).await?,
+            "id,severity,risk,category,title,file,line,recommendation\r\n\
+             SEC-001,Critical,Medium,Security,SEC-001 title,src/main.rs,3,\"Rotate it,\nthen audit\"\r\n\
+             DX-001,Low,Medium,Security,DX-001 title,src/lib.rs,,\"Rotate it,\nthen audit\"\r\n"
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_report_html() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path());
+
+        let mut report = create_test_report();
+        report.deep_scan_results.security.push(Finding {
+            id: "SEC-001".to_string(),
+            severity: Severity::High,
+            risk: RiskLevel::High,
+            category: "Security".to_string(),
+            title: "Token in <script> tag".to_string(),
+            evidence: Evidence::FileLine {
+                file: "src/main.rs".to_string(),
+                line: 3,
+                snippet: String::new(),
+            },
+            impact: "Credential leak".to_string(),
+            recommendation: "Rotate the token".to_string(),
+            suppressed: false,
+        });
+        report.immediate_actions.push(PatchAction {
+            todo_id: "SEC-001".to_string(),
+            title: "Remove token".to_string(),
+            problem: "Hardcoded token".to_string(),
+            root_cause: String::new(),
+            risk: RiskLevel::Low,
+            behavior_change: false,
+            diffs: vec![FileDiff {
+                file_path: "src/main.rs".to_string(),
+                diff_content: "@@ -1 +1 @@\n-let t = \"abc\";\n+let t = env();".to_string(),
+            }],
+            verification: vec![],
+            rollback: String::new(),
+        });
+
+        let path = writer.write_report_html(&report).await?;
+        assert_eq!(path, temp.path().join("report.html"));
+        let html = tokio::fs::read_to_string(&path).await?;
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<span class=\"badge fair\">Health Score: 7/10</span>"));
+        let sections = [
+            "1. Executive Summary",
+            "2. Project Map",
+            "4. Deep Scan Results",
+            "5. Master TODO Backlog",
+            "6. Implementation Plan",
+            "7. Immediate Actions",
+            "8. Session Log",
+        ];
+        let mut last = 0;
+        for section in sections {
+            let heading = format!("<details class=\"h2\" open>\n<summary>{section}</summary>");
+            let idx = html
+                .find(&heading)
+                .ok_or_else(|| anyhow::anyhow!("missing section {section}"))?;
+            assert!(idx > last, "{section} out of order");
+            last = idx;
+        }
+        assert!(html.contains("<details class=\"h3\" open>\n<summary>Security</summary>"));
+        assert!(html.contains("Token in &lt;script&gt; tag"));
+        assert!(!html.contains("<script>"));
+        assert!(html.contains("<span class=\"del\">-let t = &quot;abc&quot;;</span>"));
+        assert!(html.contains("<span class=\"add\">+let t = env();</span>"));
+        assert_eq!(
+            html.matches("<details").count(),
+            html.matches("</details>").count()
+        );
+        // One High finding: the only full-width bar
+        assert!(html.contains("<title>Findings by severity</title>"));
+        assert!(html.contains("width=\"360\" height=\"18\" rx=\"3\" fill=\"#cf222e\""));
+        assert!(html.contains("width=\"0\" height=\"18\" rx=\"3\" fill=\"#82071e\""));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_report_html_neutralizes_links_and_images() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path());
+
+        let mut report = create_test_report();
+        report.executive_summary.top_priorities = vec![
+            "See [details](javascript:alert(1)) and [docs](https://example.com/a)".to_string(),
+            "![tracker](https://evil.example/pixel.png)".to_string(),
+        ];
+
+        let html = tokio::fs::read_to_string(writer.write_report_html(&report).await?).await?;
+        assert!(!html.to_lowercase().contains("javascript:"));
+        assert!(html.contains("<a href=\"#\">details</a>"));
+        assert!(html.contains("<a href=\"https://example.com/a\">docs</a>"));
+        assert!(!html.contains("<img"));
+        assert!(html.contains("<em>tracker</em>"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_report_sarif() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path()).with_sarif(true);
+
+        let report = create_test_report();
+        let path = writer.write_report_sarif(&report).await?;
+
+        assert_eq!(path, temp.path().join("report.sarif"));
+        let sarif: serde_json::Value =
+            serde_json::from_str(&tokio::fs::read_to_string(&path).await?)?;
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(sarif["runs"][0]["tool"]["driver"]["name"], "HQE Workbench");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_junit_xml() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path());
+
+        let finding = |id: &str, title: &str| Finding {
+            id: id.to_string(),
+            severity: Severity::High,
+            risk: RiskLevel::Medium,
+            category: "Security".to_string(),
+            title: title.to_string(),
+            evidence: Evidence::FileLine {
+                file: "src/db.rs".to_string(),
+                line: 12,
+                snippet: String::new(),
+            },
+            impact: String::new(),
+            recommendation: "Use <bound> params & \"escape\"\u{0}".to_string(),
+            suppressed: false,
+        };
+        let mut report = create_test_report();
+        report.deep_scan_results.security.push(finding(
+            "SEC-001",
+            "SQL built with format!(\"{}\" + x) <script>",
+        ));
+        report
+            .suppressed_findings
+            .push(finding("SEC-002", "Accepted"));
+        let mut manifest = RunManifest::new("/test", "local");
+        manifest.timestamps.ended =
+            Some(manifest.timestamps.started + chrono::Duration::milliseconds(1500));
+        let result = ScanResult {
+            manifest,
+            report,
+            artifacts: hqe_core::scan::ArtifactPaths::empty(),
+            redaction: RedactionSummary {
+                total_redactions: 0,
+                by_type: HashMap::new(),
+                leak_warnings: vec![],
+                suppressed_findings: 0,
+                pii_by_type: HashMap::new(),
+            },
+            local_findings: vec![],
+        };
+
+        let path = writer.write_junit_xml(&result).await?;
+        assert_eq!(path, temp.path().join("junit.xml"));
+        let xml = tokio::fs::read_to_string(&path).await?;
+
+        assert!(xml
+            .contains(r#"tests="6" failures="1" errors="0" skipped="1" time="1.500" timestamp=""#));
+        assert!(xml.contains(
+            r#"<testsuite name="Security" tests="1" failures="1" errors="0" skipped="0""#
+        ));
+        assert!(xml.contains(
+            r#"<testcase classname="Security" name="SEC-001: SQL built with format!(&quot;{}&quot; + x) &lt;script&gt;" time="0">"#
+        ));
+        assert!(xml.contains(r#"<failure message="[High] SQL built"#));
+        assert!(xml.contains(
+            "Severity: High\nRisk: Medium\nFile: src/db.rs\nLine: 12\nRecommendation: Use &lt;bound&gt; params &amp; &quot;escape&quot;</failure>"
+        ));
+        assert!(xml.contains(r#"<testcase classname="Testing" name="No findings" time="0"/>"#));
+        assert!(xml.contains(r#"<skipped message="Accepted in baseline"/>"#));
+        assert!(!xml.contains('\u{0}') && !xml.contains("<script>"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_sbom_cyclonedx() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let writer = ArtifactWriter::new(temp.path());
+        let dependency =
+            |version: Option<&str>, requirement: Option<&str>, evidence: &str| Dependency {
+                name: "tokio".to_string(),
+                ecosystem: Ecosystem::Cargo,
+                version: version.map(str::to_string),
+                requirement: requirement.map(str::to_string),
+                evidence: evidence.to_string(),
+            };
+        let mut report = create_test_report();
+        report.project_map.tech_stack.dependencies = vec![
+            dependency(Some("1.38.0"), Some("1"), "Cargo.lock"),
+            dependency(Some("1.38.0"), None, "tools/Cargo.lock"),
+            dependency(None, Some("^1.30"), "legacy/Cargo.toml"),
+        ];
+        let result = ScanResult {
+            manifest: RunManifest::new("/work/hqe", "local"),
+            report,
+            artifacts: hqe_core::scan::ArtifactPaths::empty(),
+            redaction: RedactionSummary {
+                total_redactions: 0,
+                by_type: HashMap::new(),
+                leak_warnings: vec![],
+                suppressed_findings: 0,
+                pii_by_type: HashMap::new(),
+            },
+            local_findings: vec![],
+        };
+
+        let path = writer.write_sbom_cyclonedx(&result).await?;
+        assert_eq!(path, temp.path().join("sbom.cdx.json"));
+        let bom: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path)?)?;
+        assert_eq!(bom["bomFormat"], "CycloneDX");
+        assert_eq!(bom["specVersion"], "1.5");
+        assert!(bom["serialNumber"]
+            .as_str()
+            .is_some_and(|s| s.starts_with("urn:uuid:")));
+        assert_eq!(bom["metadata"]["component"]["name"], "hqe");
+        assert_eq!(
+            bom["components"],
+            serde_json::json!([
+                {
+                    "type": "library",
+                    "bom-ref": "pkg:cargo/tokio",
+                    "name": "tokio",
+                    "version": "^1.30",
+                    "purl": "pkg:cargo/tokio",
+                    "evidence": {"occurrences": [{"location": "legacy/Cargo.toml"}]}
+                },
+                {
+                    "type": "library",
+                    "bom-ref": "pkg:cargo/tokio@1.38.0",
+                    "name": "tokio",
+                    "version": "1.38.0",
+                    "purl": "pkg:cargo/tokio@1.38.0",
+                    "evidence": {"occurrences": [
+                        {"location": "Cargo.lock"},
+                        {"location": "tools/Cargo.lock"}
+                    ]}
+                }
+            ])
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_write_all_records_checksums_and_signature() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let key = generate_signing_key();
+        let public_key = hex::encode(key.verifying_key().as_bytes());
+        let writer = ArtifactWriter::new(temp.path())
+            .with_backlog_csv(true)
+            .with_signing_key(key);
+        let result = ScanResult {
+            manifest: RunManifest::new("/test", "local"),
+            report: create_test_report(),
+            artifacts: hqe_core::scan::ArtifactPaths::empty(),
+            redaction: RedactionSummary {
+                total_redactions: 0,
+                by_type: HashMap::new(),
+                leak_warnings: vec![],
+                suppressed_findings: 0,
+                pii_by_type: HashMap::new(),
+            },
+            local_findings: vec![],
+        };
+        let paths = writer.write_all(&result).await?;
+        assert_eq!(
+            paths.manifest_signature,
+            Some(temp.path().join(SIGNATURE_FILE))
+        );
+
+        let manifest: RunManifest =
+            serde_json::from_str(&std::fs::read_to_string(&paths.manifest_json)?)?;
+        let listed: Vec<&str> = manifest.artifacts.iter().map(|a| a.path.as_str()).collect();
+        assert_eq!(
+            listed,
+            [
+                "backlog.csv",
+                "report.json",
+                "report.md",
+                "session-log.json"
+            ]
+        );
+        let md = std::fs::read(&paths.report_md)?;
+        assert_eq!(manifest.artifacts[2].size, md.len() as u64);
+
+        let report = verify_run_dir(temp.path()).await?;
+        assert!(report.is_intact());
+        assert_eq!(report.verified.len(), 4);
+        assert_eq!(report.signature, SignatureStatus::Valid(public_key));
+
+        std::fs::write(&paths.report_md, b"# Edited\n")?;
+        std::fs::remove_file(temp.path().join("session-log.json"))?;
+        std::fs::write(temp.path().join("notes.txt"), b"added later")?;
+        let report = verify_run_dir(temp.path()).await?;
+        assert!(!report.is_intact());
+        assert_eq!(report.modified, ["report.md"]);
+        assert_eq!(report.missing, ["session-log.json"]);
+        assert_eq!(report.unlisted, ["notes.txt"]);
+
+        // Editing the manifest to match the new file breaks the signature
+        let edited = std::fs::read_to_string(&paths.manifest_json)?.replace("/test", "/elsewhere");
+        std::fs::write(&paths.manifest_json, edited)?;
+        let report = verify_run_dir(temp.path()).await?;
+        assert!(matches!(report.signature, SignatureStatus::Invalid(_)));
         Ok(())
     }
 }
@@ -0,0 +1,1230 @@
+//! Markdown report rendering.
+//!
+//! [`ReportRenderer`] builds `report.md` (and, through it, `report.html`) one
+//! section at a time. Every method has a default implementation producing the
+//! HQE v3 layout, so a custom renderer only overrides the sections it wants to
+//! change, e.g. to add a compliance header or drop the emoji markers.
+//!
+//! [`ArtifactWriter`](crate::ArtifactWriter) uses the
+//! [`TemplateRenderer`](crate::TemplateRenderer) by default; its embedded
+//! template produces the same output as [`DefaultRenderer`], which a golden
+//! file test checks for both.
+//!
+//! Table cells go through [`ReportRenderer::table_cell`]: LLM-written titles
+//! may contain pipes and line breaks that would otherwise split the row, and
+//! long descriptions are cut so the tables stay readable.
+
+use hqe_core::models::{DetectedLicense, Evidence, Finding, HqeReport};
+use hqe_core::HqeError;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// Default [`ReportRenderer::max_cell_chars`]
+pub const DEFAULT_MAX_CELL_CHARS: usize = 120;
+/// Printed under a table with truncated cells
+pub(crate) const TRUNCATION_NOTE: &str =
+    "_Cells ending in … are truncated; the full text is in [report.json](report.json)._\n\n";
+
+/// Renders an [`HqeReport`] as Markdown.
+///
+/// [`ReportRenderer::render`] calls the section methods in report order; override
+/// it to reorder or skip sections. Sections append to the `md` buffer.
+pub trait ReportRenderer: Send + Sync {
+    /// Render the full report, failing instead of degrading when the
+    /// renderer can fail (see [`TemplateRenderer`](crate::TemplateRenderer)).
+    /// [`ArtifactWriter`](crate::ArtifactWriter) calls this.
+    fn try_render(&self, report: &HqeReport) -> Result<String, HqeError> {
+        Ok(self.render(report))
+    }
+
+    /// Render the full report
+    fn render(&self, report: &HqeReport) -> String {
+        let mut md = String::new();
+        self.render_header(&mut md, report);
+        self.render_executive_summary(&mut md, report);
+        self.render_project_map(&mut md, report);
+        self.render_pr_harvest(&mut md, report);
+        self.render_deep_scan_results(&mut md, report);
+        self.render_backlog(&mut md, report);
+        self.render_implementation_plan(&mut md, report);
+        self.render_immediate_actions(&mut md, report);
+        self.render_session_log(&mut md, report);
+        md
+    }
+
+    /// Longest table cell in characters before it is cut with `…`; `None`
+    /// keeps the full text
+    fn max_cell_chars(&self) -> Option<usize> {
+        Some(DEFAULT_MAX_CELL_CHARS)
+    }
+
+    /// One table cell: truncated to [`ReportRenderer::max_cell_chars`] and
+    /// escaped with [`escape_table_cell`]. Sets `truncated` when text was cut.
+    fn table_cell(&self, text: &str, truncated: &mut bool) -> String {
+        truncate_cell(text, self.max_cell_chars(), truncated)
+    }
+
+    /// Title and run ID
+    fn render_header(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("# HQE Engineer Report\n\n");
+        md.push_str(&format!("Run ID: `{}`\n\n", report.run_id));
+        if let Some(provider) = &report.provider {
+            let mut line = format!("Provider: {}", provider.name);
+            if let Some(model) = &provider.model {
+                line.push_str(&format!(" ({})", model));
+            }
+            if let Some(base_url) = &provider.base_url {
+                line.push_str(&format!(" at {}", base_url));
+            }
+            if !provider.llm_enabled {
+                line.push_str(", LLM analysis disabled");
+            }
+            md.push_str(&line);
+            md.push_str("\n\n");
+        }
+    }
+
+    /// Section 1: Executive Summary
+    fn render_executive_summary(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("## 1. Executive Summary\n\n");
+        md.push_str(&format!(
+            "**Health Score:** {}/10\n\n",
+            report.executive_summary.health_score
+        ));
+
+        if !report.executive_summary.critical_findings.is_empty() {
+            md.push_str("### Critical Findings\n\n");
+            for finding in &report.executive_summary.critical_findings {
+                md.push_str(&format!("- 🚨 {}\n", finding));
+            }
+            md.push('\n');
+        }
+
+        if !report.executive_summary.top_priorities.is_empty() {
+            md.push_str("### Top Priorities\n\n");
+            for priority in &report.executive_summary.top_priorities {
+                md.push_str(&format!("- {}\n", priority));
+            }
+            md.push('\n');
+        }
+
+        if !report.executive_summary.blockers.is_empty() {
+            md.push_str("### Blockers\n\n");
+            for blocker in &report.executive_summary.blockers {
+                md.push_str(&format!("- **{}**\n", blocker.description));
+                md.push_str(&format!("  - Reason: {}\n", blocker.reason));
+                md.push_str(&format!("  - How to obtain: {}\n", blocker.how_to_obtain));
+            }
+            md.push('\n');
+        }
+    }
+
+    /// Section 2: Project Map
+    fn render_project_map(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("## 2. Project Map\n\n");
+
+        md.push_str("### Architecture\n\n");
+        let architecture = &report.project_map.architecture;
+        md.push_str(&format!(
+            "**Languages:** {}\n\n",
+            architecture.languages.join(", ")
+        ));
+        let lists = [
+            ("Frameworks", &architecture.frameworks),
+            ("Runtimes", &architecture.runtimes),
+            ("Databases", &architecture.databases),
+            ("Message Queues", &architecture.message_queues),
+            ("Third-party Services", &architecture.third_party_services),
+        ];
+        let mut details = Vec::new();
+        for (label, items) in lists {
+            if !items.is_empty() {
+                details.push(format!("- **{}:** {}\n", label, items.join(", ")));
+            }
+        }
+        if let Some(build_system) = &architecture.build_system {
+            details.push(format!("- **Build System:** {}\n", build_system));
+        }
+        if let Some(separation) = &architecture.frontend_backend_separation {
+            details.push(format!("- **Frontend/Backend:** {}\n", separation));
+        }
+        if !details.is_empty() {
+            md.push_str(&details.concat());
+            md.push('\n');
+        }
+
+        if let Some(data_flow) = &report.project_map.data_flow {
+            md.push_str("### Data Flow\n\n");
+            md.push_str(data_flow.trim());
+            md.push_str("\n\n");
+        }
+
+        let code_stats = &report.project_map.code_stats;
+        if !code_stats.is_empty() {
+            md.push_str("### Languages\n\n");
+            md.push_str("| Language | Files | Code | Comments | Blank |\n");
+            md.push_str("|----------|------:|-----:|---------:|------:|\n");
+            let total = code_stats.total();
+            let rows = code_stats
+                .by_code_lines()
+                .into_iter()
+                .chain(std::iter::once(("**Total**", &total)));
+            for (language, stats) in rows {
+                md.push_str(&format!(
+                    "| {} | {} | {} | {} | {} |\n",
+                    escape_table_cell(language),
+                    stats.files,
+                    stats.code_lines,
+                    stats.comment_lines,
+                    stats.blank_lines
+                ));
+            }
+            md.push('\n');
+        }
+
+        if !report.project_map.entrypoints.is_empty() {
+            md.push_str("### Entrypoints\n\n");
+            md.push_str("| File | Type | Description |\n");
+            md.push_str("|------|------|-------------|\n");
+            let mut truncated = false;
+            for ep in &report.project_map.entrypoints {
+                md.push_str(&format!(
+                    "| {} | {} | {} |\n",
+                    code_span(&self.table_cell(&ep.file_path, &mut truncated)),
+                    self.table_cell(&ep.entry_type, &mut truncated),
+                    self.table_cell(&ep.description, &mut truncated)
+                ));
+            }
+            md.push('\n');
+            if truncated {
+                md.push_str(TRUNCATION_NOTE);
+            }
+        }
+
+        let tech_stack = &report.project_map.tech_stack;
+        if !tech_stack.detected.is_empty() || !tech_stack.package_managers.is_empty() {
+            md.push_str("### Tech Stack\n\n");
+            for tech in &tech_stack.detected {
+                match &tech.version {
+                    Some(version) => md.push_str(&format!(
+                        "- **{}** {} (evidence: {})\n",
+                        tech.name, version, tech.evidence
+                    )),
+                    None => md.push_str(&format!(
+                        "- **{}** (evidence: {})\n",
+                        tech.name, tech.evidence
+                    )),
+                }
+            }
+            if !tech_stack.package_managers.is_empty() {
+                md.push_str(&format!(
+                    "- **Package Managers:** {}\n",
+                    tech_stack.package_managers.join(", ")
+                ));
+            }
+            md.push('\n');
+        }
+
+        let licenses = &report.project_map.licenses;
+        if !licenses.is_empty() {
+            md.push_str("### Licenses\n\n");
+            let repository: Vec<String> = licenses
+                .iter()
+                .filter(|l| l.package.is_none())
+                .map(|l| format!("{} ({})", l.license, code_span(&l.evidence)))
+                .collect();
+            if !repository.is_empty() {
+                md.push_str(&format!("**Repository:** {}\n\n", repository.join(", ")));
+            }
+            let counts = package_license_counts(licenses);
+            if !counts.is_empty() {
+                md.push_str("| License | Packages |\n");
+                md.push_str("|---------|---------:|\n");
+                for (license, packages) in counts {
+                    md.push_str(&format!(
+                        "| {} | {} |\n",
+                        escape_table_cell(license),
+                        packages
+                    ));
+                }
+                md.push('\n');
+            }
+        }
+    }
+
+    /// Section 3: PR Harvest (only when present)
+    fn render_pr_harvest(&self, md: &mut String, report: &HqeReport) {
+        if let Some(pr_harvest) = &report.pr_harvest {
+            md.push_str("## 3. PR Harvest\n\n");
+            if !pr_harvest.inventory.is_empty() {
+                md.push_str("| PR | Title | Status | Risk | Recommendation | Intent | Files |\n");
+                md.push_str("|----|-------|--------|------|----------------|--------|-------|\n");
+                let mut truncated = false;
+                for pr in &pr_harvest.inventory {
+                    md.push_str(&format!(
+                        "| {} | {} | {} | {} | {:?} | {} | {} |\n",
+                        self.table_cell(&pr.pr_id, &mut truncated),
+                        self.table_cell(&pr.title, &mut truncated),
+                        self.table_cell(&pr.status, &mut truncated),
+                        pr.risk,
+                        pr.recommendation,
+                        self.table_cell(&pr.intent, &mut truncated),
+                        self.table_cell(&pr.files_touched.join(", "), &mut truncated)
+                    ));
+                }
+                md.push('\n');
+                if truncated {
+                    md.push_str(TRUNCATION_NOTE);
+                }
+            }
+
+            if !pr_harvest.conflicts.is_empty() {
+                md.push_str("### Conflicts\n\n");
+                for conflict in &pr_harvest.conflicts {
+                    md.push_str(&format!(
+                        "- **{} vs {}:** {}\n",
+                        conflict.pr_x, conflict.pr_y, conflict.description
+                    ));
+                    md.push_str(&format!("  - {}: {}\n", conflict.pr_x, conflict.approach_x));
+                    md.push_str(&format!("  - {}: {}\n", conflict.pr_y, conflict.approach_y));
+                    md.push_str(&format!("  - Resolution: {}\n", conflict.resolution));
+                    if !conflict.affected_files.is_empty() {
+                        md.push_str(&format!(
+                            "  - Files: {}\n",
+                            conflict.affected_files.join(", ")
+                        ));
+                    }
+                }
+                md.push('\n');
+            }
+        }
+    }
+
+    /// Section 4: Deep Scan Results
+    fn render_deep_scan_results(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("## 4. Deep Scan Results\n\n");
+
+        if !report.deep_scan_results.security.is_empty() {
+            md.push_str("### Security\n\n");
+            self.render_findings(md, &report.deep_scan_results.security);
+        }
+
+        if !report.deep_scan_results.code_quality.is_empty() {
+            md.push_str("### Code Quality\n\n");
+            self.render_findings(md, &report.deep_scan_results.code_quality);
+        }
+
+        if !report.deep_scan_results.frontend.is_empty() {
+            md.push_str("### Frontend\n\n");
+            self.render_findings(md, &report.deep_scan_results.frontend);
+        }
+
+        if !report.deep_scan_results.backend.is_empty() {
+            md.push_str("### Backend\n\n");
+            self.render_findings(md, &report.deep_scan_results.backend);
+        }
+
+        if !report.deep_scan_results.testing.is_empty() {
+            md.push_str("### Testing\n\n");
+            self.render_findings(md, &report.deep_scan_results.testing);
+        }
+
+        if !report.suppressed_findings.is_empty() {
+            md.push_str("### Suppressed by Baseline\n\n");
+            if let Some(baseline) = &report.baseline {
+                md.push_str(&format!(
+                    "{} finding(s) matched the baseline and are not counted in the summary.\n\n",
+                    baseline.suppressed
+                ));
+            }
+            self.render_findings(md, &report.suppressed_findings);
+        }
+
+        if let Some(baseline) = report.baseline.as_ref().filter(|b| !b.stale.is_empty()) {
+            md.push_str("### Stale Baseline Entries\n\n");
+            md.push_str(
+                "These accepted findings no longer occur and can be removed from the baseline.\n\n",
+            );
+            for entry in &baseline.stale {
+                match &entry.file {
+                    Some(file) => md.push_str(&format!(
+                        "- `{}` {} ({})\n",
+                        entry.fingerprint, entry.title, file
+                    )),
+                    None => md.push_str(&format!("- `{}` {}\n", entry.fingerprint, entry.title)),
+                }
+            }
+            md.push('\n');
+        }
+    }
+
+    /// Findings of one deep scan category
+    fn render_findings(&self, md: &mut String, findings: &[Finding]) {
+        for finding in findings {
+            md.push_str(&format!("#### {}: {}\n\n", finding.id, finding.title));
+            md.push_str(&format!("- **Category:** {}\n", finding.category));
+            md.push_str(&format!("- **Severity:** {}\n", finding.severity));
+            md.push_str(&format!("- **Risk:** {}\n", finding.risk));
+            md.push_str(&format!("- **Impact:** {}\n", finding.impact));
+            md.push_str(&format!(
+                "- **Recommendation:** {}\n",
+                finding.recommendation
+            ));
+            md.push('\n');
+            self.render_evidence(md, &finding.evidence);
+        }
+    }
+
+    /// Location and snippet, or reproduction steps, of a finding or TODO item
+    fn render_evidence(&self, md: &mut String, evidence: &Evidence) {
+        let snippet = match evidence {
+            Evidence::FileLine {
+                file,
+                line,
+                snippet,
+            } => {
+                md.push_str(&format!("**Evidence:** `{}:{}`\n\n", file, line));
+                snippet
+            }
+            Evidence::FileFunction {
+                file,
+                function,
+                snippet,
+            } => {
+                md.push_str(&format!("**Evidence:** `{}` in `{}`\n\n", file, function));
+                snippet
+            }
+            Evidence::Reproduction { steps, observed } => {
+                md.push_str("**Reproduction:**\n\n");
+                for (i, step) in steps.iter().enumerate() {
+                    md.push_str(&format!("{}. {}\n", i + 1, step));
+                }
+                md.push_str(&format!("\nObserved: {}\n\n", observed));
+                return;
+            }
+        };
+        if !snippet.trim().is_empty() {
+            let fence = if snippet.contains("```") {
+                "~~~~"
+            } else {
+                "```"
+            };
+            md.push_str(&format!("{fence}\n{}\n{fence}\n\n", snippet.trim_end()));
+        }
+    }
+
+    /// Section 5: Master TODO Backlog
+    fn render_backlog(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("## 5. Master TODO Backlog\n\n");
+        md.push_str("| ID | Severity | Risk | Category | Title |\n");
+        md.push_str("|----|----------|------|----------|-------|\n");
+        let mut truncated = false;
+        for todo in &report.master_todo_backlog {
+            md.push_str(&format!(
+                "| {} | {} | {} | {} | {} |\n",
+                self.table_cell(&todo.id, &mut truncated),
+                todo.severity,
+                todo.risk,
+                todo.category,
+                self.table_cell(&todo.title, &mut truncated)
+            ));
+        }
+        md.push('\n');
+        if truncated {
+            md.push_str(TRUNCATION_NOTE);
+        }
+
+        for todo in &report.master_todo_backlog {
+            md.push_str(&format!("#### {}: {}\n\n", todo.id, todo.title));
+            md.push_str(&format!("- **Root Cause:** {}\n", todo.root_cause));
+            md.push_str(&format!("- **Fix:** {}\n", todo.fix_approach));
+            md.push_str(&format!("- **Verify:** {}\n", todo.verify));
+            if let Some(blocker) = &todo.blocked_by {
+                md.push_str(&format!("- **Blocked by:** {}\n", blocker));
+            }
+            md.push('\n');
+            self.render_evidence(md, &todo.evidence);
+        }
+    }
+
+    /// Section 6: Implementation Plan
+    fn render_implementation_plan(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("## 6. Implementation Plan\n\n");
+
+        if !report.implementation_plan.immediate.is_empty() {
+            md.push_str("### Immediate (Do Now)\n\n");
+            for item in &report.implementation_plan.immediate {
+                md.push_str(&format!("- [ ] {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if !report.implementation_plan.short_term.is_empty() {
+            md.push_str("### Short-term (This Week)\n\n");
+            for item in &report.implementation_plan.short_term {
+                md.push_str(&format!("- [ ] {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if !report.implementation_plan.medium_term.is_empty() {
+            md.push_str("### Medium-term (Next Months)\n\n");
+            for item in &report.implementation_plan.medium_term {
+                md.push_str(&format!("- [ ] {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if !report.implementation_plan.long_term.is_empty() {
+            md.push_str("### Long-term\n\n");
+            for item in &report.implementation_plan.long_term {
+                md.push_str(&format!("- [ ] {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        let graph = &report.implementation_plan.dependency_graph;
+        if graph.values().any(|deps| !deps.is_empty()) {
+            md.push_str("### Dependencies\n\n");
+            let mut items: Vec<_> = graph.iter().filter(|(_, deps)| !deps.is_empty()).collect();
+            items.sort_by(|a, b| a.0.cmp(b.0));
+            for (item, deps) in items {
+                md.push_str(&format!("- {} depends on {}\n", item, deps.join(", ")));
+            }
+            md.push('\n');
+        }
+
+        if !report.implementation_plan.risk_assessment.is_empty() {
+            md.push_str("### Risk Mitigation\n\n");
+            for assessment in &report.implementation_plan.risk_assessment {
+                md.push_str(&format!(
+                    "- **{}:** {}\n",
+                    assessment.item_id, assessment.mitigation
+                ));
+            }
+            md.push('\n');
+        }
+    }
+
+    /// Section 7: Immediate Actions
+    fn render_immediate_actions(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("## 7. Immediate Actions\n\n");
+        if report.immediate_actions.is_empty() {
+            md.push_str("No immediate actions generated.\n\n");
+            return;
+        }
+
+        for action in &report.immediate_actions {
+            md.push_str(&format!("### {}: {}\n\n", action.todo_id, action.title));
+            md.push_str(&format!("**Problem:** {}\n\n", action.problem));
+            md.push_str(&format!("**Root Cause:** {}\n\n", action.root_cause));
+            md.push_str(&format!("**Risk:** {}\n\n", action.risk));
+            if action.behavior_change {
+                md.push_str("⚠️ **BEHAVIOR CHANGE**\n\n");
+            }
+            for diff in &action.diffs {
+                md.push_str(&format!("#### File: `{}`\n\n", diff.file_path));
+                md.push_str("```diff\n");
+                md.push_str(&diff.diff_content);
+                md.push_str("\n```\n\n");
+            }
+            md.push_str("**Verification:**\n");
+            for step in &action.verification {
+                md.push_str(&format!("1. Run: `{}`\n", step.command));
+                md.push_str(&format!("   Expected: {}\n", step.expected_output));
+            }
+            md.push('\n');
+            if !action.rollback.trim().is_empty() {
+                md.push_str(&format!("**Rollback:** {}\n\n", action.rollback));
+            }
+        }
+    }
+
+    /// Section 8: Session Log
+    fn render_session_log(&self, md: &mut String, report: &HqeReport) {
+        md.push_str("## 8. Session Log\n\n");
+
+        if !report.session_log.completed.is_empty() {
+            md.push_str("### Completed\n\n");
+            for item in &report.session_log.completed {
+                md.push_str(&format!("- ✅ {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if !report.session_log.in_progress.is_empty() {
+            md.push_str("### In Progress\n\n");
+            for item in &report.session_log.in_progress {
+                md.push_str(&format!("- 🔄 {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if !report.session_log.discovered.is_empty() {
+            md.push_str("### Discovered\n\n");
+            for item in &report.session_log.discovered {
+                md.push_str(&format!("- 🆕 {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if !report.session_log.reprioritized.is_empty() {
+            md.push_str("### Reprioritized\n\n");
+            for item in &report.session_log.reprioritized {
+                md.push_str(&format!("- 🔀 {}\n", item));
+            }
+            md.push('\n');
+        }
+
+        if !report.session_log.next_session.is_empty() {
+            md.push_str("### Next Session\n\n");
+            for item in &report.session_log.next_session {
+                md.push_str(&format!("- ⏭️ {}\n", item));
+            }
+            md.push('\n');
+        }
+    }
+}
+
+/// Trims `text` and cuts it to `max` characters with `…`, then escapes it
+/// with [`escape_table_cell`]. Sets `truncated` when text was cut.
+pub(crate) fn truncate_cell(text: &str, max: Option<usize>, truncated: &mut bool) -> String {
+    let text = text.trim();
+    match max {
+        Some(max) if text.chars().count() > max => {
+            *truncated = true;
+            let cut: String = text.chars().take(max.saturating_sub(1)).collect();
+            escape_table_cell(&format!("{}…", cut.trim_end()))
+        }
+        _ => escape_table_cell(text),
+    }
+}
+
+/// Escapes text for a Markdown table cell: `|` becomes `\|` and line breaks
+/// become `<br>`
+pub fn escape_table_cell(text: &str) -> String {
+    text.replace("\r\n", "\n")
+        .replace('\r', "\n")
+        .replace('|', "\\|")
+        .replace('\n', "<br>")
+}
+
+/// Wraps an escaped cell in a code span, using a longer backtick fence when
+/// the text itself contains backticks
+pub(crate) fn code_span(text: &str) -> String {
+    if !text.contains('`') {
+        return format!("`{text}`");
+    }
+    let longest = text
+        .split(|c| c != '`')
+        .map(str::len)
+        .max()
+        .unwrap_or_default();
+    let fence = "`".repeat(longest + 1);
+    format!("{fence} {text} {fence}")
+}
+
+/// Number of distinct packages per license expression, most used first
+pub(crate) fn package_license_counts(licenses: &[DetectedLicense]) -> Vec<(&str, usize)> {
+    let mut packages: BTreeMap<&str, BTreeSet<&str>> = BTreeMap::new();
+    for license in licenses {
+        if let Some(package) = &license.package {
+            packages
+                .entry(license.license.as_str())
+                .or_default()
+                .insert(package);
+        }
+    }
+    let mut counts: Vec<(&str, usize)> = packages
+        .into_iter()
+        .map(|(license, packages)| (license, packages.len()))
+        .collect();
+    counts.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+    counts
+}
+
+/// The stock HQE v3 Markdown layout
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultRenderer;
+
+impl ReportRenderer for DefaultRenderer {}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use hqe_core::models::*;
+    use std::collections::HashMap;
+
+    fn finding(id: &str, category: &str, evidence: Evidence) -> Finding {
+        Finding {
+            id: id.to_string(),
+            severity: Severity::Medium,
+            risk: RiskLevel::Low,
+            category: category.to_string(),
+            title: format!("{id} title"),
+            evidence,
+            impact: format!("{id} impact"),
+            recommendation: format!("{id} recommendation"),
+            suppressed: false,
+        }
+    }
+
+    fn at_line(file: &str, line: usize) -> Evidence {
+        Evidence::FileLine {
+            file: file.to_string(),
+            line,
+            snippet: format!("// snippet of {file}"),
+        }
+    }
+
+    /// A report with every field populated with a distinct value
+    pub(crate) fn full_report() -> HqeReport {
+        let strings = |items: &[&str]| items.iter().map(|s| s.to_string()).collect::<Vec<_>>();
+        let mut suppressed = finding("SEC-9", "Security", at_line("src/legacy.rs", 9));
+        suppressed.suppressed = true;
+
+        HqeReport {
+            run_id: "run-golden".to_string(),
+            provider: Some(ProviderInfo {
+                name: "venice".to_string(),
+                base_url: Some("https://api.venice.ai/api/v1".to_string()),
+                model: Some("qwen-coder".to_string()),
+                llm_enabled: false,
+            }),
+            executive_summary: ExecutiveSummary {
+                health_score: 6,
+                top_priorities: strings(&["Rotate leaked keys"]),
+                critical_findings: strings(&["Hardcoded AWS key"]),
+                blockers: vec![Blocker {
+                    description: "No staging access".to_string(),
+                    reason: "VPN required".to_string(),
+                    how_to_obtain: "Ask ops".to_string(),
+                }],
+            },
+            project_map: ProjectMap {
+                architecture: Architecture {
+                    languages: strings(&["Rust", "TypeScript"]),
+                    frameworks: strings(&["Tauri", "React"]),
+                    runtimes: strings(&["Node 20"]),
+                    frontend_backend_separation: Some("IPC commands".to_string()),
+                    databases: strings(&["SQLCipher"]),
+                    message_queues: strings(&["NATS"]),
+                    third_party_services: strings(&["PostHog"]),
+                    build_system: Some("Cargo workspace".to_string()),
+                },
+                entrypoints: vec![Entrypoint {
+                    file_path: "cli/hqe/src/main.rs".to_string(),
+                    entry_type: "cli".to_string(),
+                    description: "Command line".to_string(),
+                }],
+                data_flow: Some("Scanner -> redaction -> LLM -> report".to_string()),
+                tech_stack: TechStack {
+                    detected: vec![DetectedTechnology {
+                        name: "tokio".to_string(),
+                        version: Some("1.35".to_string()),
+                        evidence: "Cargo.toml".to_string(),
+                    }],
+                    package_managers: strings(&["cargo", "npm"]),
+                    dependencies: Vec::new(),
+                },
+                code_stats: CodeStats {
+                    languages: [(
+                        "Rust".to_string(),
+                        LanguageStats {
+                            files: 40,
+                            code_lines: 9000,
+                            comment_lines: 800,
+                            blank_lines: 700,
+                        },
+                    )]
+                    .into(),
+                },
+                licenses: vec![
+                    DetectedLicense {
+                        package: None,
+                        license: "Apache-2.0".to_string(),
+                        evidence: "LICENSE".to_string(),
+                    },
+                    DetectedLicense {
+                        package: Some("serde".to_string()),
+                        license: "MIT OR Apache-2.0".to_string(),
+                        evidence: "Cargo.toml".to_string(),
+                    },
+                ],
+                hygiene: RepoHygiene::default(),
+            },
+            pr_harvest: Some(PrHarvest {
+                inventory: vec![PrInfo {
+                    pr_id: "#12".to_string(),
+                    title: "Add retries".to_string(),
+                    status: "open".to_string(),
+                    intent: "Survive 429s".to_string(),
+                    files_touched: strings(&["src/client.rs"]),
+                    risk: RiskLevel::Medium,
+                    recommendation: PrRecommendation::Modify,
+                }],
+                conflicts: vec![PrConflict {
+                    description: "Both change backoff".to_string(),
+                    pr_x: "#12".to_string(),
+                    approach_x: "Exponential".to_string(),
+                    pr_y: "#15".to_string(),
+                    approach_y: "Fixed delay".to_string(),
+                    resolution: "Keep exponential".to_string(),
+                    affected_files: strings(&["src/retry.rs"]),
+                }],
+            }),
+            deep_scan_results: DeepScanResults {
+                security: vec![finding("SEC-1", "Security", at_line("src/config.rs", 3))],
+                code_quality: vec![finding(
+                    "CQ-1",
+                    "Code Quality",
+                    Evidence::FileFunction {
+                        file: "src/scan.rs".to_string(),
+                        function: "run".to_string(),
+                        snippet: String::new(),
+                    },
+                )],
+                frontend: vec![finding("FE-1", "Frontend", at_line("src/App.tsx", 20))],
+                backend: vec![finding("BE-1", "Backend", at_line("src/db.rs", 7))],
+                testing: vec![finding(
+                    "TEST-1",
+                    "Testing",
+                    Evidence::Reproduction {
+                        steps: strings(&["cargo test -p hqe-core"]),
+                        observed: "flaky timeout".to_string(),
+                    },
+                )],
+            },
+            master_todo_backlog: vec![TodoItem {
+                id: "SEC-001".to_string(),
+                severity: Severity::High,
+                risk: RiskLevel::High,
+                category: TodoCategory::Sec,
+                title: "Rotate AWS key".to_string(),
+                root_cause: "Key committed to config".to_string(),
+                evidence: at_line("src/config.rs", 3),
+                fix_approach: "Load from env".to_string(),
+                verify: "hqe scan reports no secret".to_string(),
+                blocked_by: Some("BOOT-001".to_string()),
+            }],
+            implementation_plan: ImplementationPlan {
+                immediate: strings(&["Rotate key"]),
+                short_term: strings(&["Add secret scanning to CI"]),
+                medium_term: strings(&["Move config to vault"]),
+                long_term: strings(&["Adopt SSO"]),
+                dependency_graph: HashMap::from([
+                    ("SEC-001".to_string(), strings(&["BOOT-001"])),
+                    ("DOC-001".to_string(), vec![]),
+                ]),
+                risk_assessment: vec![RiskAssessment {
+                    item_id: "SEC-001".to_string(),
+                    mitigation: "Rotate during low traffic".to_string(),
+                }],
+            },
+            immediate_actions: vec![PatchAction {
+                todo_id: "SEC-001".to_string(),
+                title: "Read key from env".to_string(),
+                problem: "Key in source".to_string(),
+                root_cause: "No secret store".to_string(),
+                risk: RiskLevel::Low,
+                behavior_change: true,
+                diffs: vec![FileDiff {
+                    file_path: "src/config.rs".to_string(),
+                    diff_content: "-const KEY: &str = \"AKIA\";\n+let key = env::var(\"KEY\")?;"
+                        .to_string(),
+                }],
+                verification: vec![VerificationStep {
+                    command: "cargo test".to_string(),
+                    expected_output: "all pass".to_string(),
+                }],
+                rollback: "git revert the patch".to_string(),
+            }],
+            session_log: SessionLog {
+                completed: strings(&["Scanned repository"]),
+                in_progress: strings(&["Reviewing PRs"]),
+                discovered: strings(&["Unused crate"]),
+                reprioritized: strings(&["DOC-001 lowered"]),
+                next_session: strings(&["Verify rotation"]),
+            },
+            suppressed_findings: vec![suppressed],
+            baseline: Some(BaselineStatus {
+                suppressed: 1,
+                stale: vec![BaselineEntry {
+                    fingerprint: "0123456789abcdef".to_string(),
+                    title: "Old debug flag".to_string(),
+                    file: Some("src/debug.rs".to_string()),
+                }],
+            }),
+        }
+    }
+
+    fn evidence_texts(evidence: &Evidence) -> Vec<String> {
+        match evidence {
+            Evidence::FileLine {
+                file,
+                line,
+                snippet,
+            } => vec![format!("{file}:{line}"), snippet.clone()],
+            Evidence::FileFunction {
+                file,
+                function,
+                snippet,
+            } => vec![file.clone(), function.clone(), snippet.clone()],
+            Evidence::Reproduction { steps, observed } => {
+                let mut texts = steps.clone();
+                texts.push(observed.clone());
+                texts
+            }
+        }
+    }
+
+    fn finding_texts(finding: &Finding) -> Vec<String> {
+        let Finding {
+            id,
+            severity,
+            risk,
+            category,
+            title,
+            evidence,
+            impact,
+            recommendation,
+            // Shown by the section the finding is listed in
+            suppressed: _,
+        } = finding;
+        let mut texts = vec![
+            id.clone(),
+            severity.to_string(),
+            risk.to_string(),
+            category.clone(),
+            title.clone(),
+            impact.clone(),
+            recommendation.clone(),
+        ];
+        texts.extend(evidence_texts(evidence));
+        texts
+    }
+
+    /// Text of every report field, destructured without `..` so a new model
+    /// field fails to compile here until it is rendered and listed.
+    fn report_texts(report: &HqeReport) -> Vec<String> {
+        let HqeReport {
+            run_id,
+            provider,
+            executive_summary,
+            project_map,
+            pr_harvest,
+            deep_scan_results,
+            master_todo_backlog,
+            implementation_plan,
+            immediate_actions,
+            session_log,
+            suppressed_findings,
+            baseline,
+        } = report;
+        let mut texts = vec![run_id.clone()];
+
+        if let Some(ProviderInfo {
+            name,
+            base_url,
+            model,
+            llm_enabled,
+        }) = provider
+        {
+            texts.push(name.clone());
+            texts.extend(base_url.clone());
+            texts.extend(model.clone());
+            if !llm_enabled {
+                texts.push("LLM analysis disabled".to_string());
+            }
+        }
+
+        let ExecutiveSummary {
+            health_score,
+            top_priorities,
+            critical_findings,
+            blockers,
+        } = executive_summary;
+        texts.push(format!("{health_score}/10"));
+        texts.extend(top_priorities.iter().cloned());
+        texts.extend(critical_findings.iter().cloned());
+        for Blocker {
+            description,
+            reason,
+            how_to_obtain,
+        } in blockers
+        {
+            texts.extend([description.clone(), reason.clone(), how_to_obtain.clone()]);
+        }
+
+        let ProjectMap {
+            architecture,
+            entrypoints,
+            data_flow,
+            tech_stack,
+            code_stats,
+            licenses,
+            // Not in the report; the findings cover it
+            hygiene: _,
+        } = project_map;
+        let Architecture {
+            languages,
+            frameworks,
+            runtimes,
+            frontend_backend_separation,
+            databases,
+            message_queues,
+            third_party_services,
+            build_system,
+        } = architecture;
+        for list in [
+            languages,
+            frameworks,
+            runtimes,
+            databases,
+            message_queues,
+            third_party_services,
+        ] {
+            texts.push(list.join(", "));
+        }
+        texts.extend(frontend_backend_separation.clone());
+        texts.extend(build_system.clone());
+        for Entrypoint {
+            file_path,
+            entry_type,
+            description,
+        } in entrypoints
+        {
+            texts.extend([file_path.clone(), entry_type.clone(), description.clone()]);
+        }
+        texts.extend(data_flow.clone());
+        let TechStack {
+            detected,
+            package_managers,
+            // Too long for the report; see report.json and the SBOM
+            dependencies: _,
+        } = tech_stack;
+        for DetectedTechnology {
+            name,
+            version,
+            evidence,
+        } in detected
+        {
+            texts.extend([name.clone(), evidence.clone()]);
+            texts.extend(version.clone());
+        }
+        texts.push(package_managers.join(", "));
+        for DetectedLicense {
+            package,
+            license,
+            evidence,
+        } in licenses
+        {
+            texts.push(license.clone());
+            // Packages are counted per license; their names are in report.json
+            if package.is_none() {
+                texts.push(evidence.clone());
+            }
+        }
+        let CodeStats { languages } = code_stats;
+        for (
+            language,
+            LanguageStats {
+                files,
+                code_lines,
+                comment_lines,
+                blank_lines,
+            },
+        ) in languages
+        {
+            texts.push(format!(
+                "| {language} | {files} | {code_lines} | {comment_lines} | {blank_lines} |"
+            ));
+        }
+
+        if let Some(PrHarvest {
+            inventory,
+            conflicts,
+        }) = pr_harvest
+        {
+            for PrInfo {
+                pr_id,
+                title,
+                status,
+                intent,
+                files_touched,
+                risk,
+                recommendation,
+            } in inventory
+            {
+                texts.extend([pr_id.clone(), title.clone(), status.clone(), intent.clone()]);
+                texts.extend(files_touched.iter().cloned());
+                texts.push(risk.to_string());
+                texts.push(format!("{recommendation:?}"));
+            }
+            for PrConflict {
+                description,
+                pr_x,
+                approach_x,
+                pr_y,
+                approach_y,
+                resolution,
+                affected_files,
+            } in conflicts
+            {
+                texts.extend([
+                    description.clone(),
+                    format!("{pr_x}: {approach_x}"),
+                    format!("{pr_y}: {approach_y}"),
+                    resolution.clone(),
+                ]);
+                texts.extend(affected_files.iter().cloned());
+            }
+        }
+
+        let DeepScanResults {
+            security,
+            code_quality,
+            frontend,
+            backend,
+            testing,
+        } = deep_scan_results;
+        for findings in [security, code_quality, frontend, backend, testing] {
+            texts.extend(findings.iter().flat_map(finding_texts));
+        }
+
+        for TodoItem {
+            id,
+            severity,
+            risk,
+            category,
+            title,
+            root_cause,
+            evidence,
+            fix_approach,
+            verify,
+            blocked_by,
+        } in master_todo_backlog
+        {
+            texts.push(format!(
+                "| {id} | {severity} | {risk} | {category} | {title} |"
+            ));
+            texts.extend([root_cause.clone(), fix_approach.clone(), verify.clone()]);
+            texts.extend(blocked_by.clone());
+            texts.extend(evidence_texts(evidence));
+        }
+
+        let ImplementationPlan {
+            immediate,
+            short_term,
+            medium_term,
+            long_term,
+            dependency_graph,
+            risk_assessment,
+        } = implementation_plan;
+        for items in [immediate, short_term, medium_term, long_term] {
+            texts.extend(items.iter().map(|item| format!("- [ ] {item}")));
+        }
+        for (item, deps) in dependency_graph.iter().filter(|(_, d)| !d.is_empty()) {
+            texts.push(format!("{item} depends on {}", deps.join(", ")));
+        }
+        for RiskAssessment {
+            item_id,
+            mitigation,
+        } in risk_assessment
+        {
+            texts.extend([item_id.clone(), mitigation.clone()]);
+        }
+
+        for PatchAction {
+            todo_id,
+            title,
+            problem,
+            root_cause,
+            risk,
+            behavior_change,
+            diffs,
+            verification,
+            rollback,
+        } in immediate_actions
+        {
+            texts.extend([
+                format!("{todo_id}: {title}"),
+                problem.clone(),
+                root_cause.clone(),
+                risk.to_string(),
+                rollback.clone(),
+            ]);
+            if *behavior_change {
+                texts.push("BEHAVIOR CHANGE".to_string());
+            }
+            for FileDiff {
+                file_path,
+                diff_content,
+            } in diffs
+            {
+                texts.extend([file_path.clone(), diff_content.clone()]);
+            }
+            for VerificationStep {
+                command,
+                expected_output,
+            } in verification
+            {
+                texts.extend([command.clone(), expected_output.clone()]);
+            }
+        }
+
+        let SessionLog {
+            completed,
+            in_progress,
+            discovered,
+            reprioritized,
+            next_session,
+        } = session_log;
+        for items in [
+            completed,
+            in_progress,
+            discovered,
+            reprioritized,
+            next_session,
+        ] {
+            texts.extend(items.iter().cloned());
+        }
+
+        texts.extend(suppressed_findings.iter().flat_map(finding_texts));
+        if let Some(BaselineStatus { suppressed, stale }) = baseline {
+            texts.push(format!("{suppressed} finding(s) matched the baseline"));
+            for BaselineEntry {
+                fingerprint,
+                title,
+                file,
+            } in stale
+            {
+                texts.extend([fingerprint.clone(), title.clone()]);
+                texts.extend(file.clone());
+            }
+        }
+
+        texts
+    }
+
+    #[test]
+    fn test_every_report_field_is_rendered() {
+        let report = full_report();
+        let md = DefaultRenderer.render(&report);
+        let missing: Vec<String> = report_texts(&report)
+            .into_iter()
+            .filter(|text| !text.is_empty() && !md.contains(text.as_str()))
+            .collect();
+        assert!(missing.is_empty(), "not rendered: {missing:#?}");
+    }
+
+    /// Set `UPDATE_GOLDEN=1` to rewrite the expected file after an intended change
+    #[test]
+    fn test_full_report_matches_golden_file() {
+        let golden = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/full_report.md");
+        let md = DefaultRenderer.render(&full_report());
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(golden, &md).unwrap();
+        }
+        assert_eq!(md, std::fs::read_to_string(golden).unwrap());
+    }
+}
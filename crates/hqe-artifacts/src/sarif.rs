@@ -0,0 +1,433 @@
+//! SARIF 2.1.0 export for CI code scanning (GitHub, GitLab).
+//!
+//! Local heuristic findings keep their `finding_type` as the rule ID. Report
+//! findings that merely restate a local finding are skipped; the rest get a
+//! rule ID derived from their category. Findings without a file location
+//! (reproduction steps) cannot be shown by code scanning UIs and are left out.
+//!
+//! Code scanning only accepts locations relative to the repository root, so
+//! paths are written with forward slashes and without the scanned root.
+
+use hqe_core::models::{Evidence, Finding, HqeReport, LocalFinding, Severity};
+use serde::Serialize;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const SARIF_VERSION: &str = "2.1.0";
+const TOOL_NAME: &str = "HQE Workbench";
+const TOOL_URI: &str = "https://github.com/AbstergoSweden/HQE-Workbench";
+
+#[derive(Debug, Serialize)]
+pub(crate) struct SarifLog {
+    #[serde(rename = "$schema")]
+    schema: &'static str,
+    version: &'static str,
+    runs: Vec<Run>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Run {
+    tool: Tool,
+    automation_details: AutomationDetails,
+    results: Vec<SarifResult>,
+}
+
+#[derive(Debug, Serialize)]
+struct Tool {
+    driver: Driver,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Driver {
+    name: &'static str,
+    version: &'static str,
+    information_uri: &'static str,
+    rules: Vec<Rule>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Rule {
+    id: String,
+    short_description: Message,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    help: Option<Message>,
+    default_configuration: RuleConfiguration,
+}
+
+#[derive(Debug, Serialize)]
+struct RuleConfiguration {
+    level: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct AutomationDetails {
+    id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SarifResult {
+    rule_id: String,
+    rule_index: usize,
+    level: &'static str,
+    message: Message,
+    locations: Vec<Location>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Location {
+    physical_location: PhysicalLocation,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct PhysicalLocation {
+    artifact_location: ArtifactLocation,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    region: Option<Region>,
+}
+
+#[derive(Debug, Serialize)]
+struct ArtifactLocation {
+    uri: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Region {
+    start_line: usize,
+}
+
+/// Build a SARIF log from a report and the local findings of the same run.
+///
+/// `repo_root` is stripped from absolute finding paths.
+pub(crate) fn build_sarif_log(
+    report: &HqeReport,
+    local_findings: &[LocalFinding],
+    repo_root: Option<&str>,
+) -> SarifLog {
+    let mut builder = RunBuilder::default();
+    let locate = |file: &str, line: Option<usize>| location(&artifact_uri(file, repo_root), line);
+
+    for local in local_findings {
+        builder.push(
+            local.finding_type.clone(),
+            &local.severity,
+            local.description.clone(),
+            local.recommendation.clone(),
+            locate(&local.file_path, local.line_number),
+        );
+    }
+
+    let results = &report.deep_scan_results;
+    let findings = results
+        .security
+        .iter()
+        .chain(&results.code_quality)
+        .chain(&results.frontend)
+        .chain(&results.backend)
+        .chain(&results.testing);
+    for finding in findings {
+        if local_findings.iter().any(|local| restates(finding, local)) {
+            continue;
+        }
+        let location = match &finding.evidence {
+            Evidence::FileLine { file, line, .. } => locate(file, Some(*line)),
+            Evidence::FileFunction { file, .. } => locate(file, None),
+            Evidence::Reproduction { .. } => continue,
+        };
+        builder.push(
+            rule_id_for_category(&finding.category),
+            &finding.severity,
+            finding.title.clone(),
+            Some(finding.recommendation.clone()),
+            location,
+        );
+    }
+
+    SarifLog {
+        schema: SARIF_SCHEMA,
+        version: SARIF_VERSION,
+        runs: vec![Run {
+            tool: Tool {
+                driver: Driver {
+                    name: TOOL_NAME,
+                    version: env!("CARGO_PKG_VERSION"),
+                    information_uri: TOOL_URI,
+                    rules: builder.rules,
+                },
+            },
+            automation_details: AutomationDetails {
+                id: report.run_id.clone(),
+            },
+            results: builder.results,
+        }],
+    }
+}
+
+#[derive(Default)]
+struct RunBuilder {
+    rules: Vec<Rule>,
+    results: Vec<SarifResult>,
+}
+
+impl RunBuilder {
+    fn push(
+        &mut self,
+        rule_id: String,
+        severity: &Severity,
+        text: String,
+        recommendation: Option<String>,
+        location: Location,
+    ) {
+        let rule_index = match self.rules.iter().position(|rule| rule.id == rule_id) {
+            Some(index) => index,
+            None => {
+                // The first result of a rule describes it
+                self.rules.push(Rule {
+                    id: rule_id.clone(),
+                    short_description: Message { text: text.clone() },
+                    help: recommendation
+                        .filter(|text| !text.trim().is_empty())
+                        .map(|text| Message { text }),
+                    default_configuration: RuleConfiguration {
+                        level: sarif_level(severity),
+                    },
+                });
+                self.rules.len() - 1
+            }
+        };
+        self.results.push(SarifResult {
+            rule_id,
+            rule_index,
+            level: sarif_level(severity),
+            message: Message { text },
+            locations: vec![location],
+        });
+    }
+}
+
+/// Map HQE severity to a SARIF result level.
+fn sarif_level(severity: &Severity) -> &'static str {
+    match severity {
+        Severity::Critical | Severity::High => "error",
+        Severity::Medium => "warning",
+        Severity::Low | Severity::Info => "note",
+    }
+}
+
+/// `Code Quality` becomes `CODE_QUALITY`.
+fn rule_id_for_category(category: &str) -> String {
+    let id = category
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_ascii_uppercase)
+        .collect::<Vec<_>>()
+        .join("_");
+    if id.is_empty() {
+        "FINDING".to_string()
+    } else {
+        id
+    }
+}
+
+/// Whether a report finding is the formal copy of a local finding.
+fn restates(finding: &Finding, local: &LocalFinding) -> bool {
+    match &finding.evidence {
+        Evidence::FileLine { file, line, .. } => {
+            finding.title == local.description
+                && *file == local.file_path
+                && *line == local.line_number.unwrap_or(1)
+        }
+        _ => false,
+    }
+}
+
+/// Repository-relative, `/`-separated form of a finding path
+fn artifact_uri(file: &str, repo_root: Option<&str>) -> String {
+    let mut uri = file.replace('\\', "/");
+    if let Some(root) = repo_root.map(|root| root.replace('\\', "/")) {
+        let root = root.trim_end_matches('/');
+        if let Some(rest) = uri
+            .strip_prefix(root)
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            uri = rest.to_string();
+        }
+    }
+    let mut relative = uri.as_str();
+    while let Some(rest) = relative.strip_prefix("./") {
+        relative = rest.trim_start_matches('/');
+    }
+    relative.to_string()
+}
+
+fn location(uri: &str, line: Option<usize>) -> Location {
+    Location {
+        physical_location: PhysicalLocation {
+            artifact_location: ArtifactLocation {
+                uri: uri.to_string(),
+            },
+            // SARIF lines are 1-based
+            region: line.map(|line| Region {
+                start_line: line.max(1),
+            }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hqe_core::models::*;
+
+    fn local(finding_type: &str, severity: Severity, line: Option<usize>) -> LocalFinding {
+        LocalFinding {
+            finding_type: finding_type.to_string(),
+            description: format!("{finding_type} detected"),
+            file_path: "src\\config.rs".to_string(),
+            severity,
+            line_number: line,
+            snippet: None,
+            recommendation: None,
+        }
+    }
+
+    fn finding(category: &str, title: &str, evidence: Evidence) -> Finding {
+        Finding {
+            id: "SEC-001".to_string(),
+            severity: Severity::Medium,
+            risk: RiskLevel::Medium,
+            category: category.to_string(),
+            title: title.to_string(),
+            evidence,
+            impact: String::new(),
+            recommendation: String::new(),
+            suppressed: false,
+        }
+    }
+
+    #[test]
+    fn test_build_sarif_log_maps_findings() -> anyhow::Result<()> {
+        let locals = vec![
+            local("POTENTIAL_GITHUB_TOKEN", Severity::Critical, Some(12)),
+            local("POTENTIAL_GITHUB_TOKEN", Severity::Critical, Some(40)),
+            local("TODO", Severity::Info, None),
+        ];
+        let mut report = HqeReport {
+            run_id: "run-1".to_string(),
+            provider: None,
+            executive_summary: ExecutiveSummary::default(),
+            project_map: ProjectMap {
+                architecture: Architecture::default(),
+                entrypoints: vec![],
+                data_flow: None,
+                tech_stack: TechStack::default(),
+                code_stats: CodeStats::default(),
+                licenses: vec![],
+                hygiene: RepoHygiene::default(),
+            },
+            pr_harvest: None,
+            deep_scan_results: DeepScanResults::default(),
+            master_todo_backlog: vec![],
+            implementation_plan: ImplementationPlan::default(),
+            immediate_actions: vec![],
+            session_log: SessionLog::default(),
+            suppressed_findings: vec![],
+            baseline: None,
+        };
+        report.deep_scan_results.security.push(finding(
+            "Security",
+            "POTENTIAL_GITHUB_TOKEN detected",
+            Evidence::FileLine {
+                file: "src\\config.rs".to_string(),
+                line: 12,
+                snippet: String::new(),
+            },
+        ));
+        report.deep_scan_results.code_quality.push(finding(
+            "Code Quality",
+            "Unbounded retry loop",
+            Evidence::FileFunction {
+                file: "src/client.rs".to_string(),
+                function: "send".to_string(),
+                snippet: String::new(),
+            },
+        ));
+        report.deep_scan_results.testing.push(finding(
+            "Testing",
+            "Flaky test",
+            Evidence::Reproduction {
+                steps: vec![],
+                observed: String::new(),
+            },
+        ));
+
+        let sarif = serde_json::to_value(build_sarif_log(&report, &locals, None))?;
+        assert_eq!(sarif["version"], "2.1.0");
+        let run = &sarif["runs"][0];
+        assert_eq!(run["automationDetails"]["id"], "run-1");
+
+        let rules: Vec<&str> = run["tool"]["driver"]["rules"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|rule| rule["id"].as_str())
+            .collect();
+        assert_eq!(rules, ["POTENTIAL_GITHUB_TOKEN", "TODO", "CODE_QUALITY"]);
+        let rule = &run["tool"]["driver"]["rules"][0];
+        assert_eq!(rule["defaultConfiguration"]["level"], "error");
+        assert!(rule.get("help").is_none());
+
+        let results = run["results"].as_array().cloned().unwrap_or_default();
+        assert_eq!(results.len(), 4);
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/config.rs"
+        );
+        assert_eq!(
+            results[0]["locations"][0]["physicalLocation"]["region"]["startLine"],
+            12
+        );
+        assert_eq!(results[1]["ruleIndex"], 0);
+        assert_eq!(results[2]["level"], "note");
+        assert!(results[2]["locations"][0]["physicalLocation"]
+            .get("region")
+            .is_none());
+        assert_eq!(results[3]["ruleId"], "CODE_QUALITY");
+        assert_eq!(results[3]["level"], "warning");
+        Ok(())
+    }
+
+    #[test]
+    fn test_artifact_uri_is_repo_relative() {
+        let root = Some("/work/repo/");
+        assert_eq!(artifact_uri("/work/repo/src/main.rs", root), "src/main.rs");
+        assert_eq!(artifact_uri("./src\\lib.rs", root), "src/lib.rs");
+        assert_eq!(
+            artifact_uri("C:\\work\\repo\\src\\lib.rs", Some("C:\\work\\repo")),
+            "src/lib.rs"
+        );
+        // A sibling directory sharing the prefix is not inside the root
+        assert_eq!(
+            artifact_uri("/work/repository/a.rs", root),
+            "/work/repository/a.rs"
+        );
+    }
+
+    #[test]
+    fn test_rule_id_for_category() {
+        assert_eq!(rule_id_for_category("Security"), "SECURITY");
+        assert_eq!(rule_id_for_category("Code Quality"), "CODE_QUALITY");
+        assert_eq!(rule_id_for_category("  "), "FINDING");
+    }
+}
@@ -0,0 +1,137 @@
+//! CycloneDX 1.5 software bill of materials.
+//!
+//! Components come from the dependencies the scan extracted from manifests
+//! and lockfiles (`report.project_map.tech_stack.dependencies`). A package
+//! found in several files is one component with one evidence occurrence per
+//! file. Components without an exact version carry the declared requirement
+//! as their version and a purl without one.
+
+use hqe_core::models::Dependency;
+use hqe_core::scan::ScanResult;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use uuid::Uuid;
+
+const BOM_FORMAT: &str = "CycloneDX";
+const SPEC_VERSION: &str = "1.5";
+const TOOL_NAME: &str = "HQE Workbench";
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct Bom {
+    bom_format: &'static str,
+    spec_version: &'static str,
+    serial_number: String,
+    version: u32,
+    metadata: Metadata,
+    components: Vec<Component>,
+}
+
+#[derive(Debug, Serialize)]
+struct Metadata {
+    timestamp: String,
+    tools: Tools,
+    component: Component,
+}
+
+#[derive(Debug, Serialize)]
+struct Tools {
+    components: Vec<Component>,
+}
+
+#[derive(Debug, Serialize)]
+struct Component {
+    #[serde(rename = "type")]
+    kind: &'static str,
+    #[serde(rename = "bom-ref", skip_serializing_if = "Option::is_none")]
+    bom_ref: Option<String>,
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    purl: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    evidence: Option<Evidence>,
+}
+
+#[derive(Debug, Serialize)]
+struct Evidence {
+    occurrences: Vec<Occurrence>,
+}
+
+#[derive(Debug, Serialize)]
+struct Occurrence {
+    location: String,
+}
+
+impl Component {
+    fn library(dependency: &Dependency) -> Self {
+        let purl = dependency.purl();
+        Self {
+            kind: "library",
+            bom_ref: Some(purl.clone()),
+            name: dependency.name.clone(),
+            version: dependency
+                .version
+                .clone()
+                .or_else(|| dependency.requirement.clone()),
+            purl: Some(purl),
+            evidence: Some(Evidence {
+                occurrences: Vec::new(),
+            }),
+        }
+    }
+}
+
+/// Build the SBOM for a scan
+pub(crate) fn build_bom(result: &ScanResult) -> Bom {
+    // Keyed by purl so a package locked in several places is one component
+    let mut components: BTreeMap<String, Component> = BTreeMap::new();
+    for dependency in &result.report.project_map.tech_stack.dependencies {
+        let component = components
+            .entry(dependency.purl())
+            .or_insert_with(|| Component::library(dependency));
+        if let Some(evidence) = &mut component.evidence {
+            evidence.occurrences.push(Occurrence {
+                location: dependency.evidence.clone(),
+            });
+        }
+    }
+
+    let repo_path = result.manifest.repo.path.replace('\\', "/");
+    let repo_name = repo_path
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .filter(|name| !name.is_empty())
+        .unwrap_or("repository");
+
+    Bom {
+        bom_format: BOM_FORMAT,
+        spec_version: SPEC_VERSION,
+        serial_number: format!("urn:uuid:{}", Uuid::new_v4()),
+        version: 1,
+        metadata: Metadata {
+            timestamp: result.manifest.timestamps.started.to_rfc3339(),
+            tools: Tools {
+                components: vec![Component {
+                    kind: "application",
+                    bom_ref: None,
+                    name: TOOL_NAME.to_string(),
+                    version: Some(env!("CARGO_PKG_VERSION").to_string()),
+                    purl: None,
+                    evidence: None,
+                }],
+            },
+            component: Component {
+                kind: "application",
+                bom_ref: None,
+                name: repo_name.to_string(),
+                version: result.manifest.repo.git_commit.clone(),
+                purl: None,
+                evidence: None,
+            },
+        },
+        components: components.into_values().collect(),
+    }
+}
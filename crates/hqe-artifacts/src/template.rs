@@ -0,0 +1,287 @@
+//! Template-driven Markdown reports.
+//!
+//! [`TemplateRenderer`] renders `report.md` from a [MiniJinja] template. The
+//! stock layout ships as [`DEFAULT_REPORT_TEMPLATE`]; teams that want another
+//! layout copy it and point [`TemplateRenderer::from_path`] at their version.
+//!
+//! Templates see these variables:
+//!
+//! - `report`: the [`HqeReport`], in the same shape as `report.json`
+//! - `code_stats`: per-language rows (`language`, `files`, `code_lines`,
+//!   `comment_lines`, `blank_lines`), largest first
+//! - `code_stats_total`: the same counts summed over every language
+//! - `repository_licenses`: the repository's own licenses (`license`,
+//!   `evidence`)
+//! - `license_counts`: rows of `license` and the number of `packages` using
+//!   it, most used first
+//! - `dependencies`: the implementation plan's dependency graph as
+//!   `item`/`depends_on` pairs, sorted by item, without items that have none
+//!
+//! plus these filters and functions:
+//!
+//! - `cell`: trims, truncates and escapes text for a table cell
+//! - `escape_cell`: escapes text for a table cell without truncating it
+//! - `code`: wraps text in a code span
+//! - `trim_end`: strips trailing whitespace only, keeping snippet indentation
+//! - `truncation_note()`: the "cells are truncated" note if a `cell` was cut
+//!   since the previous call, otherwise nothing
+//!
+//! Blocks are trimmed (`trim_blocks` and `lstrip_blocks`), so a line holding
+//! only a `{% ... %}` tag leaves no blank line behind.
+//!
+//! [MiniJinja]: https://docs.rs/minijinja
+
+use crate::render::{
+    code_span, escape_table_cell, package_license_counts, truncate_cell, DefaultRenderer,
+    ReportRenderer, DEFAULT_MAX_CELL_CHARS, TRUNCATION_NOTE,
+};
+use hqe_core::models::{DetectedLicense, HqeReport, LanguageStats};
+use hqe_core::HqeError;
+use minijinja::Environment;
+use serde::Serialize;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tracing::warn;
+
+/// The stock HQE v3 report layout
+pub const DEFAULT_REPORT_TEMPLATE: &str = include_str!("../templates/report.md.j2");
+/// Name [`DEFAULT_REPORT_TEMPLATE`] is reported under in errors
+const DEFAULT_TEMPLATE_NAME: &str = "report.md.j2";
+
+/// Renders the report from a MiniJinja template
+#[derive(Debug, Clone)]
+pub struct TemplateRenderer {
+    name: String,
+    source: String,
+    max_cell_chars: Option<usize>,
+}
+
+impl Default for TemplateRenderer {
+    fn default() -> Self {
+        Self {
+            name: DEFAULT_TEMPLATE_NAME.to_string(),
+            source: DEFAULT_REPORT_TEMPLATE.to_string(),
+            max_cell_chars: Some(DEFAULT_MAX_CELL_CHARS),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct CodeStatsRow<'a> {
+    language: &'a str,
+    #[serde(flatten)]
+    stats: &'a LanguageStats,
+}
+
+#[derive(Serialize)]
+struct LicenseCount<'a> {
+    license: &'a str,
+    packages: usize,
+}
+
+#[derive(Serialize)]
+struct Dependency<'a> {
+    item: &'a str,
+    depends_on: &'a [String],
+}
+
+#[derive(Serialize)]
+struct TemplateContext<'a> {
+    report: &'a HqeReport,
+    code_stats: Vec<CodeStatsRow<'a>>,
+    code_stats_total: LanguageStats,
+    repository_licenses: Vec<&'a DetectedLicense>,
+    license_counts: Vec<LicenseCount<'a>>,
+    dependencies: Vec<Dependency<'a>>,
+}
+
+impl<'a> TemplateContext<'a> {
+    fn new(report: &'a HqeReport) -> Self {
+        let code_stats = &report.project_map.code_stats;
+        let licenses = &report.project_map.licenses;
+        let mut dependencies: Vec<Dependency> = report
+            .implementation_plan
+            .dependency_graph
+            .iter()
+            .filter(|(_, deps)| !deps.is_empty())
+            .map(|(item, deps)| Dependency {
+                item,
+                depends_on: deps,
+            })
+            .collect();
+        dependencies.sort_by(|a, b| a.item.cmp(b.item));
+
+        Self {
+            report,
+            code_stats: code_stats
+                .by_code_lines()
+                .into_iter()
+                .map(|(language, stats)| CodeStatsRow { language, stats })
+                .collect(),
+            code_stats_total: code_stats.total(),
+            repository_licenses: licenses.iter().filter(|l| l.package.is_none()).collect(),
+            license_counts: package_license_counts(licenses)
+                .into_iter()
+                .map(|(license, packages)| LicenseCount { license, packages })
+                .collect(),
+            dependencies,
+        }
+    }
+}
+
+impl TemplateRenderer {
+    /// Use `source` as the template; `name` identifies it in error messages.
+    ///
+    /// Fails with [`HqeError::Artifacts`] when the template does not parse.
+    pub fn new(name: impl Into<String>, source: impl Into<String>) -> Result<Self, HqeError> {
+        let renderer = Self {
+            name: name.into(),
+            source: source.into(),
+            ..Self::default()
+        };
+        renderer.environment(Arc::default())?;
+        Ok(renderer)
+    }
+
+    /// Load the template from a file
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, HqeError> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            HqeError::Artifacts(format!(
+                "Cannot read report template {}: {e}",
+                path.display()
+            ))
+        })?;
+        Self::new(path.display().to_string(), source)
+    }
+
+    /// Longest table cell for the `cell` filter; `None` keeps the full text
+    pub fn with_max_cell_chars(mut self, max: Option<usize>) -> Self {
+        self.max_cell_chars = max;
+        self
+    }
+
+    /// A fresh environment holding the compiled template. Truncation state is
+    /// per render, so concurrent renders do not share notes.
+    fn environment(&self, truncated: Arc<AtomicBool>) -> Result<Environment<'_>, HqeError> {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+        env.set_keep_trailing_newline(true);
+
+        let max = self.max_cell_chars;
+        let cut = truncated.clone();
+        env.add_filter("cell", move |text: String| {
+            let mut was_cut = false;
+            let cell = truncate_cell(&text, max, &mut was_cut);
+            if was_cut {
+                cut.store(true, Ordering::Relaxed);
+            }
+            cell
+        });
+        env.add_filter("escape_cell", |text: String| escape_table_cell(&text));
+        env.add_filter("code", |text: String| code_span(&text));
+        env.add_filter("trim_end", |text: String| text.trim_end().to_string());
+        env.add_function("truncation_note", move || {
+            if truncated.swap(false, Ordering::Relaxed) {
+                TRUNCATION_NOTE
+            } else {
+                ""
+            }
+        });
+
+        env.add_template(&self.name, &self.source)
+            .map_err(|e| self.error(&e))?;
+        Ok(env)
+    }
+
+    /// [`HqeError::Artifacts`] naming the template, line and column
+    fn error(&self, err: &minijinja::Error) -> HqeError {
+        let mut location = self.name.clone();
+        if let Some(line) = err.line() {
+            location.push_str(&format!(":{line}"));
+            if let Some(range) = err.range() {
+                let before = self.source.get(..range.start).unwrap_or_default();
+                let column = before
+                    .rsplit('\n')
+                    .next()
+                    .unwrap_or_default()
+                    .chars()
+                    .count()
+                    + 1;
+                location.push_str(&format!(":{column}"));
+            }
+        }
+        let reason = match err.detail() {
+            Some(detail) => format!("{}: {detail}", err.kind()),
+            None => err.kind().to_string(),
+        };
+        HqeError::Artifacts(format!("Report template {location}: {reason}"))
+    }
+}
+
+impl ReportRenderer for TemplateRenderer {
+    fn try_render(&self, report: &HqeReport) -> Result<String, HqeError> {
+        let env = self.environment(Arc::default())?;
+        let template = env.get_template(&self.name).map_err(|e| self.error(&e))?;
+        template
+            .render(TemplateContext::new(report))
+            .map_err(|e| self.error(&e))
+    }
+
+    /// Falls back to the [`DefaultRenderer`] layout when the template fails
+    fn render(&self, report: &HqeReport) -> String {
+        self.try_render(report).unwrap_or_else(|e| {
+            warn!("{e}; using the built-in report layout");
+            DefaultRenderer.render(report)
+        })
+    }
+
+    fn max_cell_chars(&self) -> Option<usize> {
+        self.max_cell_chars
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::render::tests::full_report;
+
+    #[test]
+    fn test_default_template_matches_default_renderer() {
+        let report = full_report();
+        let golden = include_str!("../tests/golden/full_report.md");
+        assert_eq!(
+            TemplateRenderer::default().try_render(&report).unwrap(),
+            golden
+        );
+    }
+
+    #[test]
+    fn test_custom_template_reorders_sections() {
+        let template = "{% for todo in report.master_todo_backlog %}- {{ todo.id }} ({{ todo.severity|title }})\n{% endfor %}Health: {{ report.executive_summary.health_score }}/10\n";
+        let renderer = TemplateRenderer::new("backlog.md.j2", template).unwrap();
+        assert_eq!(
+            renderer.try_render(&full_report()).unwrap(),
+            "- SEC-001 (High)\nHealth: 6/10\n"
+        );
+    }
+
+    #[test]
+    fn test_template_errors_report_line_and_column() {
+        let err = TemplateRenderer::new("broken.md.j2", "# Report\n\n{% if report.run_id %}\n")
+            .unwrap_err();
+        assert!(
+            matches!(&err, HqeError::Artifacts(msg) if msg.starts_with("Report template broken.md.j2:3")),
+            "{err}"
+        );
+
+        let renderer =
+            TemplateRenderer::new("filter.md.j2", "# Report\n  {{ report.run_id|missing }}\n")
+                .unwrap();
+        let err = renderer.try_render(&full_report()).unwrap_err().to_string();
+        assert!(err.contains("filter.md.j2:2:"), "{err}");
+        assert!(err.contains("missing"), "{err}");
+    }
+}
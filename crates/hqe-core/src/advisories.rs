@@ -0,0 +1,639 @@
+//! Dependency advisories and lockfile parsing.
+//!
+//! A small advisory list ships with the crate (`data/advisories.json`); users
+//! can point [`ScanConfig::advisory_file`](crate::models::ScanConfig) at an
+//! updated feed in the same format. [`RepoScanner`](crate::repo::RepoScanner)
+//! matches the pinned versions in `Cargo.lock`, `package-lock.json`,
+//! `requirements*.txt`, `poetry.lock` and `go.mod` against it.
+
+use crate::models::Severity;
+use crate::HqeError;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::HashSet;
+use std::path::Path;
+use tracing::warn;
+
+const BUNDLED_ADVISORIES: &str = include_str!("../data/advisories.json");
+
+/// Package ecosystem an advisory applies to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Ecosystem {
+    /// Rust crates (`Cargo.lock`)
+    Cargo,
+    /// npm packages (`package-lock.json`)
+    Npm,
+    /// Python packages (`requirements.txt`, `poetry.lock`)
+    Pypi,
+    /// Go modules (`go.mod`)
+    Go,
+}
+
+impl Ecosystem {
+    /// Package URL type (`pkg:<type>/...`) for this ecosystem
+    pub fn purl_type(self) -> &'static str {
+        match self {
+            Self::Cargo => "cargo",
+            Self::Npm => "npm",
+            Self::Pypi => "pypi",
+            Self::Go => "golang",
+        }
+    }
+
+    /// Ecosystem name in the OSV schema
+    pub fn osv_name(self) -> &'static str {
+        match self {
+            Self::Cargo => "crates.io",
+            Self::Npm => "npm",
+            Self::Pypi => "PyPI",
+            Self::Go => "Go",
+        }
+    }
+}
+
+/// Versions affected by an advisory: `introduced <= version < fixed`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VersionRange {
+    /// First affected version; all earlier versions when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub introduced: Option<String>,
+    /// First fixed version; unfixed when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fixed: Option<String>,
+}
+
+impl VersionRange {
+    /// Whether `version` falls inside this range
+    pub fn contains(&self, version: &str) -> bool {
+        let before_introduced = matches!(
+            self.introduced.as_deref(),
+            Some(introduced) if compare_versions(version, introduced).is_lt()
+        );
+        let fixed = matches!(
+            self.fixed.as_deref(),
+            Some(fixed) if compare_versions(version, fixed).is_ge()
+        );
+        !before_introduced && !fixed
+    }
+}
+
+/// A known vulnerability in a package
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Advisory {
+    /// Advisory ID (e.g. `RUSTSEC-2020-0071`, `GHSA-...`)
+    pub id: String,
+    /// Ecosystem of the affected package
+    pub ecosystem: Ecosystem,
+    /// Package name
+    pub package: String,
+    /// One-line description
+    pub summary: String,
+    /// Severity of findings raised for this advisory
+    #[serde(default = "default_advisory_severity")]
+    pub severity: Severity,
+    /// Affected version ranges
+    pub affected: Vec<VersionRange>,
+}
+
+fn default_advisory_severity() -> Severity {
+    Severity::High
+}
+
+/// Contents of an advisory feed file (JSON).
+///
+/// ```json
+/// {"advisories": [{"id": "GHSA-...", "ecosystem": "npm", "package": "lodash",
+///   "summary": "...", "affected": [{"fixed": "4.17.21"}]}]}
+/// ```
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AdvisoryFeed {
+    /// Keep the bundled advisories and add these to them
+    #[serde(default = "default_extend_defaults")]
+    pub extend_defaults: bool,
+    /// Advisories in the feed
+    #[serde(default)]
+    pub advisories: Vec<Advisory>,
+}
+
+fn default_extend_defaults() -> bool {
+    true
+}
+
+impl AdvisoryFeed {
+    /// Load a feed from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let raw = std::fs::read_to_string(path)?;
+        serde_json::from_str(&raw).map_err(|e| {
+            HqeError::Config(format!("Invalid advisory file {}: {}", path.display(), e))
+        })
+    }
+}
+
+/// Advisories matched against lockfile entries
+#[derive(Debug, Clone)]
+pub struct AdvisoryDb {
+    advisories: Vec<Advisory>,
+}
+
+impl Default for AdvisoryDb {
+    fn default() -> Self {
+        Self::new(bundled_advisories())
+    }
+}
+
+impl AdvisoryDb {
+    /// Build a database from a list of advisories
+    pub fn new(advisories: Vec<Advisory>) -> Self {
+        Self { advisories }
+    }
+
+    /// Build a database from a feed file (see [`AdvisoryFeed`])
+    pub fn from_file(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let feed = AdvisoryFeed::load(path)?;
+        let mut advisories = if feed.extend_defaults {
+            bundled_advisories()
+        } else {
+            Vec::new()
+        };
+        advisories.extend(feed.advisories);
+        Ok(Self::new(advisories))
+    }
+
+    /// Number of advisories
+    pub fn len(&self) -> usize {
+        self.advisories.len()
+    }
+
+    /// Whether the database has no advisories
+    pub fn is_empty(&self) -> bool {
+        self.advisories.is_empty()
+    }
+
+    /// Advisories affecting `package` at `version`, with the fixed version of the matching range
+    pub fn matches(
+        &self,
+        ecosystem: Ecosystem,
+        package: &str,
+        version: &str,
+    ) -> Vec<(&Advisory, Option<&str>)> {
+        let package = normalize_package_name(ecosystem, package);
+        self.advisories
+            .iter()
+            .filter(|a| {
+                a.ecosystem == ecosystem && normalize_package_name(ecosystem, &a.package) == package
+            })
+            .filter_map(|a| {
+                a.affected
+                    .iter()
+                    .find(|range| range.contains(version))
+                    .map(|range| (a, range.fixed.as_deref()))
+            })
+            .collect()
+    }
+}
+
+/// Advisories shipped with the crate
+pub fn bundled_advisories() -> Vec<Advisory> {
+    match serde_json::from_str::<AdvisoryFeed>(BUNDLED_ADVISORIES) {
+        Ok(feed) => feed.advisories,
+        Err(e) => {
+            warn!("Bundled advisory list is invalid: {}", e);
+            Vec::new()
+        }
+    }
+}
+
+/// PyPI names are case-insensitive and treat `-`, `_` and `.` alike
+fn normalize_package_name(ecosystem: Ecosystem, name: &str) -> String {
+    match ecosystem {
+        Ecosystem::Pypi => name.to_lowercase().replace(['_', '.'], "-"),
+        Ecosystem::Cargo | Ecosystem::Npm | Ecosystem::Go => name.to_string(),
+    }
+}
+
+/// Compare dotted versions numerically, ignoring pre-release and build suffixes.
+///
+/// Missing components count as zero, so `1.2` equals `1.2.0`.
+pub fn compare_versions(a: &str, b: &str) -> Ordering {
+    let parse = |v: &str| -> Vec<u64> {
+        let mut numbers = Vec::new();
+        for part in v.trim().trim_start_matches(['v', '=']).split('.') {
+            let digits: String = part.chars().take_while(char::is_ascii_digit).collect();
+            let Ok(number) = digits.parse() else {
+                break;
+            };
+            numbers.push(number);
+            // "0-rc.1" still contributes 0, but nothing after the suffix does
+            if digits.len() < part.len() {
+                break;
+            }
+        }
+        numbers
+    };
+    let (a, b) = (parse(a), parse(b));
+    for i in 0..a.len().max(b.len()) {
+        let ordering = a
+            .get(i)
+            .copied()
+            .unwrap_or(0)
+            .cmp(&b.get(i).copied().unwrap_or(0));
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    Ordering::Equal
+}
+
+/// A package version pinned by a lockfile
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LockedPackage {
+    /// Package name
+    pub name: String,
+    /// Pinned version
+    pub version: String,
+    /// Line of the entry in the lockfile, when known
+    pub line_number: Option<usize>,
+}
+
+/// Ecosystem of a lockfile, by file name
+pub fn lockfile_ecosystem(path: &str) -> Option<Ecosystem> {
+    let name = Path::new(path).file_name()?.to_str()?;
+    match name {
+        "Cargo.lock" => Some(Ecosystem::Cargo),
+        "package-lock.json" => Some(Ecosystem::Npm),
+        "poetry.lock" => Some(Ecosystem::Pypi),
+        "go.mod" => Some(Ecosystem::Go),
+        _ if name.starts_with("requirements") && name.ends_with(".txt") => Some(Ecosystem::Pypi),
+        _ => None,
+    }
+}
+
+/// Parse the packages pinned by a lockfile of the given ecosystem
+pub fn parse_lockfile(ecosystem: Ecosystem, content: &str) -> crate::Result<Vec<LockedPackage>> {
+    match ecosystem {
+        Ecosystem::Cargo => parse_cargo_lock(content),
+        Ecosystem::Npm => parse_package_lock(content),
+        // Requirements files never hold TOML array tables
+        Ecosystem::Pypi if content.contains("[[package]]") => parse_poetry_lock(content),
+        Ecosystem::Pypi => Ok(parse_requirements(content)),
+        Ecosystem::Go => Ok(parse_go_mod(content)),
+    }
+}
+
+/// `[[package]]` tables with `name` and `version`, as in `Cargo.lock` and
+/// `poetry.lock`, with the line of each `name = ...` entry
+fn parse_package_tables(content: &str, file: &str) -> crate::Result<Vec<LockedPackage>> {
+    #[derive(Deserialize)]
+    struct Lock {
+        #[serde(default)]
+        package: Vec<Package>,
+    }
+    #[derive(Deserialize)]
+    struct Package {
+        name: String,
+        version: String,
+    }
+
+    let lock: Lock = toml::from_str(content)
+        .map_err(|e| HqeError::Serialization(format!("Invalid {}: {}", file, e)))?;
+    let lines: Vec<&str> = content.lines().collect();
+    Ok(lock
+        .package
+        .into_iter()
+        .map(|p| {
+            let name_line = format!("name = \"{}\"", p.name);
+            let version_line = format!("version = \"{}\"", p.version);
+            let line_number = lines
+                .windows(2)
+                .position(|w| w[0].trim() == name_line && w[1].trim() == version_line)
+                .map(|i| i + 1);
+            LockedPackage {
+                name: p.name,
+                version: p.version,
+                line_number,
+            }
+        })
+        .collect())
+}
+
+fn parse_cargo_lock(content: &str) -> crate::Result<Vec<LockedPackage>> {
+    parse_package_tables(content, "Cargo.lock")
+}
+
+fn parse_poetry_lock(content: &str) -> crate::Result<Vec<LockedPackage>> {
+    parse_package_tables(content, "poetry.lock")
+}
+
+fn parse_package_lock(content: &str) -> crate::Result<Vec<LockedPackage>> {
+    let lock: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| HqeError::Serialization(format!("Invalid package-lock.json: {}", e)))?;
+    let line_of = |key: &str| {
+        let needle = format!("\"{}\": {{", key);
+        content
+            .lines()
+            .position(|line| line.trim_start().starts_with(&needle))
+            .map(|i| i + 1)
+    };
+
+    let mut packages = Vec::new();
+    let mut seen = HashSet::new();
+    if let Some(entries) = lock["packages"].as_object() {
+        // lockfileVersion 2 and 3: "node_modules/a/node_modules/b": {"version": ...}
+        for (key, entry) in entries {
+            let Some(version) = entry["version"].as_str() else {
+                continue;
+            };
+            let Some(name) = entry["name"]
+                .as_str()
+                .or_else(|| key.rsplit("node_modules/").next())
+                .filter(|name| !name.is_empty() && key.contains("node_modules/"))
+            else {
+                continue;
+            };
+            if seen.insert((name.to_string(), version.to_string())) {
+                packages.push(LockedPackage {
+                    name: name.to_string(),
+                    version: version.to_string(),
+                    line_number: line_of(key),
+                });
+            }
+        }
+    } else {
+        // lockfileVersion 1: nested "dependencies" maps
+        let mut stack = vec![&lock["dependencies"]];
+        while let Some(dependencies) = stack.pop() {
+            for (name, entry) in dependencies.as_object().into_iter().flatten() {
+                if let Some(version) = entry["version"].as_str() {
+                    if seen.insert((name.clone(), version.to_string())) {
+                        packages.push(LockedPackage {
+                            name: name.clone(),
+                            version: version.to_string(),
+                            line_number: line_of(name),
+                        });
+                    }
+                }
+                stack.push(&entry["dependencies"]);
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// `name==version` pins; ranges, URLs and options are not pinned and are skipped
+fn parse_requirements(content: &str) -> Vec<LockedPackage> {
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let line = match line.find(" #") {
+                Some(pos) => &line[..pos],
+                None => line,
+            };
+            let line = line.split(';').next().unwrap_or_default().trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with('-') {
+                return None;
+            }
+            let (name, version) = line.split_once("==")?;
+            let name = name.split('[').next().unwrap_or_default().trim();
+            let version = version
+                .trim_start_matches('=')
+                .split(|c: char| c.is_whitespace() || c == ',')
+                .next()
+                .unwrap_or_default();
+            if name.is_empty() || version.is_empty() || version.contains('*') {
+                return None;
+            }
+            Some(LockedPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                line_number: Some(idx + 1),
+            })
+        })
+        .collect()
+}
+
+/// `require` directives of a `go.mod`, single-line and block form
+fn parse_go_mod(content: &str) -> Vec<LockedPackage> {
+    let mut packages = Vec::new();
+    let mut in_block = false;
+    for (idx, line) in content.lines().enumerate() {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let entry = if in_block {
+            if line == ")" {
+                in_block = false;
+                continue;
+            }
+            line
+        } else if let Some(rest) = line.strip_prefix("require") {
+            let rest = rest.trim();
+            if rest == "(" {
+                in_block = true;
+                continue;
+            }
+            rest
+        } else {
+            continue;
+        };
+        let mut parts = entry.split_whitespace();
+        if let (Some(name), Some(version)) = (parts.next(), parts.next()) {
+            packages.push(LockedPackage {
+                name: name.to_string(),
+                version: version.to_string(),
+                line_number: Some(idx + 1),
+            });
+        }
+    }
+    packages
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bundled_advisories_parse() {
+        let db = AdvisoryDb::default();
+        assert!(!db.is_empty());
+        assert_eq!(db.len(), bundled_advisories().len());
+    }
+
+    #[test]
+    fn test_compare_versions() {
+        assert_eq!(compare_versions("1.2.3", "1.2.3"), Ordering::Equal);
+        assert_eq!(compare_versions("1.2", "1.2.0"), Ordering::Equal);
+        assert_eq!(compare_versions("0.2.22", "0.2.23"), Ordering::Less);
+        assert_eq!(compare_versions("4.17.21", "4.17.9"), Ordering::Greater);
+        assert_eq!(compare_versions("1.0.0-rc.1", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare_versions("v2.0", "1.9.9"), Ordering::Greater);
+    }
+
+    #[test]
+    fn test_advisory_matches_ranges_and_reports_fixed_version() {
+        let db = AdvisoryDb::default();
+
+        let hits = db.matches(Ecosystem::Cargo, "h2", "0.4.1");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].0.id, "RUSTSEC-2024-0003");
+        assert_eq!(hits[0].1, Some("0.4.2"));
+        assert!(db.matches(Ecosystem::Cargo, "h2", "0.3.24").is_empty());
+        assert!(db.matches(Ecosystem::Cargo, "h2", "0.4.2").is_empty());
+
+        // Unfixed advisory
+        let hits = db.matches(Ecosystem::Cargo, "rsa", "0.9.6");
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].1, None);
+
+        // Both lodash advisories, PyPI names normalized
+        assert_eq!(db.matches(Ecosystem::Npm, "lodash", "4.17.11").len(), 2);
+        assert_eq!(db.matches(Ecosystem::Pypi, "PyYAML", "5.3.1").len(), 1);
+        assert!(db.matches(Ecosystem::Npm, "pyyaml", "5.3.1").is_empty());
+    }
+
+    #[test]
+    fn test_advisory_file_extends_or_replaces_bundled() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let path = temp.path().join("feed.json");
+        std::fs::write(
+            &path,
+            r#"{"advisories": [{"id": "GHSA-test", "ecosystem": "npm", "package": "left-pad",
+                "summary": "Test", "affected": [{"fixed": "1.3.0"}]}]}"#,
+        )?;
+        let db = AdvisoryDb::from_file(&path)?;
+        assert_eq!(db.len(), bundled_advisories().len() + 1);
+        let hits = db.matches(Ecosystem::Npm, "left-pad", "1.1.0");
+        assert!(matches!(hits[0].0.severity, Severity::High));
+
+        std::fs::write(&path, r#"{"extend_defaults": false, "advisories": []}"#)?;
+        assert!(AdvisoryDb::from_file(&path)?.is_empty());
+
+        std::fs::write(&path, "not json")?;
+        assert!(matches!(
+            AdvisoryDb::from_file(&path),
+            Err(HqeError::Config(_))
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_cargo_lock() -> anyhow::Result<()> {
+        let lock = "version = 3\n\n[[package]]\nname = \"h2\"\nversion = \"0.3.20\"\nsource = \"registry+https://github.com/rust-lang/crates.io-index\"\n\n[[package]]\nname = \"demo\"\nversion = \"0.1.0\"\n";
+        let packages = parse_lockfile(Ecosystem::Cargo, lock)?;
+        assert_eq!(
+            packages[0],
+            LockedPackage {
+                name: "h2".to_string(),
+                version: "0.3.20".to_string(),
+                line_number: Some(4),
+            }
+        );
+        assert_eq!(packages.len(), 2);
+        assert!(parse_lockfile(Ecosystem::Cargo, "[[package]\nname=").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_package_lock_v1_and_v3() -> anyhow::Result<()> {
+        let v3 = r#"{
+  "name": "app",
+  "lockfileVersion": 3,
+  "packages": {
+    "": { "name": "app", "version": "1.0.0" },
+    "node_modules/lodash": { "version": "4.17.11" },
+    "node_modules/a/node_modules/minimist": { "version": "1.2.5" },
+    "node_modules/linked": { "link": true }
+  }
+}"#;
+        let packages = parse_lockfile(Ecosystem::Npm, v3)?;
+        let names: Vec<(&str, &str, Option<usize>)> = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str(), p.line_number))
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert!(names.contains(&("lodash", "4.17.11", Some(6))));
+        assert!(names.contains(&("minimist", "1.2.5", Some(7))));
+
+        let v1 = r#"{
+  "lockfileVersion": 1,
+  "dependencies": {
+    "mkdirp": {
+      "version": "0.5.1",
+      "dependencies": { "minimist": { "version": "0.0.8" } }
+    }
+  }
+}"#;
+        let packages = parse_lockfile(Ecosystem::Npm, v1)?;
+        assert_eq!(packages.len(), 2);
+        assert!(packages
+            .iter()
+            .any(|p| p.name == "minimist" && p.version == "0.0.8"));
+
+        assert!(parse_lockfile(Ecosystem::Npm, "{ truncated").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_requirements() -> anyhow::Result<()> {
+        let requirements = "# pinned\nrequests==2.28.0\nPyYAML[libyaml]==5.3.1 ; python_version >= \"3.8\"\nflask>=2.0\n-r base.txt\nurllib3==1.26.5 --hash=sha256:abc # trailing\ndjango==4.*\n";
+        let packages = parse_lockfile(Ecosystem::Pypi, requirements)?;
+        let pins: Vec<(&str, &str, Option<usize>)> = packages
+            .iter()
+            .map(|p| (p.name.as_str(), p.version.as_str(), p.line_number))
+            .collect();
+        assert_eq!(
+            pins,
+            vec![
+                ("requests", "2.28.0", Some(2)),
+                ("PyYAML", "5.3.1", Some(3)),
+                ("urllib3", "1.26.5", Some(6)),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_lockfile_ecosystem() {
+        assert_eq!(lockfile_ecosystem("Cargo.lock"), Some(Ecosystem::Cargo));
+        assert_eq!(
+            lockfile_ecosystem("web/package-lock.json"),
+            Some(Ecosystem::Npm)
+        );
+        assert_eq!(
+            lockfile_ecosystem("requirements-dev.txt"),
+            Some(Ecosystem::Pypi)
+        );
+        assert_eq!(lockfile_ecosystem("py/poetry.lock"), Some(Ecosystem::Pypi));
+        assert_eq!(lockfile_ecosystem("go.mod"), Some(Ecosystem::Go));
+        assert_eq!(lockfile_ecosystem("package.json"), None);
+    }
+
+    #[test]
+    fn test_parse_poetry_lock_and_go_mod() -> anyhow::Result<()> {
+        let poetry = "[[package]]\nname = \"requests\"\nversion = \"2.31.0\"\ndescription = \"HTTP\"\n\n[metadata]\nlock-version = \"2.0\"\n";
+        let packages = parse_lockfile(Ecosystem::Pypi, poetry)?;
+        assert_eq!(
+            packages,
+            vec![LockedPackage {
+                name: "requests".to_string(),
+                version: "2.31.0".to_string(),
+                line_number: Some(2),
+            }]
+        );
+
+        let go_mod = "module example.com/app\n\ngo 1.22\n\nrequire github.com/pkg/errors v0.9.1\n\nrequire (\n\tgolang.org/x/net v0.17.0 // indirect\n\tgithub.com/spf13/cobra v1.8.0\n)\n";
+        let pins: Vec<(String, String, Option<usize>)> = parse_lockfile(Ecosystem::Go, go_mod)?
+            .into_iter()
+            .map(|p| (p.name, p.version, p.line_number))
+            .collect();
+        assert_eq!(
+            pins,
+            vec![
+                ("github.com/pkg/errors".into(), "v0.9.1".into(), Some(5)),
+                ("golang.org/x/net".into(), "v0.17.0".into(), Some(8)),
+                ("github.com/spf13/cobra".into(), "v1.8.0".into(), Some(9)),
+            ]
+        );
+        Ok(())
+    }
+}
@@ -8,6 +8,9 @@
 //! - Anti-hijack protections to prevent malicious event injection
 
 use std::collections::HashMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 use std::time::{Duration, Instant};
 use tracing::{debug, error, info, warn};
@@ -20,6 +23,10 @@ const MAX_EVENT_PROPERTIES: usize = 50;
 const MAX_PROPERTY_KEY_LENGTH: usize = 100;
 const MAX_PROPERTY_VALUE_LENGTH: usize = 1000;
 const MAX_EVENTS_PER_MINUTE: u32 = 60;
+/// Buffered events that trigger an immediate flush
+const FLUSH_BATCH_SIZE: usize = 20;
+/// Size at which [`FileAnalyticsBackend`] rotates its log file
+pub const DEFAULT_LOG_MAX_BYTES: u64 = 10 * 1024 * 1024;
 const ALLOWED_EVENT_PREFIXES: &[&str] = &["app_", "chat_", "scan_", "backend_", "error_"];
 const BLOCKED_PROPERTY_KEYS: &[&str] = &[
     "password",
@@ -176,16 +183,61 @@ impl Default for RateLimiter {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// CONFIGURATION
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Embedder additions to the built-in event prefixes and blocked property keys
+///
+/// The built-in lists always apply; entries here can only extend them.
+#[derive(Debug, Clone, Default)]
+pub struct AnalyticsConfig {
+    /// Event-name prefixes accepted besides `app_`, `chat_`, `scan_`, `backend_` and `error_`
+    pub extra_event_prefixes: Vec<String>,
+    /// Property keys removed from events besides the built-in sensitive keys
+    pub extra_blocked_keys: Vec<String>,
+}
+
+impl AnalyticsConfig {
+    /// Built-in event prefixes followed by the configured ones
+    pub fn allowed_event_prefixes(&self) -> Vec<String> {
+        merge_lowercase(ALLOWED_EVENT_PREFIXES, &self.extra_event_prefixes)
+    }
+
+    /// Built-in blocked property keys followed by the configured ones
+    pub fn blocked_property_keys(&self) -> Vec<String> {
+        merge_lowercase(BLOCKED_PROPERTY_KEYS, &self.extra_blocked_keys)
+    }
+}
+
+/// Lowercased `defaults` plus `extra`, skipping blank and duplicate entries
+///
+/// A blank prefix would match every event name, so it is never accepted.
+fn merge_lowercase(defaults: &[&str], extra: &[String]) -> Vec<String> {
+    let mut merged: Vec<String> = defaults.iter().map(|d| d.to_lowercase()).collect();
+    for entry in extra {
+        let entry = entry.trim().to_lowercase();
+        if !entry.is_empty() && !merged.contains(&entry) {
+            merged.push(entry);
+        }
+    }
+    merged
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // EVENT VALIDATOR (Anti-Hijack)
 // ═══════════════════════════════════════════════════════════════════════════════
 
 /// Validates and sanitizes analytics events
+///
+/// The allowed prefixes and blocked keys are passed in, normally from
+/// [`AnalyticsConfig::allowed_event_prefixes`] and
+/// [`AnalyticsConfig::blocked_property_keys`].
 pub struct EventValidator;
 
 impl EventValidator {
     /// Validate event name against allowed prefixes
-    pub fn validate_event_name(name: &str) -> Result<String> {
+    pub fn validate_event_name(name: &str, allowed_prefixes: &[String]) -> Result<String> {
         // Must be non-empty
         if name.is_empty() {
             return Err(AnalyticsError::InvalidEventName(
@@ -201,14 +253,15 @@ impl EventValidator {
         }
 
         // Must have allowed prefix
-        let has_valid_prefix = ALLOWED_EVENT_PREFIXES
+        let has_valid_prefix = allowed_prefixes
             .iter()
+            .filter(|prefix| !prefix.is_empty())
             .any(|prefix| name.to_lowercase().starts_with(&prefix.to_lowercase()));
 
         if !has_valid_prefix {
             return Err(AnalyticsError::InvalidEventName(format!(
                 "Event name must start with one of: {:?}",
-                ALLOWED_EVENT_PREFIXES
+                allowed_prefixes
             )));
         }
 
@@ -226,6 +279,7 @@ impl EventValidator {
     /// Validate and sanitize event properties
     pub fn validate_properties(
         properties: HashMap<String, serde_json::Value>,
+        blocked_keys: &[String],
     ) -> Result<HashMap<String, serde_json::Value>> {
         if properties.len() > MAX_EVENT_PROPERTIES {
             return Err(AnalyticsError::InvalidProperties(format!(
@@ -240,9 +294,10 @@ impl EventValidator {
         for (key, value) in properties {
             // Check for blocked keys
             let key_lower = key.to_lowercase();
-            let is_blocked = BLOCKED_PROPERTY_KEYS
-                .iter()
-                .any(|blocked| key_lower == *blocked || key_lower == blocked.replace("_", ""));
+            let is_blocked = blocked_keys.iter().any(|blocked| {
+                let blocked = blocked.to_lowercase();
+                key_lower == blocked || key_lower == blocked.replace("_", "")
+            });
 
             if is_blocked {
                 warn!(key = %key, "Removing sensitive property from analytics event");
@@ -347,7 +402,15 @@ pub trait AnalyticsBackend: Send + Sync {
 // POSTHOG BACKEND
 // ═══════════════════════════════════════════════════════════════════════════════
 
+/// Upper bound for one PostHog request, so a dead endpoint falls back quickly
+#[cfg(feature = "analytics-reqwest")]
+const POSTHOG_TIMEOUT: Duration = Duration::from_secs(3);
+
 /// PostHog analytics backend implementation
+///
+/// Events go to `POST {api_host}/capture/` and batches to `POST {api_host}/batch/`.
+/// Network errors and non-success responses are reported as
+/// [`AnalyticsError::BackendUnavailable`] so the manager falls back.
 #[cfg(feature = "analytics-reqwest")]
 pub struct PostHogBackend {
     api_key: String,
@@ -358,15 +421,21 @@ pub struct PostHogBackend {
 
 #[cfg(feature = "analytics-reqwest")]
 impl PostHogBackend {
+    /// Create a backend for `api_host` (defaults to PostHog cloud)
     pub fn new(api_key: String, api_host: Option<String>) -> Self {
         let client = reqwest::Client::builder()
-            .timeout(Duration::from_secs(10))
+            .timeout(POSTHOG_TIMEOUT)
+            .connect_timeout(POSTHOG_TIMEOUT)
+            // Every send runs on its own short-lived runtime; pooled
+            // connections would outlive it
+            .pool_max_idle_per_host(0)
             .build()
             .unwrap_or_default();
 
+        let api_host = api_host.unwrap_or_else(|| "https://app.posthog.com".to_string());
         Self {
             api_key,
-            api_host: api_host.unwrap_or_else(|| "https://app.posthog.com".to_string()),
+            api_host: api_host.trim_end_matches('/').to_string(),
             client,
             enabled: true,
         }
@@ -381,15 +450,9 @@ impl PostHogBackend {
     pub fn enable(&mut self) {
         self.enabled = true;
     }
-}
-
-#[cfg(feature = "analytics-reqwest")]
-impl AnalyticsBackend for PostHogBackend {
-    fn send_event(&self, event: AnalyticsEvent) -> Result<()> {
-        if !self.enabled {
-            return Ok(());
-        }
 
+    /// PostHog representation of an event; only the validated properties are sent
+    fn event_body(event: &AnalyticsEvent) -> serde_json::Value {
         let mut properties = event.properties.clone();
         properties.insert("distinct_id".to_string(), event.session_id.clone().into());
         properties.insert("$event_id".to_string(), event.event_id.clone().into());
@@ -400,22 +463,58 @@ impl AnalyticsBackend for PostHogBackend {
         properties.insert("$lib".to_string(), "hqe-workbench-rust".into());
         properties.insert("$lib_version".to_string(), env!("CARGO_PKG_VERSION").into());
 
-        let payload = serde_json::json!({
-            "api_key": self.api_key,
+        serde_json::json!({
             "event": event.name,
             "properties": properties,
             "timestamp": event.timestamp.to_rfc3339(),
+        })
+    }
+
+    /// POST `payload` to `{api_host}{path}`.
+    ///
+    /// The backend API is synchronous, so the request runs to completion on a
+    /// separate thread; this works both inside and outside a tokio runtime.
+    fn post(&self, path: &str, payload: &serde_json::Value) -> Result<()> {
+        let url = format!("{}{}", self.api_host, path);
+        let request = self.client.post(&url).json(payload);
+        let response = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let runtime = tokio::runtime::Builder::new_current_thread()
+                        .enable_all()
+                        .build()
+                        .map_err(|e| e.to_string())?;
+                    runtime
+                        // `send` starts its timeout, so it must run inside the runtime
+                        .block_on(async { request.send().await })
+                        .map_err(|e| e.to_string())?
+                        .error_for_status()
+                        .map(|_| ())
+                        .map_err(|e| e.to_string())
+                })
+                .join()
+                .unwrap_or_else(|_| Err("PostHog request thread panicked".to_string()))
         });
 
-        // In production, this would make an actual HTTP request
-        // For now, we just log in debug mode
-        debug!(
-            event = %event.name,
-            "Would send event to PostHog: {}",
-            payload.to_string()
-        );
+        response.map_err(|e| {
+            warn!(url = %url, error = %e, "PostHog request failed");
+            AnalyticsError::BackendUnavailable
+        })
+    }
+}
 
-        Ok(())
+#[cfg(feature = "analytics-reqwest")]
+impl AnalyticsBackend for PostHogBackend {
+    fn send_event(&self, event: AnalyticsEvent) -> Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let mut payload = Self::event_body(&event);
+        payload["api_key"] = self.api_key.clone().into();
+
+        debug!(event = %event.name, "Sending event to PostHog");
+        self.post("/capture/", &payload)
     }
 
     fn send_batch(&self, events: Vec<AnalyticsEvent>) -> Result<()> {
@@ -423,13 +522,13 @@ impl AnalyticsBackend for PostHogBackend {
             return Ok(());
         }
 
-        debug!(count = events.len(), "Sending batch of events to PostHog");
-
-        for event in events {
-            self.send_event(event)?;
-        }
+        let payload = serde_json::json!({
+            "api_key": self.api_key,
+            "batch": events.iter().map(Self::event_body).collect::<Vec<_>>(),
+        });
 
-        Ok(())
+        debug!(count = events.len(), "Sending batch of events to PostHog");
+        self.post("/batch/", &payload)
     }
 
     fn is_available(&self) -> bool {
@@ -437,7 +536,7 @@ impl AnalyticsBackend for PostHogBackend {
     }
 
     fn flush(&self) -> Result<()> {
-        // No-op for this implementation
+        // Events are sent synchronously; nothing is buffered
         Ok(())
     }
 }
@@ -474,13 +573,136 @@ impl AnalyticsBackend for PostHogBackend {
     }
 }
 
+// ═══════════════════════════════════════════════════════════════════════════════
+// FILE BACKEND (NDJSON Log)
+// ═══════════════════════════════════════════════════════════════════════════════
+
+/// Backend that appends events to a local file, one JSON object per line
+///
+/// The file and its parent directories are created on the first write. When
+/// the next line would grow the file past the size cap, it is renamed to
+/// `<name>.1` (replacing any older rotation) and a new file is started.
+pub struct FileAnalyticsBackend {
+    path: PathBuf,
+    max_bytes: u64,
+    /// Open writer and the current file size
+    writer: Mutex<Option<(BufWriter<File>, u64)>>,
+}
+
+impl FileAnalyticsBackend {
+    /// Create a backend writing to `path`, rotating at [`DEFAULT_LOG_MAX_BYTES`]
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            max_bytes: DEFAULT_LOG_MAX_BYTES,
+            writer: Mutex::new(None),
+        }
+    }
+
+    /// Rotate once the file would exceed `max_bytes`
+    pub fn with_max_bytes(mut self, max_bytes: u64) -> Self {
+        self.max_bytes = max_bytes;
+        self
+    }
+
+    /// Path of the current log file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Path the log file is renamed to on rotation
+    pub fn rotated_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".1");
+        self.path.with_file_name(name)
+    }
+
+    fn open(&self) -> std::io::Result<(BufWriter<File>, u64)> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let size = file.metadata()?.len();
+        Ok((BufWriter::new(file), size))
+    }
+
+    fn write_events(&self, events: &[AnalyticsEvent]) -> std::io::Result<()> {
+        let mut guard = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        for event in events {
+            let mut line = serde_json::to_vec(event)?;
+            line.push(b'\n');
+
+            if guard.is_none() {
+                *guard = Some(self.open()?);
+            }
+            if let Some((writer, size)) = guard.as_mut() {
+                if *size > 0 && *size + line.len() as u64 > self.max_bytes {
+                    writer.flush()?;
+                    *guard = None;
+                    std::fs::rename(&self.path, self.rotated_path())?;
+                    debug!(path = %self.path.display(), "Rotated analytics log");
+                }
+            }
+            let (writer, size) = match guard.as_mut() {
+                Some(open) => open,
+                None => guard.insert(self.open()?),
+            };
+            writer.write_all(&line)?;
+            *size += line.len() as u64;
+        }
+
+        if let Some((writer, _)) = guard.as_mut() {
+            writer.flush()?;
+        }
+        Ok(())
+    }
+
+    fn log_failure(&self, error: std::io::Error) -> AnalyticsError {
+        warn!(path = %self.path.display(), error = %error, "Failed to write analytics log");
+        AnalyticsError::BackendUnavailable
+    }
+}
+
+impl AnalyticsBackend for FileAnalyticsBackend {
+    fn send_event(&self, event: AnalyticsEvent) -> Result<()> {
+        self.write_events(std::slice::from_ref(&event))
+            .map_err(|e| self.log_failure(e))
+    }
+
+    fn send_batch(&self, events: Vec<AnalyticsEvent>) -> Result<()> {
+        self.write_events(&events).map_err(|e| self.log_failure(e))
+    }
+
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    fn flush(&self) -> Result<()> {
+        let mut guard = self
+            .writer
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        match guard.as_mut() {
+            Some((writer, _)) => writer.flush().map_err(|e| self.log_failure(e)),
+            None => Ok(()),
+        }
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // FALLBACK BACKEND (Local Logging)
 // ═══════════════════════════════════════════════════════════════════════════════
 
-/// Fallback backend that logs to local file/console
+/// Fallback backend that logs to console and optionally a local file
 pub struct FallbackBackend {
-    log_file: Option<std::path::PathBuf>,
+    log_file: Option<FileAnalyticsBackend>,
 }
 
 impl FallbackBackend {
@@ -489,10 +711,12 @@ impl FallbackBackend {
         Self { log_file: None }
     }
 
-    /// Create a fallback backend that also writes to a log file
-    pub fn with_log_file(log_file: std::path::PathBuf) -> Self {
+    /// Create a fallback backend that also appends to a log file
+    ///
+    /// See [`FileAnalyticsBackend`] for the file format and rotation.
+    pub fn with_log_file(log_file: PathBuf) -> Self {
         Self {
-            log_file: Some(log_file),
+            log_file: Some(FileAnalyticsBackend::new(log_file)),
         }
     }
 }
@@ -511,19 +735,23 @@ impl AnalyticsBackend for FallbackBackend {
 
         info!(target: "analytics_fallback", "{}", log_line);
 
-        if let Some(ref path) = self.log_file {
-            // Append to file (in production, would use proper file handling)
-            debug!(path = %path.display(), "Would write analytics to file");
+        match self.log_file {
+            Some(ref file) => file.send_event(event),
+            None => Ok(()),
         }
-
-        Ok(())
     }
 
     fn send_batch(&self, events: Vec<AnalyticsEvent>) -> Result<()> {
-        for event in events {
-            self.send_event(event)?;
+        for event in &events {
+            if let Ok(log_line) = serde_json::to_string(event) {
+                info!(target: "analytics_fallback", "{}", log_line);
+            }
+        }
+
+        match self.log_file {
+            Some(ref file) => file.send_batch(events),
+            None => Ok(()),
         }
-        Ok(())
     }
 
     fn is_available(&self) -> bool {
@@ -531,7 +759,10 @@ impl AnalyticsBackend for FallbackBackend {
     }
 
     fn flush(&self) -> Result<()> {
-        Ok(())
+        match self.log_file {
+            Some(ref file) => file.flush(),
+            None => Ok(()),
+        }
     }
 }
 
@@ -540,25 +771,46 @@ impl AnalyticsBackend for FallbackBackend {
 // ═══════════════════════════════════════════════════════════════════════════════
 
 /// Main analytics manager with security protections
+///
+/// Tracked events are buffered and sent as one batch when
+/// [`FLUSH_BATCH_SIZE`] events are pending, when the task started by
+/// [`AnalyticsManager::start_flush_task`] ticks, and on
+/// [`AnalyticsManager::shutdown`] or drop. Security events are never buffered.
+///
+/// Backends send synchronously, so batches sent from [`AnalyticsManager::track`]
+/// and drop are handed off (to tokio's blocking pool inside a runtime) rather
+/// than sent on the caller's thread; only a `track` outside a runtime sends
+/// inline. Await [`AnalyticsManager::shutdown`] to be sure the last batch went out.
 pub struct AnalyticsManager {
     primary_backend: Option<Arc<dyn AnalyticsBackend>>,
     fallback_backend: Arc<dyn AnalyticsBackend>,
     rate_limiter: Arc<RateLimiter>,
     session_id: String,
     enabled: bool,
+    allowed_prefixes: Vec<String>,
+    blocked_keys: Vec<String>,
     pending_events: Mutex<Vec<AnalyticsEvent>>,
+    flush_task: Mutex<Option<tokio::task::AbortHandle>>,
 }
 
 impl AnalyticsManager {
     /// Create a new analytics manager with fallback only
     pub fn new() -> Self {
+        Self::new_with_config(AnalyticsConfig::default())
+    }
+
+    /// Create a manager with fallback only, validating events against `config`
+    pub fn new_with_config(config: AnalyticsConfig) -> Self {
         Self {
             primary_backend: None,
             fallback_backend: Arc::new(FallbackBackend::new()),
             rate_limiter: Arc::new(RateLimiter::default()),
             session_id: generate_session_id(),
             enabled: true,
+            allowed_prefixes: config.allowed_event_prefixes(),
+            blocked_keys: config.blocked_property_keys(),
             pending_events: Mutex::new(Vec::new()),
+            flush_task: Mutex::new(None),
         }
     }
 
@@ -569,7 +821,9 @@ impl AnalyticsManager {
         manager
     }
 
-    /// Track an event with full validation
+    /// Track an event with full validation.
+    ///
+    /// The event is buffered; see [`AnalyticsManager`] for when it is sent.
     pub fn track(
         &self,
         name: &str,
@@ -586,11 +840,11 @@ impl AnalyticsManager {
         }
 
         // Validate event name
-        let validated_name = EventValidator::validate_event_name(name)?;
+        let validated_name = EventValidator::validate_event_name(name, &self.allowed_prefixes)?;
 
         // Validate properties
         let validated_properties = match properties {
-            Some(props) => EventValidator::validate_properties(props)?,
+            Some(props) => EventValidator::validate_properties(props, &self.blocked_keys)?,
             None => HashMap::new(),
         };
 
@@ -603,20 +857,121 @@ impl AnalyticsManager {
             event_id: generate_event_id(),
         };
 
-        // Try primary backend first
-        if let Some(ref backend) = self.primary_backend {
-            if backend.is_available() {
-                match backend.send_event(event.clone()) {
-                    Ok(()) => return Ok(()),
-                    Err(e) => {
-                        warn!(error = %e, "Primary analytics backend failed, using fallback");
-                    }
+        let pending = {
+            let mut events = self
+                .pending_events
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            events.push(event);
+            events.len()
+        };
+
+        if pending >= FLUSH_BATCH_SIZE {
+            if tokio::runtime::Handle::try_current().is_ok() {
+                // Never stall a runtime worker on the backend's network I/O
+                self.hand_off(self.take_pending());
+            } else {
+                self.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove and return the buffered events
+    fn take_pending(&self) -> Vec<AnalyticsEvent> {
+        let mut events = self
+            .pending_events
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        std::mem::take(&mut *events)
+    }
+
+    /// Send `events` without blocking a runtime: on the blocking pool inside a
+    /// tokio runtime, otherwise on the calling thread, since a detached thread
+    /// would be killed (and the events lost) when the process exits
+    fn hand_off(&self, events: Vec<AnalyticsEvent>) {
+        if events.is_empty() {
+            return;
+        }
+        let primary = self.primary_backend.clone();
+        let fallback = Arc::clone(&self.fallback_backend);
+        let send = move || {
+            if let Err(e) = deliver(primary.as_ref(), &fallback, events) {
+                warn!(error = %e, "Failed to flush analytics events");
+            }
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn_blocking(send);
+            }
+            Err(_) => send(),
+        }
+    }
+
+    /// Flush pending events every `interval` from a tokio task.
+    ///
+    /// The task holds only a weak reference and ends when the manager is
+    /// dropped or [`AnalyticsManager::shutdown`] is called; starting a new task
+    /// replaces the previous one.
+    pub fn start_flush_task(self: &Arc<Self>, interval: Duration) -> tokio::task::JoinHandle<()> {
+        let manager = Arc::downgrade(self);
+        let task = tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+            // The first tick completes immediately
+            ticker.tick().await;
+            loop {
+                ticker.tick().await;
+                let Some(manager) = manager.upgrade() else {
+                    break;
+                };
+                // Backends send synchronously
+                let flushed = tokio::task::spawn_blocking(move || manager.flush()).await;
+                match flushed {
+                    Ok(Err(e)) => warn!(error = %e, "Periodic analytics flush failed"),
+                    Err(e) => warn!(error = %e, "Periodic analytics flush panicked"),
+                    Ok(Ok(())) => {}
                 }
             }
+        });
+
+        let previous = self
+            .flush_task
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .replace(task.abort_handle());
+        if let Some(previous) = previous {
+            previous.abort();
         }
+        task
+    }
 
-        // Use fallback
-        self.fallback_backend.send_event(event)
+    /// Stop the flush task and send every pending event
+    pub async fn shutdown(&self) -> Result<()> {
+        self.stop_flush_task();
+        let pending = self.take_pending();
+        if pending.is_empty() {
+            return Ok(());
+        }
+        let primary = self.primary_backend.clone();
+        let fallback = Arc::clone(&self.fallback_backend);
+        tokio::task::spawn_blocking(move || deliver(primary.as_ref(), &fallback, pending))
+            .await
+            .unwrap_or_else(|e| {
+                warn!(error = %e, "Analytics shutdown flush panicked");
+                Err(AnalyticsError::BackendUnavailable)
+            })
+    }
+
+    fn stop_flush_task(&self) {
+        let task = self
+            .flush_task
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .take();
+        if let Some(task) = task {
+            task.abort();
+        }
     }
 
     /// Track a security event (always logged)
@@ -682,37 +1037,45 @@ impl AnalyticsManager {
         (self.rate_limiter.current_count(), MAX_EVENTS_PER_MINUTE)
     }
 
-    /// Flush pending events
+    /// Send pending events on the calling thread.
+    ///
+    /// This blocks on the backend; from async code use
+    /// [`tokio::task::spawn_blocking`] or [`AnalyticsManager::shutdown`].
     pub fn flush(&self) -> Result<()> {
-        let pending = {
-            let mut events = self
-                .pending_events
-                .lock()
-                .unwrap_or_else(|poisoned| poisoned.into_inner());
-            std::mem::take(&mut *events)
-        };
+        deliver(
+            self.primary_backend.as_ref(),
+            &self.fallback_backend,
+            self.take_pending(),
+        )
+    }
+}
 
-        if pending.is_empty() {
-            return Ok(());
-        }
+/// Send a batch to the primary backend, falling back when it is unavailable
+fn deliver(
+    primary: Option<&Arc<dyn AnalyticsBackend>>,
+    fallback: &Arc<dyn AnalyticsBackend>,
+    pending: Vec<AnalyticsEvent>,
+) -> Result<()> {
+    if pending.is_empty() {
+        return Ok(());
+    }
 
-        info!(count = pending.len(), "Flushing pending analytics events");
+    info!(count = pending.len(), "Flushing pending analytics events");
 
-        // Try primary first
-        if let Some(ref backend) = self.primary_backend {
-            if backend.is_available() {
-                match backend.send_batch(pending.clone()) {
-                    Ok(()) => return Ok(()),
-                    Err(e) => {
-                        warn!(error = %e, "Failed to flush to primary backend");
-                    }
+    // Try primary first
+    if let Some(backend) = primary {
+        if backend.is_available() {
+            match backend.send_batch(pending.clone()) {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    warn!(error = %e, "Failed to flush to primary backend");
                 }
             }
         }
-
-        // Fallback
-        self.fallback_backend.send_batch(pending)
     }
+
+    // Fallback
+    fallback.send_batch(pending)
 }
 
 impl Default for AnalyticsManager {
@@ -721,6 +1084,13 @@ impl Default for AnalyticsManager {
     }
 }
 
+impl Drop for AnalyticsManager {
+    fn drop(&mut self) {
+        self.stop_flush_task();
+        self.hand_off(self.take_pending());
+    }
+}
+
 // ═══════════════════════════════════════════════════════════════════════════════
 // HELPER FUNCTIONS
 // ═══════════════════════════════════════════════════════════════════════════════
@@ -785,31 +1155,35 @@ mod tests {
 
     #[test]
     fn test_validate_event_name_valid() {
-        assert!(EventValidator::validate_event_name("app_started").is_ok());
-        assert!(EventValidator::validate_event_name("chat_message_sent").is_ok());
-        assert!(EventValidator::validate_event_name("scan_completed").is_ok());
+        let prefixes = AnalyticsConfig::default().allowed_event_prefixes();
+        assert!(EventValidator::validate_event_name("app_started", &prefixes).is_ok());
+        assert!(EventValidator::validate_event_name("chat_message_sent", &prefixes).is_ok());
+        assert!(EventValidator::validate_event_name("scan_completed", &prefixes).is_ok());
     }
 
     #[test]
     fn test_validate_event_name_invalid_prefix() {
-        assert!(EventValidator::validate_event_name("invalid_event").is_err());
-        assert!(EventValidator::validate_event_name("hack_attempt").is_err());
+        let prefixes = AnalyticsConfig::default().allowed_event_prefixes();
+        assert!(EventValidator::validate_event_name("invalid_event", &prefixes).is_err());
+        assert!(EventValidator::validate_event_name("hack_attempt", &prefixes).is_err());
     }
 
     #[test]
     fn test_validate_event_name_suspicious() {
-        assert!(EventValidator::validate_event_name("app_<script>").is_err());
-        assert!(EventValidator::validate_event_name("app_javascript:").is_err());
+        let prefixes = AnalyticsConfig::default().allowed_event_prefixes();
+        assert!(EventValidator::validate_event_name("app_<script>", &prefixes).is_err());
+        assert!(EventValidator::validate_event_name("app_javascript:", &prefixes).is_err());
     }
 
     #[test]
     fn test_validate_properties_removes_sensitive() {
+        let blocked = AnalyticsConfig::default().blocked_property_keys();
         let mut props = HashMap::new();
         props.insert("normal_key".to_string(), "value".into());
         props.insert("password".to_string(), "secret123".into());
         props.insert("api_key".to_string(), "key123".into());
 
-        let result = EventValidator::validate_properties(props).unwrap();
+        let result = EventValidator::validate_properties(props, &blocked).unwrap();
 
         assert!(result.contains_key("normal_key"));
         assert!(!result.contains_key("password"));
@@ -818,10 +1192,327 @@ mod tests {
 
     #[test]
     fn test_validate_properties_limits_count() {
+        let blocked = AnalyticsConfig::default().blocked_property_keys();
         let props: HashMap<String, serde_json::Value> =
             (0..100).map(|i| (format!("key_{}", i), i.into())).collect();
 
-        assert!(EventValidator::validate_properties(props).is_err());
+        assert!(EventValidator::validate_properties(props, &blocked).is_err());
+    }
+
+    #[test]
+    fn test_custom_prefix_and_blocked_key_only_when_configured() {
+        let default_manager = AnalyticsManager::new();
+        assert!(matches!(
+            default_manager.track("audit_login", None),
+            Err(AnalyticsError::InvalidEventName(_))
+        ));
+
+        let config = AnalyticsConfig {
+            extra_event_prefixes: vec!["Audit_".to_string(), " ".to_string()],
+            extra_blocked_keys: vec!["tenant_id".to_string()],
+        };
+        let prefixes = config.allowed_event_prefixes();
+        assert!(prefixes.starts_with(&["app_".to_string()]));
+        assert_eq!(prefixes.last().map(String::as_str), Some("audit_"));
+        assert!(EventValidator::validate_event_name("audit_login", &prefixes).is_ok());
+        assert!(EventValidator::validate_event_name("hack_attempt", &prefixes).is_err());
+
+        let props = HashMap::from([
+            ("tenantid".to_string(), "acme".into()),
+            ("password".to_string(), "hunter2".into()),
+            ("duration_ms".to_string(), 42.into()),
+        ]);
+        let kept =
+            EventValidator::validate_properties(props, &config.blocked_property_keys()).unwrap();
+        assert_eq!(kept.keys().collect::<Vec<_>>(), ["duration_ms"]);
+
+        let manager = AnalyticsManager::new_with_config(config);
+        assert!(manager.track("audit_login", None).is_ok());
+        assert_eq!(manager.pending_events.lock().unwrap().len(), 1);
+    }
+
+    fn event(name: &str) -> AnalyticsEvent {
+        AnalyticsEvent {
+            name: name.to_string(),
+            properties: HashMap::from([("duration_ms".to_string(), 42.into())]),
+            timestamp: chrono::Utc::now(),
+            session_id: "session_test".to_string(),
+            event_id: "event_test".to_string(),
+        }
+    }
+
+    #[cfg(feature = "analytics-reqwest")]
+    #[test]
+    fn test_posthog_capture_request_shape() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/capture/")
+            .match_header("content-type", "application/json")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "api_key": "phc_test",
+                "event": "scan_completed",
+                "properties": {
+                    "distinct_id": "session_test",
+                    "$event_id": "event_test",
+                    "duration_ms": 42,
+                    "$lib": "hqe-workbench-rust"
+                }
+            })))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let backend =
+            PostHogBackend::new("phc_test".to_string(), Some(format!("{}/", server.url())));
+        backend.send_event(event("scan_completed")).unwrap();
+        mock.assert();
+    }
+
+    #[cfg(feature = "analytics-reqwest")]
+    #[test]
+    fn test_posthog_batch_request_shape() {
+        let mut server = mockito::Server::new();
+        let mock = server
+            .mock("POST", "/batch/")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "api_key": "phc_test",
+                "batch": [
+                    {"event": "scan_started", "properties": {"distinct_id": "session_test"}},
+                    {"event": "scan_completed", "properties": {"distinct_id": "session_test"}}
+                ]
+            })))
+            .with_status(200)
+            .expect(1)
+            .create();
+
+        let backend = PostHogBackend::new("phc_test".to_string(), Some(server.url()));
+        backend
+            .send_batch(vec![event("scan_started"), event("scan_completed")])
+            .unwrap();
+        mock.assert();
+    }
+
+    #[cfg(feature = "analytics-reqwest")]
+    #[tokio::test]
+    async fn test_posthog_failure_falls_back() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/capture/")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+
+        let backend = PostHogBackend::new("phc_test".to_string(), Some(server.url()));
+        assert!(matches!(
+            backend.send_event(event("scan_completed")),
+            Err(AnalyticsError::BackendUnavailable)
+        ));
+
+        // Called from inside a runtime; the manager still logs the batch locally
+        let batch = server
+            .mock("POST", "/batch/")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let manager = AnalyticsManager::with_posthog("phc_test".to_string(), Some(server.url()));
+        assert!(manager.track("scan_completed", None).is_ok());
+        assert!(manager.flush().is_ok());
+        mock.assert_async().await;
+        batch.assert_async().await;
+    }
+
+    #[cfg(feature = "analytics-reqwest")]
+    #[test]
+    fn test_posthog_disabled_sends_nothing() {
+        let mut server = mockito::Server::new();
+        let mock = server.mock("POST", "/capture/").expect(0).create();
+
+        let mut backend = PostHogBackend::new("phc_test".to_string(), Some(server.url()));
+        backend.disable();
+        backend.send_event(event("scan_completed")).unwrap();
+        assert!(!backend.is_available());
+        mock.assert();
+    }
+
+    /// Records the size of every batch it receives
+    #[derive(Default)]
+    struct RecordingBackend {
+        events: Mutex<Vec<String>>,
+        batches: Mutex<Vec<usize>>,
+    }
+
+    impl AnalyticsBackend for RecordingBackend {
+        fn send_event(&self, event: AnalyticsEvent) -> Result<()> {
+            self.events.lock().unwrap().push(event.name);
+            Ok(())
+        }
+
+        fn send_batch(&self, events: Vec<AnalyticsEvent>) -> Result<()> {
+            self.batches.lock().unwrap().push(events.len());
+            Ok(())
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn flush(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    fn recording_manager() -> (Arc<RecordingBackend>, AnalyticsManager) {
+        let backend = Arc::new(RecordingBackend::default());
+        let mut manager = AnalyticsManager::new();
+        manager.primary_backend = Some(backend.clone());
+        (backend, manager)
+    }
+
+    #[tokio::test]
+    async fn test_flush_task_sends_buffered_events_as_one_batch() {
+        let (backend, manager) = recording_manager();
+        let manager = Arc::new(manager);
+        manager.start_flush_task(Duration::from_millis(100));
+
+        for _ in 0..10 {
+            manager.track("scan_file_analyzed", None).unwrap();
+        }
+        assert!(backend.events.lock().unwrap().is_empty());
+        assert!(backend.batches.lock().unwrap().is_empty());
+
+        tokio::time::sleep(Duration::from_millis(350)).await;
+        assert_eq!(*backend.batches.lock().unwrap(), vec![10]);
+        assert!(backend.events.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_file_backend_writes_ndjson_and_rotates() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("logs").join("analytics.ndjson");
+        let line_len = serde_json::to_vec(&event("app_started")).unwrap().len() as u64 + 1;
+        let backend = FileAnalyticsBackend::new(path.clone()).with_max_bytes(line_len * 3 + 8);
+
+        backend.send_event(event("app_started")).unwrap();
+        backend
+            .send_batch(vec![event("scan_started"), event("scan_finished")])
+            .unwrap();
+        backend.flush().unwrap();
+
+        let read = |path: &Path| -> Vec<String> {
+            std::fs::read_to_string(path)
+                .unwrap()
+                .lines()
+                .map(|line| {
+                    let value: serde_json::Value = serde_json::from_str(line).unwrap();
+                    value["name"].as_str().unwrap().to_string()
+                })
+                .collect()
+        };
+        assert_eq!(
+            read(&path),
+            ["app_started", "scan_started", "scan_finished"]
+        );
+
+        // The fourth line would pass the cap
+        backend.send_event(event("app_stopped")).unwrap();
+        assert_eq!(read(&backend.rotated_path()).len(), 3);
+        assert_eq!(read(&path), ["app_stopped"]);
+
+        let fallback = FallbackBackend::with_log_file(path.clone());
+        fallback.send_event(event("error_seen")).unwrap();
+        assert_eq!(read(&path), ["app_stopped", "error_seen"]);
+    }
+
+    #[test]
+    fn test_full_buffer_and_drop_flush_immediately() {
+        let (backend, manager) = recording_manager();
+
+        for _ in 0..FLUSH_BATCH_SIZE + 3 {
+            manager.track("scan_file_analyzed", None).unwrap();
+        }
+        // Outside a runtime the full buffer is sent inline
+        assert_eq!(*backend.batches.lock().unwrap(), vec![FLUSH_BATCH_SIZE]);
+
+        // So is the rest on drop
+        drop(manager);
+        assert_eq!(*backend.batches.lock().unwrap(), vec![FLUSH_BATCH_SIZE, 3]);
+    }
+
+    #[test]
+    fn test_drop_outside_runtime_writes_fallback_log() {
+        let temp = tempfile::TempDir::new().unwrap();
+        let path = temp.path().join("analytics.ndjson");
+        let mut manager = AnalyticsManager::new();
+        manager.fallback_backend = Arc::new(FallbackBackend::with_log_file(path.clone()));
+
+        manager.track("scan_started", None).unwrap();
+        manager.track("scan_completed", None).unwrap();
+        drop(manager);
+
+        let names: Vec<String> = std::fs::read_to_string(&path)
+            .unwrap()
+            .lines()
+            .map(|line| {
+                let value: serde_json::Value = serde_json::from_str(line).unwrap();
+                value["name"].as_str().unwrap().to_string()
+            })
+            .collect();
+        assert_eq!(names, ["scan_started", "scan_completed"]);
+    }
+
+    /// Takes a while to send each batch, like a slow network
+    struct SlowBackend(Arc<RecordingBackend>);
+
+    impl AnalyticsBackend for SlowBackend {
+        fn send_event(&self, event: AnalyticsEvent) -> Result<()> {
+            self.0.send_event(event)
+        }
+
+        fn send_batch(&self, events: Vec<AnalyticsEvent>) -> Result<()> {
+            std::thread::sleep(Duration::from_millis(300));
+            self.0.send_batch(events)
+        }
+
+        fn is_available(&self) -> bool {
+            true
+        }
+
+        fn flush(&self) -> Result<()> {
+            Ok(())
+        }
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_track_and_drop_do_not_block_the_runtime() {
+        let backend = Arc::new(RecordingBackend::default());
+        let mut manager = AnalyticsManager::new();
+        manager.primary_backend = Some(Arc::new(SlowBackend(backend.clone())));
+
+        let start = Instant::now();
+        for _ in 0..FLUSH_BATCH_SIZE + 3 {
+            manager.track("scan_file_analyzed", None).unwrap();
+        }
+        drop(manager);
+        assert!(start.elapsed() < Duration::from_millis(200));
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        let mut batches = backend.batches.lock().unwrap().clone();
+        batches.sort_unstable();
+        assert_eq!(batches, vec![3, FLUSH_BATCH_SIZE]);
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_sends_pending_events() {
+        let (backend, manager) = recording_manager();
+        let manager = Arc::new(manager);
+        manager.start_flush_task(Duration::from_secs(3600));
+        manager.track("scan_started", None).unwrap();
+
+        manager.shutdown().await.unwrap();
+        assert_eq!(*backend.batches.lock().unwrap(), vec![1]);
+        assert!(manager.flush_task.lock().unwrap().is_none());
     }
 
     #[test]
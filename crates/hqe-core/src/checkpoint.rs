@@ -0,0 +1,183 @@
+//! Checkpoints for resuming interrupted scans.
+//!
+//! While a scan runs with checkpoints enabled, [`ScanCheckpoint`] is written to
+//! the run output directory after each pipeline phase and after every few
+//! LLM-analyzed files. It records the run id, a hash of the scan
+//! configuration, the repository HEAD and the result of every file analyzed so
+//! far. [`crate::ScanPipeline::resume`] loads it, checks that neither the
+//! repository nor the configuration changed, and only sends the files that
+//! have no checkpointed result.
+
+use crate::models::{Blocker, Finding, ScanConfig, TodoItem};
+use crate::scan::ScanPhase;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// File name of the checkpoint inside the run output directory
+pub const CHECKPOINT_FILE: &str = "scan-checkpoint.json";
+
+/// Bumped whenever the on-disk layout changes; older checkpoints cannot be resumed
+const CHECKPOINT_VERSION: u32 = 1;
+
+/// Progress of a scan, persisted so the scan can be resumed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCheckpoint {
+    /// Checkpoint format version
+    pub version: u32,
+    /// Run being checkpointed; a resumed scan keeps this id
+    pub run_id: String,
+    /// [`config_hash`] of the scan configuration and analysis mode
+    pub config_hash: String,
+    /// Repository HEAD when the scan started (`None` outside a git repository)
+    pub git_commit: Option<String>,
+    /// Last pipeline phase that completed
+    pub phase: Option<ScanPhase>,
+    /// Evidence bundle content hash -> result of analyzing that bundle
+    pub files: BTreeMap<String, CheckpointedFile>,
+}
+
+/// Analysis result of one evidence bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointedFile {
+    /// File the bundle covers (`None` for repository-level findings)
+    pub file: Option<String>,
+    /// Findings reported for the bundle
+    pub findings: Vec<Finding>,
+    /// TODO items reported for the bundle
+    pub todos: Vec<TodoItem>,
+    /// Blockers reported for the bundle
+    pub blockers: Vec<Blocker>,
+}
+
+impl ScanCheckpoint {
+    /// Create an empty checkpoint for the given run
+    pub fn new(
+        run_id: impl Into<String>,
+        config_hash: impl Into<String>,
+        git_commit: Option<String>,
+    ) -> Self {
+        Self {
+            version: CHECKPOINT_VERSION,
+            run_id: run_id.into(),
+            config_hash: config_hash.into(),
+            git_commit,
+            phase: None,
+            files: BTreeMap::new(),
+        }
+    }
+
+    /// Load a checkpoint from disk.
+    ///
+    /// Unlike the scan cache, a missing or unreadable checkpoint is an error:
+    /// the caller asked to resume a specific run.
+    pub fn load(path: impl AsRef<Path>) -> crate::Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            crate::HqeError::Scan(format!("No scan checkpoint at {}: {}", path.display(), e))
+        })?;
+        let checkpoint: Self = serde_json::from_str(&content).map_err(|e| {
+            crate::HqeError::Serialization(format!(
+                "Unreadable scan checkpoint {}: {}",
+                path.display(),
+                e
+            ))
+        })?;
+        if checkpoint.version != CHECKPOINT_VERSION {
+            return Err(crate::HqeError::Scan(format!(
+                "Scan checkpoint {} has version {} (expected {}); start a new scan",
+                path.display(),
+                checkpoint.version,
+                CHECKPOINT_VERSION
+            )));
+        }
+        Ok(checkpoint)
+    }
+
+    /// Write the checkpoint to disk, creating the parent directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| crate::HqeError::Serialization(e.to_string()))?;
+
+        // Same write-then-rename as the scan cache: a scan killed mid-save
+        // must still leave the previous checkpoint readable.
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+}
+
+/// Hash of everything that affects scan results.
+///
+/// Concurrency, the request timeout and the budgets only change how the scan
+/// runs, not what it reports, so they may differ between the original run and
+/// the resumed one (a scan stopped by its budget can be resumed with a larger
+/// one). `mode_key` identifies the analysis mode and model.
+pub fn config_hash(config: &ScanConfig, mode_key: &str) -> String {
+    let mut config = config.clone();
+    config.concurrency = 0;
+    config.timeout_seconds = 0;
+    config.budget_usd = None;
+    config.budget_tokens = None;
+
+    let mut hasher = Sha256::new();
+    hasher.update(serde_json::to_vec(&config).unwrap_or_default());
+    hasher.update([0]);
+    hasher.update(mode_key.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checkpoint_round_trip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("run").join(CHECKPOINT_FILE);
+
+        let mut checkpoint = ScanCheckpoint::new("run-1", "abc", Some("deadbeef".into()));
+        checkpoint.phase = Some(ScanPhase::Ingestion);
+        checkpoint.files.insert(
+            "hash".into(),
+            CheckpointedFile {
+                file: Some("src/lib.rs".into()),
+                findings: Vec::new(),
+                todos: Vec::new(),
+                blockers: Vec::new(),
+            },
+        );
+        checkpoint.save(&path)?;
+
+        let loaded = ScanCheckpoint::load(&path)?;
+        assert_eq!(loaded.run_id, "run-1");
+        assert_eq!(loaded.git_commit.as_deref(), Some("deadbeef"));
+        assert_eq!(loaded.phase, Some(ScanPhase::Ingestion));
+        assert_eq!(loaded.files.len(), 1);
+        assert!(ScanCheckpoint::load(dir.path().join("missing.json")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_hash_ignores_execution_settings() {
+        let config = ScanConfig::default();
+        let base = config_hash(&config, "local");
+
+        let mut faster = config.clone();
+        faster.concurrency = 16;
+        faster.timeout_seconds = 5;
+        faster.budget_tokens = Some(1_000);
+        assert_eq!(config_hash(&faster, "local"), base);
+
+        let mut stricter = config.clone();
+        stricter.strict_redaction = true;
+        assert_ne!(config_hash(&stricter, "local"), base);
+        assert_ne!(config_hash(&config, "llm:gpt-4o"), base);
+    }
+}
@@ -0,0 +1,236 @@
+//! Deterministic language detection and line counting.
+//!
+//! [`crate::RepoScanner::scan`] runs every scanned file through
+//! [`count_file`] to build the per-language [`CodeStats`] of the project map,
+//! so the report has language statistics even without an LLM. A file's
+//! language comes from its extension, or from the interpreter named in its
+//! shebang line when the extension is unknown. Lines are classified with a
+//! simple heuristic: a line is a comment when it starts with a line comment
+//! marker or lies inside a block comment that starts a line; anything else
+//! that is not blank is code.
+
+use crate::models::{CodeStats, LanguageStats};
+use std::path::Path;
+
+/// Comment syntax of a language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Language {
+    /// Display name, used as the key in [`CodeStats`]
+    pub name: &'static str,
+    /// Markers that start a comment running to the end of the line
+    pub line_comments: &'static [&'static str],
+    /// Start and end markers of block comments
+    pub block_comment: Option<(&'static str, &'static str)>,
+}
+
+const fn c_style(name: &'static str) -> Language {
+    Language {
+        name,
+        line_comments: &["//"],
+        block_comment: Some(("/*", "*/")),
+    }
+}
+
+const fn hash_style(name: &'static str) -> Language {
+    Language {
+        name,
+        line_comments: &["#"],
+        block_comment: None,
+    }
+}
+
+const fn markup(name: &'static str) -> Language {
+    Language {
+        name,
+        line_comments: &[],
+        block_comment: Some(("<!--", "-->")),
+    }
+}
+
+const fn no_comments(name: &'static str) -> Language {
+    Language {
+        name,
+        line_comments: &[],
+        block_comment: None,
+    }
+}
+
+/// Language of a file extension (without the dot, lowercase)
+pub fn language_for_extension(ext: &str) -> Option<Language> {
+    let language = match ext {
+        "rs" => c_style("Rust"),
+        "ts" | "tsx" | "mts" | "cts" => c_style("TypeScript"),
+        "js" | "jsx" | "mjs" | "cjs" => c_style("JavaScript"),
+        "go" => c_style("Go"),
+        "java" => c_style("Java"),
+        "kt" | "kts" => c_style("Kotlin"),
+        "scala" => c_style("Scala"),
+        "swift" => c_style("Swift"),
+        "dart" => c_style("Dart"),
+        "c" | "h" => c_style("C"),
+        "cc" | "cpp" | "cxx" | "hh" | "hpp" | "hxx" => c_style("C++"),
+        "cs" => c_style("C#"),
+        "css" | "scss" | "less" => Language {
+            name: "CSS",
+            line_comments: &[],
+            block_comment: Some(("/*", "*/")),
+        },
+        "php" => Language {
+            name: "PHP",
+            line_comments: &["//", "#"],
+            block_comment: Some(("/*", "*/")),
+        },
+        "py" | "pyi" => hash_style("Python"),
+        "rb" => hash_style("Ruby"),
+        "pl" | "pm" => hash_style("Perl"),
+        "sh" | "bash" | "zsh" => hash_style("Shell"),
+        "yaml" | "yml" => hash_style("YAML"),
+        "toml" => hash_style("TOML"),
+        "sql" => Language {
+            name: "SQL",
+            line_comments: &["--"],
+            block_comment: Some(("/*", "*/")),
+        },
+        "lua" => Language {
+            name: "Lua",
+            line_comments: &["--"],
+            block_comment: None,
+        },
+        "html" | "htm" => markup("HTML"),
+        "md" | "markdown" => markup("Markdown"),
+        "json" => no_comments("JSON"),
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Language of the interpreter named in a `#!` line
+fn language_for_shebang(first_line: &str) -> Option<Language> {
+    let mut words = first_line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|w| !w.starts_with('-'))?;
+    }
+    // python3.12 -> python
+    let interpreter = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    let language = match interpreter {
+        "python" => hash_style("Python"),
+        "node" | "nodejs" | "deno" | "bun" => c_style("JavaScript"),
+        "sh" | "bash" | "zsh" | "dash" | "ksh" => hash_style("Shell"),
+        "ruby" => hash_style("Ruby"),
+        "perl" => hash_style("Perl"),
+        "php" => language_for_extension("php")?,
+        "lua" => language_for_extension("lua")?,
+        _ => return None,
+    };
+    Some(language)
+}
+
+/// Detect the language of a file from its extension, falling back to the
+/// shebang on the first line of `text`
+pub fn detect_language(path: &Path, text: &str) -> Option<Language> {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| language_for_extension(&ext.to_ascii_lowercase()))
+        .or_else(|| language_for_shebang(text.lines().next()?))
+}
+
+/// Classify the lines of one file written in `language`
+pub fn count_lines(text: &str, language: &Language) -> LanguageStats {
+    let mut stats = LanguageStats {
+        files: 1,
+        ..LanguageStats::default()
+    };
+    // End marker of the block comment the current line is in
+    let mut block_end: Option<&str> = None;
+
+    for line in text.lines() {
+        let line = line.trim();
+        if line.is_empty() {
+            stats.blank_lines += 1;
+        } else if let Some(end) = block_end {
+            stats.comment_lines += 1;
+            if line.contains(end) {
+                block_end = None;
+            }
+        } else if language.line_comments.iter().any(|m| line.starts_with(m)) {
+            stats.comment_lines += 1;
+        } else if let Some((start, end)) = language
+            .block_comment
+            .filter(|(start, _)| line.starts_with(start))
+        {
+            stats.comment_lines += 1;
+            if !line[start.len()..].contains(end) {
+                block_end = Some(end);
+            }
+        } else {
+            stats.code_lines += 1;
+        }
+    }
+    stats
+}
+
+/// Add the file at `path` with content `content` to `stats`.
+///
+/// Files of no known language are not counted. Returns the detected language.
+pub fn count_file(stats: &mut CodeStats, path: &Path, content: &[u8]) -> Option<&'static str> {
+    let text = String::from_utf8_lossy(content);
+    let language = detect_language(path, &text)?;
+    stats.add(language.name, &count_lines(&text, &language));
+    Some(language.name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_language_by_extension_and_shebang() {
+        let name = |path: &str, text: &str| detect_language(Path::new(path), text).map(|l| l.name);
+
+        assert_eq!(name("src/main.rs", ""), Some("Rust"));
+        assert_eq!(name("App.TSX", ""), Some("TypeScript"));
+        assert_eq!(
+            name("bin/deploy", "#!/usr/bin/env python3\n"),
+            Some("Python")
+        );
+        assert_eq!(name("bin/run", "#!/bin/bash -e\n"), Some("Shell"));
+        assert_eq!(
+            name("bin/tool", "#!/usr/bin/env -S node --harmony\n"),
+            Some("JavaScript")
+        );
+        // The extension wins over the shebang
+        assert_eq!(name("build.rb", "#!/bin/sh\n"), Some("Ruby"));
+        assert_eq!(name("LICENSE", "MIT License\n"), None);
+        assert_eq!(name("image.xyz", "#!/usr/bin/unknown\n"), None);
+    }
+
+    #[test]
+    fn test_count_lines() {
+        let rust = "//! Crate docs\n\nfn main() {\n    /* one-line block */\n    run(); // trailing\n\n    /*\n     * multi\n\n     */\n}\n";
+        let stats = count_lines(rust, &c_style("Rust"));
+        assert_eq!(
+            stats,
+            LanguageStats {
+                files: 1,
+                code_lines: 3,
+                comment_lines: 5,
+                blank_lines: 3,
+            }
+        );
+
+        let mut totals = CodeStats::default();
+        count_file(&mut totals, Path::new("a.py"), b"# comment\nx = 1\n\n");
+        count_file(&mut totals, Path::new("b.py"), b"y = 2\n");
+        assert_eq!(
+            count_file(&mut totals, Path::new("notes.txt"), b"text"),
+            None
+        );
+        let python = totals.languages["Python"];
+        assert_eq!(python.files, 2);
+        assert_eq!(python.code_lines, 2);
+        assert_eq!(python.comment_lines, 1);
+        assert_eq!(python.blank_lines, 1);
+        assert_eq!(totals.languages.len(), 1);
+    }
+}
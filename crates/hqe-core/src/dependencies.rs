@@ -0,0 +1,292 @@
+//! Dependency extraction from package manifests and lockfiles.
+//!
+//! Manifests (`Cargo.toml`, `package.json`) say what a project asks for;
+//! lockfiles (see [`lockfile_ecosystem`]) say what it resolved to. Every
+//! locked package becomes a [`Dependency`] with an exact version, annotated
+//! with the requirement its manifest declares. Declared packages that no
+//! lockfile covers are kept with their requirement only, and an exact version
+//! when the requirement pins one.
+
+use crate::advisories::{lockfile_ecosystem, parse_lockfile, Ecosystem};
+use crate::models::Dependency;
+use crate::HqeError;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tracing::warn;
+
+/// Ecosystem of a package manifest, by file name
+pub fn manifest_ecosystem(path: &str) -> Option<Ecosystem> {
+    match Path::new(path).file_name()?.to_str()? {
+        "Cargo.toml" => Some(Ecosystem::Cargo),
+        "package.json" => Some(Ecosystem::Npm),
+        _ => None,
+    }
+}
+
+/// Packages a manifest declares
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Manifest {
+    /// Name of the package the manifest describes, if it describes one
+    pub package: Option<String>,
+    /// Declared dependencies with their version requirement, if any
+    pub dependencies: Vec<(String, Option<String>)>,
+}
+
+/// Parse a `Cargo.toml` or `package.json`.
+///
+/// Cargo path dependencies are local code, not third-party packages, and are
+/// left out.
+pub fn parse_manifest(ecosystem: Ecosystem, content: &str) -> crate::Result<Manifest> {
+    match ecosystem {
+        Ecosystem::Cargo => parse_cargo_toml(content),
+        Ecosystem::Npm => parse_package_json(content),
+        Ecosystem::Pypi | Ecosystem::Go => Ok(Manifest::default()),
+    }
+}
+
+fn parse_cargo_toml(content: &str) -> crate::Result<Manifest> {
+    let doc: toml::Table = toml::from_str(content)
+        .map_err(|e| HqeError::Serialization(format!("Invalid Cargo.toml: {}", e)))?;
+    let package = doc
+        .get("package")
+        .and_then(|p| p.get("name"))
+        .and_then(|n| n.as_str())
+        .map(str::to_string);
+
+    let mut tables = Vec::new();
+    for section in ["dependencies", "dev-dependencies", "build-dependencies"] {
+        tables.extend(doc.get(section));
+        if let Some(targets) = doc.get("target").and_then(|t| t.as_table()) {
+            tables.extend(targets.values().filter_map(|cfg| cfg.get(section)));
+        }
+    }
+    tables.extend(doc.get("workspace").and_then(|w| w.get("dependencies")));
+
+    let mut dependencies = Vec::new();
+    for (name, spec) in tables.into_iter().filter_map(|t| t.as_table()).flatten() {
+        let requirement = match spec {
+            toml::Value::String(version) => Some(version.clone()),
+            toml::Value::Table(spec) => {
+                if spec.contains_key("path") {
+                    continue;
+                }
+                spec.get("version")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+            }
+            _ => None,
+        };
+        // `foo = { package = "bar" }` renames the bar crate
+        let name = spec.get("package").and_then(|p| p.as_str()).unwrap_or(name);
+        dependencies.push((name.to_string(), requirement));
+    }
+    Ok(Manifest {
+        package,
+        dependencies,
+    })
+}
+
+fn parse_package_json(content: &str) -> crate::Result<Manifest> {
+    let doc: serde_json::Value = serde_json::from_str(content)
+        .map_err(|e| HqeError::Serialization(format!("Invalid package.json: {}", e)))?;
+    let mut dependencies = Vec::new();
+    for section in [
+        "dependencies",
+        "devDependencies",
+        "optionalDependencies",
+        "peerDependencies",
+    ] {
+        for (name, spec) in doc[section].as_object().into_iter().flatten() {
+            dependencies.push((name.clone(), spec.as_str().map(str::to_string)));
+        }
+    }
+    Ok(Manifest {
+        package: doc["name"].as_str().map(str::to_string),
+        dependencies,
+    })
+}
+
+/// The version a requirement pins exactly, if it pins one. A bare Cargo
+/// version is a caret requirement; a bare npm version is exact.
+fn exact_version(ecosystem: Ecosystem, requirement: &str) -> Option<String> {
+    let requirement = requirement.trim();
+    let version = match (ecosystem, requirement.strip_prefix('=')) {
+        (_, Some(pinned)) => pinned.trim(),
+        (Ecosystem::Npm, None) => requirement,
+        _ => return None,
+    };
+    let plain = version.starts_with(|c: char| c.is_ascii_digit())
+        && version
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '+'));
+    plain.then(|| version.to_string())
+}
+
+/// Directory of a repository-relative path, `""` at the root
+fn parent_dir(path: &str) -> &str {
+    path.rsplit_once('/').map_or("", |(dir, _)| dir)
+}
+
+/// Whether `dir` is `ancestor` or lies below it
+fn is_within(dir: &str, ancestor: &str) -> bool {
+    ancestor.is_empty()
+        || dir == ancestor
+        || dir
+            .strip_prefix(ancestor)
+            .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Dependencies of the given `(path, content)` manifests and lockfiles.
+///
+/// Paths are relative to the repository root and use `/`. Files that fail to
+/// parse are skipped with a warning. Packages of the repository itself (such
+/// as workspace members in `Cargo.lock`) are left out. The result is sorted
+/// by ecosystem, name and version.
+pub fn extract_dependencies<'a>(
+    files: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<Dependency> {
+    let mut manifests = Vec::new();
+    let mut lockfiles = Vec::new();
+    for (path, content) in files {
+        if let Some(ecosystem) = manifest_ecosystem(path) {
+            match parse_manifest(ecosystem, content) {
+                Ok(manifest) => manifests.push((path, ecosystem, manifest)),
+                Err(e) => warn!("Skipping dependencies of {}: {}", path, e),
+            }
+        } else if let Some(ecosystem) = lockfile_ecosystem(path) {
+            match parse_lockfile(ecosystem, content) {
+                Ok(packages) => lockfiles.push((path, ecosystem, packages)),
+                Err(e) => warn!("Skipping dependencies of {}: {}", path, e),
+            }
+        }
+    }
+
+    let local: HashSet<(Ecosystem, &str)> = manifests
+        .iter()
+        .filter_map(|(_, ecosystem, m)| Some((*ecosystem, m.package.as_deref()?)))
+        .collect();
+    // Requirements by ecosystem and name, with the directory declaring them
+    let mut declared = HashMap::<_, Vec<_>>::new();
+    for (path, ecosystem, manifest) in &manifests {
+        for (name, requirement) in &manifest.dependencies {
+            declared
+                .entry((*ecosystem, name.as_str()))
+                .or_default()
+                .push((parent_dir(path), requirement.as_deref()));
+        }
+    }
+
+    let mut dependencies = Vec::new();
+    let mut locked: HashSet<(Ecosystem, &str, &str)> = HashSet::new();
+    for (path, ecosystem, packages) in &lockfiles {
+        let lock_dir = parent_dir(path);
+        for package in packages {
+            if local.contains(&(*ecosystem, package.name.as_str())) {
+                continue;
+            }
+            locked.insert((*ecosystem, lock_dir, package.name.as_str()));
+            let requirement =
+                declared
+                    .get(&(*ecosystem, package.name.as_str()))
+                    .and_then(|decls| {
+                        decls
+                            .iter()
+                            .find(|(dir, _)| is_within(dir, lock_dir))
+                            .and_then(|(_, requirement)| *requirement)
+                    });
+            dependencies.push(Dependency {
+                name: package.name.clone(),
+                ecosystem: *ecosystem,
+                version: Some(package.version.clone()),
+                requirement: requirement.map(str::to_string),
+                evidence: path.to_string(),
+            });
+        }
+    }
+
+    for (path, ecosystem, manifest) in &manifests {
+        let dir = parent_dir(path);
+        for (name, requirement) in &manifest.dependencies {
+            let covered = locked.iter().any(|(e, lock_dir, locked_name)| {
+                e == ecosystem && locked_name == name && is_within(dir, lock_dir)
+            });
+            if covered || local.contains(&(*ecosystem, name.as_str())) {
+                continue;
+            }
+            dependencies.push(Dependency {
+                name: name.clone(),
+                ecosystem: *ecosystem,
+                version: requirement
+                    .as_deref()
+                    .and_then(|r| exact_version(*ecosystem, r)),
+                requirement: requirement.clone(),
+                evidence: path.to_string(),
+            });
+        }
+    }
+
+    dependencies.sort_by(|a, b| {
+        (a.ecosystem.purl_type(), &a.name, &a.version, &a.evidence).cmp(&(
+            b.ecosystem.purl_type(),
+            &b.name,
+            &b.version,
+            &b.evidence,
+        ))
+    });
+    dependencies.dedup();
+    dependencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn find<'a>(deps: &'a [Dependency], name: &str) -> &'a Dependency {
+        deps.iter()
+            .find(|d| d.name == name)
+            .unwrap_or_else(|| panic!("{name} not extracted"))
+    }
+
+    #[test]
+    fn test_lockfile_versions_win_over_manifest_requirements() {
+        let cargo_toml = "[package]\nname = \"app\"\nversion = \"0.1.0\"\n\n[dependencies]\ntokio = { version = \"1.35\", features = [\"full\"] }\nserde = \"=1.0.190\"\nutil = { path = \"../util\" }\n\n[dev-dependencies]\ntempfile = \"3\"\n";
+        let cargo_lock = "[[package]]\nname = \"app\"\nversion = \"0.1.0\"\n\n[[package]]\nname = \"tokio\"\nversion = \"1.38.0\"\n";
+        let package_json =
+            r#"{"name": "web", "dependencies": {"react": "^18.2.0", "@tauri-apps/api": "2.0.1"}}"#;
+
+        let deps = extract_dependencies([
+            ("Cargo.toml", cargo_toml),
+            ("Cargo.lock", cargo_lock),
+            ("web/package.json", package_json),
+        ]);
+
+        let tokio = find(&deps, "tokio");
+        assert_eq!(tokio.version.as_deref(), Some("1.38.0"));
+        assert_eq!(tokio.requirement.as_deref(), Some("1.35"));
+        assert_eq!(tokio.evidence, "Cargo.lock");
+
+        // Not in the lockfile: exact only when pinned
+        assert_eq!(find(&deps, "serde").version.as_deref(), Some("1.0.190"));
+        assert_eq!(find(&deps, "tempfile").version, None);
+        assert_eq!(find(&deps, "react").version, None);
+        assert_eq!(find(&deps, "react").requirement.as_deref(), Some("^18.2.0"));
+        assert_eq!(
+            find(&deps, "@tauri-apps/api").purl(),
+            "pkg:npm/%40tauri-apps/api@2.0.1"
+        );
+
+        // The workspace's own crate and path dependencies are not third-party
+        assert!(deps.iter().all(|d| d.name != "app" && d.name != "util"));
+        assert_eq!(tokio.purl(), "pkg:cargo/tokio@1.38.0");
+    }
+
+    #[test]
+    fn test_unparsable_files_are_skipped() {
+        let deps = extract_dependencies([
+            ("package.json", "{ not json"),
+            ("requirements.txt", "requests==2.31.0\n"),
+        ]);
+        assert_eq!(deps.len(), 1);
+        assert_eq!(deps[0].purl(), "pkg:pypi/requests@2.31.0");
+    }
+}
@@ -0,0 +1,126 @@
+//! Structured progress events emitted while a scan runs
+//!
+//! [`ScanPipeline::run_with_events`](crate::scan::ScanPipeline::run_with_events)
+//! sends [`ScanEvent`]s over a channel as phases start and files finish, so
+//! progress UIs can show per-file status. [`write_ndjson`] drains such a
+//! channel into a newline-delimited JSON stream that external tools can tail.
+
+use crate::scan::ScanPhase;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc::Receiver;
+
+/// A progress event emitted by the scan pipeline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum ScanEvent {
+    /// A pipeline phase started
+    PhaseStarted {
+        /// Phase that started
+        phase: ScanPhase,
+    },
+    /// A file was queued for analysis; one per `FileAnalyzed` that follows
+    FileQueued {
+        /// Relative path of the file, or the chunk label for oversized files
+        path: String,
+    },
+    /// A file's analysis finished (by the LLM, the analysis cache, or locally)
+    FileAnalyzed {
+        /// Relative path of the file, or the chunk label for oversized files
+        path: String,
+        /// Findings reported for the file
+        findings: usize,
+        /// Whether the result came from the analysis cache
+        #[serde(default)]
+        cached: bool,
+    },
+    /// An LLM request for a file returned
+    LlmCallCompleted {
+        /// Relative path of the file the request was for
+        path: String,
+        /// Tokens reported by the provider, if any
+        tokens: Option<u64>,
+    },
+    /// A pipeline phase finished
+    PhaseCompleted {
+        /// Phase that finished
+        phase: ScanPhase,
+    },
+    /// Something degraded the scan without failing it (fallbacks, cancellation)
+    Warning {
+        /// Human-readable description
+        message: String,
+    },
+}
+
+/// Alias for [`ScanEvent`], for callers that think of the stream as progress
+pub type ScanProgress = ScanEvent;
+
+impl ScanEvent {
+    /// Serialize the event as one NDJSON line, without the trailing newline
+    pub fn to_ndjson(&self) -> crate::Result<String> {
+        serde_json::to_string(self).map_err(|e| crate::HqeError::Serialization(e.to_string()))
+    }
+}
+
+/// Write every event received on `rx` to `writer` as NDJSON.
+///
+/// Each line is flushed as soon as it is written so readers tailing the output
+/// see events immediately. Returns the number of events written once the
+/// channel closes.
+pub async fn write_ndjson<W>(mut rx: Receiver<ScanEvent>, mut writer: W) -> crate::Result<usize>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut written = 0;
+    while let Some(event) = rx.recv().await {
+        let mut line = event.to_ndjson()?;
+        line.push('\n');
+        writer.write_all(line.as_bytes()).await?;
+        writer.flush().await?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_writes_one_json_object_per_line() -> anyhow::Result<()> {
+        let (tx, rx) = tokio::sync::mpsc::channel(8);
+        tx.send(ScanEvent::PhaseStarted {
+            phase: ScanPhase::ReportGeneration,
+        })
+        .await?;
+        tx.send(ScanEvent::LlmCallCompleted {
+            path: "src/main.rs".to_string(),
+            tokens: Some(120),
+        })
+        .await?;
+        drop(tx);
+
+        let mut out = Vec::new();
+        assert_eq!(write_ndjson(rx, &mut out).await?, 2);
+
+        let text = String::from_utf8(out)?;
+        let lines: Vec<&str> = text.lines().collect();
+        assert_eq!(
+            lines,
+            vec![
+                r#"{"event":"phase_started","phase":"report_generation"}"#,
+                r#"{"event":"llm_call_completed","path":"src/main.rs","tokens":120}"#,
+            ]
+        );
+        let parsed: ScanEvent = serde_json::from_str(lines[1])?;
+        assert!(matches!(
+            parsed,
+            ScanEvent::LlmCallCompleted {
+                tokens: Some(120),
+                ..
+            }
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,334 @@
+//! Repository hygiene: large files, vendored directories and generated code.
+//!
+//! None of these are vulnerabilities, so they are reported as Low and Info
+//! findings. Vendored code is found by directory name, generated code by file
+//! name; a generated file counts as marked when its first lines say so (for
+//! example `@generated` or `Code generated ... DO NOT EDIT`) or when
+//! `.gitattributes` sets `linguist-generated` on it.
+
+use crate::models::{LargeFile, LocalFinding, RepoHygiene, Severity, VendoredDirectory};
+use std::collections::BTreeMap;
+
+/// Directory names that hold vendored third-party code
+pub const VENDORED_DIR_NAMES: &[&str] = &[
+    "vendor",
+    "vendors",
+    "third_party",
+    "third-party",
+    "thirdparty",
+    "node_modules",
+    "bower_components",
+];
+
+/// File name endings of common code generators' output
+const GENERATED_SUFFIXES: &[&str] = &[
+    ".pb.go",
+    ".pb.cc",
+    ".pb.h",
+    "_pb2.py",
+    "_pb2.pyi",
+    "_pb2_grpc.py",
+    "_pb.js",
+    "_pb.d.ts",
+    ".g.dart",
+    ".freezed.dart",
+    ".gen.go",
+    "_gen.go",
+    ".g.cs",
+    ".designer.cs",
+];
+
+/// Lines at the top of a file searched for a generated marker
+const GENERATED_MARKER_LINES: usize = 10;
+
+/// Phrases that mark a file as generated, matched case-insensitively
+const GENERATED_MARKERS: &[&str] = &[
+    "@generated",
+    "code generated",
+    "do not edit",
+    "auto-generated",
+    "autogenerated",
+    "generated by",
+];
+
+/// The vendored directory a path lies in: the path up to the first
+/// component named in [`VENDORED_DIR_NAMES`]
+pub fn vendored_root(path: &str) -> Option<&str> {
+    let mut end = 0;
+    for component in path.split('/') {
+        end += component.len();
+        // The last component is the file name
+        if end == path.len() {
+            return None;
+        }
+        if VENDORED_DIR_NAMES.contains(&component) {
+            return Some(&path[..end]);
+        }
+        end += 1;
+    }
+    None
+}
+
+/// The naming pattern that makes a path look like generated code
+pub fn generated_name_pattern(path: &str) -> Option<&'static str> {
+    let name = path.rsplit('/').next().unwrap_or(path).to_ascii_lowercase();
+    if let Some(suffix) = GENERATED_SUFFIXES
+        .iter()
+        .find(|suffix| name.ends_with(*suffix))
+    {
+        return Some(suffix);
+    }
+    [".generated.", "_generated."]
+        .into_iter()
+        .find(|infix| name.contains(infix))
+}
+
+/// Whether the first lines of a file mark it as generated
+pub fn has_generated_marker(head: &[u8]) -> bool {
+    head.split(|&b| b == b'\n')
+        .take(GENERATED_MARKER_LINES)
+        .any(|line| {
+            let line = String::from_utf8_lossy(line).to_lowercase();
+            GENERATED_MARKERS.iter().any(|marker| line.contains(marker))
+        })
+}
+
+/// Summarize `(path, size)` pairs of the repository's files.
+///
+/// `is_marked_generated` is only asked about files that look generated
+/// outside vendored directories.
+pub fn summarize_hygiene(
+    files: &[(String, u64)],
+    large_file_bytes: u64,
+    mut is_marked_generated: impl FnMut(&str) -> bool,
+) -> RepoHygiene {
+    let mut hygiene = RepoHygiene::default();
+    let mut vendored: BTreeMap<&str, (usize, u64)> = BTreeMap::new();
+
+    for (path, size) in files {
+        if *size > large_file_bytes {
+            hygiene.large_files.push(LargeFile {
+                path: path.clone(),
+                size_bytes: *size,
+            });
+        }
+        if let Some(root) = vendored_root(path) {
+            let entry = vendored.entry(root).or_default();
+            entry.0 += 1;
+            entry.1 += size;
+        } else if generated_name_pattern(path).is_some() && !is_marked_generated(path) {
+            hygiene.unmarked_generated_files.push(path.clone());
+        }
+    }
+
+    hygiene
+        .large_files
+        .sort_by(|a, b| b.size_bytes.cmp(&a.size_bytes).then(a.path.cmp(&b.path)));
+    hygiene.unmarked_generated_files.sort();
+    hygiene.vendored_bytes = vendored.values().map(|(_, bytes)| bytes).sum();
+    hygiene.vendored_directories = vendored
+        .into_iter()
+        .map(|(path, (files, size_bytes))| VendoredDirectory {
+            path: path.to_string(),
+            files,
+            size_bytes,
+        })
+        .collect();
+    hygiene
+}
+
+/// Human-readable size in decimal units
+pub fn format_size(bytes: u64) -> String {
+    match bytes {
+        0..=999 => format!("{bytes} bytes"),
+        1_000..=999_999 => format!("{:.1} KB", bytes as f64 / 1e3),
+        1_000_000..=999_999_999 => format!("{:.1} MB", bytes as f64 / 1e6),
+        _ => format!("{:.1} GB", bytes as f64 / 1e9),
+    }
+}
+
+/// Low findings for large files and checked-in `node_modules`, Info findings
+/// for other vendored directories and unmarked generated files
+pub fn hygiene_findings(hygiene: &RepoHygiene, large_file_bytes: u64) -> Vec<LocalFinding> {
+    let mut findings = Vec::new();
+
+    for file in &hygiene.large_files {
+        findings.push(LocalFinding {
+            finding_type: "LARGE_FILE".to_string(),
+            description: format!(
+                "{} is {}, above the {} limit for committed files",
+                file.path,
+                format_size(file.size_bytes),
+                format_size(large_file_bytes)
+            ),
+            file_path: file.path.clone(),
+            severity: Severity::Low,
+            line_number: None,
+            snippet: None,
+            recommendation: Some(
+                "Store it with Git LFS or outside the repository, and stop tracking it".to_string(),
+            ),
+        });
+    }
+
+    for dir in &hygiene.vendored_directories {
+        let node_modules = dir.path.rsplit('/').next() == Some("node_modules");
+        findings.push(LocalFinding {
+            finding_type: "VENDORED_DIRECTORY".to_string(),
+            description: format!(
+                "{}/ holds {} vendored file(s) ({})",
+                dir.path,
+                dir.files,
+                format_size(dir.size_bytes)
+            ),
+            file_path: dir.path.clone(),
+            severity: if node_modules {
+                Severity::Low
+            } else {
+                Severity::Info
+            },
+            line_number: None,
+            snippet: None,
+            recommendation: Some(if node_modules {
+                "Remove node_modules from the repository and add it to .gitignore; the lockfile pins the packages".to_string()
+            } else {
+                "Record the upstream version, and mark the directory linguist-vendored or list it in .hqeignore to keep it out of reviews".to_string()
+            }),
+        });
+    }
+
+    for path in &hygiene.unmarked_generated_files {
+        findings.push(LocalFinding {
+            finding_type: "UNMARKED_GENERATED_FILE".to_string(),
+            description: format!(
+                "{path} looks generated ({}) but is not marked as generated",
+                generated_name_pattern(path).unwrap_or("generated name")
+            ),
+            file_path: path.clone(),
+            severity: Severity::Info,
+            line_number: None,
+            snippet: None,
+            recommendation: Some(
+                "Add an @generated header, or set linguist-generated for it in .gitattributes, so reviewers and HQE skip it".to_string(),
+            ),
+        });
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vendored_and_generated_names() {
+        assert_eq!(vendored_root("vendor/github.com/x/y.go"), Some("vendor"));
+        assert_eq!(
+            vendored_root("web/node_modules/left-pad/index.js"),
+            Some("web/node_modules")
+        );
+        assert_eq!(vendored_root("src/vendor.rs"), None);
+        assert_eq!(vendored_root("vendor"), None);
+
+        assert_eq!(generated_name_pattern("api/user.pb.go"), Some(".pb.go"));
+        assert_eq!(
+            generated_name_pattern("proto/user_pb2_grpc.py"),
+            Some("_pb2_grpc.py")
+        );
+        assert_eq!(
+            generated_name_pattern("src/Schema.Generated.ts"),
+            Some(".generated.")
+        );
+        assert_eq!(generated_name_pattern("src/generator.rs"), None);
+
+        assert!(has_generated_marker(
+            b"// Code generated by protoc-gen-go. DO NOT EDIT.\npackage api\n"
+        ));
+        assert!(has_generated_marker(b"#!/usr/bin/env python\n# -*- coding: utf-8 -*-\n# Generated by the protocol buffer compiler.\n"));
+        assert!(!has_generated_marker(b"package api\n\nfunc Hand() {}\n"));
+    }
+
+    #[test]
+    fn test_summarize_hygiene() {
+        let files: Vec<(String, u64)> = [
+            ("assets/video.mp4", 12_000_000),
+            ("data/dump.sql", 6_000_000),
+            ("src/main.rs", 2_000),
+            ("vendor/a/lib.go", 3_000),
+            ("vendor/b/lib.pb.go", 9_000_000),
+            ("web/node_modules/x/index.js", 500),
+            ("api/user.pb.go", 4_000),
+            ("api/order.pb.go", 4_000),
+        ]
+        .into_iter()
+        .map(|(path, size)| (path.to_string(), size))
+        .collect();
+
+        let mut asked = Vec::new();
+        let hygiene = summarize_hygiene(&files, 5_000_000, |path| {
+            asked.push(path.to_string());
+            path == "api/order.pb.go"
+        });
+        // Vendored files are never asked about
+        assert_eq!(asked, ["api/user.pb.go", "api/order.pb.go"]);
+
+        let large: Vec<&str> = hygiene
+            .large_files
+            .iter()
+            .map(|f| f.path.as_str())
+            .collect();
+        assert_eq!(
+            large,
+            ["assets/video.mp4", "vendor/b/lib.pb.go", "data/dump.sql"]
+        );
+        assert_eq!(
+            hygiene.vendored_directories,
+            [
+                VendoredDirectory {
+                    path: "vendor".to_string(),
+                    files: 2,
+                    size_bytes: 9_003_000,
+                },
+                VendoredDirectory {
+                    path: "web/node_modules".to_string(),
+                    files: 1,
+                    size_bytes: 500,
+                },
+            ]
+        );
+        assert_eq!(hygiene.vendored_bytes, 9_003_500);
+        assert_eq!(hygiene.unmarked_generated_files, ["api/user.pb.go"]);
+
+        let findings = hygiene_findings(&hygiene, 5_000_000);
+        let summary: Vec<(&str, &str, bool)> = findings
+            .iter()
+            .map(|f| {
+                (
+                    f.finding_type.as_str(),
+                    f.file_path.as_str(),
+                    matches!(f.severity, Severity::Low),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("LARGE_FILE", "assets/video.mp4", true),
+                ("LARGE_FILE", "vendor/b/lib.pb.go", true),
+                ("LARGE_FILE", "data/dump.sql", true),
+                ("VENDORED_DIRECTORY", "vendor", false),
+                ("VENDORED_DIRECTORY", "web/node_modules", true),
+                ("UNMARKED_GENERATED_FILE", "api/user.pb.go", false),
+            ]
+        );
+        assert_eq!(
+            findings[0].description,
+            "assets/video.mp4 is 12.0 MB, above the 5.0 MB limit for committed files"
+        );
+        assert_eq!(
+            findings[3].description,
+            "vendor/ holds 2 vendored file(s) (9.0 MB)"
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Misconfiguration checks for Dockerfiles and GitHub Actions workflows.
+//!
+//! Both are read line by line rather than parsed, so every finding carries
+//! the line it was found on. Dockerfile instructions continued with a
+//! trailing `\` are joined and reported at their first line.
+
+use crate::models::{LocalFinding, Severity};
+
+/// Parts of an ENV or ARG name that suggest it carries a secret
+const SECRET_NAME_PARTS: &[&str] = &[
+    "PASSWORD",
+    "PASSWD",
+    "SECRET",
+    "TOKEN",
+    "API_KEY",
+    "APIKEY",
+    "PRIVATE_KEY",
+    "ACCESS_KEY",
+    "CREDENTIAL",
+];
+
+/// Shells that a downloaded script may be piped into
+const SHELLS: &[&str] = &["sh", "bash", "zsh", "dash", "ash"];
+
+/// Action owners whose actions are maintained by GitHub itself
+const FIRST_PARTY_ACTION_OWNERS: &[&str] = &["actions", "github"];
+
+/// Whether a repository-relative path is a Dockerfile (`Dockerfile`,
+/// `Dockerfile.<variant>`, `<name>.dockerfile` or `Containerfile`)
+pub fn is_dockerfile(path: &str) -> bool {
+    let name = path.rsplit('/').next().unwrap_or(path).to_ascii_lowercase();
+    name == "dockerfile"
+        || name.starts_with("dockerfile.")
+        || name.ends_with(".dockerfile")
+        || name == "containerfile"
+}
+
+/// Whether a repository-relative path is a GitHub Actions workflow
+pub fn is_workflow_file(path: &str) -> bool {
+    path.starts_with(".github/workflows/") && (path.ends_with(".yml") || path.ends_with(".yaml"))
+}
+
+/// One Dockerfile instruction with its continuation lines joined
+struct Instruction {
+    /// 1-based line of the instruction keyword
+    line: usize,
+    /// Keyword, uppercased
+    keyword: String,
+    /// Everything after the keyword
+    args: String,
+}
+
+impl Instruction {
+    fn snippet(&self) -> String {
+        format!("{} {}", self.keyword, self.args)
+    }
+
+    /// Arguments without leading `--flag` options, and the flags
+    fn split_flags(&self) -> (Vec<&str>, Vec<&str>) {
+        self.args
+            .split_whitespace()
+            .partition(|token| !token.starts_with("--"))
+    }
+}
+
+fn instructions(content: &str) -> Vec<Instruction> {
+    let mut out = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+    for (idx, line) in content.lines().enumerate() {
+        let trimmed = line.trim();
+        // Comments may also appear between continuation lines
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let (text, continued) = match trimmed.strip_suffix('\\') {
+            Some(text) => (text.trim_end(), true),
+            None => (trimmed, false),
+        };
+        let (start, mut joined) = pending.take().unwrap_or((idx + 1, String::new()));
+        if !joined.is_empty() {
+            joined.push(' ');
+        }
+        joined.push_str(text);
+        if continued {
+            pending = Some((start, joined));
+            continue;
+        }
+        let (keyword, args) = joined
+            .split_once(char::is_whitespace)
+            .unwrap_or((&joined, ""));
+        out.push(Instruction {
+            line: start,
+            keyword: keyword.to_ascii_uppercase(),
+            args: args.trim().to_string(),
+        });
+    }
+    out
+}
+
+/// Findings for a Dockerfile: mutable base image tags, remote `ADD`s,
+/// containers running as root, secrets in `ENV`/`ARG` and `curl | sh`
+pub fn check_dockerfile(path: &str, content: &str) -> Vec<LocalFinding> {
+    let finding = |finding_type: &str,
+                   severity: Severity,
+                   instruction: &Instruction,
+                   description: String,
+                   snippet: String,
+                   recommendation: &str| LocalFinding {
+        finding_type: finding_type.to_string(),
+        description,
+        file_path: path.to_string(),
+        severity,
+        line_number: Some(instruction.line),
+        snippet: Some(snippet),
+        recommendation: Some(recommendation.to_string()),
+    };
+
+    let mut findings = Vec::new();
+    let mut stages: Vec<String> = Vec::new();
+    // FROM of the final stage, and the last USER instruction in it
+    let mut final_from: Option<&Instruction> = None;
+    let mut final_user: Option<&Instruction> = None;
+    let parsed = instructions(content);
+
+    for instruction in &parsed {
+        match instruction.keyword.as_str() {
+            "FROM" => {
+                final_from = Some(instruction);
+                final_user = None;
+                let (args, _) = instruction.split_flags();
+                let Some(image) = args.first() else {
+                    continue;
+                };
+                let image_lower = image.to_ascii_lowercase();
+                // `FROM <earlier stage>` builds on that stage, not on an image
+                let is_stage = stages.contains(&image_lower);
+                if let [_, alias, name, ..] = args.as_slice() {
+                    if alias.eq_ignore_ascii_case("as") {
+                        stages.push(name.to_ascii_lowercase());
+                    }
+                }
+                if is_stage || image.contains(['$', '@']) || image_lower == "scratch" {
+                    continue;
+                }
+                let last_segment = image.rsplit('/').next().unwrap_or(image);
+                let description = match last_segment.split_once(':') {
+                    Some((_, "latest")) => {
+                        format!("Base image {image} uses the mutable `latest` tag")
+                    }
+                    Some(_) => continue,
+                    None => format!("Base image {image} has no tag, so it resolves to `latest`"),
+                };
+                findings.push(finding(
+                    "DOCKER_LATEST_TAG",
+                    Severity::Medium,
+                    instruction,
+                    description,
+                    instruction.snippet(),
+                    "Pin the base image to a version tag, ideally with its digest (image:1.2.3@sha256:...)",
+                ));
+            }
+            "USER" => final_user = Some(instruction),
+            "ADD" => {
+                let (args, flags) = instruction.split_flags();
+                if flags.iter().any(|flag| flag.starts_with("--checksum")) {
+                    continue;
+                }
+                let sources = args.split_last().map(|(_, sources)| sources).unwrap_or(&[]);
+                let remote = sources.iter().find_map(|source| {
+                    let source = source.trim_matches(|c| "[\",]".contains(c));
+                    (source.starts_with("http://") || source.starts_with("https://"))
+                        .then_some(source)
+                });
+                if let Some(url) = remote {
+                    findings.push(finding(
+                        "DOCKER_ADD_REMOTE_URL",
+                        Severity::Medium,
+                        instruction,
+                        format!("ADD downloads {url} without verifying its content"),
+                        instruction.snippet(),
+                        "Use ADD --checksum=sha256:..., or download with RUN and verify a checksum",
+                    ));
+                }
+            }
+            "ENV" | "ARG" => {
+                for name in declared_names(&instruction.keyword, &instruction.args) {
+                    if !is_secret_name(name) {
+                        continue;
+                    }
+                    let (severity, stored) = if instruction.keyword == "ENV" {
+                        (
+                            Severity::High,
+                            "is stored in the image and visible to anyone who runs it",
+                        )
+                    } else {
+                        (Severity::Medium, "is recorded in the image history")
+                    };
+                    findings.push(finding(
+                        "DOCKER_SECRET_IN_BUILD",
+                        severity,
+                        instruction,
+                        format!(
+                            "{} {name} looks like a secret; its value {stored}",
+                            instruction.keyword
+                        ),
+                        format!("{} {name}=***REDACTED***", instruction.keyword),
+                        "Pass secrets with RUN --mount=type=secret or provide them at runtime",
+                    ));
+                }
+            }
+            "RUN" if pipes_download_to_shell(&instruction.args) => {
+                findings.push(finding(
+                    "DOCKER_CURL_PIPE_SHELL",
+                    Severity::High,
+                    instruction,
+                    "A downloaded script is piped straight into a shell".to_string(),
+                    instruction.snippet(),
+                    "Download the script, verify its checksum or signature, then run it",
+                ));
+            }
+            _ => {}
+        }
+    }
+
+    if let Some(from) = final_from {
+        let root_user = final_user.filter(|user| {
+            let name = user.args.split(':').next().unwrap_or_default().trim();
+            name == "root" || name == "0"
+        });
+        match (final_user, root_user) {
+            (Some(_), None) => {}
+            (_, Some(user)) => findings.push(finding(
+                "DOCKER_RUNS_AS_ROOT",
+                Severity::Medium,
+                user,
+                "The container explicitly runs as root".to_string(),
+                user.snippet(),
+                "Switch to an unprivileged user with USER before the final instructions",
+            )),
+            (None, None) => findings.push(finding(
+                "DOCKER_RUNS_AS_ROOT",
+                Severity::Medium,
+                from,
+                "The final stage has no USER instruction, so the container runs as root"
+                    .to_string(),
+                from.snippet(),
+                "Create an unprivileged user and switch to it with USER",
+            )),
+        }
+    }
+
+    findings
+}
+
+/// Names declared by an ENV (`K=v ...` or legacy `K v`) or ARG instruction
+fn declared_names<'a>(keyword: &str, args: &'a str) -> Vec<&'a str> {
+    let mut tokens = args.split_whitespace();
+    match tokens.next() {
+        Some(first) if keyword == "ENV" && !first.contains('=') => vec![first],
+        Some(first) => std::iter::once(first)
+            .chain(tokens)
+            .filter(|token| keyword == "ARG" || token.contains('='))
+            .map(|token| token.split('=').next().unwrap_or(token))
+            .filter(|name| !name.is_empty() && !name.starts_with('"'))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let upper = name.to_ascii_uppercase();
+    // Paths to mounted secrets are fine
+    !upper.ends_with("_FILE")
+        && !upper.ends_with("_PATH")
+        && SECRET_NAME_PARTS.iter().any(|part| upper.contains(part))
+}
+
+/// Whether a shell command pipes `curl`/`wget` output into a shell
+fn pipes_download_to_shell(command: &str) -> bool {
+    let program = |token: &str| token.rsplit('/').next().unwrap_or(token).to_string();
+    let segments: Vec<&str> = command.split('|').collect();
+    segments.windows(2).any(|pair| {
+        let fetches = pair[0]
+            .split_whitespace()
+            .any(|token| matches!(program(token).as_str(), "curl" | "wget"));
+        let runs = pair[1]
+            .split_whitespace()
+            .find(|token| *token != "sudo" && !token.starts_with('-'))
+            .is_some_and(|token| SHELLS.contains(&program(token).as_str()));
+        fetches && runs
+    })
+}
+
+/// Findings for a GitHub Actions workflow: `pull_request_target` workflows
+/// that check out the pull request, and third-party actions not pinned to a
+/// commit SHA
+pub fn check_workflow(path: &str, content: &str) -> Vec<LocalFinding> {
+    let lines: Vec<(usize, &str)> = content
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| {
+            let code = line.split(" #").next().unwrap_or(line);
+            let trimmed = code.trim();
+            (!trimmed.is_empty() && !trimmed.starts_with('#')).then_some((idx + 1, trimmed))
+        })
+        .collect();
+    let privileged_trigger = lines
+        .iter()
+        .any(|(_, line)| line.contains("pull_request_target"));
+    let checks_out = lines
+        .iter()
+        .any(|(_, line)| line.contains("actions/checkout"));
+
+    let mut findings = Vec::new();
+    for (line_number, line) in &lines {
+        let key_value = line.trim_start_matches("- ").trim_start();
+
+        if let Some(reference) = key_value.strip_prefix("ref:") {
+            let pr_code = [
+                "github.event.pull_request.head",
+                "github.head_ref",
+                "refs/pull/",
+            ]
+            .iter()
+            .any(|pattern| reference.contains(pattern));
+            if privileged_trigger && checks_out && pr_code {
+                findings.push(LocalFinding {
+                    finding_type: "GHA_PR_TARGET_CHECKOUT".to_string(),
+                    description: "pull_request_target workflow checks out the pull request's code, which then runs with repository secrets and a write token".to_string(),
+                    file_path: path.to_string(),
+                    severity: Severity::High,
+                    line_number: Some(*line_number),
+                    snippet: Some(line.to_string()),
+                    recommendation: Some(
+                        "Build untrusted code under pull_request, and keep privileged steps in a separate workflow_run workflow".to_string(),
+                    ),
+                });
+            }
+        }
+
+        let Some(action) = key_value.strip_prefix("uses:") else {
+            continue;
+        };
+        let action = action.trim().trim_matches(|c| c == '"' || c == '\'');
+        if action.starts_with("./") || action.starts_with("docker://") {
+            continue;
+        }
+        let (name, reference) = action.split_once('@').unwrap_or((action, ""));
+        let owner = name.split('/').next().unwrap_or(name);
+        let pinned = reference.len() == 40 && reference.chars().all(|c| c.is_ascii_hexdigit());
+        if pinned || FIRST_PARTY_ACTION_OWNERS.contains(&owner.to_ascii_lowercase().as_str()) {
+            continue;
+        }
+        let description = if reference.is_empty() {
+            format!("Third-party action {name} is used without a version")
+        } else {
+            format!(
+                "Third-party action {name} is pinned to `{reference}`, which its owner can move to different code"
+            )
+        };
+        findings.push(LocalFinding {
+            finding_type: "GHA_UNPINNED_ACTION".to_string(),
+            description,
+            file_path: path.to_string(),
+            severity: Severity::Medium,
+            line_number: Some(*line_number),
+            snippet: Some(line.to_string()),
+            recommendation: Some(format!(
+                "Pin {name} to a full commit SHA (uses: {name}@<sha> # {})",
+                if reference.is_empty() {
+                    "vX.Y.Z"
+                } else {
+                    reference
+                }
+            )),
+        });
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines_of(findings: &[LocalFinding], finding_type: &str) -> Vec<usize> {
+        findings
+            .iter()
+            .filter(|f| f.finding_type == finding_type)
+            .filter_map(|f| f.line_number)
+            .collect()
+    }
+
+    #[test]
+    fn test_dockerfile_fixture_findings() {
+        let findings = check_dockerfile(
+            "Dockerfile",
+            include_str!("../tests/fixtures/infra/Dockerfile"),
+        );
+        assert!(findings.iter().all(|f| f.recommendation.is_some()));
+
+        assert_eq!(lines_of(&findings, "DOCKER_LATEST_TAG"), [2, 21]);
+        assert_eq!(lines_of(&findings, "DOCKER_ADD_REMOTE_URL"), [11]);
+        assert_eq!(lines_of(&findings, "DOCKER_CURL_PIPE_SHELL"), [13, 23]);
+        assert_eq!(lines_of(&findings, "DOCKER_RUNS_AS_ROOT"), [21]);
+        assert_eq!(lines_of(&findings, "DOCKER_SECRET_IN_BUILD"), [5, 7, 25]);
+
+        let secrets: Vec<&LocalFinding> = findings
+            .iter()
+            .filter(|f| f.finding_type == "DOCKER_SECRET_IN_BUILD")
+            .collect();
+        assert!(matches!(secrets[0].severity, Severity::Medium));
+        assert!(matches!(secrets[2].severity, Severity::High));
+        assert_eq!(
+            secrets[2].snippet.as_deref(),
+            Some("ENV DB_PASSWORD=***REDACTED***")
+        );
+        assert!(findings.iter().all(|f| !f
+            .snippet
+            .as_deref()
+            .unwrap_or_default()
+            .contains("hunter2")));
+    }
+
+    #[test]
+    fn test_hardened_dockerfile_is_clean() {
+        let findings = check_dockerfile(
+            "Dockerfile.hardened",
+            include_str!("../tests/fixtures/infra/Dockerfile.hardened"),
+        );
+        assert!(findings.is_empty(), "{findings:?}");
+    }
+
+    #[test]
+    fn test_workflow_fixture_findings() {
+        let findings = check_workflow(
+            ".github/workflows/pr-target.yml",
+            include_str!("../tests/fixtures/infra/pr-target.yml"),
+        );
+        assert_eq!(lines_of(&findings, "GHA_PR_TARGET_CHECKOUT"), [16]);
+        assert_eq!(lines_of(&findings, "GHA_UNPINNED_ACTION"), [17, 21, 23]);
+        assert_eq!(findings.len(), 4);
+
+        // Checking out the PR under plain pull_request is the safe pattern
+        let safe = include_str!("../tests/fixtures/infra/pr-target.yml")
+            .replace("pull_request_target", "pull_request");
+        assert!(lines_of(&check_workflow("ci.yml", &safe), "GHA_PR_TARGET_CHECKOUT").is_empty());
+    }
+
+    #[test]
+    fn test_infra_file_names() {
+        assert!(is_dockerfile("Dockerfile"));
+        assert!(is_dockerfile("deploy/Dockerfile.prod"));
+        assert!(is_dockerfile("images/api.dockerfile"));
+        assert!(!is_dockerfile("docs/dockerfile-guide.md"));
+        assert!(is_workflow_file(".github/workflows/ci.yaml"));
+        assert!(!is_workflow_file(".github/dependabot.yml"));
+    }
+}
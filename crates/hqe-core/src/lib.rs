@@ -4,30 +4,59 @@
 //!
 //! # Modules
 //!
+//! - [`advisories`] - Dependency advisories and lockfile parsing
+//! - [`checkpoint`] - Checkpoints for resuming interrupted scans
+//! - [`code_stats`] - Language detection and per-language line counts
+//! - [`dependencies`] - Dependency extraction from manifests and lockfiles
+//! - [`events`] - Scan progress events and NDJSON output
+//! - [`hygiene`] - Large, vendored and generated file checks
+//! - [`infra`] - Dockerfile and CI workflow misconfiguration checks
 //! - [`models`] - Core data models for scans, findings, and reports
 //! - [`redaction`] - PII and secret redaction utilities
 //! - [`repo`] - Repository scanning and analysis
 //! - [`scan`] - The main scan pipeline
+//! - [`scan_cache`] - Content-hash cache for incremental scans
 
 #![warn(missing_docs)]
 #![warn(clippy::unwrap_used)]
 #![warn(clippy::expect_used)]
+#![cfg_attr(test, allow(clippy::unwrap_used, clippy::expect_used))]
 
+pub mod advisories;
 pub mod analytics;
+pub mod checkpoint;
+pub mod code_stats;
+pub mod dependencies;
 pub mod encrypted_db;
+pub mod events;
+pub mod hygiene;
+pub mod infra;
+pub mod licenses;
 pub mod models;
+pub mod osv;
 pub mod persistence;
 pub mod prompt_runner;
 pub mod redaction;
 pub mod repo;
 pub mod scan;
+pub mod scan_cache;
 pub mod system_prompt;
 
+pub use advisories::*;
+pub use checkpoint::*;
+pub use code_stats::*;
+pub use dependencies::*;
+pub use events::*;
+pub use hygiene::*;
+pub use infra::*;
+pub use licenses::*;
 pub use models::*;
+pub use osv::*;
 pub use persistence::*;
 pub use redaction::*;
 pub use repo::*;
 pub use scan::*;
+pub use scan_cache::*;
 
 use thiserror::Error;
 
@@ -65,6 +94,18 @@ pub enum HqeError {
     #[error("LLM provider error: {0}")]
     Provider(String),
 
+    /// LLM provider is down and requests are failing fast
+    #[error("LLM provider unavailable: {0}")]
+    ProviderUnavailable(String),
+
+    /// The operation was cancelled by the caller
+    #[error("Operation cancelled")]
+    Cancelled,
+
+    /// A configured token or cost budget ran out
+    #[error("Budget exceeded: {0}")]
+    BudgetExceeded(String),
+
     /// Report or manifest generation failed
     #[error("Artifact generation failed: {0}")]
     Artifacts(String),
@@ -0,0 +1,635 @@
+//! License detection and license policy.
+//!
+//! The repository's own license comes from the license files in its root
+//! (`LICENSE*`, `LICENCE*`, `COPYING*`, `UNLICENSE`): an
+//! `SPDX-License-Identifier` line if there is one, otherwise the text is
+//! matched against common licenses. Package licenses come from the `license`
+//! field of `Cargo.toml` and `package.json` manifests and from the package
+//! entries of `package-lock.json`, which record the license of every
+//! installed package.
+//!
+//! A repository restricts licenses in [`REPO_POLICY_FILE`]; see
+//! [`LicensePolicy`].
+
+use crate::models::{DetectedLicense, LocalFinding, Severity};
+use serde::Deserialize;
+use std::path::Path;
+use tracing::warn;
+
+/// Per-repository policy config, relative to the repository root
+pub const REPO_POLICY_FILE: &str = ".hqe/policy.toml";
+
+/// SPDX value for a license file that matches no known license
+pub const UNKNOWN_LICENSE: &str = "NOASSERTION";
+
+/// Phrases identifying license texts, matched against the normalized text
+/// (lowercase, punctuation other than `.` and `-` removed). Every phrase of
+/// an entry must occur; the first matching entry wins, so licenses whose
+/// text mentions another (the LGPL and AGPL cite the GPL) come first.
+const LICENSE_TEXTS: &[(&str, &[&str])] = &[
+    ("AGPL-3.0", &["gnu affero general public license version 3"]),
+    ("LGPL-3.0", &["gnu lesser general public license version 3"]),
+    (
+        "LGPL-2.1",
+        &["gnu lesser general public license version 2.1"],
+    ),
+    ("GPL-3.0", &["gnu general public license version 3"]),
+    ("GPL-2.0", &["gnu general public license version 2"]),
+    ("MPL-2.0", &["mozilla public license version 2.0"]),
+    ("Apache-2.0", &["apache license version 2.0"]),
+    ("BSL-1.0", &["boost software license - version 1.0"]),
+    (
+        "Unlicense",
+        &["this is free and unencumbered software released into the public domain"],
+    ),
+    ("CC0-1.0", &["cc0 1.0 universal"]),
+    (
+        "ISC",
+        &["permission to use copy modify and or distribute this software for any purpose"],
+    ),
+    (
+        "MIT",
+        &["permission is hereby granted free of charge to any person obtaining a copy"],
+    ),
+    (
+        "BSD-3-Clause",
+        &[
+            "redistribution and use in source and binary forms",
+            "neither the name of",
+        ],
+    ),
+    (
+        "BSD-2-Clause",
+        &["redistribution and use in source and binary forms"],
+    ),
+];
+
+/// Whether a repository-relative path is a license file in the root
+pub fn is_license_file(path: &str) -> bool {
+    if path.contains(['/', '\\']) {
+        return false;
+    }
+    let name = path.to_ascii_uppercase();
+    ["LICENSE", "LICENCE", "COPYING", "UNLICENSE"]
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+/// SPDX identifier of a license text, if it is a known license or names its
+/// license in an `SPDX-License-Identifier` line
+pub fn identify_license(text: &str) -> Option<String> {
+    if let Some(id) = text.lines().find_map(|line| {
+        line.split_once("SPDX-License-Identifier:")
+            .map(|(_, id)| id.trim().trim_end_matches("*/").trim())
+    }) {
+        if !id.is_empty() {
+            return Some(id.to_string());
+        }
+    }
+
+    let normalized = text
+        .to_lowercase()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                ' '
+            }
+        })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    LICENSE_TEXTS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|p| normalized.contains(p)))
+        .map(|(id, _)| id.to_string())
+}
+
+/// Licenses in the given `(path, content)` license files, manifests and
+/// lockfiles.
+///
+/// Paths are relative to the repository root and use `/`. Files that fail to
+/// parse are skipped with a warning. The repository's licenses come first,
+/// then packages by name.
+pub fn extract_licenses<'a>(
+    files: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Vec<DetectedLicense> {
+    let files: Vec<(&str, &str)> = files.into_iter().collect();
+    // `license.workspace = true` inherits the root workspace's license
+    let workspace_license = files
+        .iter()
+        .find(|(path, _)| *path == "Cargo.toml")
+        .and_then(|(_, content)| toml::from_str::<toml::Table>(content).ok())
+        .and_then(|doc| {
+            doc.get("workspace")?
+                .get("package")?
+                .get("license")?
+                .as_str()
+                .map(str::to_string)
+        });
+
+    let mut licenses = Vec::new();
+    for (path, content) in files {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        let found = if is_license_file(path) {
+            Ok(vec![DetectedLicense {
+                package: None,
+                license: identify_license(content).unwrap_or_else(|| UNKNOWN_LICENSE.to_string()),
+                evidence: path.to_string(),
+            }])
+        } else {
+            match name {
+                "Cargo.toml" => cargo_toml_license(path, content, workspace_license.as_deref()),
+                "package.json" => package_json_license(path, content),
+                "package-lock.json" => package_lock_licenses(path, content),
+                _ => continue,
+            }
+        };
+        match found {
+            Ok(found) => licenses.extend(found),
+            Err(e) => warn!("Skipping licenses of {}: {}", path, e),
+        }
+    }
+
+    licenses.sort_by(|a, b| (&a.package, &a.evidence).cmp(&(&b.package, &b.evidence)));
+    licenses.dedup();
+    licenses
+}
+
+fn cargo_toml_license(
+    path: &str,
+    content: &str,
+    workspace_license: Option<&str>,
+) -> Result<Vec<DetectedLicense>, String> {
+    let doc: toml::Table = toml::from_str(content).map_err(|e| e.to_string())?;
+    let Some(package) = doc.get("package") else {
+        return Ok(Vec::new());
+    };
+    let license = match package.get("license") {
+        Some(toml::Value::String(license)) => Some(license.as_str()),
+        Some(toml::Value::Table(t))
+            if t.get("workspace").and_then(|w| w.as_bool()) == Some(true) =>
+        {
+            workspace_license
+        }
+        _ => None,
+    };
+    Ok(package
+        .get("name")
+        .and_then(|n| n.as_str())
+        .zip(license)
+        .map(|(name, license)| DetectedLicense {
+            package: Some(name.to_string()),
+            license: license.to_string(),
+            evidence: path.to_string(),
+        })
+        .into_iter()
+        .collect())
+}
+
+/// `"license": "MIT"`, or the older `"license": {"type": "MIT"}`
+fn npm_license(value: &serde_json::Value) -> Option<&str> {
+    value.as_str().or_else(|| value["type"].as_str())
+}
+
+fn package_json_license(path: &str, content: &str) -> Result<Vec<DetectedLicense>, String> {
+    let doc: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    Ok(doc["name"]
+        .as_str()
+        .zip(npm_license(&doc["license"]))
+        .map(|(name, license)| DetectedLicense {
+            package: Some(name.to_string()),
+            license: license.to_string(),
+            evidence: path.to_string(),
+        })
+        .into_iter()
+        .collect())
+}
+
+fn package_lock_licenses(path: &str, content: &str) -> Result<Vec<DetectedLicense>, String> {
+    let doc: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let mut licenses = Vec::new();
+    // Lockfile v2 and v3; v1 records no licenses. The "" entry is the project.
+    for (key, entry) in doc["packages"].as_object().into_iter().flatten() {
+        let Some((_, name)) = key.rsplit_once("node_modules/") else {
+            continue;
+        };
+        if let Some(license) = npm_license(&entry["license"]) {
+            licenses.push(DetectedLicense {
+                package: Some(entry["name"].as_str().unwrap_or(name).to_string()),
+                license: license.to_string(),
+                evidence: path.to_string(),
+            });
+        }
+    }
+    Ok(licenses)
+}
+
+/// Whether two SPDX identifiers name the same license, ignoring case and
+/// the `-only`, `-or-later` and `+` variants
+fn same_license(a: &str, b: &str) -> bool {
+    fn base(id: &str) -> &str {
+        let id = id.trim_end_matches('+');
+        id.strip_suffix("-only")
+            .or_else(|| id.strip_suffix("-or-later"))
+            .unwrap_or(id)
+    }
+    base(a).eq_ignore_ascii_case(base(b))
+}
+
+/// SPDX expression: `AND` binds tighter than `OR`, `WITH` attaches an
+/// exception to a license, and Cargo's legacy `/` means `OR`
+enum Expression<'a> {
+    License(&'a str),
+    And(Vec<Expression<'a>>),
+    Or(Vec<Expression<'a>>),
+}
+
+/// Words of an expression, with each parenthesis and `/` a token of its own
+fn expression_tokens(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() || "()/".contains(c) {
+            if let Some(start) = start.take() {
+                tokens.push(&text[start..i]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&text[i..i + c.len_utf8()]);
+            }
+        } else if start.is_none() {
+            start = Some(i);
+        }
+    }
+    tokens.extend(start.map(|start| &text[start..]));
+    tokens
+}
+
+fn is_operator(token: &str) -> bool {
+    ["(", ")", "/", "AND", "OR", "WITH"]
+        .iter()
+        .any(|op| token.eq_ignore_ascii_case(op))
+}
+
+impl<'a> Expression<'a> {
+    /// Parse an expression; `None` when it is malformed
+    fn parse(text: &'a str) -> Option<Self> {
+        let tokens = expression_tokens(text);
+        let mut pos = 0;
+        let expression = Self::parse_or(&tokens, &mut pos)?;
+        (pos == tokens.len()).then_some(expression)
+    }
+
+    fn parse_or(tokens: &[&'a str], pos: &mut usize) -> Option<Self> {
+        let mut terms = vec![Self::parse_and(tokens, pos)?];
+        while tokens
+            .get(*pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case("OR") || *t == "/")
+        {
+            *pos += 1;
+            terms.push(Self::parse_and(tokens, pos)?);
+        }
+        Some(if terms.len() == 1 {
+            terms.remove(0)
+        } else {
+            Self::Or(terms)
+        })
+    }
+
+    fn parse_and(tokens: &[&'a str], pos: &mut usize) -> Option<Self> {
+        let mut factors = vec![Self::parse_license(tokens, pos)?];
+        while tokens
+            .get(*pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case("AND"))
+        {
+            *pos += 1;
+            factors.push(Self::parse_license(tokens, pos)?);
+        }
+        Some(if factors.len() == 1 {
+            factors.remove(0)
+        } else {
+            Self::And(factors)
+        })
+    }
+
+    fn parse_license(tokens: &[&'a str], pos: &mut usize) -> Option<Self> {
+        let token = *tokens.get(*pos)?;
+        *pos += 1;
+        if token == "(" {
+            let inner = Self::parse_or(tokens, pos)?;
+            if tokens.get(*pos) != Some(&")") {
+                return None;
+            }
+            *pos += 1;
+            return Some(inner);
+        }
+        if is_operator(token) {
+            return None;
+        }
+        // The exception narrows the license; the policy judges the license
+        if tokens
+            .get(*pos)
+            .is_some_and(|t| t.eq_ignore_ascii_case("WITH"))
+        {
+            tokens.get(*pos + 1)?;
+            *pos += 2;
+        }
+        Some(Self::License(token))
+    }
+
+    /// Whether some choice of licenses satisfies `allowed`
+    fn satisfied_by(&self, allowed: &impl Fn(&str) -> bool) -> bool {
+        match self {
+            Self::License(id) => allowed(id),
+            Self::And(all) => all.iter().all(|e| e.satisfied_by(allowed)),
+            Self::Or(any) => any.iter().any(|e| e.satisfied_by(allowed)),
+        }
+    }
+
+    fn licenses(&self, out: &mut Vec<&'a str>) {
+        match self {
+            Self::License(id) => out.push(id),
+            Self::And(all) | Self::Or(all) => all.iter().for_each(|e| e.licenses(out)),
+        }
+    }
+}
+
+/// On-disk layout of [`REPO_POLICY_FILE`]
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RepoPolicyFile {
+    #[serde(default)]
+    licenses: LicensePolicyTable,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LicensePolicyTable {
+    #[serde(default)]
+    deny: Vec<String>,
+    #[serde(default)]
+    allow: Vec<String>,
+}
+
+/// Licenses a repository forbids or permits, from [`REPO_POLICY_FILE`].
+///
+/// ```toml
+/// [licenses]
+/// deny = ["AGPL-3.0", "SSPL-1.0"]
+/// allow = ["MIT", "Apache-2.0", "BSD-3-Clause"]  # optional: only these
+/// ```
+///
+/// Identifiers match regardless of case and of `-only`/`-or-later`
+/// variants, so `AGPL-3.0` also denies `AGPL-3.0-or-later`. A license
+/// expression is a violation when no choice among its `OR` alternatives
+/// avoids every denied license (and, with an allow list, uses only allowed
+/// ones).
+///
+/// Loading never fails: an invalid file is described in
+/// [`LicensePolicy::problems`] and enforces nothing.
+#[derive(Debug, Clone, Default)]
+pub struct LicensePolicy {
+    /// Forbidden licenses
+    pub deny: Vec<String>,
+    /// If not empty, the only permitted licenses
+    pub allow: Vec<String>,
+    /// Why the policy file was ignored, one message each
+    pub problems: Vec<String>,
+}
+
+impl LicensePolicy {
+    /// Load [`REPO_POLICY_FILE`] from the repository at `root`; a missing
+    /// file gives an empty policy
+    pub fn load(root: impl AsRef<Path>) -> Self {
+        let path = root.as_ref().join(REPO_POLICY_FILE);
+        match std::fs::read_to_string(&path) {
+            Ok(raw) => Self::from_toml(&raw),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Self::default(),
+            Err(e) => Self {
+                problems: vec![format!("Unreadable {}: {}", REPO_POLICY_FILE, e)],
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Parse the contents of a [`REPO_POLICY_FILE`]
+    pub fn from_toml(raw: &str) -> Self {
+        match toml::from_str::<RepoPolicyFile>(raw) {
+            Ok(file) => Self {
+                deny: file.licenses.deny,
+                allow: file.licenses.allow,
+                problems: Vec::new(),
+            },
+            Err(e) => Self {
+                problems: vec![format!("Invalid {}: {}", REPO_POLICY_FILE, e)],
+                ..Self::default()
+            },
+        }
+    }
+
+    /// Whether the policy restricts nothing
+    pub fn is_empty(&self) -> bool {
+        self.deny.is_empty() && self.allow.is_empty()
+    }
+
+    fn permits(&self, id: &str) -> bool {
+        !self.deny.iter().any(|d| same_license(d, id))
+            && (self.allow.is_empty() || self.allow.iter().any(|a| same_license(a, id)))
+    }
+
+    /// Licenses of `expression` the policy rejects, empty when it complies.
+    ///
+    /// An expression that does not parse is judged license by license.
+    pub fn violations<'a>(&self, expression: &'a str) -> Vec<&'a str> {
+        let permits = |id: &str| self.permits(id);
+        let mut ids = Vec::new();
+        match Expression::parse(expression) {
+            Some(parsed) if parsed.satisfied_by(&permits) => return Vec::new(),
+            Some(parsed) => parsed.licenses(&mut ids),
+            None => ids.extend(
+                expression_tokens(expression)
+                    .into_iter()
+                    .filter(|t| !is_operator(t)),
+            ),
+        }
+        ids.retain(|id| !permits(id));
+        ids.dedup();
+        ids
+    }
+
+    /// A Medium finding for each license that violates the policy, and for
+    /// each problem with the policy file
+    pub fn check(&self, licenses: &[DetectedLicense]) -> Vec<LocalFinding> {
+        let mut findings: Vec<LocalFinding> = self
+            .problems
+            .iter()
+            .map(|problem| LocalFinding {
+                finding_type: "INVALID_POLICY_CONFIG".to_string(),
+                description: problem.clone(),
+                file_path: REPO_POLICY_FILE.to_string(),
+                severity: Severity::Medium,
+                line_number: None,
+                snippet: None,
+                recommendation: Some("Fix the file; the policy is ignored until then".to_string()),
+            })
+            .collect();
+
+        for detected in licenses {
+            let rejected = self.violations(&detected.license);
+            if rejected.is_empty() {
+                continue;
+            }
+            let (subject, recommendation) = match &detected.package {
+                Some(package) => (
+                    package.clone(),
+                    format!(
+                        "Replace {} or get its license approved in {}",
+                        package, REPO_POLICY_FILE
+                    ),
+                ),
+                None => (
+                    "The repository".to_string(),
+                    format!("Relicense the repository or update {}", REPO_POLICY_FILE),
+                ),
+            };
+            findings.push(LocalFinding {
+                finding_type: "LICENSE_POLICY_VIOLATION".to_string(),
+                description: format!(
+                    "{} is licensed under {}, which the license policy does not permit ({})",
+                    subject,
+                    detected.license,
+                    rejected.join(", ")
+                ),
+                file_path: detected.evidence.clone(),
+                severity: Severity::Medium,
+                line_number: None,
+                snippet: Some(detected.license.clone()),
+                recommendation: Some(recommendation),
+            });
+        }
+        findings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIT: &str = "MIT License\n\nCopyright (c) 2024 Example\n\nPermission is hereby granted, free of charge, to any person obtaining a copy\nof this software and associated documentation files (the \"Software\"), to deal\n";
+    const GPL3: &str = "                    GNU GENERAL PUBLIC LICENSE\n                       Version 3, 29 June 2007\n\n ... use the GNU Lesser General Public License instead of this License.\n";
+    const AGPL3: &str = "                    GNU AFFERO GENERAL PUBLIC LICENSE\n                       Version 3, 19 November 2007\n\n ... version 3 of the GNU General Public License\n";
+
+    #[test]
+    fn test_license_texts_are_identified() {
+        assert_eq!(identify_license(MIT).as_deref(), Some("MIT"));
+        assert_eq!(identify_license(GPL3).as_deref(), Some("GPL-3.0"));
+        assert_eq!(identify_license(AGPL3).as_deref(), Some("AGPL-3.0"));
+        assert_eq!(
+            identify_license("// SPDX-License-Identifier: MIT OR Apache-2.0\n").as_deref(),
+            Some("MIT OR Apache-2.0")
+        );
+        assert_eq!(identify_license("All rights reserved."), None);
+        assert!(is_license_file("LICENSE-APACHE"));
+        assert!(is_license_file("COPYING"));
+        assert!(!is_license_file("docs/LICENSE"));
+    }
+
+    #[test]
+    fn test_licenses_come_from_license_files_manifests_and_lockfiles() {
+        let workspace =
+            "[workspace]\nmembers = [\"cli\"]\n\n[workspace.package]\nlicense = \"Apache-2.0\"\n";
+        let member = "[package]\nname = \"cli\"\nlicense.workspace = true\n";
+        let lock = r#"{"lockfileVersion": 3, "packages": {
+            "": {"name": "web", "license": "MIT"},
+            "node_modules/left-pad": {"version": "1.3.0", "license": "WTFPL"},
+            "node_modules/a/node_modules/b": {"version": "1.0.0", "license": {"type": "ISC"}},
+            "node_modules/unlicensed": {"version": "1.0.0"}
+        }}"#;
+
+        let licenses = extract_licenses([
+            ("LICENSE", MIT),
+            ("NOTICE-LICENSE.txt", "Proprietary"),
+            ("Cargo.toml", workspace),
+            ("cli/Cargo.toml", member),
+            ("web/package.json", r#"{"name": "web", "license": "MIT"}"#),
+            ("web/package-lock.json", lock),
+        ]);
+        let summary: Vec<(Option<&str>, &str, &str)> = licenses
+            .iter()
+            .map(|l| {
+                (
+                    l.package.as_deref(),
+                    l.license.as_str(),
+                    l.evidence.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (None, "MIT", "LICENSE"),
+                (Some("b"), "ISC", "web/package-lock.json"),
+                (Some("cli"), "Apache-2.0", "cli/Cargo.toml"),
+                (Some("left-pad"), "WTFPL", "web/package-lock.json"),
+                (Some("web"), "MIT", "web/package.json"),
+            ]
+        );
+        // "NOTICE-LICENSE.txt" is not a license file name
+        assert!(licenses.iter().all(|l| l.license != UNKNOWN_LICENSE));
+    }
+
+    #[test]
+    fn test_policy_judges_spdx_expressions() {
+        let policy = LicensePolicy::from_toml("[licenses]\ndeny = [\"AGPL-3.0\", \"GPL-3.0\"]\n");
+        assert!(policy.violations("MIT").is_empty());
+        assert_eq!(
+            policy.violations("AGPL-3.0-or-later"),
+            ["AGPL-3.0-or-later"]
+        );
+        // A permitted alternative avoids the denied license
+        assert!(policy.violations("MIT OR GPL-3.0-only").is_empty());
+        assert!(policy.violations("MIT/GPL-3.0").is_empty());
+        assert_eq!(
+            policy.violations("(MIT OR Apache-2.0) AND GPL-3.0"),
+            ["GPL-3.0"]
+        );
+        assert!(policy
+            .violations("GPL-2.0 WITH Classpath-exception-2.0")
+            .is_empty());
+
+        let allow = LicensePolicy::from_toml("[licenses]\nallow = [\"MIT\", \"Apache-2.0\"]\n");
+        assert!(allow.violations("MIT OR Apache-2.0").is_empty());
+        assert_eq!(allow.violations("ISC"), ["ISC"]);
+
+        let broken = LicensePolicy::from_toml("[licenses]\ndenied = []\n");
+        assert!(broken.is_empty());
+        assert_eq!(broken.problems.len(), 1);
+    }
+
+    #[test]
+    fn test_policy_violations_become_medium_findings() {
+        let policy = LicensePolicy::from_toml("[licenses]\ndeny = [\"AGPL-3.0\"]\n");
+        let licenses = [
+            DetectedLicense {
+                package: None,
+                license: "AGPL-3.0".to_string(),
+                evidence: "COPYING".to_string(),
+            },
+            DetectedLicense {
+                package: Some("left-pad".to_string()),
+                license: "MIT".to_string(),
+                evidence: "package-lock.json".to_string(),
+            },
+        ];
+        let findings = policy.check(&licenses);
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].finding_type, "LICENSE_POLICY_VIOLATION");
+        assert_eq!(findings[0].file_path, "COPYING");
+        assert!(matches!(findings[0].severity, Severity::Medium));
+        assert!(findings[0]
+            .description
+            .starts_with("The repository is licensed under AGPL-3.0"));
+    }
+}
@@ -1,8 +1,11 @@
 //! Data models for HQE protocol
 
+use crate::advisories::Ecosystem;
 use chrono::{DateTime, Utc};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 /// Current HQE protocol version
@@ -25,6 +28,40 @@ pub struct RunManifest {
     pub timestamps: Timestamps,
     /// Protocol and schema versions used
     pub protocol: ProtocolVersions,
+    /// File reuse counts when the scan ran incrementally
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub incremental: Option<IncrementalSummary>,
+    /// Prompt trimming applied to fit the model context window
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prompt_truncation: Option<PromptTruncation>,
+    /// Token usage and estimated cost of the LLM requests made during the run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cost_estimate: Option<CostEstimate>,
+    /// Files whose LLM analysis failed while the rest of the scan completed
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub degraded_files: Vec<DegradedAnalysis>,
+    /// Files analyzed locally after the LLM provider became unavailable
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub provider_fallback: Option<ProviderFallback>,
+    /// Per-file analysis cache hits and misses
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub analysis_cache: Option<AnalysisCacheStats>,
+    /// The scan was cancelled and the report only covers work finished before that
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub cancelled: bool,
+    /// Why LLM analysis stopped early because a scan budget ran out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_exceeded: Option<String>,
+    /// Files left out of the analysis because they are binary, minified or generated
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped_files: Option<SkippedFileCounts>,
+    /// Set when only files changed since a git ref were checked; the report
+    /// does not cover the rest of the repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_since: Option<ChangedSinceScope>,
+    /// Size and SHA-256 of every other file written to the run directory
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub artifacts: Vec<ArtifactDigest>,
 }
 
 impl RunManifest {
@@ -57,10 +94,32 @@ impl RunManifest {
                 protocol_version: HQE_PROTOCOL_VERSION.to_string(),
                 schema_version: HQE_SCHEMA_VERSION.to_string(),
             },
+            incremental: None,
+            prompt_truncation: None,
+            cost_estimate: None,
+            degraded_files: Vec::new(),
+            provider_fallback: None,
+            analysis_cache: None,
+            cancelled: false,
+            budget_exceeded: None,
+            skipped_files: None,
+            changed_since: None,
+            artifacts: Vec::new(),
         }
     }
 }
 
+/// Checksum of one file in the run directory, recorded in the manifest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactDigest {
+    /// Path relative to the run directory, with forward slashes
+    pub path: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Lowercase hex SHA-256 of the contents
+    pub sha256: String,
+}
+
 /// Source type for a repository
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -106,6 +165,45 @@ pub struct ScanLimits {
     pub max_total_chars_sent: usize,
     /// Character limit per code snippet
     pub snippet_chars: usize,
+    /// Shannon entropy (bits per character) above which a string literal is
+    /// reported as a possible secret
+    #[serde(default = "default_entropy_threshold")]
+    pub entropy_threshold: f64,
+    /// Minimum length of string literals checked for entropy
+    #[serde(default = "default_entropy_min_length")]
+    pub entropy_min_length: usize,
+    /// File globs excluded from entropy checks (e.g. `fixtures/**`)
+    #[serde(default)]
+    pub entropy_allowlist: Vec<String>,
+    /// Skip files matched by `.gitignore`, `.git/info/exclude` and `.hqeignore`
+    #[serde(default = "default_respect_ignore_files")]
+    pub respect_ignore_files: bool,
+    /// Only scan files matching one of these globs (empty means every file)
+    #[serde(default)]
+    pub include_globs: Vec<String>,
+    /// Skip files and directories matching any of these globs, even if included
+    #[serde(default)]
+    pub exclude_globs: Vec<String>,
+    /// Size (in bytes) above which a committed file is reported as a
+    /// `LARGE_FILE` finding
+    #[serde(default = "default_large_file_bytes")]
+    pub large_file_bytes: u64,
+}
+
+fn default_entropy_threshold() -> f64 {
+    4.0
+}
+
+fn default_entropy_min_length() -> usize {
+    20
+}
+
+fn default_respect_ignore_files() -> bool {
+    true
+}
+
+fn default_large_file_bytes() -> u64 {
+    5_000_000
 }
 
 impl Default for ScanLimits {
@@ -114,6 +212,13 @@ impl Default for ScanLimits {
             max_files_sent: 40,
             max_total_chars_sent: 250_000,
             snippet_chars: 4_000,
+            entropy_threshold: default_entropy_threshold(),
+            entropy_min_length: default_entropy_min_length(),
+            entropy_allowlist: Vec::new(),
+            respect_ignore_files: default_respect_ignore_files(),
+            include_globs: Vec::new(),
+            exclude_globs: Vec::new(),
+            large_file_bytes: default_large_file_bytes(),
         }
     }
 }
@@ -144,6 +249,36 @@ impl ScanLimits {
             )));
         }
 
+        if !(self.entropy_threshold > 0.0 && self.entropy_threshold <= 8.0) {
+            return Err(crate::HqeError::Scan(format!(
+                "entropy_threshold must be between 0 and 8 bits per character, got {}",
+                self.entropy_threshold
+            )));
+        }
+
+        if self.entropy_min_length == 0 {
+            return Err(crate::HqeError::Scan(
+                "entropy_min_length must be at least 1".to_string(),
+            ));
+        }
+
+        if self.large_file_bytes == 0 {
+            return Err(crate::HqeError::Scan(
+                "large_file_bytes must be at least 1".to_string(),
+            ));
+        }
+
+        if let Some(bad) = self
+            .entropy_allowlist
+            .iter()
+            .find(|pattern| glob::Pattern::new(pattern).is_err())
+        {
+            return Err(crate::HqeError::Scan(format!(
+                "Invalid entropy_allowlist glob: {}",
+                bad
+            )));
+        }
+
         Ok(())
     }
 }
@@ -157,6 +292,168 @@ pub struct Timestamps {
     pub ended: Option<DateTime<Utc>>,
 }
 
+/// File counts for an incremental scan
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IncrementalSummary {
+    /// Files whose content was unchanged and whose findings were reused
+    pub reused: usize,
+    /// New or modified files that were analyzed again
+    pub rescanned: usize,
+    /// Files present in the previous scan that no longer exist
+    pub removed: usize,
+}
+
+/// Files a scan restricted with [`ScanConfig::changed_since`] covered
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangedSinceScope {
+    /// Git ref the changes were taken against, e.g. `origin/main`
+    pub base_ref: String,
+    /// Files changed since `base_ref` plus untracked files, deleted ones included
+    pub changed_files: usize,
+}
+
+/// Why a file was found during the walk but not analyzed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SkipReason {
+    /// NUL bytes or mostly control characters
+    Binary,
+    /// Text with very long lines, e.g. a bundled or minified script
+    Minified,
+    /// Marked `@generated` near the top of the file
+    Generated,
+}
+
+/// Number of files skipped for each [`SkipReason`]
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SkippedFileCounts {
+    /// Binary files
+    pub binary: usize,
+    /// Minified files
+    pub minified: usize,
+    /// Generated files
+    pub generated: usize,
+}
+
+impl SkippedFileCounts {
+    /// Count one skipped file
+    pub fn add(&mut self, reason: SkipReason) {
+        match reason {
+            SkipReason::Binary => self.binary += 1,
+            SkipReason::Minified => self.minified += 1,
+            SkipReason::Generated => self.generated += 1,
+        }
+    }
+
+    /// Total number of skipped files
+    pub fn total(&self) -> usize {
+        self.binary + self.minified + self.generated
+    }
+}
+
+/// Prompt trimming applied before an LLM request
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PromptTruncation {
+    /// Context window the prompt was fitted to
+    pub context_window: usize,
+    /// Estimated prompt tokens before trimming
+    pub original_tokens: usize,
+    /// Estimated prompt tokens actually sent
+    pub sent_tokens: usize,
+    /// Files whose content was dropped or cut, least important first
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub trimmed_files: Vec<String>,
+}
+
+/// Token usage and estimated cost of LLM requests
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CostEstimate {
+    /// Number of completed requests
+    pub requests: u32,
+    /// Prompt tokens reported by the provider
+    pub prompt_tokens: u64,
+    /// Completion tokens reported by the provider
+    pub completion_tokens: u64,
+    /// Estimated cost in USD (None if model pricing is unknown)
+    pub total_usd: Option<f64>,
+    /// Models used without known pricing, which make `total_usd` unknown
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unpriced_models: Vec<String>,
+}
+
+/// Token and cost limits on the LLM requests of one scan
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ScanBudget {
+    /// Limit on the estimated cost in USD
+    pub usd: Option<f64>,
+    /// Limit on prompt plus completion tokens
+    pub tokens: Option<u64>,
+}
+
+impl ScanBudget {
+    /// Whether any limit is set
+    pub fn is_set(&self) -> bool {
+        self.usd.is_some() || self.tokens.is_some()
+    }
+
+    /// Why `spent` uses up this budget, if it does.
+    ///
+    /// A cost budget counts as used up once a model without known pricing was
+    /// used, since the cost can no longer be bounded.
+    pub fn exhausted_by(&self, spent: &CostEstimate) -> Option<String> {
+        if let Some(limit) = self.tokens {
+            let used = spent.prompt_tokens + spent.completion_tokens;
+            if used >= limit {
+                return Some(format!(
+                    "token budget of {limit} reached ({used} tokens used)"
+                ));
+            }
+        }
+        let limit = self.usd?;
+        match spent.total_usd {
+            Some(usd) if usd >= limit => Some(format!(
+                "cost budget of ${limit:.2} reached (${usd:.4} spent)"
+            )),
+            None if spent.requests > 0 => Some(format!(
+                "cost budget of ${limit:.2} cannot be enforced without pricing for {}",
+                if spent.unpriced_models.is_empty() {
+                    "the model".to_string()
+                } else {
+                    spent.unpriced_models.join(", ")
+                }
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Hit and miss counts of the per-file analysis cache
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnalysisCacheStats {
+    /// Files whose analysis was served from the cache
+    pub hits: usize,
+    /// Files that were sent to the LLM
+    pub misses: usize,
+}
+
+/// A file whose LLM analysis failed during a scan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DegradedAnalysis {
+    /// Relative path of the file
+    pub file: String,
+    /// Error reported by the analyzer
+    pub reason: String,
+}
+
+/// Files that fell back to local-only analysis because the provider was down
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProviderFallback {
+    /// Error that marked the provider unavailable
+    pub reason: String,
+    /// Relative paths of the files analyzed locally
+    pub files: Vec<String>,
+}
+
 /// Protocol and schema version information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProtocolVersions {
@@ -190,10 +487,16 @@ pub struct HqeReport {
     pub immediate_actions: Vec<PatchAction>,
     /// Session log with completed, in-progress, and discovered items
     pub session_log: SessionLog,
+    /// Findings accepted by the baseline; not counted in the summary or score
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suppressed_findings: Vec<Finding>,
+    /// Outcome of applying a baseline, if one was given
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline: Option<BaselineStatus>,
 }
 
 /// Section 1: Executive Summary
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct ExecutiveSummary {
     /// Health score from 1-10 indicating overall codebase health
     pub health_score: u8, // 1-10
@@ -206,7 +509,7 @@ pub struct ExecutiveSummary {
 }
 
 /// A blocking issue that prevents progress
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Blocker {
     /// Description of the blocker
     pub description: String,
@@ -227,6 +530,82 @@ pub struct ProjectMap {
     pub data_flow: Option<String>,
     /// Technology stack information
     pub tech_stack: TechStack,
+    /// File and line counts per language, computed locally
+    #[serde(default)]
+    pub code_stats: CodeStats,
+    /// Licenses of the repository and of the packages its manifests and
+    /// lockfiles declare, computed locally
+    #[serde(default)]
+    pub licenses: Vec<DetectedLicense>,
+    /// Large, vendored and generated files committed to the repository,
+    /// computed locally
+    #[serde(default)]
+    pub hygiene: RepoHygiene,
+}
+
+/// File and line counts of one language
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageStats {
+    /// Number of files
+    pub files: usize,
+    /// Lines containing code
+    pub code_lines: usize,
+    /// Lines containing only a comment
+    pub comment_lines: usize,
+    /// Empty or whitespace-only lines
+    pub blank_lines: usize,
+}
+
+impl LanguageStats {
+    /// Add the counts of `other`
+    pub fn add(&mut self, other: &LanguageStats) {
+        self.files += other.files;
+        self.code_lines += other.code_lines;
+        self.comment_lines += other.comment_lines;
+        self.blank_lines += other.blank_lines;
+    }
+}
+
+/// Per-language file and line counts of a repository
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CodeStats {
+    /// Language name -> counts
+    pub languages: BTreeMap<String, LanguageStats>,
+}
+
+impl CodeStats {
+    /// Add the counts of one file written in `language`
+    pub fn add(&mut self, language: &str, stats: &LanguageStats) {
+        self.languages
+            .entry(language.to_string())
+            .or_default()
+            .add(stats);
+    }
+
+    /// Languages ordered by code lines, largest first
+    pub fn by_code_lines(&self) -> Vec<(&str, &LanguageStats)> {
+        let mut languages: Vec<_> = self
+            .languages
+            .iter()
+            .map(|(name, stats)| (name.as_str(), stats))
+            .collect();
+        languages.sort_by(|a, b| b.1.code_lines.cmp(&a.1.code_lines).then(a.0.cmp(b.0)));
+        languages
+    }
+
+    /// Counts summed over every language
+    pub fn total(&self) -> LanguageStats {
+        let mut total = LanguageStats::default();
+        for stats in self.languages.values() {
+            total.add(stats);
+        }
+        total
+    }
+
+    /// Whether no file of a known language was counted
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
 }
 
 /// Project architecture information
@@ -268,6 +647,93 @@ pub struct TechStack {
     pub detected: Vec<DetectedTechnology>,
     /// Package managers used
     pub package_managers: Vec<String>,
+    /// Third-party packages declared in manifests or pinned by lockfiles
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub dependencies: Vec<Dependency>,
+}
+
+/// A third-party package found in a manifest or lockfile
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Dependency {
+    /// Package name as the ecosystem spells it
+    pub name: String,
+    /// Ecosystem the package comes from
+    pub ecosystem: Ecosystem,
+    /// Exact version, from a lockfile or an exact pin in the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+    /// Version requirement as declared in the manifest
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub requirement: Option<String>,
+    /// Manifest or lockfile the entry was read from, relative to the repository root
+    pub evidence: String,
+}
+
+impl Dependency {
+    /// Package URL, including the version only when it is exact
+    pub fn purl(&self) -> String {
+        let name = match self.ecosystem {
+            // Scoped npm packages keep the scope as the purl namespace
+            Ecosystem::Npm => self.name.replacen('@', "%40", 1),
+            Ecosystem::Pypi => self.name.to_lowercase().replace('_', "-"),
+            Ecosystem::Cargo | Ecosystem::Go => self.name.clone(),
+        };
+        match &self.version {
+            Some(version) => format!("pkg:{}/{}@{}", self.ecosystem.purl_type(), name, version),
+            None => format!("pkg:{}/{}", self.ecosystem.purl_type(), name),
+        }
+    }
+}
+
+/// A license of the repository or of a package in it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DetectedLicense {
+    /// Package the license applies to; `None` for the repository itself
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub package: Option<String>,
+    /// SPDX license expression; `NOASSERTION` for a license file matching no
+    /// known license
+    pub license: String,
+    /// File the license was read from
+    pub evidence: String,
+}
+
+/// Repository hygiene: committed files that are not hand-written source code
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RepoHygiene {
+    /// Files larger than [`ScanLimits::large_file_bytes`], largest first
+    #[serde(default)]
+    pub large_files: Vec<LargeFile>,
+    /// Directories of vendored third-party code, by path
+    #[serde(default)]
+    pub vendored_directories: Vec<VendoredDirectory>,
+    /// Total size of the files in `vendored_directories`
+    #[serde(default)]
+    pub vendored_bytes: u64,
+    /// Files named like generated code that carry no generated marker
+    #[serde(default)]
+    pub unmarked_generated_files: Vec<String>,
+}
+
+/// A committed file above the size threshold
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LargeFile {
+    /// Relative file path
+    pub path: String,
+    /// File size in bytes
+    pub size_bytes: u64,
+}
+
+/// A directory of vendored third-party code (`vendor/`, `third_party/`,
+/// `node_modules/`, ...)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VendoredDirectory {
+    /// Relative directory path, without a trailing `/`
+    pub path: String,
+    /// Number of files in it
+    pub files: usize,
+    /// Total size of those files in bytes
+    pub size_bytes: u64,
 }
 
 /// A detected technology in the project
@@ -341,7 +807,7 @@ pub struct PrConflict {
 }
 
 /// Section 4: Deep Scan Results
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize, Default, JsonSchema)]
 pub struct DeepScanResults {
     /// Security-related findings
     pub security: Vec<Finding>,
@@ -355,8 +821,20 @@ pub struct DeepScanResults {
     pub testing: Vec<Finding>,
 }
 
+impl DeepScanResults {
+    /// All findings across categories
+    pub fn iter(&self) -> impl Iterator<Item = &Finding> {
+        self.security
+            .iter()
+            .chain(&self.code_quality)
+            .chain(&self.frontend)
+            .chain(&self.backend)
+            .chain(&self.testing)
+    }
+}
+
 /// A finding from deep scan
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct Finding {
     /// Unique identifier for this finding
     pub id: String,
@@ -374,10 +852,53 @@ pub struct Finding {
     pub impact: String,
     /// Recommendation for addressing the issue
     pub recommendation: String,
+    /// Whether the finding matched the baseline and was accepted
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub suppressed: bool,
+}
+
+impl Finding {
+    /// Stable identifier used by baselines: the first 16 hex digits of a
+    /// SHA-256 over the category, title and evidence.
+    ///
+    /// Ids and line numbers are left out so that renumbered findings and code
+    /// that merely moved keep their fingerprint.
+    pub fn fingerprint(&self) -> String {
+        let location = match &self.evidence {
+            Evidence::FileLine { file, snippet, .. } => format!("{file}\0{}", collapse(snippet)),
+            Evidence::FileFunction {
+                file,
+                function,
+                snippet,
+            } => format!("{file}\0{function}\0{}", collapse(snippet)),
+            Evidence::Reproduction { steps, observed } => {
+                format!("{}\0{}", collapse(&steps.join("\n")), collapse(observed))
+            }
+        };
+        let input = format!(
+            "{}\0{}\0{location}",
+            self.category.trim().to_lowercase(),
+            collapse(&self.title)
+        );
+        hex::encode(Sha256::digest(input.as_bytes()))[..16].to_string()
+    }
+
+    /// File the evidence points at, if any
+    pub fn file(&self) -> Option<&str> {
+        match &self.evidence {
+            Evidence::FileLine { file, .. } | Evidence::FileFunction { file, .. } => Some(file),
+            Evidence::Reproduction { .. } => None,
+        }
+    }
+}
+
+/// Joins whitespace runs so reformatting does not change a fingerprint
+fn collapse(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
 }
 
 /// Severity level of a finding
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Severity {
     /// Critical severity - requires immediate attention
@@ -392,6 +913,19 @@ pub enum Severity {
     Info,
 }
 
+impl Severity {
+    /// Numeric rank for sorting; higher is more severe
+    pub fn rank(&self) -> u8 {
+        match self {
+            Severity::Critical => 4,
+            Severity::High => 3,
+            Severity::Medium => 2,
+            Severity::Low => 1,
+            Severity::Info => 0,
+        }
+    }
+}
+
 impl std::fmt::Display for Severity {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -405,7 +939,7 @@ impl std::fmt::Display for Severity {
 }
 
 /// Risk level assessment
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum RiskLevel {
     /// Low risk
@@ -416,6 +950,17 @@ pub enum RiskLevel {
     High,
 }
 
+impl RiskLevel {
+    /// Numeric rank for sorting; higher is riskier
+    pub fn rank(&self) -> u8 {
+        match self {
+            RiskLevel::High => 3,
+            RiskLevel::Medium => 2,
+            RiskLevel::Low => 1,
+        }
+    }
+}
+
 impl std::fmt::Display for RiskLevel {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -427,7 +972,7 @@ impl std::fmt::Display for RiskLevel {
 }
 
 /// Evidence for a finding
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(tag = "type", rename_all = "snake_case")]
 pub enum Evidence {
     /// Evidence at a specific line in a file
@@ -457,8 +1002,111 @@ pub enum Evidence {
     },
 }
 
-/// Section 5: Master TODO Backlog
+/// Current version of the baseline file format
+pub const BASELINE_VERSION: u32 = 1;
+
+/// Findings accepted on a legacy codebase, written by
+/// `hqe scan --write-baseline` and read back with `--baseline`
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Baseline {
+    /// Baseline format version
+    pub version: u32,
+    /// When the baseline was written
+    pub created_at: DateTime<Utc>,
+    /// Accepted findings, sorted by fingerprint
+    pub findings: Vec<BaselineEntry>,
+}
+
+/// One accepted finding in a baseline
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct BaselineEntry {
+    /// Fingerprint from [`Finding::fingerprint`]
+    pub fingerprint: String,
+    /// Title of the finding, kept so the file can be reviewed by hand
+    pub title: String,
+    /// File the finding pointed at
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+}
+
+impl BaselineEntry {
+    /// Entry accepting the given finding
+    pub fn from_finding(finding: &Finding) -> Self {
+        Self {
+            fingerprint: finding.fingerprint(),
+            title: finding.title.clone(),
+            file: finding.file().map(str::to_string),
+        }
+    }
+}
+
+impl Baseline {
+    /// Baseline accepting every finding of a report, including ones that an
+    /// earlier baseline already suppressed
+    pub fn from_report(report: &HqeReport) -> Self {
+        let entries: BTreeMap<String, BaselineEntry> = report
+            .deep_scan_results
+            .iter()
+            .chain(&report.suppressed_findings)
+            .map(|finding| {
+                let entry = BaselineEntry::from_finding(finding);
+                (entry.fingerprint.clone(), entry)
+            })
+            .collect();
+
+        Self {
+            version: BASELINE_VERSION,
+            created_at: Utc::now(),
+            findings: entries.into_values().collect(),
+        }
+    }
+
+    /// Whether a finding with this fingerprint was accepted
+    pub fn contains(&self, fingerprint: &str) -> bool {
+        self.findings
+            .iter()
+            .any(|entry| entry.fingerprint == fingerprint)
+    }
+
+    /// Read a baseline file
+    pub fn load(path: impl AsRef<std::path::Path>) -> Result<Self, crate::HqeError> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let baseline: Self = serde_json::from_str(&content).map_err(|e| {
+            crate::HqeError::Config(format!("Invalid baseline {}: {e}", path.display()))
+        })?;
+        if baseline.version != BASELINE_VERSION {
+            return Err(crate::HqeError::Config(format!(
+                "Unsupported baseline version {} in {} (expected {})",
+                baseline.version,
+                path.display(),
+                BASELINE_VERSION
+            )));
+        }
+        Ok(baseline)
+    }
+
+    /// Write the baseline as pretty-printed JSON
+    pub fn save(&self, path: impl AsRef<std::path::Path>) -> Result<(), crate::HqeError> {
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| crate::HqeError::Serialization(e.to_string()))?;
+        std::fs::write(path, json)?;
+        Ok(())
+    }
+}
+
+/// Result of applying a baseline to a scan
+#[derive(Debug, Clone, Default, Serialize, Deserialize, JsonSchema)]
+pub struct BaselineStatus {
+    /// Number of findings suppressed by the baseline
+    pub suppressed: usize,
+    /// Accepted findings that no longer occur; prune them from the baseline
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stale: Vec<BaselineEntry>,
+}
+
+/// Section 5: Master TODO Backlog
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 pub struct TodoItem {
     /// Unique identifier for this TODO item
     pub id: String,
@@ -483,7 +1131,7 @@ pub struct TodoItem {
 }
 
 /// Category for TODO items
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "SCREAMING_SNAKE_CASE")]
 pub enum TodoCategory {
     /// Bootstrap/setup issues
@@ -656,12 +1304,58 @@ pub struct ScanConfig {
     /// Whether to enable parallel tool calls (provider-specific).
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub parallel_tool_calls: Option<bool>,
+    /// Maximum number of per-file LLM analysis requests in flight at once.
+    #[serde(default = "default_scan_concurrency")]
+    pub concurrency: usize,
+    /// TOML or JSON file with custom secret detection rules
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub secret_rules_file: Option<std::path::PathBuf>,
+    /// Fail the scan if any secret is still detected after redaction
+    #[serde(default)]
+    pub strict_redaction: bool,
+    /// Also replace personal data (emails, IP addresses, phone and card
+    /// numbers) with placeholders before content is sent to the LLM
+    #[serde(default)]
+    pub redact_pii: bool,
+    /// Run detected linters (clippy, eslint, ruff) and include their diagnostics
+    #[serde(default)]
+    pub run_external_tools: bool,
+    /// JSON advisory feed matched against lockfile versions (bundled list if unset)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub advisory_file: Option<std::path::PathBuf>,
+    /// Look up dependency versions in the OSV vulnerability database (network call)
+    #[serde(default)]
+    pub osv_enabled: bool,
+    /// Stop sending LLM requests once their estimated cost reaches this many USD
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_usd: Option<f64>,
+    /// Stop sending LLM requests once they have used this many tokens
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub budget_tokens: Option<u64>,
+    /// Only check files changed since this git ref (e.g. `origin/main`) and
+    /// untracked files; the directory summary still covers the whole repository
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub changed_since: Option<String>,
+}
+
+impl ScanConfig {
+    /// The scan's token and cost budget
+    pub fn budget(&self) -> ScanBudget {
+        ScanBudget {
+            usd: self.budget_usd,
+            tokens: self.budget_tokens,
+        }
+    }
 }
 
 fn default_scan_timeout_seconds() -> u64 {
     120
 }
 
+fn default_scan_concurrency() -> usize {
+    4
+}
+
 impl Default for ScanConfig {
     fn default() -> Self {
         Self {
@@ -672,6 +1366,16 @@ impl Default for ScanConfig {
             timeout_seconds: default_scan_timeout_seconds(), // 2 minute default for LLM operations
             venice_parameters: None,
             parallel_tool_calls: None,
+            concurrency: default_scan_concurrency(),
+            secret_rules_file: None,
+            strict_redaction: false,
+            redact_pii: false,
+            run_external_tools: false,
+            advisory_file: None,
+            osv_enabled: false,
+            budget_usd: None,
+            budget_tokens: None,
+            changed_since: None,
         }
     }
 }
@@ -689,6 +1393,12 @@ pub struct IngestedFile {
     pub language: Option<String>,
     /// Whether this is an entry point
     pub is_entrypoint: bool,
+    /// First line of the content when it is a chunk of a larger file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    /// Last line of the content when it is a chunk of a larger file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
 }
 
 /// Summary of redactions performed
@@ -698,6 +1408,28 @@ pub struct RedactionSummary {
     pub total_redactions: usize,
     /// Redactions grouped by type
     pub by_type: HashMap<String, usize>,
+    /// Secrets still detected in the content after redaction
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub leak_warnings: Vec<LeakWarning>,
+    /// Secret findings silenced by the repository's allowlist
+    #[serde(default)]
+    pub suppressed_findings: usize,
+    /// Personal data redacted, by type (`EMAIL`, `IPV4`, ...), when PII
+    /// redaction is enabled; not included in `total_redactions`
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub pii_by_type: HashMap<String, usize>,
+}
+
+/// A secret that was still detected after redaction
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LeakWarning {
+    /// File the redacted content came from
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Name of the detector rule that matched
+    pub rule: String,
+    /// Line (1-based) of the redacted content where the match starts
+    pub line: usize,
 }
 
 /// Evidence bundle sent to LLM
@@ -724,6 +1456,9 @@ pub struct RepoSummary {
     pub tech_stack: TechStack,
     /// Application entry points
     pub entrypoints: Vec<Entrypoint>,
+    /// File and line counts per language
+    #[serde(default)]
+    pub code_stats: CodeStats,
 }
 
 /// A code snippet from a file
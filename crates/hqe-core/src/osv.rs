@@ -0,0 +1,607 @@
+//! Known-vulnerability lookup against the [OSV](https://osv.dev) database.
+//!
+//! Dependencies with an exact version are sent to the OSV batch API in one
+//! request per thousand packages; every advisory it names is then fetched for
+//! its severity and fixed versions. Hits become `VULNERABLE_DEPENDENCY`
+//! findings on the manifest or lockfile the package came from.
+//!
+//! This is a network call, so it only runs when [`ScanConfig::osv_enabled`]
+//! is set, and only package names and versions leave the machine. Lookups are
+//! cached per package version in [`LocalDb`] for [`OSV_CACHE_TTL`]. The HTTP
+//! client needs the `osv` feature; without it every lookup fails, which the
+//! scan reports as a warning.
+//!
+//! [`ScanConfig::osv_enabled`]: crate::models::ScanConfig::osv_enabled
+//! [`OSV_CACHE_TTL`]: crate::persistence::OSV_CACHE_TTL
+
+use crate::advisories::{compare_versions, Ecosystem};
+use crate::models::{Dependency, LocalFinding, Severity};
+use crate::persistence::LocalDb;
+use crate::HqeError;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use tracing::{debug, warn};
+
+/// Public OSV API
+pub const OSV_API_URL: &str = "https://api.osv.dev";
+
+/// Upper bound for one OSV request
+#[cfg(feature = "osv")]
+const OSV_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Most queries the batch endpoint accepts in one request
+const MAX_BATCH_QUERIES: usize = 1000;
+
+/// Advisory details fetched at once
+const DETAIL_CONCURRENCY: usize = 8;
+
+/// A package version in one ecosystem
+type Package<'a> = (Ecosystem, &'a str, &'a str);
+
+/// An advisory affecting one package version, as cached in [`LocalDb`]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OsvVulnerability {
+    /// OSV identifier (`GHSA-…`, `RUSTSEC-…`, `PYSEC-…`)
+    pub id: String,
+    /// Other identifiers of the same advisory, such as CVEs
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    /// One-line summary
+    pub summary: String,
+    /// Severity from the CVSS v3 vector, else the database's own rating
+    pub severity: Severity,
+    /// Lowest fixed version above the affected one, if any
+    pub fixed: Option<String>,
+}
+
+impl OsvVulnerability {
+    /// Read an OSV record (`GET /v1/vulns/{id}`) as it applies to `version`
+    /// of a package
+    pub fn from_record(record: &Value, ecosystem: Ecosystem, name: &str, version: &str) -> Self {
+        let id = record["id"].as_str().unwrap_or_default().to_string();
+        let affected: Vec<&Value> = record["affected"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter(|a| {
+                a["package"]["ecosystem"].as_str() == Some(ecosystem.osv_name())
+                    && a["package"]["name"]
+                        .as_str()
+                        .is_some_and(|n| n.eq_ignore_ascii_case(name))
+            })
+            .collect();
+
+        let cvss = std::iter::once(record)
+            .chain(affected.iter().copied())
+            .flat_map(|v| v["severity"].as_array().into_iter().flatten())
+            .filter(|s| s["type"].as_str() == Some("CVSS_V3"))
+            .find_map(|s| s["score"].as_str().and_then(cvss_v3_base_score));
+        let severity = match cvss {
+            Some(score) => severity_from_score(score),
+            None => record["database_specific"]["severity"]
+                .as_str()
+                .and_then(severity_from_rating)
+                .unwrap_or(Severity::Medium),
+        };
+
+        let fixed = affected
+            .iter()
+            .flat_map(|a| a["ranges"].as_array().into_iter().flatten())
+            .flat_map(|r| r["events"].as_array().into_iter().flatten())
+            .filter_map(|e| e["fixed"].as_str())
+            .filter(|fixed| compare_versions(fixed, version).is_gt())
+            .min_by(|a, b| compare_versions(a, b))
+            .map(str::to_string);
+
+        let summary = record["summary"]
+            .as_str()
+            .or_else(|| record["details"].as_str().and_then(|d| d.lines().next()))
+            .filter(|s| !s.trim().is_empty())
+            .unwrap_or("no summary available")
+            .trim()
+            .to_string();
+
+        Self {
+            aliases: record["aliases"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter_map(|a| a.as_str().map(str::to_string))
+                .collect(),
+            id,
+            summary,
+            severity,
+            fixed,
+        }
+    }
+
+    /// Whether `finding` already reports this advisory, under any of its ids
+    fn reported_by(&self, finding: &LocalFinding) -> bool {
+        std::iter::once(&self.id)
+            .chain(&self.aliases)
+            .any(|id| finding.description.contains(id.as_str()))
+    }
+
+    fn finding(&self, dependency: &Dependency, version: &str) -> LocalFinding {
+        let name = &dependency.name;
+        let id = if self.aliases.is_empty() {
+            self.id.clone()
+        } else {
+            format!("{} ({})", self.id, self.aliases.join(", "))
+        };
+        LocalFinding {
+            finding_type: "VULNERABLE_DEPENDENCY".to_string(),
+            description: format!(
+                "{} {} is affected by {}: {}",
+                name, version, id, self.summary
+            ),
+            file_path: dependency.evidence.clone(),
+            severity: self.severity.clone(),
+            line_number: None,
+            snippet: Some(format!("{} {}", name, version)),
+            recommendation: Some(match &self.fixed {
+                Some(fixed) => format!("Upgrade {} to {} or later ({})", name, fixed, self.id),
+                None => format!(
+                    "No fixed version of {} is available; replace it or assess the exposure ({})",
+                    name, self.id
+                ),
+            }),
+        }
+    }
+}
+
+/// CVSS v3.0/v3.1 base score of a vector such as
+/// `CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H`
+pub fn cvss_v3_base_score(vector: &str) -> Option<f64> {
+    let metrics = vector
+        .strip_prefix("CVSS:3.1/")
+        .or_else(|| vector.strip_prefix("CVSS:3.0/"))?;
+    let metrics: HashMap<&str, &str> = metrics
+        .split('/')
+        .filter_map(|m| m.split_once(':'))
+        .collect();
+    let metric = |name: &str| metrics.get(name).copied();
+
+    let changed = match metric("S")? {
+        "U" => false,
+        "C" => true,
+        _ => return None,
+    };
+    let attack_vector = match metric("AV")? {
+        "N" => 0.85,
+        "A" => 0.62,
+        "L" => 0.55,
+        "P" => 0.2,
+        _ => return None,
+    };
+    let complexity = match metric("AC")? {
+        "L" => 0.77,
+        "H" => 0.44,
+        _ => return None,
+    };
+    let privileges = match (metric("PR")?, changed) {
+        ("N", _) => 0.85,
+        ("L", false) => 0.62,
+        ("L", true) => 0.68,
+        ("H", false) => 0.27,
+        ("H", true) => 0.5,
+        _ => return None,
+    };
+    let interaction = match metric("UI")? {
+        "N" => 0.85,
+        "R" => 0.62,
+        _ => return None,
+    };
+    let impact_of = |name: &str| match metric(name)? {
+        "H" => Some(0.56),
+        "L" => Some(0.22),
+        "N" => Some(0.0),
+        _ => None,
+    };
+    let iss = 1.0 - (1.0 - impact_of("C")?) * (1.0 - impact_of("I")?) * (1.0 - impact_of("A")?);
+
+    let impact = if changed {
+        7.52 * (iss - 0.029) - 3.25 * (iss - 0.02f64).powi(15)
+    } else {
+        6.42 * iss
+    };
+    if impact <= 0.0 {
+        return Some(0.0);
+    }
+    let exploitability = 8.22 * attack_vector * complexity * privileges * interaction;
+    let score = if changed {
+        1.08 * (impact + exploitability)
+    } else {
+        impact + exploitability
+    };
+    Some(round_up(score.min(10.0)))
+}
+
+/// CVSS v3.1 `Roundup`: the smallest one-decimal number not below `value`
+fn round_up(value: f64) -> f64 {
+    let scaled = (value * 100_000.0).round() as i64;
+    if scaled % 10_000 == 0 {
+        scaled as f64 / 100_000.0
+    } else {
+        (scaled / 10_000 + 1) as f64 / 10.0
+    }
+}
+
+/// CVSS qualitative rating of a base score
+pub fn severity_from_score(score: f64) -> Severity {
+    match score {
+        s if s >= 9.0 => Severity::Critical,
+        s if s >= 7.0 => Severity::High,
+        s if s >= 4.0 => Severity::Medium,
+        s if s > 0.0 => Severity::Low,
+        _ => Severity::Info,
+    }
+}
+
+/// Severity from a database's own rating (`database_specific.severity`)
+fn severity_from_rating(rating: &str) -> Option<Severity> {
+    match rating.to_ascii_uppercase().as_str() {
+        "CRITICAL" => Some(Severity::Critical),
+        "HIGH" => Some(Severity::High),
+        "MODERATE" | "MEDIUM" => Some(Severity::Medium),
+        "LOW" => Some(Severity::Low),
+        _ => None,
+    }
+}
+
+/// Client for the OSV API
+#[derive(Debug, Clone)]
+pub struct OsvClient {
+    base_url: String,
+    cache: Option<LocalDb>,
+    #[cfg(feature = "osv")]
+    http: reqwest::Client,
+}
+
+impl Default for OsvClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OsvClient {
+    /// Client for the public OSV API, without a cache
+    pub fn new() -> Self {
+        Self {
+            base_url: OSV_API_URL.to_string(),
+            cache: None,
+            #[cfg(feature = "osv")]
+            http: reqwest::Client::builder()
+                .timeout(OSV_TIMEOUT)
+                .build()
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Send requests to another OSV-compatible server
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into().trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Cache lookups per package version in `db`
+    pub fn with_cache(mut self, db: LocalDb) -> Self {
+        self.cache = Some(db);
+        self
+    }
+
+    /// Findings for the dependencies with known vulnerabilities.
+    ///
+    /// Dependencies without an exact version cannot be looked up and are
+    /// skipped. Advisories `existing` already reports for the same file and
+    /// package version, under their id or an alias, are left out. Fails when
+    /// OSV cannot be reached; cached lookups are not enough to report a
+    /// partial result.
+    pub async fn check(
+        &self,
+        dependencies: &[Dependency],
+        existing: &[LocalFinding],
+    ) -> crate::Result<Vec<LocalFinding>> {
+        let mut packages: Vec<Package<'_>> = dependencies
+            .iter()
+            .filter_map(|d| Some((d.ecosystem, d.name.as_str(), d.version.as_deref()?)))
+            .collect();
+        packages.sort_by_key(|(ecosystem, name, version)| (ecosystem.osv_name(), *name, *version));
+        packages.dedup();
+
+        let mut known = HashMap::new();
+        let mut misses = Vec::new();
+        for package in packages {
+            match self.cached(package) {
+                Some(vulns) => {
+                    known.insert(package, vulns);
+                }
+                None => misses.push(package),
+            }
+        }
+        debug!(
+            "OSV lookup: {} cached, {} to query",
+            known.len(),
+            misses.len()
+        );
+        if !misses.is_empty() {
+            let found = self.lookup(&misses).await?;
+            for (package, vulns) in misses.into_iter().zip(found) {
+                self.store(package, &vulns);
+                known.insert(package, vulns);
+            }
+        }
+
+        let mut findings = Vec::new();
+        for dependency in dependencies {
+            let Some(version) = dependency.version.as_deref() else {
+                continue;
+            };
+            let snippet = format!("{} {}", dependency.name, version);
+            let vulns = known
+                .get(&(dependency.ecosystem, dependency.name.as_str(), version))
+                .into_iter()
+                .flatten();
+            for vuln in vulns {
+                let reported = existing.iter().any(|f| {
+                    f.file_path == dependency.evidence
+                        && f.snippet.as_deref() == Some(snippet.as_str())
+                        && vuln.reported_by(f)
+                });
+                if !reported {
+                    findings.push(vuln.finding(dependency, version));
+                }
+            }
+        }
+        Ok(findings)
+    }
+
+    fn cached(&self, (ecosystem, name, version): Package<'_>) -> Option<Vec<OsvVulnerability>> {
+        let json =
+            match self
+                .cache
+                .as_ref()?
+                .get_osv_vulnerabilities(ecosystem.osv_name(), name, version)
+            {
+                Ok(json) => json?,
+                Err(e) => {
+                    warn!("Failed to read OSV cache: {}", e);
+                    return None;
+                }
+            };
+        serde_json::from_str(&json)
+            .map_err(|e| warn!("Ignoring unreadable OSV cache entry: {}", e))
+            .ok()
+    }
+
+    fn store(&self, (ecosystem, name, version): Package<'_>, vulns: &[OsvVulnerability]) {
+        let Some(db) = &self.cache else {
+            return;
+        };
+        let stored = serde_json::to_string(vulns)
+            .map_err(|e| e.to_string())
+            .and_then(|json| {
+                db.put_osv_vulnerabilities(ecosystem.osv_name(), name, version, &json)
+                    .map_err(|e| e.to_string())
+            });
+        if let Err(e) = stored {
+            warn!("Failed to write OSV cache: {}", e);
+        }
+    }
+
+    /// Vulnerabilities of each package, in order
+    async fn lookup(&self, packages: &[Package<'_>]) -> crate::Result<Vec<Vec<OsvVulnerability>>> {
+        let mut ids = Vec::with_capacity(packages.len());
+        for chunk in packages.chunks(MAX_BATCH_QUERIES) {
+            let queries: Vec<Value> = chunk
+                .iter()
+                .map(|(ecosystem, name, version)| {
+                    json!({
+                        "package": {"name": name, "ecosystem": ecosystem.osv_name()},
+                        "version": version,
+                    })
+                })
+                .collect();
+            let response = self
+                .request("/v1/querybatch", Some(json!({ "queries": queries })))
+                .await?;
+            let results = response["results"]
+                .as_array()
+                .filter(|results| results.len() == chunk.len())
+                .ok_or_else(|| {
+                    HqeError::Scan("OSV batch response does not match the query".to_string())
+                })?;
+            for result in results {
+                ids.push(
+                    result["vulns"]
+                        .as_array()
+                        .into_iter()
+                        .flatten()
+                        .filter_map(|v| v["id"].as_str().map(str::to_string))
+                        .collect::<Vec<_>>(),
+                );
+            }
+        }
+
+        let mut unique: Vec<&str> = ids.iter().flatten().map(String::as_str).collect();
+        unique.sort_unstable();
+        unique.dedup();
+        let records: HashMap<&str, Value> = stream::iter(unique)
+            .map(|id| async move {
+                let record = self.request(&format!("/v1/vulns/{id}"), None).await?;
+                Ok::<_, HqeError>((id, record))
+            })
+            .buffer_unordered(DETAIL_CONCURRENCY)
+            .try_collect()
+            .await?;
+
+        Ok(packages
+            .iter()
+            .zip(&ids)
+            .map(|((ecosystem, name, version), ids)| {
+                ids.iter()
+                    .filter_map(|id| records.get(id.as_str()))
+                    .map(|record| OsvVulnerability::from_record(record, *ecosystem, name, version))
+                    .collect()
+            })
+            .collect())
+    }
+
+    /// POST `body` to, or GET without one, `{base_url}{path}`
+    #[cfg(feature = "osv")]
+    async fn request(&self, path: &str, body: Option<Value>) -> crate::Result<Value> {
+        let url = format!("{}{}", self.base_url, path);
+        let request = match &body {
+            Some(body) => self.http.post(&url).json(body),
+            None => self.http.get(&url),
+        };
+        let response = request
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| HqeError::Scan(format!("OSV request to {} failed: {}", url, e)))?;
+        response.json().await.map_err(|e| {
+            HqeError::Serialization(format!("Invalid OSV response from {}: {}", url, e))
+        })
+    }
+
+    #[cfg(not(feature = "osv"))]
+    async fn request(&self, _path: &str, _body: Option<Value>) -> crate::Result<Value> {
+        Err(HqeError::Config(
+            "OSV lookups require hqe-core's 'osv' feature".to_string(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cvss_v3_base_scores() {
+        let score = |v| cvss_v3_base_score(v).unwrap();
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:H/I:H/A:H"), 9.8);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:R/S:C/C:L/I:L/A:N"), 6.1);
+        assert_eq!(score("CVSS:3.0/AV:L/AC:H/PR:H/UI:R/S:U/C:L/I:N/A:N"), 1.8);
+        assert_eq!(score("CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:N"), 0.0);
+        assert_eq!(cvss_v3_base_score("CVSS:4.0/AV:N/AC:L"), None);
+        assert_eq!(cvss_v3_base_score("CVSS:3.1/AV:N/AC:L"), None);
+
+        assert!(matches!(severity_from_score(9.8), Severity::Critical));
+        assert!(matches!(severity_from_score(6.1), Severity::Medium));
+        assert!(matches!(severity_from_score(0.0), Severity::Info));
+    }
+
+    fn h2_record() -> Value {
+        json!({
+            "id": "GHSA-8r5v-vm4m-4g25",
+            "aliases": ["RUSTSEC-2024-0003"],
+            "summary": "Resource exhaustion vulnerability in h2",
+            "severity": [{"type": "CVSS_V3", "score": "CVSS:3.1/AV:N/AC:L/PR:N/UI:N/S:U/C:N/I:N/A:H"}],
+            "affected": [{
+                "package": {"ecosystem": "crates.io", "name": "h2"},
+                "ranges": [{"type": "SEMVER", "events": [
+                    {"introduced": "0"}, {"fixed": "0.3.24"},
+                    {"introduced": "0.4.0"}, {"fixed": "0.4.2"}
+                ]}]
+            }]
+        })
+    }
+
+    #[test]
+    fn test_records_map_severity_and_fixed_version() {
+        let vuln = OsvVulnerability::from_record(&h2_record(), Ecosystem::Cargo, "h2", "0.3.20");
+        assert_eq!(vuln.id, "GHSA-8r5v-vm4m-4g25");
+        assert!(matches!(vuln.severity, Severity::High));
+        assert_eq!(vuln.fixed.as_deref(), Some("0.3.24"));
+
+        let later = OsvVulnerability::from_record(&h2_record(), Ecosystem::Cargo, "h2", "0.4.1");
+        assert_eq!(later.fixed.as_deref(), Some("0.4.2"));
+
+        let rated = json!({
+            "id": "GHSA-xxxx",
+            "details": "Prototype pollution\n\nMore text",
+            "database_specific": {"severity": "MODERATE"}
+        });
+        let vuln = OsvVulnerability::from_record(&rated, Ecosystem::Npm, "lodash", "4.17.0");
+        assert!(matches!(vuln.severity, Severity::Medium));
+        assert_eq!(vuln.summary, "Prototype pollution");
+        assert_eq!(vuln.fixed, None);
+    }
+
+    fn h2_dependency() -> Dependency {
+        Dependency {
+            name: "h2".to_string(),
+            ecosystem: Ecosystem::Cargo,
+            version: Some("0.3.20".to_string()),
+            requirement: None,
+            evidence: "Cargo.lock".to_string(),
+        }
+    }
+
+    #[cfg(feature = "osv")]
+    #[tokio::test]
+    async fn test_check_queries_osv_once_per_day() {
+        let mut server = mockito::Server::new_async().await;
+        let batch = server
+            .mock("POST", "/v1/querybatch")
+            .match_body(mockito::Matcher::PartialJson(json!({
+                "queries": [{"package": {"name": "h2", "ecosystem": "crates.io"}, "version": "0.3.20"}]
+            })))
+            .with_body(r#"{"results": [{"vulns": [{"id": "GHSA-8r5v-vm4m-4g25"}]}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let detail = server
+            .mock("GET", "/v1/vulns/GHSA-8r5v-vm4m-4g25")
+            .with_body(h2_record().to_string())
+            .expect(1)
+            .create_async()
+            .await;
+
+        let client = OsvClient::new()
+            .with_base_url(server.url())
+            .with_cache(LocalDb::in_memory().unwrap());
+        let unpinned = Dependency {
+            version: None,
+            ..h2_dependency()
+        };
+        let findings = client
+            .check(&[h2_dependency(), unpinned.clone()], &[])
+            .await
+            .unwrap();
+        assert_eq!(findings.len(), 1);
+        assert_eq!(findings[0].file_path, "Cargo.lock");
+        assert!(findings[0].description.contains("RUSTSEC-2024-0003"));
+        assert_eq!(
+            findings[0].recommendation.as_deref(),
+            Some("Upgrade h2 to 0.3.24 or later (GHSA-8r5v-vm4m-4g25)")
+        );
+
+        // Served from the cache, and skipped when already reported under an alias
+        let again = client.check(&[h2_dependency()], &[]).await.unwrap();
+        assert_eq!(again.len(), 1);
+        let bundled = LocalFinding {
+            description: "h2 0.3.20 is affected by RUSTSEC-2024-0003: ...".to_string(),
+            ..findings[0].clone()
+        };
+        assert!(client
+            .check(&[h2_dependency()], &[bundled])
+            .await
+            .unwrap()
+            .is_empty());
+        batch.assert_async().await;
+        detail.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_osv_is_an_error() {
+        // Nothing listens on port 9 (discard) in the test environment
+        let client = OsvClient::new().with_base_url("http://127.0.0.1:9");
+        assert!(client.check(&[h2_dependency()], &[]).await.is_err());
+        // Nothing to look up, nothing to fail
+        assert!(client.check(&[], &[]).await.unwrap().is_empty());
+    }
+}
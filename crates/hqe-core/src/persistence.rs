@@ -2,24 +2,146 @@
 //!
 //! Uses SQLite to store:
 //! - Request/Response Cache (hashed by input)
+//! - Per-file scan analysis cache (keyed by model, prompt version and content hash)
+//! - OSV vulnerability lookups (keyed by package and version, kept for [`OSV_CACHE_TTL`])
 //! - Session History (audit logs)
+//!
+//! Both caches are bounded by [`CacheLimits`]: entries past the maximum age are
+//! never returned and get dropped, and above the size or entry limit the least
+//! recently used entries are evicted. Sweeps run on init, periodically after
+//! inserts and on [`LocalDb::prune`].
 
+use chrono::{DateTime, NaiveDateTime, Utc};
 use rusqlite::{params, Connection, Result};
 use sha2::{Digest, Sha256};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, Mutex};
-use tracing::{debug, info};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Current schema version, stored in `PRAGMA user_version`
+const SCHEMA_VERSION: i32 = 2;
+
+/// How long an OSV lookup of a package version is served from the cache
+pub const OSV_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Every cache entry with its model, key, size (prompt + response bytes) and timestamps
+const CACHE_ENTRIES_SQL: &str = "
+    SELECT 'request_cache' AS tbl, rowid AS id, model, hash AS key,
+           length(CAST(prompt_json AS BLOB)) + length(CAST(response_json AS BLOB)) AS bytes,
+           created_at, last_accessed_at
+    FROM request_cache
+    UNION ALL
+    SELECT 'file_analysis_cache', rowid, model, content_hash, length(CAST(result_json AS BLOB)),
+           created_at, last_accessed_at
+    FROM file_analysis_cache";
+
+/// Condition on `model` (?1) and `created_at` age (?2) shared by listing and clearing
+const CACHE_FILTER_SQL: &str =
+    "(?1 IS NULL OR model = ?1) AND (?2 IS NULL OR created_at < datetime('now', ?2))";
+
+/// Size and age limits for the response and file analysis caches
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CacheLimits {
+    /// Maximum combined size of cached entries in bytes (`None` for unbounded)
+    pub max_total_bytes: Option<u64>,
+    /// Maximum number of cached entries across both caches (`None` for unbounded)
+    pub max_entries: Option<u64>,
+    /// Entries created longer ago than this are treated as misses and
+    /// evicted (`None` keeps them)
+    pub max_age: Option<Duration>,
+    /// Run an eviction sweep after this many inserts (0 sweeps only on init)
+    pub sweep_every: u64,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            max_total_bytes: Some(256 * 1024 * 1024),
+            max_entries: Some(100_000),
+            max_age: Some(Duration::from_secs(30 * 24 * 60 * 60)),
+            sweep_every: 100,
+        }
+    }
+}
+
+/// Summary of the cache contents
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of cached entries
+    pub entries: u64,
+    /// Combined size of cached entries in bytes
+    pub total_bytes: u64,
+    /// Creation time of the oldest entry
+    pub oldest_entry: Option<DateTime<Utc>>,
+}
+
+/// Selects cache entries for [`LocalDb::list_cached`] and [`LocalDb::clear_cached`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CacheFilter {
+    /// Only entries produced by this model
+    pub model: Option<String>,
+    /// Only entries created longer ago than this
+    pub older_than: Option<Duration>,
+}
+
+impl CacheFilter {
+    fn age_modifier(&self) -> Option<String> {
+        self.older_than
+            .map(|age| format!("-{} seconds", age.as_secs()))
+    }
+}
+
+/// Which cache an entry lives in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheKind {
+    /// A chat completion response
+    Response,
+    /// A per-file scan analysis
+    FileAnalysis,
+}
+
+/// Metadata of one cache entry; never includes the cached prompt or response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CachedEntry {
+    /// Cache the entry lives in
+    pub kind: CacheKind,
+    /// Model that produced the entry
+    pub model: String,
+    /// Request hash for responses, content hash for file analyses
+    pub key: String,
+    /// Size of the stored prompt and response in bytes
+    pub size_bytes: u64,
+    /// When the entry was stored
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the entry was last served
+    pub last_accessed_at: Option<DateTime<Utc>>,
+}
 
 /// Local database manager
 #[derive(Debug, Clone)]
 pub struct LocalDb {
     conn: Arc<Mutex<Connection>>,
+    limits: CacheLimits,
+    inserts: Arc<AtomicU64>,
 }
 
 impl LocalDb {
-    /// Initialize the local database
+    /// Initialize the local database with the default [`CacheLimits`]
     pub fn init() -> anyhow::Result<Self> {
-        let db_path = get_db_path()?;
+        Self::init_with_limits(CacheLimits::default())
+    }
+
+    /// Initialize the local database, evicting cache entries outside `limits`
+    pub fn init_with_limits(limits: CacheLimits) -> anyhow::Result<Self> {
+        Self::open(get_db_path()?, limits)
+    }
+
+    /// Open the database at `path` instead of the default location, creating
+    /// it if needed
+    pub fn open(path: impl AsRef<Path>, limits: CacheLimits) -> anyhow::Result<Self> {
+        let db_path = path.as_ref();
 
         info!("Initializing local database at {:?}", db_path);
 
@@ -27,16 +149,39 @@ impl LocalDb {
             std::fs::create_dir_all(parent)?;
         }
 
-        let conn = Connection::open(&db_path)?;
+        let conn = Connection::open(db_path)?;
 
-        // Enable WAL mode for better concurrency
-        conn.execute("PRAGMA journal_mode=WAL;", [])?;
+        // Enable WAL mode for better concurrency; the pragma reports the new
+        // mode as a row, which `execute` would reject
+        conn.query_row("PRAGMA journal_mode=WAL;", [], |row| {
+            row.get::<_, String>(0)
+        })?;
+
+        Ok(Self::from_connection(conn, limits)?)
+    }
 
+    /// Open a throwaway in-memory database
+    #[cfg(test)]
+    pub(crate) fn in_memory() -> anyhow::Result<Self> {
+        Ok(Self::from_connection(
+            Connection::open_in_memory()?,
+            CacheLimits::default(),
+        )?)
+    }
+
+    fn from_connection(conn: Connection, limits: CacheLimits) -> Result<Self> {
         // Initialize tables
         Self::init_tables(&conn)?;
 
+        let removed = evict(&conn, &limits)?;
+        if removed > 0 {
+            info!("Evicted {} cache entries on startup", removed);
+        }
+
         Ok(Self {
             conn: Arc::new(Mutex::new(conn)),
+            limits,
+            inserts: Arc::new(AtomicU64::new(0)),
         })
     }
 
@@ -85,9 +230,55 @@ impl LocalDb {
             [],
         )?;
 
+        Self::migrate(conn)
+    }
+
+    /// Apply schema migrations newer than the database's `user_version`
+    fn migrate(conn: &Connection) -> Result<()> {
+        let version: i32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+
+        if version < 1 {
+            info!("Migrating local database to schema version 1");
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS file_analysis_cache (
+                    model TEXT NOT NULL,
+                    prompt_version TEXT NOT NULL,
+                    content_hash TEXT NOT NULL,
+                    result_json TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    last_accessed_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    PRIMARY KEY (model, prompt_version, content_hash)
+                )",
+                [],
+            )?;
+        }
+
+        if version < 2 {
+            info!("Migrating local database to schema version 2");
+            conn.execute(
+                "CREATE TABLE IF NOT EXISTS osv_cache (
+                    ecosystem TEXT NOT NULL,
+                    name TEXT NOT NULL,
+                    version TEXT NOT NULL,
+                    vulns_json TEXT NOT NULL,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+                    PRIMARY KEY (ecosystem, name, version)
+                )",
+                [],
+            )?;
+        }
+
+        if version < SCHEMA_VERSION {
+            conn.execute_batch(&format!("PRAGMA user_version = {SCHEMA_VERSION};"))?;
+        }
         Ok(())
     }
 
+    /// BLAKE3 hex digest of file content, used to key the file analysis cache.
+    pub fn content_hash(content: &[u8]) -> String {
+        blake3::hash(content).to_hex().to_string()
+    }
+
     /// Calculate a stable hash for a request to be used as a cache key.
     ///
     /// The hash is based on the model name, messages, and parameters.
@@ -103,15 +294,19 @@ impl LocalDb {
 
     /// Retrieve a cached response from the database.
     ///
-    /// Returns `Ok(Some(response))` if found, `Ok(None)` if not, or an error if the query fails.
+    /// Returns `Ok(Some(response))` if found, `Ok(None)` if not or if the entry
+    /// is older than [`CacheLimits::max_age`], or an error if the query fails.
     pub fn get_cached_response(&self, hash: &str) -> Result<Option<String>> {
         let conn = self
             .conn
             .lock()
             .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
-        let mut stmt = conn.prepare("SELECT response_json FROM request_cache WHERE hash = ?1")?;
+        let mut stmt = conn.prepare(
+            "SELECT response_json FROM request_cache
+             WHERE hash = ?1 AND (?2 IS NULL OR created_at >= datetime('now', ?2))",
+        )?;
 
-        let mut rows = stmt.query(params![hash])?;
+        let mut rows = stmt.query(params![hash, self.expiry_modifier()])?;
 
         if let Some(row) = rows.next()? {
             // Update last accessed time asynchronously (fire and forget pattern ideally, but sync here for safety)
@@ -145,9 +340,256 @@ impl LocalDb {
             params![hash, model, prompt, response],
         )?;
         debug!("Cached response for hash {}", hash);
+        self.sweep_after_insert(&conn);
+        Ok(())
+    }
+
+    /// Retrieve the cached analysis of a file.
+    ///
+    /// Results are keyed by model, analysis prompt version and the
+    /// [`LocalDb::content_hash`] of the file, so they survive profile renames and
+    /// unrelated request changes. Expired entries are misses, as in
+    /// [`LocalDb::get_cached_response`].
+    pub fn get_file_analysis(
+        &self,
+        model: &str,
+        prompt_version: &str,
+        content_hash: &str,
+    ) -> Result<Option<String>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        let mut stmt = conn.prepare(
+            "SELECT result_json FROM file_analysis_cache
+             WHERE model = ?1 AND prompt_version = ?2 AND content_hash = ?3
+               AND (?4 IS NULL OR created_at >= datetime('now', ?4))",
+        )?;
+
+        let mut rows = stmt.query(params![
+            model,
+            prompt_version,
+            content_hash,
+            self.expiry_modifier()
+        ])?;
+
+        if let Some(row) = rows.next()? {
+            let _ = conn.execute(
+                "UPDATE file_analysis_cache SET last_accessed_at = CURRENT_TIMESTAMP
+                 WHERE model = ?1 AND prompt_version = ?2 AND content_hash = ?3",
+                params![model, prompt_version, content_hash],
+            );
+            Ok(Some(row.get(0)?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Store the analysis of a file in the cache.
+    pub fn put_file_analysis(
+        &self,
+        model: &str,
+        prompt_version: &str,
+        content_hash: &str,
+        result_json: &str,
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        conn.execute(
+            "INSERT OR REPLACE INTO file_analysis_cache
+             (model, prompt_version, content_hash, result_json)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![model, prompt_version, content_hash, result_json],
+        )?;
+        debug!("Cached file analysis for content hash {}", content_hash);
+        self.sweep_after_insert(&conn);
+        Ok(())
+    }
+
+    /// Retrieve the cached OSV vulnerabilities of a package version.
+    ///
+    /// Lookups older than [`OSV_CACHE_TTL`] are misses, so new advisories
+    /// show up within a day.
+    pub fn get_osv_vulnerabilities(
+        &self,
+        ecosystem: &str,
+        name: &str,
+        version: &str,
+    ) -> Result<Option<String>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        let mut stmt = conn.prepare(
+            "SELECT vulns_json FROM osv_cache
+             WHERE ecosystem = ?1 AND name = ?2 AND version = ?3
+               AND created_at >= datetime('now', ?4)",
+        )?;
+        let mut rows = stmt.query(params![
+            ecosystem,
+            name,
+            version,
+            format!("-{} seconds", OSV_CACHE_TTL.as_secs())
+        ])?;
+        match rows.next()? {
+            Some(row) => Ok(Some(row.get(0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Store the OSV vulnerabilities of a package version, dropping expired
+    /// lookups of any package.
+    pub fn put_osv_vulnerabilities(
+        &self,
+        ecosystem: &str,
+        name: &str,
+        version: &str,
+        vulns_json: &str,
+    ) -> Result<()> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        conn.execute(
+            "DELETE FROM osv_cache WHERE created_at < datetime('now', ?1)",
+            params![format!("-{} seconds", OSV_CACHE_TTL.as_secs())],
+        )?;
+        conn.execute(
+            "INSERT OR REPLACE INTO osv_cache (ecosystem, name, version, vulns_json)
+             VALUES (?1, ?2, ?3, ?4)",
+            params![ecosystem, name, version, vulns_json],
+        )?;
+        debug!("Cached OSV lookup for {} {} {}", ecosystem, name, version);
         Ok(())
     }
 
+    /// SQLite `datetime` modifier for the oldest creation time still served
+    fn expiry_modifier(&self) -> Option<String> {
+        self.limits
+            .max_age
+            .map(|age| format!("-{} seconds", age.as_secs()))
+    }
+
+    /// Count an insert and run an eviction sweep every `sweep_every` inserts
+    fn sweep_after_insert(&self, conn: &Connection) {
+        if self.limits.sweep_every == 0 {
+            return;
+        }
+        let inserts = self.inserts.fetch_add(1, Ordering::Relaxed) + 1;
+        if inserts % self.limits.sweep_every != 0 {
+            return;
+        }
+        match evict(conn, &self.limits) {
+            Ok(0) => {}
+            Ok(removed) => debug!("Evicted {} cache entries", removed),
+            Err(e) => warn!("Cache eviction failed: {}", e),
+        }
+    }
+
+    /// Entry count, total size and oldest entry across both caches.
+    pub fn cache_stats(&self) -> Result<CacheStats> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        let (entries, total_bytes, oldest): (i64, i64, Option<String>) = conn.query_row(
+            &format!("SELECT COUNT(*), COALESCE(SUM(bytes), 0), MIN(created_at) FROM ({CACHE_ENTRIES_SQL})"),
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )?;
+
+        Ok(CacheStats {
+            entries: entries.max(0) as u64,
+            total_bytes: total_bytes.max(0) as u64,
+            oldest_entry: oldest.as_deref().and_then(parse_sqlite_timestamp),
+        })
+    }
+
+    /// Apply the [`CacheLimits`] now instead of waiting for the next sweep.
+    ///
+    /// Drops expired entries, then least recently used ones until the size
+    /// and entry limits hold. Returns the number of removed entries.
+    pub fn prune(&self) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        let removed = evict(&conn, &self.limits)?;
+        if removed > 0 {
+            conn.execute_batch("VACUUM;")?;
+        }
+        info!("Pruned {} cache entries", removed);
+        Ok(removed)
+    }
+
+    /// Metadata of the cache entries matching `filter`, newest first
+    pub fn list_cached(&self, filter: &CacheFilter) -> Result<Vec<CachedEntry>> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        let mut stmt = conn.prepare(&format!(
+            "SELECT tbl, model, key, bytes, created_at, last_accessed_at
+             FROM ({CACHE_ENTRIES_SQL})
+             WHERE {CACHE_FILTER_SQL}
+             ORDER BY created_at DESC, key ASC"
+        ))?;
+        let entries = stmt
+            .query_map(params![filter.model, filter.age_modifier()], |row| {
+                let created: Option<String> = row.get(4)?;
+                let accessed: Option<String> = row.get(5)?;
+                Ok(CachedEntry {
+                    kind: match row.get::<_, String>(0)?.as_str() {
+                        "request_cache" => CacheKind::Response,
+                        _ => CacheKind::FileAnalysis,
+                    },
+                    model: row.get(1)?,
+                    key: row.get(2)?,
+                    size_bytes: row.get::<_, i64>(3)?.max(0) as u64,
+                    created_at: created.as_deref().and_then(parse_sqlite_timestamp),
+                    last_accessed_at: accessed.as_deref().and_then(parse_sqlite_timestamp),
+                })
+            })?
+            .collect::<Result<Vec<_>>>()?;
+        Ok(entries)
+    }
+
+    /// Remove the cached responses and file analyses matching `filter`.
+    ///
+    /// The database file is compacted afterwards. Returns the number of
+    /// removed entries.
+    pub fn clear_cached(&self, filter: &CacheFilter) -> Result<usize> {
+        let conn = self
+            .conn
+            .lock()
+            .map_err(|_| rusqlite::Error::InvalidParameterName("Mutex poisoned".to_string()))?;
+        let params = params![filter.model, filter.age_modifier()];
+        let removed = conn.execute(
+            &format!("DELETE FROM request_cache WHERE {CACHE_FILTER_SQL}"),
+            params,
+        )? + conn.execute(
+            &format!("DELETE FROM file_analysis_cache WHERE {CACHE_FILTER_SQL}"),
+            params,
+        )?;
+        // Deleted pages are otherwise only reused, never returned to the OS
+        conn.execute_batch("VACUUM;")?;
+        info!("Removed {} cache entries", removed);
+        Ok(removed)
+    }
+
+    /// Remove cached responses and file analyses.
+    ///
+    /// With `older_than`, only entries created before that age are removed;
+    /// otherwise both caches are emptied. See [`LocalDb::clear_cached`].
+    pub fn purge_cache(&self, older_than: Option<Duration>) -> Result<usize> {
+        self.clear_cached(&CacheFilter {
+            older_than,
+            ..CacheFilter::default()
+        })
+    }
+
     /// Log a message or interaction into the session audit log.
     ///
     /// This provides a durable history of all prompts and responses.
@@ -205,7 +647,86 @@ impl LocalDb {
         Ok(cost.unwrap_or(0.0))
     }
 }
-// Add optional trait import for query_row optional
+
+/// Drop entries outside `limits`: first by age, then least recently used
+/// entries until the total size and entry count fit. Returns the number of
+/// removed entries.
+fn evict(conn: &Connection, limits: &CacheLimits) -> Result<usize> {
+    let mut removed = 0;
+    if let Some(age) = limits.max_age {
+        removed += delete_older_than(conn, age)?;
+    }
+    if limits.max_total_bytes.is_none() && limits.max_entries.is_none() {
+        return Ok(removed);
+    }
+
+    let (total, count): (i64, i64) = conn.query_row(
+        &format!("SELECT COALESCE(SUM(bytes), 0), COUNT(*) FROM ({CACHE_ENTRIES_SQL})"),
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let limit =
+        |max: Option<u64>| max.map_or(i64::MAX, |max| i64::try_from(max).unwrap_or(i64::MAX));
+    let mut excess_bytes = total - limit(limits.max_total_bytes);
+    let mut excess_entries = count - limit(limits.max_entries);
+    if excess_bytes <= 0 && excess_entries <= 0 {
+        return Ok(removed);
+    }
+
+    let mut stmt = conn.prepare(&format!(
+        "SELECT tbl, id, bytes FROM ({CACHE_ENTRIES_SQL})
+         ORDER BY last_accessed_at ASC, created_at ASC"
+    ))?;
+    let entries = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, i64>(1)?,
+                row.get::<_, i64>(2)?,
+            ))
+        })?
+        .collect::<Result<Vec<_>>>()?;
+
+    for (table, id, bytes) in entries {
+        if excess_bytes <= 0 && excess_entries <= 0 {
+            break;
+        }
+        let sql = match table.as_str() {
+            "request_cache" => "DELETE FROM request_cache WHERE rowid = ?1",
+            _ => "DELETE FROM file_analysis_cache WHERE rowid = ?1",
+        };
+        removed += conn.execute(sql, params![id])?;
+        excess_bytes -= bytes;
+        excess_entries -= 1;
+    }
+    Ok(removed)
+}
+
+fn delete_older_than(conn: &Connection, age: Duration) -> Result<usize> {
+    let modifier = format!("-{} seconds", age.as_secs());
+    Ok(conn.execute(
+        "DELETE FROM request_cache WHERE created_at < datetime('now', ?1)",
+        params![modifier],
+    )? + conn.execute(
+        "DELETE FROM file_analysis_cache WHERE created_at < datetime('now', ?1)",
+        params![modifier],
+    )?)
+}
+
+/// Parse SQLite's `CURRENT_TIMESTAMP` format (UTC)
+fn parse_sqlite_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .ok()
+        .map(|naive| naive.and_utc())
+}
+
+fn get_db_path() -> anyhow::Result<PathBuf> {
+    let mut path = dirs::data_local_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
+    path.push("hqe-workbench");
+    path.push("hqe.db");
+    Ok(path)
+}
 
 #[cfg(test)]
 mod tests {
@@ -295,11 +816,7 @@ mod tests {
 
     #[test]
     fn test_usage_logging_persistence() {
-        let conn = Connection::open_in_memory().unwrap();
-        LocalDb::init_tables(&conn).unwrap();
-        let db = LocalDb {
-            conn: Arc::new(Mutex::new(conn)),
-        };
+        let db = LocalDb::in_memory().unwrap();
 
         // Log some usage
         db.log_usage("2024-01-01", "gpt-4", 100, 50, 0.05).unwrap();
@@ -317,12 +834,230 @@ mod tests {
         let cost_empty_day = db.get_daily_cost("2024-01-03").unwrap();
         assert_eq!(cost_empty_day, 0.0);
     }
-}
 
-fn get_db_path() -> anyhow::Result<PathBuf> {
-    let mut path = dirs::data_local_dir()
-        .ok_or_else(|| anyhow::anyhow!("Could not determine data directory"))?;
-    path.push("hqe-workbench");
-    path.push("hqe.db");
-    Ok(path)
+    #[test]
+    fn test_file_analysis_cache_roundtrip() {
+        let conn = Connection::open_in_memory().unwrap();
+        LocalDb::init_tables(&conn).unwrap();
+        // Migrations are idempotent
+        LocalDb::init_tables(&conn).unwrap();
+        let version: i32 = conn
+            .query_row("PRAGMA user_version", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(version, SCHEMA_VERSION);
+        let db = LocalDb::from_connection(conn, CacheLimits::default()).unwrap();
+
+        let hash = LocalDb::content_hash(b"fn main() {}");
+        assert_eq!(hash.len(), 64);
+        assert_eq!(db.get_file_analysis("gpt-4", "1", &hash).unwrap(), None);
+
+        db.put_file_analysis("gpt-4", "1", &hash, r#"{"findings":[]}"#)
+            .unwrap();
+        assert_eq!(
+            db.get_file_analysis("gpt-4", "1", &hash)
+                .unwrap()
+                .as_deref(),
+            Some(r#"{"findings":[]}"#)
+        );
+        assert_eq!(db.get_file_analysis("gpt-4", "2", &hash).unwrap(), None);
+        assert_eq!(db.get_file_analysis("gpt-3.5", "1", &hash).unwrap(), None);
+    }
+
+    #[test]
+    fn test_osv_cache_expires_after_a_day() {
+        let db = LocalDb::in_memory().unwrap();
+        assert_eq!(
+            db.get_osv_vulnerabilities("crates.io", "h2", "0.3.20")
+                .unwrap(),
+            None
+        );
+        db.put_osv_vulnerabilities("crates.io", "h2", "0.3.20", "[]")
+            .unwrap();
+        assert_eq!(
+            db.get_osv_vulnerabilities("crates.io", "h2", "0.3.20")
+                .unwrap()
+                .as_deref(),
+            Some("[]")
+        );
+        assert_eq!(
+            db.get_osv_vulnerabilities("crates.io", "h2", "0.3.24")
+                .unwrap(),
+            None
+        );
+
+        db.conn
+            .lock()
+            .unwrap()
+            .execute(
+                "UPDATE osv_cache SET created_at = datetime('now', '-25 hours')",
+                [],
+            )
+            .unwrap();
+        assert_eq!(
+            db.get_osv_vulnerabilities("crates.io", "h2", "0.3.20")
+                .unwrap(),
+            None
+        );
+    }
+
+    fn backdate(db: &LocalDb, hash: &str, days: u32) {
+        let conn = db.conn.lock().unwrap();
+        conn.execute(
+            "UPDATE request_cache
+             SET created_at = datetime('now', ?1), last_accessed_at = datetime('now', ?1)
+             WHERE hash = ?2",
+            params![format!("-{days} days"), hash],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_cache_stats_and_purge() {
+        let db = LocalDb::in_memory().unwrap();
+        assert_eq!(db.cache_stats().unwrap(), CacheStats::default());
+
+        db.cache_response("old", "gpt-4", "abc", "defgh").unwrap();
+        db.cache_response("new", "gpt-4", "xy", "z").unwrap();
+        db.put_file_analysis("gpt-4", "1", "hash", "{}").unwrap();
+        backdate(&db, "old", 10);
+
+        let stats = db.cache_stats().unwrap();
+        assert_eq!(stats.entries, 3);
+        assert_eq!(stats.total_bytes, 8 + 3 + 2);
+        let oldest = stats.oldest_entry.unwrap();
+        assert!(oldest < Utc::now() - chrono::Duration::days(9));
+
+        let removed = db
+            .purge_cache(Some(Duration::from_secs(5 * 24 * 60 * 60)))
+            .unwrap();
+        assert_eq!(removed, 1);
+        assert_eq!(db.get_cached_response("old").unwrap(), None);
+        assert_eq!(db.cache_stats().unwrap().entries, 2);
+
+        assert_eq!(db.purge_cache(None).unwrap(), 2);
+        assert_eq!(db.cache_stats().unwrap().entries, 0);
+    }
+
+    #[test]
+    fn test_eviction_enforces_age_and_size() {
+        let limits = CacheLimits {
+            max_total_bytes: Some(25),
+            max_entries: None,
+            max_age: Some(Duration::from_secs(7 * 24 * 60 * 60)),
+            sweep_every: 2,
+        };
+        let db = LocalDb::from_connection(Connection::open_in_memory().unwrap(), limits).unwrap();
+
+        db.cache_response("expired", "m", "p", "r").unwrap();
+        db.cache_response("lru", "m", "0123456789", "").unwrap();
+        backdate(&db, "expired", 8);
+        backdate(&db, "lru", 1);
+        db.cache_response("recent", "m", "0123456789", "").unwrap();
+        // Fourth insert triggers a sweep: "expired" is too old, and dropping the
+        // least recently used "lru" brings the total back under 25 bytes.
+        db.cache_response("newest", "m", "0123456789", "").unwrap();
+
+        assert_eq!(db.get_cached_response("expired").unwrap(), None);
+        assert_eq!(db.get_cached_response("lru").unwrap(), None);
+        assert!(db.get_cached_response("recent").unwrap().is_some());
+        assert!(db.get_cached_response("newest").unwrap().is_some());
+        assert_eq!(db.cache_stats().unwrap().total_bytes, 20);
+    }
+
+    #[test]
+    fn test_expired_entries_are_misses() {
+        let limits = CacheLimits {
+            max_total_bytes: None,
+            max_entries: None,
+            max_age: Some(Duration::from_secs(24 * 60 * 60)),
+            // No sweep runs, so only the read path hides the expired entry
+            sweep_every: 0,
+        };
+        let db = LocalDb::from_connection(Connection::open_in_memory().unwrap(), limits).unwrap();
+
+        db.cache_response("stale", "m", "p", "r").unwrap();
+        db.cache_response("fresh", "m", "p", "r").unwrap();
+        backdate(&db, "stale", 2);
+
+        assert_eq!(db.get_cached_response("stale").unwrap(), None);
+        assert_eq!(
+            db.get_cached_response("fresh").unwrap().as_deref(),
+            Some("r")
+        );
+        assert_eq!(db.cache_stats().unwrap().entries, 2);
+
+        assert_eq!(db.prune().unwrap(), 1);
+        assert_eq!(db.cache_stats().unwrap().entries, 1);
+    }
+
+    #[test]
+    fn test_prune_enforces_entry_limit() {
+        let limits = CacheLimits {
+            max_total_bytes: None,
+            max_entries: Some(2),
+            max_age: None,
+            sweep_every: 0,
+        };
+        let db = LocalDb::from_connection(Connection::open_in_memory().unwrap(), limits).unwrap();
+
+        for hash in ["a", "b", "c"] {
+            db.cache_response(hash, "m", "p", "r").unwrap();
+        }
+        backdate(&db, "a", 1);
+        db.put_file_analysis("m", "1", "hash", "{}").unwrap();
+
+        // Two entries over the limit; "a" was used least recently
+        assert_eq!(db.prune().unwrap(), 2);
+        assert_eq!(db.get_cached_response("a").unwrap(), None);
+        assert_eq!(db.cache_stats().unwrap().entries, 2);
+        assert_eq!(db.prune().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_list_and_clear_cached_by_model() {
+        let db = LocalDb::in_memory().unwrap();
+        db.cache_response("aaaa1111", "gpt-4", "secret prompt", "secret answer")
+            .unwrap();
+        db.cache_response("bbbb2222", "claude", "p", "r").unwrap();
+        db.put_file_analysis("gpt-4", "1", "cccc3333", "{}")
+            .unwrap();
+        backdate(&db, "aaaa1111", 3);
+
+        let all = db.list_cached(&CacheFilter::default()).unwrap();
+        assert_eq!(all.len(), 3);
+        // Newest first
+        assert_eq!(all[2].key, "aaaa1111");
+        assert_eq!(all[2].kind, CacheKind::Response);
+        assert_eq!(all[2].size_bytes, 26);
+        assert!(all[2].created_at.is_some());
+
+        let gpt = CacheFilter {
+            model: Some("gpt-4".to_string()),
+            ..CacheFilter::default()
+        };
+        let mut keys: Vec<_> = db
+            .list_cached(&gpt)
+            .unwrap()
+            .into_iter()
+            .map(|e| (e.kind, e.key))
+            .collect();
+        keys.sort_by(|a, b| a.1.cmp(&b.1));
+        assert_eq!(
+            keys,
+            vec![
+                (CacheKind::Response, "aaaa1111".to_string()),
+                (CacheKind::FileAnalysis, "cccc3333".to_string()),
+            ]
+        );
+        let old = CacheFilter {
+            older_than: Some(Duration::from_secs(24 * 60 * 60)),
+            ..CacheFilter::default()
+        };
+        assert_eq!(db.list_cached(&old).unwrap().len(), 1);
+
+        assert_eq!(db.clear_cached(&gpt).unwrap(), 2);
+        let remaining = db.list_cached(&CacheFilter::default()).unwrap();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].model, "claude");
+    }
 }
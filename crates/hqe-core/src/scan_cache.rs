@@ -0,0 +1,239 @@
+//! Content-hash cache for incremental scans.
+//!
+//! The cache lives next to the scan artifacts and records a SHA-256 hash for
+//! every scanned file together with the findings and TODOs of the previous
+//! run. [`crate::ScanPipeline::run_incremental`] compares the current hashes
+//! against it so that only new or modified files are analyzed again.
+
+use crate::models::{Evidence, Finding, TodoItem};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
+use tracing::{debug, warn};
+
+/// File name of the scan cache inside the output directory
+pub const SCAN_CACHE_FILE: &str = "scan-cache.json";
+
+/// Bumped whenever the on-disk layout changes; older caches are ignored
+const SCAN_CACHE_VERSION: u32 = 1;
+
+/// Per-file content hashes plus the result of the scan that produced them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScanCache {
+    /// Cache format version
+    pub version: u32,
+    /// Run that last wrote the cache
+    pub run_id: String,
+    /// Analysis mode (local or LLM + model); a mismatch invalidates the cache
+    pub mode_key: String,
+    /// Relative file path -> SHA-256 hex digest of its content
+    pub files: BTreeMap<String, String>,
+    /// Findings from the previous run
+    pub findings: Vec<Finding>,
+    /// TODO items from the previous run
+    pub todos: Vec<TodoItem>,
+}
+
+/// Classification of the current files against a previous cache
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileChanges {
+    /// Files with identical content
+    pub unchanged: BTreeSet<String>,
+    /// Files that are new or whose content changed
+    pub changed: BTreeSet<String>,
+    /// Files from the previous scan that no longer exist (deleted or renamed)
+    pub removed: BTreeSet<String>,
+}
+
+impl FileChanges {
+    /// Treat every current file as changed (no usable cache)
+    pub fn all_changed(current: &BTreeMap<String, String>) -> Self {
+        Self {
+            changed: current.keys().cloned().collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether nothing was added, modified or removed
+    pub fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.removed.is_empty()
+    }
+}
+
+impl ScanCache {
+    /// Create a cache for the given run
+    pub fn new(
+        run_id: impl Into<String>,
+        mode_key: impl Into<String>,
+        files: BTreeMap<String, String>,
+    ) -> Self {
+        Self {
+            version: SCAN_CACHE_VERSION,
+            run_id: run_id.into(),
+            mode_key: mode_key.into(),
+            files,
+            findings: Vec::new(),
+            todos: Vec::new(),
+        }
+    }
+
+    /// Load a cache from disk.
+    ///
+    /// Returns `None` when the file is missing, unreadable or was written by an
+    /// incompatible version, in which case the scan falls back to a full run.
+    pub fn load(path: impl AsRef<Path>) -> Option<Self> {
+        let path = path.as_ref();
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) => {
+                debug!("No scan cache at {:?}: {}", path, e);
+                return None;
+            }
+        };
+
+        match serde_json::from_str::<Self>(&content) {
+            Ok(cache) if cache.version == SCAN_CACHE_VERSION => Some(cache),
+            Ok(cache) => {
+                warn!(
+                    "Ignoring scan cache with version {} (expected {})",
+                    cache.version, SCAN_CACHE_VERSION
+                );
+                None
+            }
+            Err(e) => {
+                warn!("Ignoring unreadable scan cache {:?}: {}", path, e);
+                None
+            }
+        }
+    }
+
+    /// Write the cache to disk, creating the parent directory if needed
+    pub fn save(&self, path: impl AsRef<Path>) -> crate::Result<()> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| crate::HqeError::Serialization(e.to_string()))?;
+
+        // Write to a sibling file first so an interrupted save never leaves a
+        // truncated cache behind.
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)?;
+        Ok(())
+    }
+
+    /// Compare current file hashes against this cache.
+    ///
+    /// A renamed file shows up as one removed path and one changed path.
+    pub fn diff(&self, current: &BTreeMap<String, String>) -> FileChanges {
+        let mut changes = FileChanges::default();
+
+        for (path, hash) in current {
+            if self.files.get(path) == Some(hash) {
+                changes.unchanged.insert(path.clone());
+            } else {
+                changes.changed.insert(path.clone());
+            }
+        }
+
+        changes.removed = self
+            .files
+            .keys()
+            .filter(|path| !current.contains_key(*path))
+            .cloned()
+            .collect();
+
+        changes
+    }
+}
+
+/// Hash the content of `files` (relative to `root`).
+///
+/// Files that cannot be read are left out and therefore always count as changed.
+pub async fn hash_files(root: &Path, files: &[String]) -> BTreeMap<String, String> {
+    let mut hashes = BTreeMap::new();
+    for file in files {
+        match tokio::fs::read(root.join(file)).await {
+            Ok(bytes) => {
+                hashes.insert(file.clone(), format!("{:x}", Sha256::digest(&bytes)));
+            }
+            Err(e) => debug!("Skipping hash for {}: {}", file, e),
+        }
+    }
+    hashes
+}
+
+/// File an evidence item points at, if any
+pub(crate) fn evidence_file(evidence: &Evidence) -> Option<&str> {
+    match evidence {
+        Evidence::FileLine { file, .. } | Evidence::FileFunction { file, .. } => Some(file),
+        Evidence::Reproduction { .. } => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn hashes(entries: &[(&str, &str)]) -> BTreeMap<String, String> {
+        entries
+            .iter()
+            .map(|(path, hash)| (path.to_string(), hash.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_detects_changes_and_renames() {
+        let cache = ScanCache::new(
+            "run",
+            "local",
+            hashes(&[("a.rs", "1"), ("b.rs", "2"), ("old.rs", "3")]),
+        );
+        let changes = cache.diff(&hashes(&[("a.rs", "1"), ("b.rs", "9"), ("new.rs", "3")]));
+
+        assert_eq!(changes.unchanged, BTreeSet::from(["a.rs".to_string()]));
+        assert_eq!(
+            changes.changed,
+            BTreeSet::from(["b.rs".to_string(), "new.rs".to_string()])
+        );
+        assert_eq!(changes.removed, BTreeSet::from(["old.rs".to_string()]));
+        assert!(!changes.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_save_and_load_roundtrip() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        tokio::fs::write(temp.path().join("main.rs"), "fn main() {}").await?;
+
+        let files = hash_files(
+            temp.path(),
+            &["main.rs".to_string(), "missing.rs".to_string()],
+        )
+        .await;
+        assert_eq!(files.len(), 1);
+        assert_eq!(files["main.rs"].len(), 64);
+
+        let path = temp.path().join("out").join(SCAN_CACHE_FILE);
+        ScanCache::new("run-1", "local", files.clone()).save(&path)?;
+
+        let loaded = ScanCache::load(&path).ok_or_else(|| anyhow::anyhow!("cache not loaded"))?;
+        assert_eq!(loaded.run_id, "run-1");
+        assert!(loaded.diff(&files).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_ignores_missing_and_corrupt() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let path = temp.path().join(SCAN_CACHE_FILE);
+        assert!(ScanCache::load(&path).is_none());
+
+        std::fs::write(&path, "{not json")?;
+        assert!(ScanCache::load(&path).is_none());
+        Ok(())
+    }
+}
@@ -0,0 +1,124 @@
+use hqe_mcp::{is_truthy, resolve_path};
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::str::FromStr;
+
+/// A branch predicate over the flow variables.
+///
+/// Either a path alone, true when the value there is truthy (see
+/// [`is_truthy`]), or a path compared with a JSON literal:
+///
+/// ```text
+/// $.review.approved
+/// $.input.score >= 7
+/// $.summary != "unchanged"
+/// ```
+///
+/// Paths are dotted, optionally prefixed with `$.`. A missing value compares
+/// as `null`. `<`, `<=`, `>` and `>=` compare numbers with numbers and
+/// strings with strings; any other pair is false.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Condition {
+    path: String,
+    test: Option<(Operator, Value)>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl FromStr for Condition {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, String> {
+        // Two-character operators first so `>=` is not read as `>`
+        const OPERATORS: [(&str, Operator); 6] = [
+            ("==", Operator::Eq),
+            ("!=", Operator::Ne),
+            ("<=", Operator::Le),
+            (">=", Operator::Ge),
+            ("<", Operator::Lt),
+            (">", Operator::Gt),
+        ];
+        let text = text.trim();
+        let found = OPERATORS
+            .iter()
+            .filter_map(|(symbol, op)| text.find(symbol).map(|at| (at, *symbol, *op)))
+            .min_by_key(|(at, symbol, _)| (*at, std::cmp::Reverse(symbol.len())));
+        let (path, test) = match found {
+            Some((at, symbol, op)) => {
+                let literal = text[at + symbol.len()..].trim();
+                let value = serde_json::from_str(literal)
+                    .map_err(|_| format!("'{literal}' is not a JSON literal"))?;
+                (text[..at].trim(), Some((op, value)))
+            }
+            None => (text, None),
+        };
+
+        let path = path.strip_prefix("$.").unwrap_or(path);
+        if path.is_empty() || path.split('.').any(str::is_empty) {
+            return Err(format!("'{text}' does not start with a variable path"));
+        }
+        Ok(Self {
+            path: path.to_string(),
+            test,
+        })
+    }
+}
+
+impl Condition {
+    /// Evaluate against the flow variables
+    pub fn evaluate(&self, vars: &Value) -> bool {
+        let value = resolve_path(vars, &self.path);
+        let Some((op, expected)) = &self.test else {
+            return is_truthy(value);
+        };
+        let actual = value.unwrap_or(&Value::Null);
+        let ordering = match (actual, expected) {
+            (Value::Number(a), Value::Number(b)) => a.as_f64().partial_cmp(&b.as_f64()),
+            (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+            _ => None,
+        };
+        match op {
+            Operator::Eq => actual == expected,
+            Operator::Ne => actual != expected,
+            Operator::Lt => ordering == Some(Ordering::Less),
+            Operator::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Operator::Gt => ordering == Some(Ordering::Greater),
+            Operator::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn eval(condition: &str, vars: &Value) -> bool {
+        condition.parse::<Condition>().unwrap().evaluate(vars)
+    }
+
+    #[test]
+    fn conditions_compare_paths_with_literals() {
+        let vars = json!({"input": {"score": 7, "tags": []}, "summary": "ok"});
+        assert!(eval("$.input.score >= 7", &vars));
+        assert!(!eval("input.score > 7", &vars));
+        assert!(eval("$.summary == \"ok\"", &vars));
+        assert!(eval("$.summary != \"changed\"", &vars));
+        assert!(eval("$.missing == null", &vars));
+        assert!(!eval("$.summary < 3", &vars));
+        assert!(eval("$.summary", &vars));
+        assert!(!eval("$.input.tags", &vars));
+        assert!(!eval("$.missing", &vars));
+
+        assert!("$.summary == ok".parse::<Condition>().is_err());
+        assert!("== 3".parse::<Condition>().is_err());
+    }
+}
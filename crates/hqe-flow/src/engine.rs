@@ -1,11 +1,15 @@
-use anyhow::{anyhow, Result};
+use crate::report::{FlowRunReport, StepReport, StepStatus};
+use crate::step::{bind_args, FlowError, FlowStep, RetryPolicy};
 use hqe_mcp::ToolRegistry;
 use hqe_protocol::models::{WorkflowDefinition, WorkflowStep};
-use serde_json::Value;
+use serde_json::{Map, Value};
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tokio::sync::RwLock;
-use tracing::{info, instrument};
+use tracing::{info, instrument, warn};
 
 /// Engine for managing and executing workflows
 #[derive(Clone)]
@@ -36,46 +40,194 @@ impl FlowEngine {
         flows.values().cloned().collect()
     }
 
-    /// Execute a workflow by ID
+    /// Execute a workflow by ID.
+    ///
+    /// Step args can reference the flow input as `{{input}}` (or a field of
+    /// it, `{{input.repo}}`) and the output of earlier prompt steps by their
+    /// `output_var`. Returns the last step's tool result; see
+    /// [`run_flow`](Self::run_flow) for per-step details.
+    pub async fn execute_flow(&self, flow_id: &str, input: Value) -> Result<Value, FlowError> {
+        self.run_flow(flow_id, input).await?.result
+    }
+
+    /// Execute a workflow by ID and report the status, attempts and duration
+    /// of every step.
+    ///
+    /// Fails only when no flow has this ID; step failures end the run and are
+    /// in [`FlowRunReport::result`].
     #[instrument(skip(self, input))]
-    pub async fn execute_flow(&self, flow_id: &str, input: Value) -> Result<Value> {
+    pub async fn run_flow(&self, flow_id: &str, input: Value) -> Result<FlowRunReport, FlowError> {
         let flow = {
             let flows = self.flows.read().await;
             flows
                 .get(flow_id)
-                .ok_or_else(|| anyhow!("Flow not found: {}", flow_id))?
+                .ok_or_else(|| FlowError::NotFound(flow_id.to_string()))?
                 .clone()
         };
 
         info!("Starting flow execution: {}", flow.name);
+        let started = Instant::now();
 
-        let mut context = input;
+        let mut vars = Map::new();
+        vars.insert("input".to_string(), input.clone());
+        let mut run = Run {
+            vars: Value::Object(vars),
+            output: input,
+            steps: Vec::new(),
+        };
+        let outcome = self.run_steps(&flow.steps, &mut run).await;
 
-        for step in &flow.steps {
-            context = self.execute_step(step, context).await?;
-        }
+        Ok(FlowRunReport {
+            flow_id: flow.id,
+            steps: run.steps,
+            duration: started.elapsed(),
+            result: outcome.map(|()| run.output),
+        })
+    }
 
-        Ok(context)
+    /// Run `steps` in order, stopping at the first failure
+    fn run_steps<'a>(
+        &'a self,
+        steps: &'a [WorkflowStep],
+        run: &'a mut Run,
+    ) -> Pin<Box<dyn Future<Output = Result<(), FlowError>> + Send + 'a>> {
+        Box::pin(async move {
+            for (i, step) in steps.iter().enumerate() {
+                if let Err(e) = self.run_step(step, run).await {
+                    run.skip(&steps[i + 1..]);
+                    return Err(e);
+                }
+            }
+            Ok(())
+        })
     }
 
-    async fn execute_step(&self, step: &WorkflowStep, _input: Value) -> Result<Value> {
-        match step.action.as_str() {
-            "call_tool" => {
-                let tool_name = step
-                    .params
-                    .get("tool")
-                    .and_then(|v| v.as_str())
-                    .ok_or_else(|| anyhow!("Step {} missing 'tool' param", step.id))?;
-
-                let args = step.params.clone();
-
-                info!("Step {}: Calling tool {}", step.id, tool_name);
-                self.tool_registry
-                    .call_tool(tool_name, args)
-                    .await
-                    .map_err(|e| anyhow!(e))
+    async fn run_step(&self, step: &WorkflowStep, run: &mut Run) -> Result<(), FlowError> {
+        let started = Instant::now();
+        let parsed = FlowStep::try_from(step).and_then(|parsed| {
+            let retry = RetryPolicy::for_step(step)?;
+            Ok((parsed, retry))
+        });
+        let (parsed, retry) = match parsed {
+            Ok(parsed) => parsed,
+            Err(e) => {
+                run.record(step, StepStatus::Failed(e.to_string()), 0, started);
+                return Err(e);
+            }
+        };
+
+        if let FlowStep::Branch {
+            condition,
+            then,
+            otherwise,
+        } = &parsed
+        {
+            let taken = condition.evaluate(&run.vars);
+            info!(
+                "Step {}: branch {}",
+                step.id,
+                if taken { "then" } else { "else" }
+            );
+            run.record(step, StepStatus::Succeeded, 1, started);
+            let (chosen, skipped) = if taken {
+                (then, otherwise)
+            } else {
+                (otherwise, then)
+            };
+            run.skip(skipped);
+            return self.run_steps(chosen, run).await;
+        }
+
+        let mut attempts = 0;
+        let result = loop {
+            attempts += 1;
+            match self.execute_step(step, &parsed, &mut run.vars).await {
+                Err(e) if e.is_recoverable() && attempts < retry.max_attempts => {
+                    let delay = retry.delay(attempts);
+                    warn!(
+                        "Step {} failed (attempt {}/{}), retrying in {:?}: {}",
+                        step.id, attempts, retry.max_attempts, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                result => break result,
+            }
+        };
+        match result {
+            Ok(output) => {
+                run.record(step, StepStatus::Succeeded, attempts, started);
+                run.output = output;
+                Ok(())
+            }
+            Err(e) => {
+                run.record(step, StepStatus::Failed(e.to_string()), attempts, started);
+                Err(e)
             }
-            _ => Err(anyhow!("Unknown action: {}", step.action)),
         }
     }
+
+    async fn execute_step(
+        &self,
+        step: &WorkflowStep,
+        parsed: &FlowStep,
+        vars: &mut Value,
+    ) -> Result<Value, FlowError> {
+        let (tool, args, output_var) = match parsed {
+            FlowStep::CallTool { tool, args } => (tool, args, None),
+            FlowStep::Prompt {
+                tool,
+                args,
+                output_var,
+            } => (tool, args, Some(output_var)),
+            FlowStep::Branch { .. } => unreachable!("branches are run by run_step"),
+        };
+        let args = bind_args(&step.id, args, vars)?;
+
+        info!("Step {}: Calling tool {}", step.id, tool);
+        let result = self
+            .tool_registry
+            .call_tool(tool, args)
+            .await
+            .map_err(|source| FlowError::Tool {
+                step: step.id.clone(),
+                source,
+            })?;
+
+        if let (Some(name), Value::Object(vars)) = (output_var, vars) {
+            // Prompt tools answer `{"result": text}`
+            let output = result
+                .get("result")
+                .cloned()
+                .unwrap_or_else(|| result.clone());
+            vars.insert(name.clone(), output);
+        }
+        Ok(result)
+    }
+}
+
+/// State of a flow run in progress
+struct Run {
+    vars: Value,
+    output: Value,
+    steps: Vec<StepReport>,
+}
+
+impl Run {
+    fn record(&mut self, step: &WorkflowStep, status: StepStatus, attempts: u32, started: Instant) {
+        self.steps.push(StepReport {
+            id: step.id.clone(),
+            status,
+            attempts,
+            duration: started.elapsed(),
+        });
+    }
+
+    fn skip(&mut self, steps: &[WorkflowStep]) {
+        self.steps.extend(steps.iter().map(|step| StepReport {
+            id: step.id.clone(),
+            status: StepStatus::Skipped,
+            attempts: 0,
+            duration: Duration::ZERO,
+        }));
+    }
 }
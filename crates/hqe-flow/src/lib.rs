@@ -4,10 +4,19 @@
 
 #![warn(missing_docs)]
 
+/// Branch conditions
+pub mod condition;
 /// The core execution engine
 pub mod engine;
+/// Per-step run reports
+pub mod report;
+/// Step types and flow errors
+pub mod step;
 
+pub use condition::Condition;
 pub use engine::FlowEngine;
+pub use report::{FlowRunReport, StepReport, StepStatus};
+pub use step::{FlowError, FlowStep, RetryPolicy};
 
 /// Initialize the flow subsystem
 pub fn init() {
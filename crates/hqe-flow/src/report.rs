@@ -0,0 +1,53 @@
+use crate::step::FlowError;
+use serde_json::Value;
+use std::time::Duration;
+
+/// Outcome of one step
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StepStatus {
+    /// The step completed; for a branch, the condition was evaluated
+    Succeeded,
+    /// The last attempt failed with this error
+    Failed(String),
+    /// Not run: on the branch side not taken, or after an earlier failure
+    Skipped,
+}
+
+/// What happened to one step of a run
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StepReport {
+    /// Step ID
+    pub id: String,
+    /// Outcome
+    pub status: StepStatus,
+    /// Attempts made, zero for skipped steps
+    pub attempts: u32,
+    /// Time spent on the step, retries and backoff included
+    pub duration: Duration,
+}
+
+/// Result of [`FlowEngine::run_flow`](crate::FlowEngine::run_flow)
+#[derive(Debug)]
+pub struct FlowRunReport {
+    /// Flow that ran
+    pub flow_id: String,
+    /// Every step in execution order; branch steps come before the steps
+    /// they ran or skipped
+    pub steps: Vec<StepReport>,
+    /// Wall time of the whole run
+    pub duration: Duration,
+    /// The last tool result, or the error that stopped the run
+    pub result: Result<Value, FlowError>,
+}
+
+impl FlowRunReport {
+    /// Report of the step with this ID
+    pub fn step(&self, id: &str) -> Option<&StepReport> {
+        self.steps.iter().find(|s| s.id == id)
+    }
+
+    /// Whether every step that ran succeeded
+    pub fn succeeded(&self) -> bool {
+        self.result.is_ok()
+    }
+}
@@ -0,0 +1,297 @@
+use crate::condition::Condition;
+use hqe_mcp::{resolve_path, substitute_template, unresolved_placeholders, ToolError};
+use hqe_protocol::models::WorkflowStep;
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::time::Duration;
+use thiserror::Error;
+
+/// A workflow step parsed from its [`WorkflowStep`] action and params
+#[derive(Debug, Clone)]
+pub enum FlowStep {
+    /// `call_tool`: call a registry tool with the step params, `tool` included
+    /// and `retry` left out. The result becomes the flow output.
+    CallTool {
+        /// Registry name of the tool
+        tool: String,
+        /// Arguments, as given in the step params
+        args: Value,
+    },
+    /// `prompt`: run a prompt tool and store its `result` text in the variable
+    /// `output_var` for later steps
+    Prompt {
+        /// Registry name of the prompt tool
+        tool: String,
+        /// Prompt arguments; strings may reference variables as `{{name}}`
+        args: Value,
+        /// Variable that receives the prompt output
+        output_var: String,
+    },
+    /// `branch`: run the `then` steps when `condition` holds for the flow
+    /// variables, the `else` steps otherwise
+    Branch {
+        /// Predicate over the flow variables
+        condition: Condition,
+        /// Steps run when the condition holds
+        then: Vec<WorkflowStep>,
+        /// Steps run when it does not (`else` in the params)
+        otherwise: Vec<WorkflowStep>,
+    },
+}
+
+/// Errors from parsing and running flow steps
+#[derive(Debug, Error)]
+pub enum FlowError {
+    /// No flow is registered under this ID
+    #[error("Flow not found: {0}")]
+    NotFound(String),
+    /// The step action is not one [`FlowStep`] knows
+    #[error("Step {step}: unknown action '{action}'")]
+    UnknownAction {
+        /// Step ID
+        step: String,
+        /// Action from the definition
+        action: String,
+    },
+    /// A required step param is absent or has the wrong type
+    #[error("Step {step}: missing '{param}' param")]
+    MissingParam {
+        /// Step ID
+        step: String,
+        /// Param name
+        param: &'static str,
+    },
+    /// A step param has the wrong shape
+    #[error("Step {step}: invalid '{param}' param: {reason}")]
+    InvalidParam {
+        /// Step ID
+        step: String,
+        /// Param name
+        param: &'static str,
+        /// What is wrong with it
+        reason: String,
+    },
+    /// The step args reference a variable no earlier step or flow input set
+    #[error("Step {step}: variable '{variable}' is not defined")]
+    MissingVariable {
+        /// Step ID
+        step: String,
+        /// Dotted path of the first unresolved reference
+        variable: String,
+    },
+    /// The tool call failed
+    #[error("Step {step}: {source}")]
+    Tool {
+        /// Step ID
+        step: String,
+        /// Registry error
+        #[source]
+        source: ToolError,
+    },
+}
+
+impl FlowError {
+    /// Whether running the step again might succeed. Only failures inside the
+    /// tool are; a missing tool, bad arguments or a bad definition fail the
+    /// same way every time.
+    pub fn is_recoverable(&self) -> bool {
+        matches!(
+            self,
+            Self::Tool {
+                source: ToolError::ExecutionError(_),
+                ..
+            }
+        )
+    }
+}
+
+fn param(step: &WorkflowStep, name: &'static str) -> Result<String, FlowError> {
+    step.params
+        .get(name)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| FlowError::MissingParam {
+            step: step.id.clone(),
+            param: name,
+        })
+}
+
+impl TryFrom<&WorkflowStep> for FlowStep {
+    type Error = FlowError;
+
+    fn try_from(step: &WorkflowStep) -> Result<Self, FlowError> {
+        match step.action.as_str() {
+            "call_tool" => {
+                let mut args = step.params.clone();
+                if let Value::Object(map) = &mut args {
+                    map.remove("retry");
+                }
+                Ok(Self::CallTool {
+                    tool: param(step, "tool")?,
+                    args,
+                })
+            }
+            "prompt" => Ok(Self::Prompt {
+                tool: param(step, "tool")?,
+                args: step
+                    .params
+                    .get("args")
+                    .cloned()
+                    .unwrap_or_else(|| Value::Object(Map::new())),
+                output_var: param(step, "output_var")?,
+            }),
+            "branch" => Ok(Self::Branch {
+                condition: param(step, "condition")?.parse().map_err(|reason| {
+                    FlowError::InvalidParam {
+                        step: step.id.clone(),
+                        param: "condition",
+                        reason,
+                    }
+                })?,
+                then: nested_steps(step, "then")?,
+                otherwise: nested_steps(step, "else")?,
+            }),
+            action => Err(FlowError::UnknownAction {
+                step: step.id.clone(),
+                action: action.to_string(),
+            }),
+        }
+    }
+}
+
+/// Steps listed under `name`; none when the param is absent
+fn nested_steps(step: &WorkflowStep, name: &'static str) -> Result<Vec<WorkflowStep>, FlowError> {
+    match step.params.get(name) {
+        None | Some(Value::Null) => Ok(Vec::new()),
+        Some(steps) => serde_json::from_value(steps.clone()).map_err(|e| FlowError::InvalidParam {
+            step: step.id.clone(),
+            param: name,
+            reason: e.to_string(),
+        }),
+    }
+}
+
+/// How often a step is attempted, from its `retry` param:
+///
+/// ```json
+/// {"retry": {"max_attempts": 3, "backoff_ms": 200}}
+/// ```
+///
+/// Only recoverable failures (see [`FlowError::is_recoverable`]) are retried.
+/// The wait doubles after each attempt, starting at `backoff`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts in total, including the first
+    pub max_attempts: u32,
+    /// Wait before the second attempt
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    /// A single attempt
+    fn default() -> Self {
+        Self {
+            max_attempts: 1,
+            backoff: Duration::ZERO,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// The policy in the step's `retry` param, or a single attempt
+    pub fn for_step(step: &WorkflowStep) -> Result<Self, FlowError> {
+        #[derive(Deserialize)]
+        #[serde(deny_unknown_fields)]
+        struct Params {
+            max_attempts: u32,
+            #[serde(default)]
+            backoff_ms: u64,
+        }
+
+        let Some(retry) = step.params.get("retry") else {
+            return Ok(Self::default());
+        };
+        let invalid = |reason: String| FlowError::InvalidParam {
+            step: step.id.clone(),
+            param: "retry",
+            reason,
+        };
+        let params: Params =
+            serde_json::from_value(retry.clone()).map_err(|e| invalid(e.to_string()))?;
+        if params.max_attempts == 0 {
+            return Err(invalid("max_attempts must be at least 1".to_string()));
+        }
+        Ok(Self {
+            max_attempts: params.max_attempts,
+            backoff: Duration::from_millis(params.backoff_ms),
+        })
+    }
+
+    /// Wait after failed attempt number `attempt` (1-based)
+    pub fn delay(&self, attempt: u32) -> Duration {
+        self.backoff
+            .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+    }
+}
+
+/// Replace `{{name}}` references in every string of `args` with values from
+/// `vars`. A string that is a single reference takes the referenced value as
+/// is, so objects and numbers keep their type.
+pub(crate) fn bind_args(step_id: &str, args: &Value, vars: &Value) -> Result<Value, FlowError> {
+    match args {
+        Value::String(text) => {
+            if let Some(missing) = unresolved_placeholders(text, vars).into_iter().next() {
+                return Err(FlowError::MissingVariable {
+                    step: step_id.to_string(),
+                    variable: missing,
+                });
+            }
+            if let Some(path) = text
+                .trim()
+                .strip_prefix("{{")
+                .and_then(|t| t.strip_suffix("}}"))
+                .filter(|path| !path.contains("{{") && !path.contains("}}"))
+            {
+                if let Some(value) = resolve_path(vars, path.trim()) {
+                    return Ok(value.clone());
+                }
+            }
+            Ok(Value::String(substitute_template(
+                text,
+                vars,
+                str::to_string,
+            )))
+        }
+        Value::Array(items) => items
+            .iter()
+            .map(|item| bind_args(step_id, item, vars))
+            .collect::<Result<_, _>>()
+            .map(Value::Array),
+        Value::Object(map) => map
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), bind_args(step_id, value, vars)?)))
+            .collect::<Result<_, _>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn bind_args_keeps_types_of_whole_references() {
+        let vars = json!({"input": {"repo": "hqe", "limit": 3}, "summary": "ok"});
+        let args =
+            json!({"limit": "{{input.limit}}", "text": "{{input.repo}}: {{summary}}", "n": 1});
+        assert_eq!(
+            bind_args("s", &args, &vars).unwrap(),
+            json!({"limit": 3, "text": "hqe: ok", "n": 1})
+        );
+
+        let err = bind_args("s2", &json!(["{{draft}}"]), &vars).unwrap_err();
+        assert_eq!(err.to_string(), "Step s2: variable 'draft' is not defined");
+    }
+}
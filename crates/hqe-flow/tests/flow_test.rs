@@ -1,7 +1,9 @@
-use hqe_flow::FlowEngine;
+use hqe_flow::{FlowEngine, FlowError, StepStatus};
 use hqe_mcp::ToolRegistry;
 use hqe_protocol::models::{MCPToolDefinition, WorkflowDefinition, WorkflowStep};
 use serde_json::json;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Arc;
 
 #[tokio::test]
 async fn test_flow_execution() -> anyhow::Result<()> {
@@ -98,3 +100,216 @@ async fn test_flow_execution() -> anyhow::Result<()> {
     assert_eq!(value["symbol"], "AAPL");
     Ok(())
 }
+
+/// Register a prompt-style tool that answers `{"result": <prefix><text>}`
+async fn register_echo_prompt(registry: &ToolRegistry, name: &str, prefix: &'static str) {
+    let tool_def = MCPToolDefinition {
+        name: name.to_string(),
+        description: "Echo prompt".to_string(),
+        input_schema: json!({
+            "type": "object",
+            "properties": {"text": {"type": "string"}},
+            "required": ["text"]
+        }),
+    };
+    registry
+        .register_tool(
+            "prompts",
+            tool_def,
+            Box::new(move |args| {
+                Box::pin(async move {
+                    let text = args["text"].as_str().unwrap_or_default();
+                    Ok(json!({ "result": format!("{prefix}{text}") }))
+                })
+            }),
+        )
+        .await
+        .expect("Failed to register tool");
+}
+
+#[tokio::test]
+async fn test_prompt_step_output_feeds_next_step() -> anyhow::Result<()> {
+    let registry = ToolRegistry::new();
+    register_echo_prompt(&registry, "summarize", "summary of ").await;
+    register_echo_prompt(&registry, "critique", "critique of ").await;
+    let engine = FlowEngine::new(registry);
+
+    engine
+        .register_flow(WorkflowDefinition {
+            id: "review".to_string(),
+            name: "Summarize then critique".to_string(),
+            steps: vec![
+                WorkflowStep {
+                    id: "summarize".to_string(),
+                    action: "prompt".to_string(),
+                    params: json!({
+                        "tool": "summarize",
+                        "args": {"text": "{{input.repo}}"},
+                        "output_var": "summary"
+                    }),
+                },
+                WorkflowStep {
+                    id: "critique".to_string(),
+                    action: "prompt".to_string(),
+                    params: json!({
+                        "tool": "critique",
+                        "args": {"text": "[{{summary}}]"},
+                        "output_var": "critique"
+                    }),
+                },
+            ],
+        })
+        .await;
+
+    let value = engine
+        .execute_flow("review", json!({"repo": "hqe"}))
+        .await?;
+    assert_eq!(value["result"], "critique of [summary of hqe]");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_prompt_step_with_undefined_variable_fails() {
+    let registry = ToolRegistry::new();
+    register_echo_prompt(&registry, "critique", "").await;
+    let engine = FlowEngine::new(registry);
+
+    engine
+        .register_flow(WorkflowDefinition {
+            id: "broken".to_string(),
+            name: "Critique without summary".to_string(),
+            steps: vec![WorkflowStep {
+                id: "critique".to_string(),
+                action: "prompt".to_string(),
+                params: json!({
+                    "tool": "critique",
+                    "args": {"text": "{{summary}}"},
+                    "output_var": "critique"
+                }),
+            }],
+        })
+        .await;
+
+    let err = engine.execute_flow("broken", json!({})).await.unwrap_err();
+    assert!(
+        matches!(&err, FlowError::MissingVariable { step, variable } if step == "critique" && variable == "summary"),
+        "{err}"
+    );
+}
+
+fn branch_flow() -> WorkflowDefinition {
+    WorkflowDefinition {
+        id: "triage".to_string(),
+        name: "Escalate high scores".to_string(),
+        steps: vec![WorkflowStep {
+            id: "check".to_string(),
+            action: "branch".to_string(),
+            params: json!({
+                "condition": "$.input.score >= 7",
+                "then": [{
+                    "id": "escalate",
+                    "action": "prompt",
+                    "params": {"tool": "escalate", "args": {"text": "score {{input.score}}"}, "output_var": "note"}
+                }],
+                "else": [{
+                    "id": "archive",
+                    "action": "prompt",
+                    "params": {"tool": "archive", "args": {"text": "score {{input.score}}"}, "output_var": "note"}
+                }]
+            }),
+        }],
+    }
+}
+
+#[tokio::test]
+async fn test_branch_runs_only_the_matching_side() -> anyhow::Result<()> {
+    let registry = ToolRegistry::new();
+    register_echo_prompt(&registry, "escalate", "escalated ").await;
+    register_echo_prompt(&registry, "archive", "archived ").await;
+    let engine = FlowEngine::new(registry);
+    engine.register_flow(branch_flow()).await;
+
+    let taken = engine.run_flow("triage", json!({"score": 9})).await?;
+    assert_eq!(taken.result?["result"], "escalated score 9");
+    let statuses: Vec<(&str, &StepStatus)> = taken
+        .steps
+        .iter()
+        .map(|s| (s.id.as_str(), &s.status))
+        .collect();
+    assert_eq!(
+        statuses,
+        [
+            ("check", &StepStatus::Succeeded),
+            ("archive", &StepStatus::Skipped),
+            ("escalate", &StepStatus::Succeeded),
+        ]
+    );
+
+    let not_taken = engine.run_flow("triage", json!({"score": 3})).await?;
+    assert_eq!(
+        not_taken.step("escalate").map(|s| &s.status),
+        Some(&StepStatus::Skipped)
+    );
+    assert_eq!(not_taken.result?["result"], "archived score 3");
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_step_succeeds_on_second_attempt() -> anyhow::Result<()> {
+    let registry = ToolRegistry::new();
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = calls.clone();
+    registry
+        .register_tool(
+            "ci",
+            MCPToolDefinition {
+                name: "flaky".to_string(),
+                description: "Fails once".to_string(),
+                input_schema: json!({}),
+            },
+            Box::new(move |_| {
+                let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                Box::pin(async move {
+                    if attempt == 1 {
+                        anyhow::bail!("connection reset");
+                    }
+                    Ok(json!({ "attempt": attempt }))
+                })
+            }),
+        )
+        .await?;
+    let engine = FlowEngine::new(registry);
+    let step = |id: &str, tool: &str| WorkflowStep {
+        id: id.to_string(),
+        action: "call_tool".to_string(),
+        params: json!({"tool": tool, "retry": {"max_attempts": 3, "backoff_ms": 1}}),
+    };
+    engine
+        .register_flow(WorkflowDefinition {
+            id: "retry".to_string(),
+            name: "Retry a flaky tool".to_string(),
+            steps: vec![
+                step("flaky", "flaky"),
+                step("missing", "no_such_tool"),
+                step("after", "flaky"),
+            ],
+        })
+        .await;
+
+    let report = engine.run_flow("retry", json!({})).await?;
+    let flaky = report.step("flaky").expect("flaky step reported");
+    assert_eq!(flaky.status, StepStatus::Succeeded);
+    assert_eq!(flaky.attempts, 2);
+
+    // A missing tool is not retried and stops the run
+    let missing = report.step("missing").expect("missing step reported");
+    assert_eq!(missing.attempts, 1);
+    assert!(matches!(missing.status, StepStatus::Failed(_)));
+    assert_eq!(
+        report.step("after").map(|s| &s.status),
+        Some(&StepStatus::Skipped)
+    );
+    assert!(matches!(report.result, Err(FlowError::Tool { .. })));
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+    Ok(())
+}
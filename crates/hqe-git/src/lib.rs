@@ -8,7 +8,7 @@ use std::path::{Path, PathBuf};
 use std::process::Stdio;
 use thiserror::Error;
 use tokio::process::Command;
-use tracing::{debug, error, info, instrument};
+use tracing::{debug, error, info, instrument, warn};
 
 /// Errors that can occur during git operations
 #[derive(Debug, Error)]
@@ -43,6 +43,10 @@ pub enum GitError {
     #[error("Clone failed: {0}")]
     CloneFailed(String),
 
+    /// A commit was requested but the index has no staged changes
+    #[error("Nothing staged to commit")]
+    NothingStaged,
+
     /// Operation failed
     #[error("Failed to {operation}: {details}")]
     OperationFailed {
@@ -99,6 +103,33 @@ pub struct CommitInfo {
     pub date: String,
 }
 
+/// Options for [`GitRepo::clone_with_options`]
+#[derive(Debug, Clone, Default)]
+pub struct CloneOptions {
+    /// Create a shallow clone truncated to this many commits (`--depth`)
+    pub depth: Option<u32>,
+    /// Branch or tag to check out instead of the remote HEAD (`--branch`)
+    pub branch: Option<String>,
+    /// Only fetch history for the checked-out branch (`--single-branch`)
+    pub single_branch: bool,
+}
+
+impl CloneOptions {
+    fn args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(depth) = self.depth {
+            args.push(format!("--depth={}", depth));
+        }
+        if let Some(branch) = &self.branch {
+            args.push(format!("--branch={}", branch));
+        }
+        if self.single_branch {
+            args.push("--single-branch".to_string());
+        }
+        args
+    }
+}
+
 impl GitRepo {
     /// Open a git repository at the given path
     pub async fn open(path: impl AsRef<Path>) -> Result<Self, GitError> {
@@ -216,6 +247,65 @@ impl GitRepo {
         }
     }
 
+    /// Paths of the files tracked in the index, relative to the repository root
+    pub async fn tracked_files(&self) -> Result<Vec<String>, GitError> {
+        let result = self.run_git(&["ls-files", "-z"]).await?;
+        if result.success {
+            Ok(result
+                .stdout
+                .split('\0')
+                .filter(|path| !path.is_empty())
+                .map(str::to_string)
+                .collect())
+        } else {
+            Err(GitError::OperationFailed {
+                operation: "list tracked files".to_string(),
+                details: result.stderr,
+            })
+        }
+    }
+
+    /// Files changed on this branch since it diverged from `base` (`git diff
+    /// <base>...HEAD`), followed by untracked files that are not ignored.
+    ///
+    /// Paths are relative to the repository root and include files deleted
+    /// since `base`.
+    pub async fn changed_files(&self, base: &str) -> Result<Vec<String>, GitError> {
+        if base.is_empty() || base.starts_with('-') {
+            return Err(GitError::OperationFailed {
+                operation: "list changed files".to_string(),
+                details: format!("invalid base ref '{}'", base),
+            });
+        }
+        let range = format!("{}...HEAD", base);
+        let diff = self
+            .run_git(&["diff", "--name-only", "-z", &range, "--"])
+            .await?;
+        if !diff.success {
+            return Err(GitError::OperationFailed {
+                operation: "list changed files".to_string(),
+                details: diff.stderr,
+            });
+        }
+        let untracked = self
+            .run_git(&["ls-files", "--others", "--exclude-standard", "-z"])
+            .await?;
+        if !untracked.success {
+            return Err(GitError::OperationFailed {
+                operation: "list untracked files".to_string(),
+                details: untracked.stderr,
+            });
+        }
+
+        let mut files: Vec<String> = Vec::new();
+        for path in diff.stdout.split('\0').chain(untracked.stdout.split('\0')) {
+            if !path.is_empty() && !files.iter().any(|f| f == path) {
+                files.push(path.to_string());
+            }
+        }
+        Ok(files)
+    }
+
     /// Get remote URL
     pub async fn remote_url(&self, remote: &str) -> Result<Option<String>, GitError> {
         let result = self.run_git(&["remote", "get-url", remote]).await?;
@@ -372,6 +462,35 @@ impl GitRepo {
         }
     }
 
+    /// Preview a commit without creating it.
+    ///
+    /// Returns the proposed message followed by a diffstat and the full diff of
+    /// the staged changes, i.e. exactly what [`GitRepo::commit`] would record.
+    /// Returns [`GitError::NothingStaged`] if the index matches `HEAD`.
+    pub async fn commit_dry_run(&self, message: &str) -> Result<String, GitError> {
+        let result = self
+            .run_git(&[
+                "diff",
+                "--cached",
+                "--no-color",
+                "--no-ext-diff",
+                "--stat",
+                "--patch",
+            ])
+            .await?;
+        if !result.success {
+            return Err(GitError::OperationFailed {
+                operation: "preview commit".to_string(),
+                details: result.stderr,
+            });
+        }
+        if result.stdout.trim().is_empty() {
+            return Err(GitError::NothingStaged);
+        }
+
+        Ok(format!("{}\n\n{}", message.trim_end(), result.stdout))
+    }
+
     /// Get diff
     pub async fn diff(&self, target: Option<&str>) -> Result<String, GitError> {
         let args = match target {
@@ -390,20 +509,96 @@ impl GitRepo {
         }
     }
 
+    /// List recent commits, newest first.
+    ///
+    /// Returns at most `max_count` commits. `since` is passed to `git log --since`
+    /// (e.g. `"2 weeks ago"` or an ISO date). A repository without commits yields
+    /// an empty list.
+    pub async fn log(
+        &self,
+        max_count: usize,
+        since: Option<&str>,
+    ) -> Result<Vec<CommitInfo>, GitError> {
+        if max_count == 0 {
+            return Ok(Vec::new());
+        }
+
+        // Fields are separated by US (0x1f); `-z` terminates each commit with NUL so
+        // multi-line bodies survive intact.
+        let max_count_arg = format!("--max-count={}", max_count);
+        let mut args = vec![
+            "log",
+            "-z",
+            max_count_arg.as_str(),
+            "--format=%H%x1f%h%x1f%an <%ae>%x1f%aI%x1f%B",
+        ];
+        let since_arg = since.map(|s| format!("--since={}", s));
+        if let Some(since_arg) = &since_arg {
+            args.push(since_arg);
+        }
+
+        let result = self.run_git(&args).await?;
+        if !result.success {
+            if result.stderr.contains("does not have any commits") {
+                return Ok(Vec::new());
+            }
+            return Err(GitError::OperationFailed {
+                operation: "read log".to_string(),
+                details: result.stderr,
+            });
+        }
+
+        Ok(parse_log(&result.stdout, max_count))
+    }
+
     /// Clone a repository
     pub async fn clone(url: &str, target: impl AsRef<Path>) -> Result<Self, GitError> {
+        Self::clone_with_options(url, target, &CloneOptions::default()).await
+    }
+
+    /// Clone a repository with shallow / branch options.
+    ///
+    /// If the server rejects a shallow clone, the clone is retried once without
+    /// `--depth` (branch options are kept).
+    pub async fn clone_with_options(
+        url: &str,
+        target: impl AsRef<Path>,
+        options: &CloneOptions,
+    ) -> Result<Self, GitError> {
         let target = target.as_ref();
 
         info!("Cloning {} into {}", url, target.display());
 
+        match Self::run_clone(url, target, options).await {
+            Err(GitError::CloneFailed(stderr))
+                if options.depth.is_some() && is_shallow_unsupported(&stderr) =>
+            {
+                warn!("Shallow clone rejected by server, retrying with full history");
+                let full = CloneOptions {
+                    depth: None,
+                    ..options.clone()
+                };
+                Self::run_clone(url, target, &full).await?;
+            }
+            result => result?,
+        }
+
+        info!("Clone successful");
+        Self::open(target).await
+    }
+
+    async fn run_clone(url: &str, target: &Path, options: &CloneOptions) -> Result<(), GitError> {
         let result = Command::new("git")
-            .args(["clone", url, &target.to_string_lossy()])
+            .arg("clone")
+            .args(options.args())
+            .arg("--")
+            .arg(url)
+            .arg(target)
             .output()
             .await?;
 
         if result.status.success() {
-            info!("Clone successful");
-            Self::open(target).await
+            Ok(())
         } else {
             let stderr = String::from_utf8_lossy(&result.stderr).to_string();
             Err(GitError::CloneFailed(stderr))
@@ -411,6 +606,37 @@ impl GitRepo {
     }
 }
 
+/// Whether a clone failure means the remote does not support shallow fetches
+fn is_shallow_unsupported(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    stderr.contains("shallow")
+        && (stderr.contains("not support")
+            || stderr.contains("unsupported")
+            || stderr.contains("refusing"))
+}
+
+/// Parse `git log -z` output produced by [`GitRepo::log`]
+fn parse_log(output: &str, max_count: usize) -> Vec<CommitInfo> {
+    output
+        .split('\0')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(5, '\x1f');
+            let hash = fields.next()?.trim();
+            if hash.is_empty() {
+                return None;
+            }
+            Some(CommitInfo {
+                hash: hash.to_string(),
+                short_hash: fields.next()?.to_string(),
+                author: fields.next()?.to_string(),
+                date: fields.next()?.to_string(),
+                message: fields.next().unwrap_or_default().trim_end().to_string(),
+            })
+        })
+        .take(max_count)
+        .collect()
+}
+
 /// Clone a repository from URL
 ///
 /// This is a convenience wrapper around `GitRepo::clone`.
@@ -518,4 +744,247 @@ mod tests {
         assert!(!branch.is_empty());
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_tracked_files_lists_the_index() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let init = Command::new("git")
+            .args(["init"])
+            .current_dir(temp.path())
+            .output()
+            .await?;
+        if !init.status.success() {
+            return Err(anyhow::anyhow!(
+                "git init failed: {}",
+                String::from_utf8_lossy(&init.stderr)
+            ));
+        }
+        tokio::fs::create_dir(temp.path().join("src")).await?;
+        tokio::fs::write(temp.path().join("src/main rs.txt"), "a").await?;
+        tokio::fs::write(temp.path().join("untracked.txt"), "b").await?;
+
+        let repo = GitRepo::open(temp.path()).await?;
+        repo.add(&["src"]).await?;
+        assert_eq!(repo.tracked_files().await?, ["src/main rs.txt"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_changed_files_since_base() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let git = |args: &'static [&'static str]| {
+            let dir = temp.path().to_path_buf();
+            async move {
+                let output = Command::new("git")
+                    .args(["-c", "user.email=test@test.com", "-c", "user.name=Test"])
+                    .args(["-c", "commit.gpgsign=false"])
+                    .args(args)
+                    .current_dir(dir)
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow::anyhow!(
+                        "git {:?} failed: {}",
+                        args,
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                Ok(())
+            }
+        };
+
+        git(&["init"]).await?;
+        tokio::fs::write(temp.path().join("kept.rs"), "a").await?;
+        tokio::fs::write(temp.path().join("edited.rs"), "a").await?;
+        tokio::fs::write(temp.path().join("deleted.rs"), "a").await?;
+        tokio::fs::write(temp.path().join(".gitignore"), "*.log\n").await?;
+        git(&["add", "."]).await?;
+        git(&["commit", "-m", "base"]).await?;
+        git(&["tag", "base"]).await?;
+
+        tokio::fs::write(temp.path().join("edited.rs"), "b").await?;
+        tokio::fs::remove_file(temp.path().join("deleted.rs")).await?;
+        git(&["add", "-A"]).await?;
+        git(&["commit", "-m", "change"]).await?;
+        tokio::fs::write(temp.path().join("new.rs"), "c").await?;
+        tokio::fs::write(temp.path().join("debug.log"), "d").await?;
+
+        let repo = GitRepo::open(temp.path()).await?;
+        assert_eq!(
+            repo.changed_files("base").await?,
+            ["deleted.rs", "edited.rs", "new.rs"]
+        );
+        assert!(repo.changed_files("--output=x").await.is_err());
+        assert!(repo.changed_files("no-such-ref").await.is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_log_multiline_bodies() {
+        let output = concat!(
+            "aaa111\x1faaa\x1fAda <ada@example.com>\x1f2024-01-02T03:04:05+00:00\x1fSubject\n\nBody line 1\nBody line 2\n\0",
+            "bbb222\x1fbbb\x1fBob <bob@example.com>\x1f2024-01-01T00:00:00+00:00\x1fFirst\n\0",
+        );
+
+        let commits = parse_log(output, 10);
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].short_hash, "aaa");
+        assert_eq!(commits[0].author, "Ada <ada@example.com>");
+        assert_eq!(commits[0].message, "Subject\n\nBody line 1\nBody line 2");
+        assert_eq!(commits[1].message, "First");
+
+        assert_eq!(parse_log(output, 1).len(), 1);
+        assert!(parse_log("", 5).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_log_reads_commits() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let git = |args: Vec<&'static str>| {
+            let dir = temp.path().to_path_buf();
+            async move {
+                let output = Command::new("git")
+                    .args(["-c", "user.email=test@test.com", "-c", "user.name=Test"])
+                    .args(["-c", "commit.gpgsign=false"])
+                    .args(&args)
+                    .current_dir(dir)
+                    .output()
+                    .await?;
+                if !output.status.success() {
+                    return Err(anyhow::anyhow!(
+                        "git {:?} failed: {}",
+                        args,
+                        String::from_utf8_lossy(&output.stderr)
+                    ));
+                }
+                Ok(())
+            }
+        };
+
+        git(vec!["init"]).await?;
+        let repo = GitRepo::open(temp.path()).await?;
+        assert!(repo.log(5, None).await?.is_empty());
+
+        git(vec!["commit", "--allow-empty", "-m", "First"]).await?;
+        git(vec![
+            "commit",
+            "--allow-empty",
+            "-m",
+            "Second\n\nWith a longer body",
+        ])
+        .await?;
+
+        let commits = repo.log(5, None).await?;
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].message, "Second\n\nWith a longer body");
+        assert_eq!(commits[0].author, "Test <test@test.com>");
+        assert_eq!(commits[0].hash.len(), 40);
+        assert!(commits[0].hash.starts_with(&commits[0].short_hash));
+
+        assert_eq!(repo.log(1, None).await?.len(), 1);
+        assert_eq!(repo.log(5, Some("1 week ago")).await?.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_clone_options_args_and_shallow_detection() {
+        let options = CloneOptions {
+            depth: Some(1),
+            branch: Some("main".to_string()),
+            single_branch: true,
+        };
+        assert_eq!(
+            options.args(),
+            vec!["--depth=1", "--branch=main", "--single-branch"]
+        );
+        assert!(CloneOptions::default().args().is_empty());
+
+        assert!(is_shallow_unsupported(
+            "fatal: dumb http transport does not support shallow capabilities"
+        ));
+        assert!(is_shallow_unsupported(
+            "fatal: Server does not support shallow clients"
+        ));
+        assert!(!is_shallow_unsupported("fatal: repository 'x' not found"));
+    }
+
+    #[tokio::test]
+    async fn test_clone_with_options_shallow_branch() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let source = temp.path().join("source");
+        std::fs::create_dir(&source)?;
+        for args in [
+            vec!["init", "-b", "main"],
+            vec!["commit", "--allow-empty", "-m", "First"],
+            vec!["commit", "--allow-empty", "-m", "Second"],
+            vec!["checkout", "-b", "feature"],
+            vec!["commit", "--allow-empty", "-m", "Feature"],
+        ] {
+            let output = Command::new("git")
+                .args(["-c", "user.email=test@test.com", "-c", "user.name=Test"])
+                .args(["-c", "commit.gpgsign=false"])
+                .args(&args)
+                .current_dir(&source)
+                .output()
+                .await?;
+            anyhow::ensure!(
+                output.status.success(),
+                "git {:?} failed: {}",
+                args,
+                String::from_utf8_lossy(&output.stderr)
+            );
+        }
+
+        // Local paths ignore --depth; a file:// URL goes through the transport.
+        let url = format!("file://{}", source.display());
+        let shallow = GitRepo::clone_with_options(
+            &url,
+            temp.path().join("shallow"),
+            &CloneOptions {
+                depth: Some(1),
+                branch: Some("main".to_string()),
+                single_branch: true,
+            },
+        )
+        .await?;
+        assert_eq!(shallow.current_branch().await?, "main");
+        let commits = shallow.log(10, None).await?;
+        assert_eq!(commits.len(), 1);
+        assert_eq!(commits[0].message, "Second");
+
+        let full = clone_repo(&url, temp.path().join("full")).await?;
+        assert_eq!(full.log(10, None).await?.len(), 3);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_commit_dry_run_previews_staged_changes() -> anyhow::Result<()> {
+        let temp = TempDir::new()?;
+        let status = Command::new("git")
+            .arg("init")
+            .current_dir(temp.path())
+            .output()
+            .await?
+            .status;
+        anyhow::ensure!(status.success(), "git init failed");
+
+        let repo = GitRepo::open(temp.path()).await?;
+        assert!(matches!(
+            repo.commit_dry_run("Empty").await,
+            Err(GitError::NothingStaged)
+        ));
+
+        tokio::fs::write(temp.path().join("fix.txt"), "patched\n").await?;
+        repo.add(&["fix.txt"]).await?;
+
+        let preview = repo.commit_dry_run("Apply HQE fix\n").await?;
+        assert!(preview.starts_with("Apply HQE fix\n\n"));
+        assert!(preview.contains("fix.txt | 1 +"));
+        assert!(preview.contains("+patched"));
+
+        // Nothing was committed and the change is still staged
+        assert!(repo.log(5, None).await?.is_empty());
+        assert!(repo.status().await?.contains("A  fix.txt"));
+        Ok(())
+    }
 }
@@ -9,8 +9,8 @@ pub mod loader;
 /// File watcher and event handling
 pub mod watcher;
 
-pub use loader::TopicLoader;
-pub use watcher::{IngestEngine, IngestEvent};
+pub use loader::{ManifestErrors, TopicLoader, ValidationIssue};
+pub use watcher::{IngestEngine, IngestEvent, DEFAULT_DEBOUNCE_WINDOW};
 
 /// Initialize the ingestion subsystem
 pub fn init() {
@@ -1,14 +1,112 @@
 use anyhow::{Context, Result};
 use hqe_protocol::models::TopicManifest;
-use std::path::Path;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
 use tokio::fs;
 
 /// Helper for loading topic manifests
 pub struct TopicLoader;
 
+/// A single problem found in a topic manifest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidationIssue {
+    /// Manifest file the issue was found in, when known
+    pub file: Option<PathBuf>,
+    /// Path of the offending field, e.g. `capabilities.tools[1].name`;
+    /// empty when the document could not be parsed at all
+    pub field: String,
+    /// What is wrong with the field
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self {
+            file: None,
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(file) = &self.file {
+            write!(f, "{}: ", file.display())?;
+        }
+        if !self.field.is_empty() {
+            write!(f, "{}: ", self.field)?;
+        }
+        write!(f, "{}", self.message)
+    }
+}
+
+/// Every issue found while loading one or more manifests
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestErrors(pub Vec<ValidationIssue>);
+
+impl fmt::Display for ManifestErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} manifest issue(s)", self.0.len())?;
+        for issue in &self.0 {
+            write!(f, "\n  - {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ManifestErrors {}
+
 impl TopicLoader {
+    /// Loads every topic directory directly below `root`.
+    ///
+    /// All manifests are read and validated before returning, so a single
+    /// error lists the issues of every broken topic instead of the first one.
+    pub async fn load(root: &Path) -> Result<Vec<TopicManifest>, ManifestErrors> {
+        let mut dirs = Vec::new();
+        let mut entries = fs::read_dir(root).await.map_err(|e| {
+            ManifestErrors(vec![ValidationIssue {
+                file: Some(root.to_path_buf()),
+                field: String::new(),
+                message: format!("Failed to read topic directory: {e}"),
+            }])
+        })?;
+        while let Ok(Some(entry)) = entries.next_entry().await {
+            if entry.path().is_dir() {
+                dirs.push(entry.path());
+            }
+        }
+        dirs.sort();
+
+        let mut manifests = Vec::new();
+        let mut issues = Vec::new();
+        for dir in dirs {
+            match Self::load_from_path(&dir).await {
+                Ok(manifest) => manifests.push(manifest),
+                Err(e) => match e.downcast::<ManifestErrors>() {
+                    Ok(errors) => issues.extend(errors.0),
+                    Err(e) => issues.push(ValidationIssue {
+                        file: Some(dir),
+                        field: String::new(),
+                        message: e.to_string(),
+                    }),
+                },
+            }
+        }
+
+        if issues.is_empty() {
+            Ok(manifests)
+        } else {
+            Err(ManifestErrors(issues))
+        }
+    }
+
     /// Loads and parses a TopicManifest from a given path (directory or file).
     /// If a directory is provided, it looks for `manifest.yaml` or `manifest.json`.
+    ///
+    /// Parse and validation failures are returned as [`ManifestErrors`].
     pub async fn load_from_path(path: &Path) -> Result<TopicManifest> {
         let manifest_path = if path.is_dir() {
             let yaml = path.join("manifest.yaml");
@@ -32,13 +130,183 @@ impl TopicLoader {
             .await
             .with_context(|| format!("Failed to read manifest file at {:?}", manifest_path))?;
 
-        let manifest: TopicManifest = if manifest_path.extension().is_some_and(|ext| ext == "json")
-        {
-            serde_json::from_str(&content)?
-        } else {
-            serde_yaml::from_str(&content)?
+        let parsed: Result<TopicManifest, String> =
+            if manifest_path.extension().is_some_and(|ext| ext == "json") {
+                serde_json::from_str(&content).map_err(|e| e.to_string())
+            } else {
+                serde_yaml::from_str(&content).map_err(|e| e.to_string())
+            };
+
+        let in_file = |mut issue: ValidationIssue| {
+            issue.file = Some(manifest_path.clone());
+            issue
         };
+        let manifest = parsed
+            .map_err(|message| ManifestErrors(vec![in_file(ValidationIssue::new("", message))]))?;
+        Self::validate(&manifest)
+            .map_err(|issues| ManifestErrors(issues.into_iter().map(in_file).collect()))?;
 
         Ok(manifest)
     }
+
+    /// Checks a parsed manifest for problems serde cannot catch: empty
+    /// required fields, duplicate names and invalid JSON Schemas.
+    ///
+    /// Returns every issue found rather than stopping at the first.
+    pub fn validate(manifest: &TopicManifest) -> Result<(), Vec<ValidationIssue>> {
+        let mut issues = Vec::new();
+
+        require(&mut issues, "id", &manifest.id);
+        require(&mut issues, "name", &manifest.name);
+        require(&mut issues, "version", &manifest.version);
+
+        let capabilities = &manifest.capabilities;
+        let mut tool_names = HashMap::new();
+        for (idx, tool) in capabilities.tools.iter().enumerate() {
+            let field = format!("capabilities.tools[{idx}]");
+            require(&mut issues, &format!("{field}.name"), &tool.name);
+            require(
+                &mut issues,
+                &format!("{field}.description"),
+                &tool.description,
+            );
+            check_schema(
+                &mut issues,
+                &format!("{field}.input_schema"),
+                &tool.input_schema,
+            );
+            unique(&mut issues, &mut tool_names, &field, "name", &tool.name);
+        }
+
+        let mut prompt_names = HashMap::new();
+        for (idx, prompt) in capabilities.prompts.iter().enumerate() {
+            let field = format!("capabilities.prompts[{idx}]");
+            require(&mut issues, &format!("{field}.name"), &prompt.name);
+            require(&mut issues, &format!("{field}.template"), &prompt.template);
+            unique(&mut issues, &mut prompt_names, &field, "name", &prompt.name);
+        }
+
+        let mut flow_ids = HashMap::new();
+        for (idx, flow) in capabilities.flows.iter().enumerate() {
+            let field = format!("capabilities.flows[{idx}]");
+            require(&mut issues, &format!("{field}.id"), &flow.id);
+            unique(&mut issues, &mut flow_ids, &field, "id", &flow.id);
+        }
+
+        let mut kinds: Vec<_> = manifest.data_schemas.iter().collect();
+        kinds.sort_by(|a, b| a.0.cmp(b.0));
+        for (kind, schema) in kinds {
+            check_schema(&mut issues, &format!("data_schemas.{kind}"), schema);
+        }
+
+        if issues.is_empty() {
+            Ok(())
+        } else {
+            Err(issues)
+        }
+    }
+}
+
+fn require(issues: &mut Vec<ValidationIssue>, field: &str, value: &str) {
+    if value.trim().is_empty() {
+        issues.push(ValidationIssue::new(field, "must not be empty"));
+    }
+}
+
+/// Records the `key` value of the entry at `entry`, flagging it if an
+/// earlier entry of the same list used it
+fn unique(
+    issues: &mut Vec<ValidationIssue>,
+    seen: &mut HashMap<String, String>,
+    entry: &str,
+    key: &str,
+    value: &str,
+) {
+    if value.trim().is_empty() {
+        return;
+    }
+    match seen.get(value) {
+        Some(first) => issues.push(ValidationIssue::new(
+            format!("{entry}.{key}"),
+            format!("duplicate {key} '{value}' (first defined at {first})"),
+        )),
+        None => {
+            seen.insert(value.to_string(), entry.to_string());
+        }
+    }
+}
+
+fn check_schema(issues: &mut Vec<ValidationIssue>, field: &str, schema: &Value) {
+    if let Err(e) = jsonschema::validator_for(schema) {
+        issues.push(ValidationIssue::new(
+            field,
+            format!("invalid JSON Schema: {e}"),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hqe_protocol::models::{MCPToolDefinition, TopicCapabilities};
+    use serde_json::json;
+
+    fn tool(name: &str) -> MCPToolDefinition {
+        MCPToolDefinition {
+            name: name.to_string(),
+            description: format!("Runs {name}"),
+            input_schema: json!({ "type": "object" }),
+        }
+    }
+
+    fn manifest(tools: Vec<MCPToolDefinition>) -> TopicManifest {
+        TopicManifest {
+            id: "finance".to_string(),
+            name: "Finance".to_string(),
+            version: "1.0.0".to_string(),
+            capabilities: TopicCapabilities {
+                tools,
+                prompts: vec![],
+                flows: vec![],
+            },
+            data_schemas: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_validate_reports_duplicate_tool_names() {
+        let manifest = manifest(vec![tool("quote"), tool("history"), tool("quote")]);
+
+        let issues = TopicLoader::validate(&manifest).unwrap_err();
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].field, "capabilities.tools[2].name");
+        assert!(issues[0]
+            .message
+            .contains("duplicate name 'quote' (first defined at capabilities.tools[0])"));
+    }
+
+    #[test]
+    fn test_validate_collects_all_issues() {
+        let mut manifest = manifest(vec![tool("quote")]);
+        manifest.version = String::new();
+        manifest.capabilities.tools[0].input_schema = json!({ "type": "nonsense" });
+        manifest
+            .data_schemas
+            .insert("Ticker".to_string(), json!({ "minLength": "three" }));
+
+        let fields: Vec<String> = TopicLoader::validate(&manifest)
+            .unwrap_err()
+            .into_iter()
+            .map(|issue| issue.field)
+            .collect();
+        assert_eq!(
+            fields,
+            [
+                "version",
+                "capabilities.tools[0].input_schema",
+                "data_schemas.Ticker"
+            ]
+        );
+        assert!(TopicLoader::validate(&self::manifest(vec![tool("quote")])).is_ok());
+    }
 }
@@ -3,7 +3,9 @@ use notify::{Event, RecursiveMode, Watcher};
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::sync::{mpsc, RwLock};
+use tokio::time::Instant;
 use tracing::{error, info, warn};
 
 use crate::loader::TopicLoader;
@@ -19,12 +21,16 @@ pub enum IngestEvent {
     Error(String),
 }
 
+/// Default quiet period before a burst of changes to one manifest is processed
+pub const DEFAULT_DEBOUNCE_WINDOW: Duration = Duration::from_millis(200);
+
 /// The main ingestion engine that watches for file changes
 pub struct IngestEngine {
     root_path: PathBuf,
     event_tx: mpsc::Sender<IngestEvent>,
     /// Tracks loaded topics: manifest_path -> topic_id
     topic_map: Arc<RwLock<HashMap<PathBuf, String>>>,
+    debounce_window: Duration,
 }
 
 impl IngestEngine {
@@ -34,20 +40,34 @@ impl IngestEngine {
             root_path,
             event_tx,
             topic_map: Arc::new(RwLock::new(HashMap::new())),
+            debounce_window: DEFAULT_DEBOUNCE_WINDOW,
         }
     }
 
+    /// Set how long a manifest must stay quiet before its changes are processed.
+    ///
+    /// Editors typically save through several writes or a temp-file rename;
+    /// every notification inside the window collapses into one reload.
+    pub fn with_debounce_window(mut self, window: Duration) -> Self {
+        self.debounce_window = window;
+        self
+    }
+
     /// Starts the file watcher and processes events.
     /// This function runs indefinitely until the channel is closed.
+    ///
+    /// Notifications are coalesced per manifest path: once a path has been
+    /// quiet for the debounce window it is reloaded if it exists and reported
+    /// as removed otherwise, which also covers atomic saves that write a temp
+    /// file and rename it over the manifest.
     pub async fn start(&self) -> Result<()> {
         let (tx, mut rx) = mpsc::channel(100);
-        let tx_clone = tx.clone();
 
         // Bridge notify (sync) to tokio channel
         let mut watcher =
             notify::recommended_watcher(move |res: Result<Event, notify::Error>| match res {
                 Ok(event) => {
-                    let _ = tx_clone.blocking_send(event);
+                    let _ = tx.blocking_send(event);
                 }
                 Err(e) => {
                     error!("Watch error: {:?}", e);
@@ -57,28 +77,36 @@ impl IngestEngine {
         watcher.watch(&self.root_path, RecursiveMode::Recursive)?;
         info!("Ingestion Engine watching: {:?}", self.root_path);
 
-        // Process file system events
-        while let Some(event) = rx.recv().await {
-            match event.kind {
-                notify::EventKind::Create(_) | notify::EventKind::Modify(_) => {
-                    for path in event.paths {
-                        // We only care about manifest files for now
-                        if is_manifest_file(&path) {
-                            self.process_manifest_change(&path).await;
+        let mut debouncer = Debouncer::new(self.debounce_window);
+
+        loop {
+            let deadline = debouncer.next_deadline();
+            tokio::select! {
+                received = rx.recv() => match received {
+                    Some(event) => {
+                        if !is_relevant_kind(&event.kind) {
+                            continue;
                         }
-                    }
-                }
-                notify::EventKind::Remove(_) => {
-                    for path in event.paths {
-                        if is_manifest_file(&path) {
-                            self.process_manifest_removal(&path).await;
+                        // Rename events carry both the source and destination;
+                        // we only care about manifest files for now
+                        for path in event.paths.into_iter().filter(|p| is_manifest_file(p)) {
+                            debouncer.record(path, Instant::now());
                         }
                     }
+                    None => break,
+                },
+                _ = sleep_until_deadline(deadline) => {
+                    for path in debouncer.take_due(Instant::now()) {
+                        self.process_settled_path(&path).await;
+                    }
                 }
-                _ => {}
             }
         }
 
+        for path in debouncer.drain() {
+            self.process_settled_path(&path).await;
+        }
+
         Ok(())
     }
 
@@ -95,6 +123,15 @@ impl IngestEngine {
         Ok(())
     }
 
+    /// Resolves a debounced path against the filesystem's final state.
+    async fn process_settled_path(&self, path: &Path) {
+        if path.exists() {
+            self.process_manifest_change(path).await;
+        } else {
+            self.process_manifest_removal(path).await;
+        }
+    }
+
     async fn process_manifest_change(&self, path: &Path) {
         info!("Processing potential topic at: {:?}", path);
         match TopicLoader::load_from_path(path).await {
@@ -155,9 +192,110 @@ impl IngestEngine {
     }
 }
 
+/// Collects filesystem notifications and releases each path once it has been
+/// quiet for the configured window.
+struct Debouncer {
+    window: Duration,
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl Debouncer {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Records activity on a path, pushing its deadline out by a full window.
+    fn record(&mut self, path: PathBuf, now: Instant) {
+        self.pending.insert(path, now + self.window);
+    }
+
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending.values().min().copied()
+    }
+
+    /// Removes and returns every path whose deadline has passed.
+    fn take_due(&mut self, now: Instant) -> Vec<PathBuf> {
+        let mut due: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, deadline)| **deadline <= now)
+            .map(|(path, _)| path.clone())
+            .collect();
+        due.sort();
+        for path in &due {
+            self.pending.remove(path);
+        }
+        due
+    }
+
+    fn drain(&mut self) -> Vec<PathBuf> {
+        let mut paths: Vec<PathBuf> = self.pending.drain().map(|(path, _)| path).collect();
+        paths.sort();
+        paths
+    }
+}
+
+async fn sleep_until_deadline(deadline: Option<Instant>) {
+    match deadline {
+        Some(deadline) => tokio::time::sleep_until(deadline).await,
+        None => std::future::pending().await,
+    }
+}
+
+fn is_relevant_kind(kind: &notify::EventKind) -> bool {
+    matches!(
+        kind,
+        notify::EventKind::Create(_) | notify::EventKind::Modify(_) | notify::EventKind::Remove(_)
+    )
+}
+
 fn is_manifest_file(path: &Path) -> bool {
     if let Some(name) = path.file_name() {
         return name == "manifest.yaml" || name == "manifest.json";
     }
     false
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debouncer_coalesces_burst_per_path() {
+        let window = Duration::from_millis(100);
+        let mut debouncer = Debouncer::new(window);
+        let start = Instant::now();
+        let manifest = PathBuf::from("/topics/a/manifest.yaml");
+        let other = PathBuf::from("/topics/b/manifest.yaml");
+
+        debouncer.record(manifest.clone(), start);
+        debouncer.record(manifest.clone(), start + Duration::from_millis(40));
+        debouncer.record(other.clone(), start + Duration::from_millis(50));
+        debouncer.record(manifest.clone(), start + Duration::from_millis(80));
+
+        // The first write's window has passed, but later writes pushed it out
+        assert!(debouncer
+            .take_due(start + Duration::from_millis(120))
+            .is_empty());
+        assert_eq!(
+            debouncer.take_due(start + Duration::from_millis(150)),
+            vec![other]
+        );
+        assert_eq!(
+            debouncer.take_due(start + Duration::from_millis(180)),
+            vec![manifest]
+        );
+        assert!(debouncer.next_deadline().is_none());
+    }
+
+    #[test]
+    fn test_is_manifest_file_ignores_temp_files() {
+        assert!(is_manifest_file(Path::new("/t/manifest.yaml")));
+        assert!(is_manifest_file(Path::new("/t/manifest.json")));
+        assert!(!is_manifest_file(Path::new("/t/manifest.yaml.tmp")));
+        assert!(!is_manifest_file(Path::new("/t/.manifest.yaml.swp")));
+    }
+}
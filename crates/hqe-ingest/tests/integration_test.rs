@@ -1,4 +1,6 @@
-use hqe_ingest::{IngestEngine, IngestEvent};
+use hqe_ingest::{IngestEngine, IngestEvent, TopicLoader};
+use std::path::Path;
+use std::sync::Arc;
 use std::time::Duration;
 use tempfile::TempDir;
 use tokio::sync::mpsc;
@@ -51,3 +53,144 @@ data_schemas: {}
         _ => panic!("Received unexpected event or channel closed"),
     }
 }
+
+const WINDOW: Duration = Duration::from_millis(300);
+
+fn manifest_yaml(name: &str) -> String {
+    format!(
+        r#"
+id: "watch-test"
+name: "{name}"
+version: "1.0.0"
+capabilities:
+  tools: []
+  prompts: []
+  flows: []
+data_schemas: {{}}
+"#
+    )
+}
+
+/// Spawns a watching engine over `root` and waits for the watcher to attach.
+async fn start_watching(root: &Path) -> mpsc::Receiver<IngestEvent> {
+    let (tx, rx) = mpsc::channel(10);
+    let engine = Arc::new(IngestEngine::new(root.to_path_buf(), tx).with_debounce_window(WINDOW));
+    tokio::spawn(async move {
+        let _ = engine.start().await;
+    });
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    rx
+}
+
+async fn expect_event(rx: &mut mpsc::Receiver<IngestEvent>) -> IngestEvent {
+    tokio::time::timeout(Duration::from_secs(5), rx.recv())
+        .await
+        .expect("Timed out waiting for event")
+        .expect("Event channel closed")
+}
+
+async fn expect_quiet(rx: &mut mpsc::Receiver<IngestEvent>) {
+    if let Ok(Some(event)) = tokio::time::timeout(WINDOW * 3, rx.recv()).await {
+        match event {
+            IngestEvent::TopicLoaded(m) => panic!("Unexpected extra load of {}", m.name),
+            IngestEvent::TopicRemoved(id) => panic!("Unexpected removal of {}", id),
+            IngestEvent::Error(e) => panic!("Unexpected error event: {}", e),
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_watch_coalesces_burst_of_writes() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let topic_dir = temp_dir.path().join("watch-test");
+    tokio::fs::create_dir(&topic_dir)
+        .await
+        .expect("Failed to create topic dir");
+    let mut rx = start_watching(temp_dir.path()).await;
+
+    let manifest = topic_dir.join("manifest.yaml");
+    for name in ["first", "second", "third"] {
+        tokio::fs::write(&manifest, manifest_yaml(name))
+            .await
+            .expect("Failed to write manifest");
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+
+    match expect_event(&mut rx).await {
+        IngestEvent::TopicLoaded(m) => assert_eq!(m.name, "third"),
+        IngestEvent::TopicRemoved(id) => panic!("Unexpected removal of {}", id),
+        IngestEvent::Error(e) => panic!("Received error event: {}", e),
+    }
+    expect_quiet(&mut rx).await;
+}
+
+#[tokio::test]
+async fn test_watch_handles_atomic_save_and_removal() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let topic_dir = temp_dir.path().join("watch-test");
+    tokio::fs::create_dir(&topic_dir)
+        .await
+        .expect("Failed to create topic dir");
+    let mut rx = start_watching(temp_dir.path()).await;
+
+    // Editors commonly write a sibling temp file and rename it into place
+    let manifest = topic_dir.join("manifest.yaml");
+    let temp_file = topic_dir.join("manifest.yaml.tmp");
+    tokio::fs::write(&temp_file, manifest_yaml("renamed"))
+        .await
+        .expect("Failed to write temp manifest");
+    tokio::fs::rename(&temp_file, &manifest)
+        .await
+        .expect("Failed to rename manifest");
+
+    match expect_event(&mut rx).await {
+        IngestEvent::TopicLoaded(m) => assert_eq!(m.name, "renamed"),
+        IngestEvent::TopicRemoved(id) => panic!("Unexpected removal of {}", id),
+        IngestEvent::Error(e) => panic!("Received error event: {}", e),
+    }
+    expect_quiet(&mut rx).await;
+
+    tokio::fs::remove_file(&manifest)
+        .await
+        .expect("Failed to remove manifest");
+    match expect_event(&mut rx).await {
+        IngestEvent::TopicRemoved(id) => assert_eq!(id, "watch-test"),
+        IngestEvent::TopicLoaded(m) => panic!("Unexpected load of {}", m.name),
+        IngestEvent::Error(e) => panic!("Received error event: {}", e),
+    }
+}
+
+#[tokio::test]
+async fn test_load_aggregates_issues_from_every_manifest() {
+    let temp_dir = TempDir::new().expect("Failed to create temp dir");
+    let root = temp_dir.path();
+    for (dir, content) in [
+        ("good", manifest_yaml("Good")),
+        ("empty-name", manifest_yaml("")),
+        ("broken", "id: [unterminated".to_string()),
+    ] {
+        tokio::fs::create_dir(root.join(dir))
+            .await
+            .expect("Failed to create topic dir");
+        tokio::fs::write(root.join(dir).join("manifest.yaml"), content)
+            .await
+            .expect("Failed to write manifest");
+    }
+
+    let errors = TopicLoader::load(root)
+        .await
+        .expect_err("Broken manifests should fail the load");
+    let files: Vec<_> = errors
+        .0
+        .iter()
+        .map(|issue| issue.file.clone().unwrap_or_default())
+        .collect();
+    assert_eq!(
+        files,
+        [
+            root.join("broken/manifest.yaml"),
+            root.join("empty-name/manifest.yaml")
+        ]
+    );
+    assert_eq!(errors.0[1].field, "name");
+}
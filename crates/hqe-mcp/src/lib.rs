@@ -10,10 +10,13 @@ pub mod loader;
 pub mod registry;
 /// Enhanced prompt registry (v2)
 pub mod registry_v2;
+/// Prompt template substitution
+pub mod template;
 
 pub use loader::*;
 pub use registry::*;
 pub use registry_v2::*;
+pub use template::*;
 
 /// Initialize the MCP subsystem
 pub fn init() {
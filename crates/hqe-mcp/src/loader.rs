@@ -1,3 +1,4 @@
+use crate::template::{template_placeholders, unresolved_placeholders};
 use hqe_protocol::models::MCPToolDefinition;
 use serde::{de::Error, Deserialize};
 use serde_json::json;
@@ -51,6 +52,32 @@ pub enum LoaderError {
     /// Failed to strip prefix
     #[error("Failed to strip prefix from path: {0}")]
     StripPrefix(#[from] std::path::StripPrefixError),
+
+    /// Arguments do not match the prompt's declared input schema
+    #[error("Invalid arguments for prompt '{tool}': {}", describe_arg_problems(.missing, .unknown, .unresolved))]
+    InvalidArgs {
+        /// Tool name
+        tool: String,
+        /// Required arguments that were not provided
+        missing: Vec<String>,
+        /// Provided arguments the prompt neither declares nor references
+        unknown: Vec<String>,
+        /// Placeholders left without a value (only reported in strict mode)
+        unresolved: Vec<String>,
+    },
+}
+
+fn describe_arg_problems(missing: &[String], unknown: &[String], unresolved: &[String]) -> String {
+    [
+        ("missing required", missing),
+        ("unknown", unknown),
+        ("unresolved placeholders", unresolved),
+    ]
+    .iter()
+    .filter(|(_, names)| !names.is_empty())
+    .map(|(label, names)| format!("{label}: {}", names.join(", ")))
+    .collect::<Vec<_>>()
+    .join("; ")
 }
 
 /// A loaded prompt file parsed from disk
@@ -148,6 +175,61 @@ impl PromptLoader {
         Ok(tools)
     }
 
+    /// Check `args` against the tool's input schema and template before execution.
+    ///
+    /// Fails when a required argument is missing or a provided argument is
+    /// neither declared in the schema nor referenced by the template (usually a
+    /// typo). With `strict`, placeholders that would stay as literal `{{name}}`
+    /// in the prompt (outside skipped `{{#if}}` blocks) are an error too; otherwise they pass through.
+    pub fn validate_args(
+        tool: &LoadedPromptTool,
+        args: &serde_json::Value,
+        strict: bool,
+    ) -> Result<(), LoaderError> {
+        let schema = &tool.definition.input_schema;
+        let declared: Vec<&str> = schema
+            .get("properties")
+            .and_then(|p| p.as_object())
+            .map(|p| p.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        let required = schema
+            .get("required")
+            .and_then(|r| r.as_array())
+            .map(|r| r.iter().filter_map(|v| v.as_str()).collect::<Vec<_>>())
+            .unwrap_or_default();
+        let provided: Vec<&str> = args
+            .as_object()
+            .map(|a| a.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        let placeholders = template_placeholders(&tool.template);
+
+        let missing: Vec<String> = required
+            .iter()
+            .filter(|name| !provided.contains(name))
+            .map(|name| name.to_string())
+            .collect();
+        let unknown: Vec<String> = provided
+            .iter()
+            .filter(|name| !declared.contains(name) && !placeholders.iter().any(|p| p == *name))
+            .map(|name| name.to_string())
+            .collect();
+        let unresolved: Vec<String> = if strict {
+            unresolved_placeholders(&tool.template, args)
+        } else {
+            Vec::new()
+        };
+
+        if missing.is_empty() && unknown.is_empty() && unresolved.is_empty() {
+            return Ok(());
+        }
+        Err(LoaderError::InvalidArgs {
+            tool: tool.definition.name.clone(),
+            missing,
+            unknown,
+            unresolved,
+        })
+    }
+
     /// Clear cached prompt results (used when refreshing prompts).
     pub fn clear_cache(root_path: impl AsRef<Path>) {
         if let Ok(mut cache) = PROMPT_CACHE
@@ -273,6 +355,16 @@ impl PromptLoader {
         // Validate the prompt template for malicious content
         Self::validate_prompt_template(&prompt_file.prompt)?;
 
+        for placeholder in template_placeholders(&prompt_file.prompt) {
+            if !properties.contains_key(&placeholder) {
+                warn!(
+                    "Prompt {} references undeclared argument '{{{{{}}}}}'",
+                    path.display(),
+                    placeholder
+                );
+            }
+        }
+
         Ok(LoadedPromptTool {
             definition: MCPToolDefinition {
                 name,
@@ -429,4 +521,39 @@ prompt = "NOPE"
         assert_eq!(tools.len(), 1);
         assert_eq!(tools[0].definition.name, "valid");
     }
+
+    #[test]
+    fn validate_args_reports_missing_unknown_and_undeclared() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        fs::write(
+            dir.path().join("greet.toml"),
+            r#"
+description = "Greeting"
+prompt = "Hello {{name}}, welcome to {{team}}"
+
+[[args]]
+name = "name"
+"#,
+        )
+        .expect("write prompt");
+        let tools = PromptLoader::new(dir.path()).load().expect("load prompts");
+        let tool = &tools[0];
+        assert_eq!(template_placeholders(&tool.template), ["name", "team"]);
+
+        // `team` is referenced but not declared: it passes through unless strict
+        PromptLoader::validate_args(tool, &json!({"name": "Ada"}), false).expect("lenient");
+        match PromptLoader::validate_args(tool, &json!({"name": "Ada"}), true) {
+            Err(LoaderError::InvalidArgs { unresolved, .. }) => assert_eq!(unresolved, ["team"]),
+            other => panic!("expected unresolved placeholder, got {other:?}"),
+        }
+        PromptLoader::validate_args(tool, &json!({"name": "Ada", "team": "core"}), true)
+            .expect("all placeholders resolved");
+
+        let err = PromptLoader::validate_args(tool, &json!({"nmae": "Ada"}), false)
+            .expect_err("typo in arg name");
+        assert_eq!(
+            err.to_string(),
+            "Invalid arguments for prompt 'greet': missing required: name; unknown: nmae"
+        );
+    }
 }
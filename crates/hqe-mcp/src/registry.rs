@@ -1,11 +1,12 @@
 use anyhow::Result;
 use hqe_protocol::models::MCPToolDefinition;
 use jsonschema::Validator;
-use serde_json::Value;
+use serde_json::{json, Value};
 use std::collections::HashMap;
 use std::future::Future;
 use std::pin::Pin;
 use std::sync::Arc;
+use std::time::Duration;
 use thiserror::Error;
 use tokio::sync::RwLock;
 use tracing::{debug, warn};
@@ -15,6 +16,9 @@ use tracing::{debug, warn};
 pub type ToolHandler =
     Box<dyn Fn(Value) -> Pin<Box<dyn Future<Output = Result<Value>> + Send>> + Send + Sync>;
 
+/// How long [`ToolRegistry::health_check`] waits for one handler
+pub const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
 /// Registry for all available MCP tools across all topics.
 #[derive(Default, Clone)]
 pub struct ToolRegistry {
@@ -29,6 +33,8 @@ pub struct RegisteredTool {
     pub handler: ToolHandler,
     /// Topic that registered this tool
     pub topic_id: String,
+    /// Calling the tool has no side effects, so health checks may invoke it
+    pub idempotent: bool,
     /// Compiled JSON schema for validation
     schema_validator: Option<Validator>,
 }
@@ -48,6 +54,20 @@ pub enum ToolError {
     /// Handler execution failed
     #[error("Execution error: {0}")]
     ExecutionError(String),
+    /// A bare tool name matches tools of several topics
+    #[error("Ambiguous tool name '{0}', use one of: {1}")]
+    Ambiguous(String, String),
+}
+
+/// Result of [`ToolRegistry::health_check`] for one tool
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToolHealth {
+    /// The handler answered a minimal call
+    Healthy,
+    /// The handler failed or did not answer within [`HEALTH_CHECK_TIMEOUT`]
+    Failed(String),
+    /// Not invoked because the tool is not declared idempotent
+    Skipped,
 }
 
 impl ToolRegistry {
@@ -69,6 +89,28 @@ impl ToolRegistry {
         topic_id: &str,
         def: MCPToolDefinition,
         handler: ToolHandler,
+    ) -> Result<(), ToolError> {
+        self.insert_tool(topic_id, def, handler, false).await
+    }
+
+    /// Register a tool whose calls have no side effects.
+    ///
+    /// [`ToolRegistry::health_check`] only invokes tools registered this way.
+    pub async fn register_idempotent_tool(
+        &self,
+        topic_id: &str,
+        def: MCPToolDefinition,
+        handler: ToolHandler,
+    ) -> Result<(), ToolError> {
+        self.insert_tool(topic_id, def, handler, true).await
+    }
+
+    async fn insert_tool(
+        &self,
+        topic_id: &str,
+        def: MCPToolDefinition,
+        handler: ToolHandler,
+        idempotent: bool,
     ) -> Result<(), ToolError> {
         let mut tools = self.tools.write().await;
         let key = format!("{}__{}", topic_id, def.name);
@@ -94,6 +136,7 @@ impl ToolRegistry {
                 definition: def,
                 handler,
                 topic_id: topic_id.to_string(),
+                idempotent,
                 schema_validator,
             },
         );
@@ -101,10 +144,153 @@ impl ToolRegistry {
         Ok(())
     }
 
-    /// List all registered tools.
+    /// List all registered tools, ordered by their `topic__toolname` key.
     pub async fn list_tools(&self) -> Vec<MCPToolDefinition> {
+        self.list_callable_tools()
+            .await
+            .into_iter()
+            .map(|(_, def)| def)
+            .collect()
+    }
+
+    /// Definition of a tool by `topic__toolname` key or by bare name when only
+    /// one topic registered it.
+    pub async fn describe_tool(&self, name: &str) -> Option<MCPToolDefinition> {
+        let tools = self.tools.read().await;
+        let key = Self::resolve_key(&tools, name).ok()?;
+        tools.get(&key).map(|t| t.definition.clone())
+    }
+
+    /// Registry key for `name`: the key itself, or the only `topic__name`
+    /// whose tool is called `name`.
+    fn resolve_key(
+        tools: &HashMap<String, RegisteredTool>,
+        name: &str,
+    ) -> Result<String, ToolError> {
+        if tools.contains_key(name) {
+            return Ok(name.to_string());
+        }
+        let mut matches: Vec<&String> = tools
+            .iter()
+            .filter(|(_, t)| t.definition.name == name)
+            .map(|(key, _)| key)
+            .collect();
+        match matches.len() {
+            0 => Err(ToolError::NotFound(name.to_string())),
+            1 => Ok(matches[0].clone()),
+            _ => {
+                matches.sort();
+                let keys: Vec<&str> = matches.iter().map(|k| k.as_str()).collect();
+                Err(ToolError::Ambiguous(name.to_string(), keys.join(", ")))
+            }
+        }
+    }
+
+    /// Invoke every idempotent tool with the smallest arguments its schema
+    /// accepts and report which handlers fail or hang.
+    ///
+    /// Results are keyed by `topic__toolname` and sorted; tools registered with
+    /// [`ToolRegistry::register_tool`] are [`ToolHealth::Skipped`].
+    pub async fn health_check(&self) -> Vec<(String, ToolHealth)> {
+        let mut probes: Vec<(String, Option<Value>)> = {
+            let tools = self.tools.read().await;
+            tools
+                .iter()
+                .map(|(key, t)| {
+                    let args = t
+                        .idempotent
+                        .then(|| minimal_args(&t.definition.input_schema));
+                    (key.clone(), args)
+                })
+                .collect()
+        };
+        probes.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut report = Vec::with_capacity(probes.len());
+        for (key, args) in probes {
+            let health = match args {
+                None => ToolHealth::Skipped,
+                Some(args) => {
+                    match tokio::time::timeout(HEALTH_CHECK_TIMEOUT, self.call_tool(&key, args))
+                        .await
+                    {
+                        Ok(Ok(_)) => ToolHealth::Healthy,
+                        Ok(Err(e)) => ToolHealth::Failed(e.to_string()),
+                        Err(_) => ToolHealth::Failed(format!(
+                            "no response within {}s",
+                            HEALTH_CHECK_TIMEOUT.as_secs()
+                        )),
+                    }
+                }
+            };
+            if let ToolHealth::Failed(reason) = &health {
+                warn!("Health check failed for tool {}: {}", key, reason);
+            }
+            report.push((key, health));
+        }
+        report
+    }
+
+    /// List all registered tools keyed by the name [`ToolRegistry::call_tool`]
+    /// expects (`topic__toolname`).
+    pub async fn list_callable_tools(&self) -> Vec<(String, MCPToolDefinition)> {
+        let tools = self.tools.read().await;
+        let mut listed: Vec<(String, MCPToolDefinition)> = tools
+            .iter()
+            .map(|(key, t)| (key.clone(), t.definition.clone()))
+            .collect();
+        listed.sort_by(|a, b| a.0.cmp(&b.0));
+        listed
+    }
+
+    /// Registered tools as OpenAI function definitions, optionally limited to one topic.
+    ///
+    /// Each entry is `{"type": "function", "function": {name, description, parameters}}`
+    /// with the tool's input schema as `parameters` and the `topic__toolname` key as
+    /// the function name, so the model's calls can be passed to
+    /// [`ToolRegistry::dispatch_tool_call`] unchanged.
+    pub async fn to_openai_tools(&self, topic: Option<&str>) -> Vec<Value> {
         let tools = self.tools.read().await;
-        tools.values().map(|t| t.definition.clone()).collect()
+        let mut listed: Vec<(&String, &RegisteredTool)> = tools
+            .iter()
+            .filter(|(_, t)| topic.is_none() || topic == Some(t.topic_id.as_str()))
+            .collect();
+        listed.sort_by(|a, b| a.0.cmp(b.0));
+        listed
+            .into_iter()
+            .map(|(key, t)| {
+                json!({
+                    "type": "function",
+                    "function": {
+                        "name": key,
+                        "description": t.definition.description,
+                        "parameters": t.definition.input_schema,
+                    }
+                })
+            })
+            .collect()
+    }
+
+    /// Route an OpenAI `tool_call` payload to the registered handler.
+    ///
+    /// Accepts `{"function": {"name", "arguments"}}` with the arguments either as
+    /// a JSON string (OpenAI) or an object (Ollama); missing arguments are an
+    /// empty object. Arguments are validated against the tool's schema before
+    /// the handler runs.
+    pub async fn dispatch_tool_call(&self, call: &Value) -> Result<Value, ToolError> {
+        let function = &call["function"];
+        let name = function["name"].as_str().ok_or_else(|| {
+            ToolError::InvalidArguments("tool call has no function name".to_string())
+        })?;
+        let args = match &function["arguments"] {
+            Value::Null => json!({}),
+            Value::String(raw) if raw.trim().is_empty() => json!({}),
+            Value::String(raw) => serde_json::from_str(raw).map_err(|e| {
+                ToolError::InvalidArguments(format!("arguments are not valid JSON: {}", e))
+            })?,
+            other => other.clone(),
+        };
+        self.call_tool(name, args).await
     }
 
     /// Validate arguments against the tool's input schema
@@ -145,19 +331,16 @@ impl ToolRegistry {
     /// Validates arguments against the tool's input schema before calling.
     pub async fn call_tool(&self, name: &str, args: Value) -> Result<Value, ToolError> {
         let tools = self.tools.read().await;
+        let key = Self::resolve_key(&tools, name)?;
+        let tool = &tools[&key];
 
-        // Simple lookup for now
-        if let Some(tool) = tools.get(name) {
-            // Validate arguments against schema
-            Self::validate_args(tool, &args)?;
+        // Validate arguments against schema
+        Self::validate_args(tool, &args)?;
 
-            // Call the handler
-            return (tool.handler)(args)
-                .await
-                .map_err(|e| ToolError::ExecutionError(e.to_string()));
-        }
-
-        Err(ToolError::NotFound(name.to_string()))
+        // Call the handler
+        (tool.handler)(args)
+            .await
+            .map_err(|e| ToolError::ExecutionError(e.to_string()))
     }
 
     /// Get a tool's definition by name
@@ -179,6 +362,33 @@ impl ToolRegistry {
     }
 }
 
+/// Smallest arguments satisfying the `required` properties of an object
+/// schema: empty strings, zeros, `false`, empty arrays and objects, or the
+/// first `enum` value.
+fn minimal_args(schema: &Value) -> Value {
+    let mut args = serde_json::Map::new();
+    let required = schema["required"].as_array().into_iter().flatten();
+    for name in required.filter_map(Value::as_str) {
+        let property = &schema["properties"][name];
+        let value = match property["enum"]
+            .as_array()
+            .and_then(|values| values.first())
+        {
+            Some(first) => first.clone(),
+            None => match property["type"].as_str() {
+                Some("string") => json!(""),
+                Some("integer") | Some("number") => json!(0),
+                Some("boolean") => json!(false),
+                Some("array") => json!([]),
+                Some("object") => minimal_args(property),
+                _ => Value::Null,
+            },
+        };
+        args.insert(name.to_string(), value);
+    }
+    Value::Object(args)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -284,6 +494,107 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Tool not found"));
     }
 
+    #[tokio::test]
+    async fn test_to_openai_tools_filters_by_topic() {
+        let registry = ToolRegistry::new();
+        for (topic, name) in [
+            ("repo", "read_file"),
+            ("repo", "grep"),
+            ("prompts", "explain"),
+        ] {
+            let def = MCPToolDefinition {
+                name: name.to_string(),
+                description: format!("{} tool", name),
+                input_schema: json!({ "type": "object", "properties": {} }),
+            };
+            registry
+                .register_tool(topic, def, create_test_handler())
+                .await
+                .expect("Failed to register tool");
+        }
+
+        let all = registry.to_openai_tools(None).await;
+        let names: Vec<&str> = all
+            .iter()
+            .filter_map(|t| t["function"]["name"].as_str())
+            .collect();
+        assert_eq!(
+            names,
+            vec!["prompts__explain", "repo__grep", "repo__read_file"]
+        );
+
+        let repo = registry.to_openai_tools(Some("repo")).await;
+        assert_eq!(repo.len(), 2);
+        assert_eq!(
+            repo[1],
+            json!({
+                "type": "function",
+                "function": {
+                    "name": "repo__read_file",
+                    "description": "read_file tool",
+                    "parameters": { "type": "object", "properties": {} }
+                }
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn test_dispatch_tool_call_validates_arguments() {
+        let registry = ToolRegistry::new();
+        let def = MCPToolDefinition {
+            name: "read_file".to_string(),
+            description: "Read a file".to_string(),
+            input_schema: json!({
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }),
+        };
+        registry
+            .register_tool("repo", def, create_test_handler())
+            .await
+            .expect("Failed to register tool");
+
+        // OpenAI sends arguments as a JSON string
+        let result = registry
+            .dispatch_tool_call(&json!({
+                "id": "call_1",
+                "type": "function",
+                "function": { "name": "repo__read_file", "arguments": "{\"path\":\"src/lib.rs\"}" }
+            }))
+            .await
+            .expect("dispatch failed");
+        assert_eq!(result, json!({ "received": { "path": "src/lib.rs" } }));
+
+        // Ollama sends an object
+        let result = registry
+            .dispatch_tool_call(&json!({
+                "function": { "name": "repo__read_file", "arguments": { "path": "a.rs" } }
+            }))
+            .await
+            .expect("dispatch failed");
+        assert_eq!(result["received"]["path"], "a.rs");
+
+        let missing = registry
+            .dispatch_tool_call(&json!({ "function": { "name": "repo__read_file" } }))
+            .await;
+        assert!(matches!(missing, Err(ToolError::InvalidArguments(_))));
+
+        let malformed = registry
+            .dispatch_tool_call(&json!({
+                "function": { "name": "repo__read_file", "arguments": "{\"path\":" }
+            }))
+            .await;
+        assert!(matches!(malformed, Err(ToolError::InvalidArguments(_))));
+
+        let unknown = registry
+            .dispatch_tool_call(
+                &json!({ "function": { "name": "repo__delete", "arguments": "{}" } }),
+            )
+            .await;
+        assert!(matches!(unknown, Err(ToolError::NotFound(_))));
+    }
+
     #[tokio::test]
     async fn test_register_invalid_schema() {
         let registry = ToolRegistry::new();
@@ -311,4 +622,101 @@ mod tests {
             _ => panic!("Expected SchemaError, got {:?}", err),
         }
     }
+
+    fn tool_def(name: &str, schema: Value) -> MCPToolDefinition {
+        MCPToolDefinition {
+            name: name.to_string(),
+            description: format!("{name} tool"),
+            input_schema: schema,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_describe_tool_accepts_bare_unique_names() {
+        let registry = ToolRegistry::new();
+        let schema = json!({"type": "object"});
+        for (topic, name) in [
+            ("prompts", "summarize"),
+            ("prompts", "review"),
+            ("git", "review"),
+        ] {
+            registry
+                .register_tool(topic, tool_def(name, schema.clone()), create_test_handler())
+                .await
+                .expect("Failed to register tool");
+        }
+
+        let names: Vec<String> = registry
+            .list_tools()
+            .await
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        assert_eq!(names, ["review", "review", "summarize"]);
+
+        let described = registry
+            .describe_tool("summarize")
+            .await
+            .expect("bare name");
+        assert_eq!(described.description, "summarize tool");
+        assert!(registry.describe_tool("prompts__summarize").await.is_some());
+        assert!(registry.describe_tool("review").await.is_none());
+        assert!(registry.describe_tool("missing").await.is_none());
+
+        assert!(registry.call_tool("summarize", json!({})).await.is_ok());
+        let err = registry.call_tool("review", json!({})).await.unwrap_err();
+        assert!(
+            matches!(err, ToolError::Ambiguous(_, ref keys) if keys == "git__review, prompts__review")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_health_check_invokes_only_idempotent_tools() {
+        let registry = ToolRegistry::new();
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": {"type": "string"},
+                "mode": {"type": "string", "enum": ["fast", "full"]},
+                "limit": {"type": "integer", "minimum": 0}
+            },
+            "required": ["path", "mode", "limit"]
+        });
+        registry
+            .register_idempotent_tool(
+                "repo",
+                tool_def("stat", schema.clone()),
+                create_test_handler(),
+            )
+            .await
+            .expect("Failed to register tool");
+        let failing: ToolHandler =
+            Box::new(|_| Box::pin(async { Err(anyhow::anyhow!("index missing")) }));
+        registry
+            .register_idempotent_tool("repo", tool_def("search", schema.clone()), failing)
+            .await
+            .expect("Failed to register tool");
+        let side_effect: ToolHandler = Box::new(|_| Box::pin(async { panic!("must not run") }));
+        registry
+            .register_tool("repo", tool_def("delete", schema), side_effect)
+            .await
+            .expect("Failed to register tool");
+
+        let report = registry.health_check().await;
+        assert_eq!(
+            report,
+            [
+                ("repo__delete".to_string(), ToolHealth::Skipped),
+                (
+                    "repo__search".to_string(),
+                    ToolHealth::Failed("Execution error: index missing".to_string())
+                ),
+                ("repo__stat".to_string(), ToolHealth::Healthy),
+            ]
+        );
+        assert_eq!(
+            minimal_args(&registry.describe_tool("stat").await.unwrap().input_schema),
+            json!({"path": "", "mode": "fast", "limit": 0})
+        );
+    }
 }
@@ -0,0 +1,264 @@
+//! Placeholder substitution for prompt templates.
+//!
+//! Templates support three tags:
+//!
+//! - `{{name}}` or `{{repo.name}}`: the argument at that dotted path in the
+//!   JSON args (numeric segments index arrays), passed through the caller's
+//!   escape function
+//! - `{{#if path}}...{{/if}}`: the enclosed text only when the value at `path`
+//!   is present and truthy (not null, `false`, `0`, `""`, `[]` or `{}`);
+//!   blocks nest
+//!
+//! Anything else between braces, and placeholders whose path does not
+//! resolve, is left in the output as written.
+
+use serde_json::Value;
+
+enum Node<'a> {
+    Text(&'a str),
+    Var { path: &'a str, raw: &'a str },
+    If { path: &'a str, body: Vec<Node<'a>> },
+}
+
+enum Tag<'a> {
+    Var(&'a str),
+    If(&'a str),
+    EndIf,
+}
+
+fn is_path(text: &str) -> bool {
+    text.split('.').all(|segment| {
+        !segment.is_empty()
+            && segment
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    })
+}
+
+fn classify(inner: &str) -> Option<Tag<'_>> {
+    let inner = inner.trim();
+    if inner == "/if" {
+        return Some(Tag::EndIf);
+    }
+    if let Some(path) = inner.strip_prefix("#if ") {
+        let path = path.trim();
+        return is_path(path).then_some(Tag::If(path));
+    }
+    is_path(inner).then_some(Tag::Var(inner))
+}
+
+fn parse(template: &str) -> Vec<Node<'_>> {
+    // Open `{{#if}}` blocks: condition, opening tag and the enclosing nodes
+    let mut open: Vec<(&str, &str, Vec<Node>)> = Vec::new();
+    let mut nodes = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        let Some(len) = rest[start + 2..].find("}}") else {
+            break;
+        };
+        let end = start + len + 4;
+        if start > 0 {
+            nodes.push(Node::Text(&rest[..start]));
+        }
+        let raw = &rest[start..end];
+        match classify(&rest[start + 2..end - 2]) {
+            Some(Tag::Var(path)) => nodes.push(Node::Var { path, raw }),
+            Some(Tag::If(path)) => open.push((path, raw, std::mem::take(&mut nodes))),
+            Some(Tag::EndIf) => match open.pop() {
+                Some((path, _, parent)) => {
+                    let body = std::mem::replace(&mut nodes, parent);
+                    nodes.push(Node::If { path, body });
+                }
+                None => nodes.push(Node::Text(raw)),
+            },
+            None => nodes.push(Node::Text(raw)),
+        }
+        rest = &rest[end..];
+    }
+    if !rest.is_empty() {
+        nodes.push(Node::Text(rest));
+    }
+    // An unclosed block is plain text
+    while let Some((_, raw, mut parent)) = open.pop() {
+        parent.push(Node::Text(raw));
+        parent.append(&mut nodes);
+        nodes = parent;
+    }
+    nodes
+}
+
+/// The value at a dotted `path` in `args`, as `{{path}}` would resolve it
+pub fn resolve_path<'v>(args: &'v Value, path: &str) -> Option<&'v Value> {
+    path.split('.')
+        .try_fold(args, |value, segment| match value {
+            Value::Object(map) => map.get(segment),
+            Value::Array(items) => segment.parse::<usize>().ok().and_then(|i| items.get(i)),
+            _ => None,
+        })
+}
+
+/// Whether `{{#if}}` includes its block for this value: present and not
+/// null, `false`, `0`, `""`, `[]` or `{}`
+pub fn is_truthy(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) | Some(Value::Bool(false)) => false,
+        Some(Value::String(s)) => !s.is_empty(),
+        Some(Value::Number(n)) => n.as_f64() != Some(0.0),
+        Some(Value::Array(items)) => !items.is_empty(),
+        Some(Value::Object(map)) => !map.is_empty(),
+        Some(Value::Bool(true)) => true,
+    }
+}
+
+fn render(
+    nodes: &[Node],
+    args: &Value,
+    escape: &dyn Fn(&str) -> String,
+    out: &mut String,
+    unresolved: &mut Vec<String>,
+) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { path, raw } => match resolve_path(args, path) {
+                Some(Value::String(s)) => out.push_str(&escape(s)),
+                Some(value) => out.push_str(&escape(&value.to_string())),
+                None => {
+                    out.push_str(raw);
+                    unresolved.push(path.to_string());
+                }
+            },
+            Node::If { path, body } => {
+                if is_truthy(resolve_path(args, path)) {
+                    render(body, args, escape, out, unresolved);
+                }
+            }
+        }
+    }
+}
+
+/// Fill `template` from the JSON `args`, passing every substituted value
+/// through `escape` (e.g. `sanitize_for_prompt`)
+pub fn substitute_template(
+    template: &str,
+    args: &Value,
+    escape: impl Fn(&str) -> String,
+) -> String {
+    let mut out = String::with_capacity(template.len());
+    render(&parse(template), args, &escape, &mut out, &mut Vec::new());
+    out
+}
+
+/// Placeholders [`substitute_template`] would leave as literal `{{path}}`,
+/// sorted and deduplicated. Placeholders inside skipped `{{#if}}` blocks do
+/// not count.
+pub fn unresolved_placeholders(template: &str, args: &Value) -> Vec<String> {
+    let mut unresolved = Vec::new();
+    render(
+        &parse(template),
+        args,
+        &str::to_string,
+        &mut String::new(),
+        &mut unresolved,
+    );
+    unresolved.sort();
+    unresolved.dedup();
+    unresolved
+}
+
+/// Top-level argument names a template refers to in placeholders or
+/// conditions (`repo` for `{{repo.name}}`), sorted and deduplicated.
+///
+/// Other brace expressions (such as `{{ref:id}}`) are left to the template
+/// author.
+pub fn template_placeholders(template: &str) -> Vec<String> {
+    fn collect(nodes: &[Node], names: &mut Vec<String>) {
+        for node in nodes {
+            let path = match node {
+                Node::Text(_) => continue,
+                Node::Var { path, .. } => path,
+                Node::If { path, body } => {
+                    collect(body, names);
+                    path
+                }
+            };
+            let root = path.split('.').next().unwrap_or(path);
+            names.push(root.to_string());
+        }
+    }
+    let mut names = Vec::new();
+    collect(&parse(template), &mut names);
+    names.sort();
+    names.dedup();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn shout(value: &str) -> String {
+        value.to_uppercase()
+    }
+
+    #[test]
+    fn dotted_paths_resolve_into_nested_args() {
+        let args = json!({"repo": {"name": "hqe", "tags": ["rust", "cli"]}, "stars": 42});
+        let out = substitute_template(
+            "{{repo.name}} ({{repo.tags.1}}, {{stars}} stars) {{repo.owner}} {{ref:id}}",
+            &args,
+            shout,
+        );
+        assert_eq!(out, "HQE (CLI, 42 stars) {{repo.owner}} {{ref:id}}");
+        assert_eq!(
+            unresolved_placeholders("{{repo.name}} {{repo.owner}}", &args),
+            ["repo.owner"]
+        );
+    }
+
+    #[test]
+    fn conditional_blocks_follow_truthiness() {
+        let template =
+            "Review{{#if focus}} focusing on {{focus}}{{#if strict}}, strictly{{/if}}{{/if}}.";
+        assert_eq!(
+            substitute_template(template, &json!({}), str::to_string),
+            "Review."
+        );
+        assert_eq!(
+            substitute_template(
+                template,
+                &json!({"focus": "", "strict": true}),
+                str::to_string
+            ),
+            "Review."
+        );
+        assert_eq!(
+            substitute_template(template, &json!({"focus": "auth"}), str::to_string),
+            "Review focusing on auth."
+        );
+        assert_eq!(
+            substitute_template(
+                template,
+                &json!({"focus": "auth", "strict": true}),
+                str::to_string
+            ),
+            "Review focusing on auth, strictly."
+        );
+        // Placeholders in a skipped block are not reported as unresolved
+        assert!(unresolved_placeholders(template, &json!({})).is_empty());
+        assert_eq!(template_placeholders(template), ["focus", "strict"]);
+    }
+
+    #[test]
+    fn unbalanced_blocks_stay_literal() {
+        assert_eq!(
+            substitute_template("{{#if a}}x {{b}}", &json!({"b": 1}), str::to_string),
+            "{{#if a}}x 1"
+        );
+        assert_eq!(
+            substitute_template("x{{/if}}", &json!({}), str::to_string),
+            "x{{/if}}"
+        );
+    }
+}
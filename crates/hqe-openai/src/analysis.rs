@@ -1,14 +1,34 @@
 //! LLM-backed analysis integration for HQE scans.
 
 use async_trait::async_trait;
-use hqe_core::scan::{AnalysisResult, LlmAnalyzer};
-use hqe_core::{Blocker, EvidenceBundle, Finding, HqeError, TodoItem};
-use serde::Deserialize;
+use hqe_core::scan::{AnalysisResult, AnalyzerIdentity, LlmAnalyzer};
+use hqe_core::{
+    Blocker, EvidenceBundle, Finding, HqeError, PromptTruncation, ScanBudget, TodoItem,
+};
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
 
-use crate::prompts::{build_analysis_json_prompt, HQE_SYSTEM_PROMPT};
-use crate::{ChatRequest, Message, OpenAIClient, ResponseFormat, Role};
+use crate::prompts::{
+    build_analysis_json_prompt, ANALYSIS_PROMPT_VERSION, HQE_SYSTEM_PROMPT, REPO_TOOLS_PROMPT,
+};
+use crate::provider_discovery::ProviderModelTraits;
+use crate::repo_tools::RepoTools;
+use crate::token_count;
+use crate::{
+    total_cost, ChatRequest, FailoverClient, Message, ModelCapabilities, OpenAIClient, OpenAIError,
+    ResponseFormat, Role, RoutingStrategy, SpendLimit, StructuredReply, ToolAccess,
+    MAX_TOOL_ROUNDS,
+};
 
-#[derive(Debug, Default, Deserialize)]
+/// Completion budget for one analysis reply.
+const ANALYSIS_MAX_TOKENS: u32 = 2000;
+
+#[derive(Debug, Default, Serialize, Deserialize, JsonSchema)]
 struct LlmAnalysisPayload {
     #[serde(default)]
     findings: Vec<Finding>,
@@ -23,21 +43,114 @@ struct LlmAnalysisPayload {
 /// LLM-backed analyzer that returns structured findings/todos.
 #[derive(Debug, Clone)]
 pub struct OpenAIAnalyzer {
-    client: OpenAIClient,
+    /// The primary client, followed by any fallbacks
+    router: FailoverClient,
+    cancel: Option<CancellationToken>,
     venice_parameters: Option<serde_json::Value>,
     parallel_tool_calls: Option<bool>,
+    context_window: Option<usize>,
+    /// Traits of the primary client's model
+    model_traits: Option<ProviderModelTraits>,
+    /// Probed once per backend, on its first request
+    capabilities: Arc<Mutex<HashMap<String, ModelCapabilities>>>,
+    /// Read-only tools the model may call while analyzing
+    repo_tools: Option<Arc<RepoTools>>,
+    max_tool_rounds: usize,
+    /// Shared by every backend so their usage counts together
+    spend_limit: Option<SpendLimit>,
+}
+
+/// How the analyzer asks the model for structured output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OutputMode {
+    /// `json_schema` response format, retried as plain text if rejected
+    JsonSchema,
+    /// `json_object` response format
+    JsonObject,
+    /// No response format; JSON is extracted from the reply
+    PlainText,
+}
+
+impl OutputMode {
+    /// Probed capabilities win over discovery metadata.
+    fn select(traits: Option<&ProviderModelTraits>, capabilities: &ModelCapabilities) -> Self {
+        match capabilities.supports_json_schema {
+            Some(true) => Self::JsonSchema,
+            Some(false) if traits.is_some_and(|t| t.supports_json_mode) => Self::JsonObject,
+            Some(false) => Self::PlainText,
+            None => Self::for_traits(traits),
+        }
+    }
+
+    fn for_traits(traits: Option<&ProviderModelTraits>) -> Self {
+        match traits {
+            // Unknown models get the schema attempt with its fallback
+            None => Self::JsonSchema,
+            Some(t) if t.supports_response_schema => Self::JsonSchema,
+            Some(t) if t.supports_json_mode => Self::JsonObject,
+            Some(_) => Self::PlainText,
+        }
+    }
 }
 
 impl OpenAIAnalyzer {
     /// Create a new analyzer from an OpenAI-compatible client.
+    ///
+    /// The client's [`crate::CostLedger`] supplies the token usage and cost
+    /// reported in the scan manifest. The model's discovered capabilities
+    /// decide which structured output format is requested.
     pub fn new(client: OpenAIClient) -> Self {
+        let model_traits = client.model_traits(client.default_model());
         Self {
-            client,
+            router: FailoverClient::from(client),
+            cancel: None,
             venice_parameters: None,
             parallel_tool_calls: None,
+            context_window: None,
+            model_traits,
+            capabilities: Arc::new(Mutex::new(HashMap::new())),
+            repo_tools: None,
+            max_tool_rounds: MAX_TOOL_ROUNDS,
+            spend_limit: None,
         }
     }
 
+    /// Fall back to other providers when the primary keeps failing.
+    ///
+    /// Each file's request goes to the clients in `strategy` order and moves on
+    /// after a client's own retries end in a retryable error (see
+    /// [`crate::routing::should_fail_over`]). Every fallback uses its own
+    /// default model, and the scan's session log records which backend
+    /// answered each request.
+    pub fn with_fallbacks(
+        mut self,
+        fallbacks: Vec<OpenAIClient>,
+        strategy: RoutingStrategy,
+    ) -> Self {
+        let mut clients = self.router.clients().to_vec();
+        clients.extend(fallbacks.into_iter().map(|client| {
+            let client = match &self.cancel {
+                Some(token) => client.with_cancellation(token.clone()),
+                None => client,
+            };
+            match &self.spend_limit {
+                Some(limit) => client.with_spend_limit(limit.clone()),
+                None => client,
+            }
+        }));
+        // Never empty: the primary is always first
+        if let Ok(router) = FailoverClient::new(clients) {
+            self.router = router.with_strategy(strategy);
+        }
+        self
+    }
+
+    /// Override the model capabilities discovered for the client's model.
+    pub fn with_model_traits(mut self, traits: Option<ProviderModelTraits>) -> Self {
+        self.model_traits = traits;
+        self
+    }
+
     /// Attach Venice-specific parameters to chat requests.
     pub fn with_venice_parameters(mut self, params: Option<serde_json::Value>) -> Self {
         self.venice_parameters = params;
@@ -49,169 +162,361 @@ impl OpenAIAnalyzer {
         self.parallel_tool_calls = value;
         self
     }
-}
 
-#[async_trait]
-impl LlmAnalyzer for OpenAIAnalyzer {
-    async fn analyze(&self, bundle: EvidenceBundle) -> hqe_core::Result<AnalysisResult> {
-        let prompt = build_analysis_json_prompt(&bundle);
+    /// Abandon in-flight requests once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.router = self.router.with_cancellation(token.clone());
+        self.cancel = Some(token);
+        self
+    }
 
-        let request = ChatRequest {
-            model: self.client.default_model().to_string(),
-            messages: vec![
-                Message {
-                    role: Role::System,
-                    content: Some(HQE_SYSTEM_PROMPT.to_string().into()),
-                    tool_calls: None,
-                },
-                Message {
-                    role: Role::User,
-                    content: Some(prompt.into()),
-                    tool_calls: None,
-                },
-            ],
-            frequency_penalty: None,
-            presence_penalty: None,
-            repetition_penalty: None,
-            logprobs: None,
-            top_logprobs: None,
-            temperature: Some(0.2),
-            min_temp: None,
-            max_temp: None,
-            top_p: None,
-            top_k: None,
-            max_tokens: Some(2000),
-            max_completion_tokens: None,
-            n: None,
-            stop: None,
-            stop_token_ids: None,
-            seed: None,
-            user: None,
-            prompt_cache_key: None,
-            prompt_cache_retention: None,
-            reasoning_effort: None,
-            reasoning: None,
-            stream: None,
-            stream_options: None,
-            tool_choice: None,
-            tools: None,
-            venice_parameters: self.venice_parameters.clone(),
-            parallel_tool_calls: self.parallel_tool_calls,
-            response_format: Some(ResponseFormat::JsonObject),
-        };
+    /// Override the model context window used to trim oversized prompts.
+    ///
+    /// Defaults to the discovered or known window for the model, or
+    /// [`token_count::DEFAULT_CONTEXT_WINDOW`] for unknown models.
+    pub fn with_context_window(mut self, tokens: Option<usize>) -> Self {
+        self.context_window = tokens;
+        self
+    }
 
-        let response = match self.client.chat(request.clone()).await {
-            Ok(resp) => resp,
-            Err(err) => {
-                let message = err.to_string();
-                if should_retry_without_format(&message) {
-                    let mut fallback = request;
-                    fallback.response_format = None;
-                    self.client
-                        .chat(fallback)
-                        .await
-                        .map_err(|e| HqeError::Provider(e.to_string()))?
-                } else {
-                    return Err(HqeError::Provider(message));
-                }
-            }
-        };
+    /// Let the model read files of the scanned repository while analyzing.
+    ///
+    /// Offered only to models not known to reject tools. The model's tool calls
+    /// are executed and answered until it returns the analysis, for at most
+    /// [`with_max_tool_rounds`](Self::with_max_tool_rounds) rounds per request.
+    pub fn with_repo_tools(mut self, tools: RepoTools) -> Self {
+        self.repo_tools = Some(Arc::new(tools));
+        self
+    }
 
-        let content = response
-            .choices
-            .first()
-            .and_then(|c| c.message.content.as_ref().and_then(|c| c.to_text_lossy()))
-            .ok_or_else(|| HqeError::Provider("Empty response content".to_string()))?;
+    /// Stop sending requests once `budget` is used up.
+    ///
+    /// Checked by every backend before each request, including tool rounds,
+    /// repair retries and failover within one file, against the usage of all
+    /// backends together; analysis then fails with
+    /// [`HqeError::BudgetExceeded`].
+    pub fn with_budget(mut self, budget: ScanBudget) -> Self {
+        let limit = SpendLimit::new(budget);
+        self.router = self
+            .router
+            .map_clients(|client| client.with_spend_limit(limit.clone()));
+        self.spend_limit = Some(limit);
+        self
+    }
 
-        let json_str = extract_json_object(&content)
-            .ok_or_else(|| HqeError::Provider("No JSON object found in response".to_string()))?;
+    /// Cap the tool-calling rounds of one request; defaults to [`MAX_TOOL_ROUNDS`].
+    pub fn with_max_tool_rounds(mut self, rounds: usize) -> Self {
+        self.max_tool_rounds = rounds;
+        self
+    }
 
-        let payload: LlmAnalysisPayload = serde_json::from_str(&json_str)
-            .map_err(|e| HqeError::Provider(format!("Failed to parse JSON: {e}")))?;
+    /// Fit the request to the context window, reserving room for the reply.
+    ///
+    /// File snippets are trimmed first, least important first (see
+    /// [`trim_file_snippets`]), and the prompt rebuilt from the smaller bundle.
+    /// Whatever is still over budget (e.g. a huge directory tree) is cut from
+    /// the tail of the prompt. The system prompt is never trimmed.
+    fn fit_to_context(
+        &self,
+        bundle: &mut EvidenceBundle,
+        request: &mut ChatRequest,
+        traits: Option<&ProviderModelTraits>,
+        capabilities: &ModelCapabilities,
+    ) -> Option<PromptTruncation> {
+        let context_window = self
+            .context_window
+            .or_else(|| {
+                traits
+                    .and_then(|t| t.context_window)
+                    .or(capabilities.max_context)
+                    .map(|n| n as usize)
+            })
+            .or_else(|| token_count::context_window(&request.model))
+            .unwrap_or(token_count::DEFAULT_CONTEXT_WINDOW);
+        let reserve_output = request
+            .max_tokens
+            .or(request.max_completion_tokens)
+            .unwrap_or(0) as usize;
 
-        Ok(AnalysisResult {
-            findings: payload.findings,
-            todos: payload.todos,
-            is_partial: payload.is_partial,
-            blockers: payload.blockers,
+        let budget = context_window.saturating_sub(reserve_output);
+        let original_tokens = token_count::estimate_request_tokens(request);
+        let mut trimmed_files = Vec::new();
+        if original_tokens > budget {
+            trimmed_files = trim_file_snippets(bundle, &request.model, original_tokens - budget);
+            if !trimmed_files.is_empty() {
+                request.messages = analysis_messages(bundle);
+            }
+        }
+
+        let truncation = request.truncate_to_fit(context_window, reserve_output);
+        if trimmed_files.is_empty() && truncation.is_none() {
+            return None;
+        }
+        let sent_tokens = truncation.map_or_else(
+            || token_count::estimate_request_tokens(request),
+            |t| t.final_tokens,
+        );
+        warn!(
+            "Prompt trimmed from {} to {} tokens to fit the {}-token context window ({} file(s) cut)",
+            original_tokens,
+            sent_tokens,
+            context_window,
+            trimmed_files.len()
+        );
+        Some(PromptTruncation {
+            context_window,
+            original_tokens,
+            sent_tokens,
+            trimmed_files,
         })
     }
-}
 
-fn should_retry_without_format(error: &str) -> bool {
-    let msg = error.to_lowercase();
-    msg.contains("response_format")
-        || msg.contains("json_schema")
-        || msg.contains("json object")
-        || msg.contains("json_object")
-        || msg.contains("unsupported")
-        || msg.contains("not supported")
-}
+    /// Discovered traits of `client`'s model; the override applies to the primary
+    fn traits_for(&self, client: &OpenAIClient) -> Option<ProviderModelTraits> {
+        if client.backend_label() == self.router.primary().backend_label() {
+            self.model_traits.clone()
+        } else {
+            client.model_traits(client.default_model())
+        }
+    }
 
-fn extract_json_object(input: &str) -> Option<String> {
-    if let Some(fenced) = extract_fenced_json(input) {
-        return validate_and_extract_json(&fenced);
+    /// Probe `client`'s model capabilities on its first request
+    async fn capabilities_for(&self, client: &OpenAIClient) -> ModelCapabilities {
+        // Held across the probe so concurrent requests wait for one result
+        let mut probed = self.capabilities.lock().await;
+        let label = client.backend_label();
+        if let Some(capabilities) = probed.get(&label) {
+            return *capabilities;
+        }
+        let capabilities = client.model_capabilities(client.default_model()).await;
+        probed.insert(label, capabilities);
+        capabilities
     }
 
-    let mut in_string = false;
-    let mut escape = false;
-    let mut depth = 0usize;
-    let mut start_idx: Option<usize> = None;
+    /// Token usage and cost summed over every backend
+    fn cost_estimate(&self) -> Option<hqe_core::CostEstimate> {
+        total_cost(self.router.clients().iter().map(OpenAIClient::cost_so_far))
+    }
 
-    for (idx, ch) in input.char_indices() {
-        if in_string {
-            if escape {
-                escape = false;
-                continue;
-            }
-            match ch {
-                '\\' => escape = true,
-                '"' => in_string = false,
-                _ => {}
-            }
-            continue;
+    /// Send one analysis request to `client`
+    async fn analyze_with(
+        &self,
+        client: &OpenAIClient,
+        mut bundle: EvidenceBundle,
+    ) -> Result<
+        (
+            StructuredReply<LlmAnalysisPayload>,
+            Option<PromptTruncation>,
+        ),
+        OpenAIError,
+    > {
+        let capabilities = self.capabilities_for(client).await;
+        let traits = self.traits_for(client);
+        let max_tokens = traits
+            .as_ref()
+            .and_then(|t| t.max_output_tokens)
+            .map_or(ANALYSIS_MAX_TOKENS, |max| max.min(ANALYSIS_MAX_TOKENS));
+
+        let mut request = ChatRequest {
+            temperature: Some(0.2),
+            max_tokens: Some(max_tokens),
+            venice_parameters: self.venice_parameters.clone(),
+            // Models that reject tools also reject tool-call settings
+            parallel_tool_calls: self
+                .parallel_tool_calls
+                .filter(|_| capabilities.supports_tools != Some(false)),
+            ..ChatRequest::new(client.default_model(), analysis_messages(&bundle))
+        };
+        let prompt_truncation =
+            self.fit_to_context(&mut bundle, &mut request, traits.as_ref(), &capabilities);
+
+        let tools = self
+            .repo_tools
+            .as_deref()
+            .filter(|_| capabilities.supports_tools != Some(false))
+            .map(|executor| ToolAccess {
+                executor,
+                max_rounds: self.max_tool_rounds,
+            });
+        if tools.is_some() {
+            request.messages.insert(
+                1,
+                Message {
+                    role: Role::System,
+                    content: Some(REPO_TOOLS_PROMPT.to_string().into()),
+                    tool_calls: None,
+                    tool_call_id: None,
+                },
+            );
         }
 
-        match ch {
-            '"' => in_string = true,
-            '{' => {
-                if depth == 0 {
-                    start_idx = Some(idx);
-                }
-                depth = depth.saturating_add(1);
-            }
-            '}' => {
-                if depth > 0 {
-                    depth -= 1;
-                    if depth == 0 {
-                        if let Some(start) = start_idx {
-                            let extracted = input[start..=idx].to_string();
-                            return validate_and_extract_json(&extracted);
-                        }
+        let reply = match OutputMode::select(traits.as_ref(), &capabilities) {
+            OutputMode::JsonSchema => {
+                let reply = match tools {
+                    Some(tools) => {
+                        client
+                            .chat_json_reply_with_tools(request.clone(), tools)
+                            .await
+                    }
+                    None => client.chat_json_reply(request.clone()).await,
+                };
+                match reply {
+                    Ok(reply) => reply,
+                    Err(OpenAIError::SchemaUnsupported(reason)) => {
+                        warn!(
+                            "Structured output unsupported, retrying without response_format: {reason}"
+                        );
+                        complete_json(client, request, tools).await?
                     }
+                    Err(err) => return Err(err),
                 }
             }
-            _ => {}
+            OutputMode::JsonObject => {
+                request.response_format = Some(ResponseFormat::JsonObject);
+                complete_json(client, request, tools).await?
+            }
+            OutputMode::PlainText => complete_json(client, request, tools).await?,
+        };
+        Ok((reply, prompt_truncation))
+    }
+}
+
+/// [`OpenAIClient::complete_json`], executing tool calls when `tools` are offered
+async fn complete_json(
+    client: &OpenAIClient,
+    request: ChatRequest,
+    tools: Option<ToolAccess<'_>>,
+) -> Result<StructuredReply<LlmAnalysisPayload>, OpenAIError> {
+    match tools {
+        Some(tools) => client.complete_json_with_tools(request, tools).await,
+        None => client.complete_json(request).await,
+    }
+}
+
+/// System and user messages for one analysis request.
+fn analysis_messages(bundle: &EvidenceBundle) -> Vec<Message> {
+    vec![
+        Message {
+            role: Role::System,
+            content: Some(HQE_SYSTEM_PROMPT.to_string().into()),
+            tool_calls: None,
+            tool_call_id: None,
+        },
+        Message {
+            role: Role::User,
+            content: Some(build_analysis_json_prompt(bundle).into()),
+            tool_calls: None,
+            tool_call_id: None,
+        },
+    ]
+}
+
+/// Remove about `excess` tokens of file content from the bundle.
+///
+/// Files without local findings go first, then files by their most severe
+/// finding, later files before earlier ones within a rank. Whole snippets are
+/// dropped until the last one needed, which is cut to size instead. Returns
+/// the paths of the dropped or cut files.
+fn trim_file_snippets(bundle: &mut EvidenceBundle, model: &str, excess: usize) -> Vec<String> {
+    let priority = |path: &str| {
+        bundle
+            .local_findings
+            .iter()
+            .filter(|f| f.file_path == path)
+            .map(|f| f.severity.rank() + 1)
+            .max()
+            .unwrap_or(0)
+    };
+    let mut order: Vec<usize> = (0..bundle.files.len()).collect();
+    order.sort_by_key(|&i| (priority(&bundle.files[i].path), std::cmp::Reverse(i)));
+
+    let mut remaining = excess;
+    let mut dropped = vec![false; bundle.files.len()];
+    let mut trimmed = Vec::new();
+    for i in order {
+        if remaining == 0 {
+            break;
+        }
+        let file = &mut bundle.files[i];
+        let tokens = token_count::count_tokens(model, &file.content);
+        if tokens == 0 {
+            continue;
         }
+        if tokens <= remaining {
+            dropped[i] = true;
+            remaining -= tokens;
+        } else {
+            token_count::truncate_text(model, &mut file.content, tokens - remaining);
+            remaining = 0;
+        }
+        trimmed.push(file.path.clone());
     }
 
-    None
+    let mut index = 0;
+    bundle.files.retain(|_| {
+        index += 1;
+        !dropped[index - 1]
+    });
+    trimmed
 }
 
-/// Validate and extract JSON after initial extraction to prevent injection
-fn validate_and_extract_json(json_str: &str) -> Option<String> {
-    // First, try to parse the JSON to ensure it's valid
-    if let Ok(value) = serde_json::from_str::<serde_json::Value>(json_str) {
-        // Check for suspicious patterns that might indicate injection
+#[async_trait]
+impl LlmAnalyzer for OpenAIAnalyzer {
+    async fn analyze(&self, bundle: EvidenceBundle) -> hqe_core::Result<AnalysisResult> {
+        let routed = self
+            .router
+            .route(|client| {
+                let bundle = bundle.clone();
+                async move { self.analyze_with(&client, bundle).await }
+            })
+            .await?;
+        let (reply, prompt_truncation) = routed.value;
+        let payload = reply.value;
+
+        let value =
+            serde_json::to_value(&payload).map_err(|e| HqeError::Serialization(e.to_string()))?;
         if contains_suspicious_patterns(&value) {
-            tracing::warn!("Suspicious patterns detected in LLM response JSON, rejecting");
-            return None;
+            warn!("Suspicious patterns detected in LLM response JSON, rejecting");
+            return Err(HqeError::Provider(
+                "LLM response contained suspicious content".to_string(),
+            ));
         }
 
-        // Return the validated JSON
-        Some(json_str.to_string())
-    } else {
-        None
+        Ok(AnalysisResult {
+            findings: payload.findings,
+            todos: payload.todos,
+            is_partial: payload.is_partial,
+            blockers: payload.blockers,
+            prompt_truncation,
+            cost_estimate: self.cost_estimate(),
+            degraded_files: Vec::new(),
+            provider_fallback: None,
+            tokens_used: reply.tokens,
+            analysis_cache: None,
+            structured_output_repairs: reply.repair.into_iter().collect(),
+            // Only worth recording when there was a choice
+            backends: if self.router.clients().len() > 1 {
+                vec![routed.backend]
+            } else {
+                Vec::new()
+            },
+        })
+    }
+
+    fn cache_identity(&self) -> Option<AnalyzerIdentity> {
+        Some(AnalyzerIdentity {
+            model: self.router.primary().default_model().to_string(),
+            // Tool access changes the prompt and what the model can see
+            prompt_version: if self.repo_tools.is_some() {
+                format!("{ANALYSIS_PROMPT_VERSION}+repo-tools")
+            } else {
+                ANALYSIS_PROMPT_VERSION.to_string()
+            },
+        })
+    }
+
+    fn cost_so_far(&self) -> Option<hqe_core::CostEstimate> {
+        self.cost_estimate()
     }
 }
 
@@ -237,14 +542,301 @@ fn contains_suspicious_patterns(value: &serde_json::Value) -> bool {
     }
 }
 
-fn extract_fenced_json(input: &str) -> Option<String> {
-    let fence_start = input.find("```json")?;
-    let after = &input[fence_start + "```json".len()..];
-    let fence_end = after.find("```")?;
-    let candidate = after[..fence_end].trim();
-    if candidate.is_empty() {
-        None
-    } else {
-        Some(candidate.to_string())
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{completion, completion_message, mock_client};
+    use hqe_core::models::{CodeStats, FileSnippet, LocalFinding, RepoSummary, TechStack};
+
+    #[test]
+    fn test_output_mode_follows_traits() {
+        assert_eq!(OutputMode::for_traits(None), OutputMode::JsonSchema);
+
+        let mut traits = ProviderModelTraits {
+            supports_json_mode: true,
+            ..ProviderModelTraits::default()
+        };
+        assert_eq!(
+            OutputMode::for_traits(Some(&traits)),
+            OutputMode::JsonObject
+        );
+
+        traits.supports_response_schema = true;
+        assert_eq!(
+            OutputMode::for_traits(Some(&traits)),
+            OutputMode::JsonSchema
+        );
+
+        let plain = ProviderModelTraits::default();
+        assert_eq!(OutputMode::for_traits(Some(&plain)), OutputMode::PlainText);
+    }
+
+    fn bundle(files: Vec<FileSnippet>, local_findings: Vec<LocalFinding>) -> EvidenceBundle {
+        EvidenceBundle {
+            repo_summary: RepoSummary {
+                name: "repo".to_string(),
+                commit_hash: None,
+                directory_tree: String::new(),
+                tech_stack: TechStack::default(),
+                entrypoints: Vec::new(),
+                code_stats: CodeStats::default(),
+            },
+            files,
+            local_findings,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_analyze_accepts_damaged_json_without_repair() -> anyhow::Result<()> {
+        let reply = "Here is my analysis:\n```json\n{\n  \"blockers\": [\n    {\"description\": \"No tests\nfound\", \"reason\": \"CI\", \"how_to_obtain\": \"Add tests\",},\n  ],\n  \"is_partial\": false,\n}\n```";
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(completion(reply))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let result = OpenAIAnalyzer::new(mock_client(&server)?)
+            .analyze(bundle(Vec::new(), Vec::new()))
+            .await?;
+
+        assert_eq!(result.blockers.len(), 1);
+        assert_eq!(result.blockers[0].description, "No tests\nfound");
+        assert!(result.structured_output_repairs.is_empty());
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_analyze_fails_over_and_records_backend() -> anyhow::Result<()> {
+        let mut primary = mockito::Server::new_async().await;
+        let mut backup = mockito::Server::new_async().await;
+        let down = primary
+            .mock("POST", "/v1/chat/completions")
+            .with_status(503)
+            .expect(1)
+            .create_async()
+            .await;
+        let up = backup
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(completion(r#"{"findings": [], "todos": []}"#))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let fallback = mock_client(&backup)?;
+        let backup_label = fallback.backend_label();
+        let result = OpenAIAnalyzer::new(mock_client(&primary)?)
+            .with_fallbacks(vec![fallback], RoutingStrategy::Failover)
+            .analyze(bundle(Vec::new(), Vec::new()))
+            .await?;
+
+        down.assert_async().await;
+        up.assert_async().await;
+        assert_eq!(result.backends, vec![backup_label]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_analyze_trims_least_important_files_to_fit_context() -> anyhow::Result<()> {
+        // 20 files of ~10k tokens each against a 32k window
+        let files = (0..20)
+            .map(|i| FileSnippet {
+                path: format!("src/file{i}.rs"),
+                content: format!("// file{i}\n{}", "x".repeat(40_000)),
+                start_line: None,
+                end_line: None,
+            })
+            .collect();
+        let findings = vec![LocalFinding {
+            finding_type: "secret".to_string(),
+            description: "Hardcoded key".to_string(),
+            file_path: "src/file7.rs".to_string(),
+            severity: hqe_core::Severity::High,
+            line_number: Some(1),
+            snippet: None,
+            recommendation: None,
+        }];
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex("HQE Engineer v3 protocol".to_string()),
+                mockito::Matcher::Regex("Return ONLY a JSON object".to_string()),
+                mockito::Matcher::Regex("// file7".to_string()),
+                mockito::Matcher::Regex("Hardcoded key".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(completion("{}"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let result = OpenAIAnalyzer::new(mock_client(&server)?)
+            .with_context_window(Some(32_768))
+            .analyze(bundle(files, findings))
+            .await?;
+
+        let truncation = result
+            .prompt_truncation
+            .ok_or_else(|| anyhow::anyhow!("expected a truncation"))?;
+        assert_eq!(truncation.context_window, 32_768);
+        assert!(truncation.original_tokens > 200_000);
+        assert!(truncation.sent_tokens <= 32_768 - ANALYSIS_MAX_TOKENS as usize);
+        // Files without findings go first, from the end
+        assert_eq!(truncation.trimmed_files[0], "src/file19.rs");
+        assert!(!truncation
+            .trimmed_files
+            .contains(&"src/file7.rs".to_string()));
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    fn tool_call_reply(path: &str) -> String {
+        completion_message(serde_json::json!({
+            "role": "assistant",
+            "content": null,
+            "tool_calls": [{
+                "id": "call_1",
+                "type": "function",
+                "function": {"name": "read_file", "arguments": serde_json::json!({"path": path}).to_string()}
+            }]
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_analyze_reads_repository_files_through_tools() -> anyhow::Result<()> {
+        let repo = tempfile::TempDir::new()?;
+        std::fs::create_dir_all(repo.path().join("src"))?;
+        std::fs::write(repo.path().join("src/util.rs"), "pub fn helper() {}\n")?;
+
+        let mut server = mockito::Server::new_async().await;
+        let answer = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(r#""role":"tool""#.to_string()),
+                mockito::Matcher::Regex("pub fn helper".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(completion(r#"{"findings": [], "todos": []}"#))
+            .expect(1)
+            .create_async()
+            .await;
+        let tool_round = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::AllOf(vec![
+                mockito::Matcher::Regex(r#""name":"read_file""#.to_string()),
+                mockito::Matcher::Regex("read_file and list_dir tools".to_string()),
+            ]))
+            .with_status(200)
+            .with_body(tool_call_reply("src/util.rs"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let analyzer = OpenAIAnalyzer::new(mock_client(&server)?)
+            .with_repo_tools(crate::RepoTools::new(repo.path())?);
+        let identity = analyzer
+            .cache_identity()
+            .ok_or_else(|| anyhow::anyhow!("expected a cache identity"))?;
+        assert_eq!(identity.prompt_version, "1+repo-tools");
+        analyzer.analyze(bundle(Vec::new(), Vec::new())).await?;
+
+        tool_round.assert_async().await;
+        answer.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_analyze_stops_after_tool_round_limit() -> anyhow::Result<()> {
+        let repo = tempfile::TempDir::new()?;
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(tool_call_reply("../secrets.txt"))
+            .expect(3)
+            .create_async()
+            .await;
+
+        let result = OpenAIAnalyzer::new(mock_client(&server)?)
+            .with_repo_tools(crate::RepoTools::new(repo.path())?)
+            .with_max_tool_rounds(2)
+            .analyze(bundle(Vec::new(), Vec::new()))
+            .await;
+
+        assert!(result.is_err());
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_budget_is_checked_before_each_tool_round() -> anyhow::Result<()> {
+        let repo = tempfile::TempDir::new()?;
+        let mut reply: serde_json::Value = serde_json::from_str(&tool_call_reply("README.md"))?;
+        reply["usage"] =
+            serde_json::json!({"prompt_tokens": 30, "completion_tokens": 10, "total_tokens": 40});
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(reply.to_string())
+            .expect(2)
+            .create_async()
+            .await;
+
+        let result = OpenAIAnalyzer::new(mock_client(&server)?)
+            .with_repo_tools(crate::RepoTools::new(repo.path())?)
+            .with_budget(ScanBudget {
+                tokens: Some(50),
+                ..ScanBudget::default()
+            })
+            .analyze(bundle(Vec::new(), Vec::new()))
+            .await;
+
+        assert!(
+            matches!(&result, Err(HqeError::BudgetExceeded(reason)) if reason.contains("token budget of 50")),
+            "{result:?}"
+        );
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[test]
+    fn test_output_mode_prefers_probed_capabilities() {
+        let rejects_schema = ModelCapabilities {
+            supports_json_schema: Some(false),
+            ..ModelCapabilities::default()
+        };
+        let traits = ProviderModelTraits {
+            supports_json_mode: true,
+            supports_response_schema: true,
+            ..ProviderModelTraits::default()
+        };
+        assert_eq!(
+            OutputMode::select(Some(&traits), &rejects_schema),
+            OutputMode::JsonObject
+        );
+        assert_eq!(
+            OutputMode::select(None, &rejects_schema),
+            OutputMode::PlainText
+        );
+
+        let accepts_schema = ModelCapabilities {
+            supports_json_schema: Some(true),
+            ..ModelCapabilities::default()
+        };
+        let plain = ProviderModelTraits::default();
+        assert_eq!(
+            OutputMode::select(Some(&plain), &accepts_schema),
+            OutputMode::JsonSchema
+        );
+        assert_eq!(
+            OutputMode::select(Some(&plain), &ModelCapabilities::default()),
+            OutputMode::PlainText
+        );
     }
 }
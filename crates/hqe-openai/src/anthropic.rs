@@ -0,0 +1,357 @@
+//! Adapter for Anthropic's native Messages API.
+//!
+//! Anthropic does not accept the OpenAI chat-completions shape: the system prompt is a
+//! top-level field, `max_tokens` is mandatory and responses are returned as content
+//! blocks. This adapter translates between [`ChatRequest`]/[`ChatResponse`] and the
+//! `/v1/messages` wire format so the rest of the client can stay provider-agnostic.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ChatRequest, ChatResponse, ChatStreamChunk, Choice, Message, OpenAIError, Role, Stop, Usage,
+};
+
+/// Value sent in the `anthropic-version` header.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Completion budget used when the request does not set `max_tokens`.
+pub const DEFAULT_ANTHROPIC_MAX_TOKENS: u32 = 4096;
+
+/// Translates chat requests and responses to and from the Anthropic Messages API.
+#[derive(Debug, Clone)]
+pub struct AnthropicAdapter {
+    default_max_tokens: u32,
+}
+
+impl Default for AnthropicAdapter {
+    fn default() -> Self {
+        Self {
+            default_max_tokens: DEFAULT_ANTHROPIC_MAX_TOKENS,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    messages: Vec<MessagesMessage>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesMessage {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    id: String,
+    model: String,
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    usage: Option<MessagesUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    block_type: String,
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesUsage {
+    #[serde(default)]
+    input_tokens: i32,
+    #[serde(default)]
+    output_tokens: i32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    ContentBlockDelta {
+        delta: StreamDelta,
+    },
+    MessageDelta {
+        delta: StreamMessageDelta,
+    },
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamDelta {
+    text: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamMessageDelta {
+    stop_reason: Option<String>,
+}
+
+impl AnthropicAdapter {
+    /// Create an adapter with the default `max_tokens` fallback.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Override the `max_tokens` value used when the request does not specify one.
+    pub fn with_default_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.default_max_tokens = max_tokens;
+        self
+    }
+
+    /// Path of the Messages endpoint relative to the provider base URL.
+    pub fn endpoint(&self) -> &'static str {
+        "messages"
+    }
+
+    /// Translate an OpenAI-style request into a Messages API body.
+    ///
+    /// System messages are concatenated into the top-level `system` field and
+    /// `max_completion_tokens` is folded into the required `max_tokens`.
+    pub fn to_request_body(&self, request: &ChatRequest) -> Result<serde_json::Value, OpenAIError> {
+        let mut system_parts = Vec::new();
+        let mut messages = Vec::new();
+
+        for message in &request.messages {
+            let text = message_text(message);
+            match message.role {
+                Role::System => system_parts.push(text),
+                Role::User => messages.push(MessagesMessage {
+                    role: "user",
+                    content: text,
+                }),
+                Role::Assistant => messages.push(MessagesMessage {
+                    role: "assistant",
+                    content: text,
+                }),
+                // Tool results travel as user turns; the adapter sends no tool_result blocks
+                Role::Tool => messages.push(MessagesMessage {
+                    role: "user",
+                    content: format!(
+                        "Result of tool call {}: {text}",
+                        message.tool_call_id.as_deref().unwrap_or_default()
+                    ),
+                }),
+            }
+        }
+
+        let body = MessagesRequest {
+            model: request.model.clone(),
+            max_tokens: request
+                .max_tokens
+                .or(request.max_completion_tokens)
+                .unwrap_or(self.default_max_tokens),
+            messages,
+            system: if system_parts.is_empty() {
+                None
+            } else {
+                Some(system_parts.join("\n\n"))
+            },
+            temperature: request.temperature,
+            top_p: request.top_p,
+            top_k: request.top_k,
+            stop_sequences: request.stop.as_ref().map(|stop| match stop {
+                Stop::String(s) => vec![s.clone()],
+                Stop::Array(v) => v.clone(),
+            }),
+            stream: request.stream,
+        };
+
+        Ok(serde_json::to_value(body)?)
+    }
+
+    /// Parse a Messages API response body into a [`ChatResponse`].
+    pub fn parse_response(&self, body: &str) -> Result<ChatResponse, OpenAIError> {
+        let response: MessagesResponse = serde_json::from_str(body).map_err(|e| {
+            OpenAIError::Deserialization(format!("Invalid Anthropic response: {e}"))
+        })?;
+
+        let text = response
+            .content
+            .iter()
+            .filter(|block| block.block_type == "text")
+            .filter_map(|block| block.text.as_deref())
+            .collect::<Vec<_>>()
+            .join("");
+
+        Ok(ChatResponse {
+            id: response.id,
+            object: "chat.completion".to_string(),
+            created: chrono::Utc::now().timestamp(),
+            model: response.model,
+            choices: vec![Choice {
+                index: 0,
+                message: Message {
+                    role: Role::Assistant,
+                    content: Some(text.into()),
+                    tool_calls: None,
+                    tool_call_id: None,
+                },
+                finish_reason: response.stop_reason.as_deref().map(map_stop_reason),
+            }],
+            usage: response.usage.map(|u| Usage {
+                prompt_tokens: u.input_tokens,
+                completion_tokens: u.output_tokens,
+                total_tokens: u.input_tokens + u.output_tokens,
+            }),
+        })
+    }
+
+    /// Parse one streamed `data:` payload into chunks.
+    pub(crate) fn parse_stream_event(data: &str) -> Result<Vec<ChatStreamChunk>, OpenAIError> {
+        let event: StreamEvent = serde_json::from_str(data).map_err(|e| {
+            OpenAIError::Deserialization(format!("Invalid Anthropic stream event: {e}"))
+        })?;
+
+        Ok(match event {
+            StreamEvent::ContentBlockDelta { delta } => match delta.text {
+                Some(text) => vec![ChatStreamChunk {
+                    delta_content: Some(text),
+                    finish_reason: None,
+                }],
+                None => Vec::new(),
+            },
+            StreamEvent::MessageDelta { delta } => match delta.stop_reason {
+                Some(reason) => vec![ChatStreamChunk {
+                    delta_content: None,
+                    finish_reason: Some(map_stop_reason(&reason)),
+                }],
+                None => Vec::new(),
+            },
+            StreamEvent::Other => Vec::new(),
+        })
+    }
+}
+
+fn message_text(message: &Message) -> String {
+    message
+        .content
+        .as_ref()
+        .and_then(|c| c.to_text_lossy())
+        .unwrap_or_default()
+}
+
+/// Map Anthropic stop reasons onto OpenAI `finish_reason` values.
+fn map_stop_reason(reason: &str) -> String {
+    match reason {
+        "end_turn" | "stop_sequence" => "stop",
+        "max_tokens" => "length",
+        "tool_use" => "tool_calls",
+        other => other,
+    }
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MessageContent;
+
+    fn request(messages: Vec<Message>) -> ChatRequest {
+        ChatRequest {
+            temperature: Some(0.2),
+            max_completion_tokens: Some(1024),
+            stop: Some(Stop::String("END".to_string())),
+            ..ChatRequest::new("claude-3-5-sonnet-latest", messages)
+        }
+    }
+
+    fn message(role: Role, text: &str) -> Message {
+        Message {
+            role,
+            content: Some(MessageContent::Text(text.to_string())),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    #[test]
+    fn test_request_lifts_system_and_max_tokens() -> anyhow::Result<()> {
+        let body = AnthropicAdapter::new().to_request_body(&request(vec![
+            message(Role::System, "Be terse."),
+            message(Role::User, "Hi"),
+        ]))?;
+
+        assert_eq!(body["system"], "Be terse.");
+        assert_eq!(body["max_tokens"], 1024);
+        assert_eq!(body["stop_sequences"], serde_json::json!(["END"]));
+        assert_eq!(body["messages"].as_array().map(|m| m.len()), Some(1));
+        assert_eq!(body["messages"][0]["role"], "user");
+        assert!(body.get("max_completion_tokens").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_request_defaults_max_tokens() -> anyhow::Result<()> {
+        let mut req = request(vec![message(Role::User, "Hi")]);
+        req.max_completion_tokens = None;
+        let body = AnthropicAdapter::new()
+            .with_default_max_tokens(256)
+            .to_request_body(&req)?;
+        assert_eq!(body["max_tokens"], 256);
+        assert!(body.get("system").is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_response() -> anyhow::Result<()> {
+        let body = r#"{
+            "id": "msg_01",
+            "type": "message",
+            "role": "assistant",
+            "model": "claude-3-5-sonnet-latest",
+            "content": [{"type": "text", "text": "Hello"}, {"type": "text", "text": " world"}],
+            "stop_reason": "end_turn",
+            "usage": {"input_tokens": 10, "output_tokens": 5}
+        }"#;
+
+        let response = AnthropicAdapter::new().parse_response(body)?;
+        assert_eq!(response.id, "msg_01");
+        let choice = &response.choices[0];
+        assert_eq!(choice.finish_reason.as_deref(), Some("stop"));
+        assert_eq!(
+            choice
+                .message
+                .content
+                .as_ref()
+                .and_then(|c| c.to_text_lossy()),
+            Some("Hello world".to_string())
+        );
+        assert_eq!(response.usage.map(|u| u.total_tokens), Some(15));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_stream_events() -> anyhow::Result<()> {
+        let delta = AnthropicAdapter::parse_stream_event(
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"text_delta","text":"Hi"}}"#,
+        )?;
+        assert_eq!(delta[0].delta_content.as_deref(), Some("Hi"));
+
+        let stop = AnthropicAdapter::parse_stream_event(
+            r#"{"type":"message_delta","delta":{"stop_reason":"max_tokens"}}"#,
+        )?;
+        assert_eq!(stop[0].finish_reason.as_deref(), Some("length"));
+
+        let ping = AnthropicAdapter::parse_stream_event(r#"{"type":"ping"}"#)?;
+        assert!(ping.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+//! Cache keys for chat completion requests.
+//!
+//! Requests that would get the same answer should share a response cache
+//! entry even when they differ in fields that only identify or deliver the
+//! request. [`request_cache_key`] therefore hashes a canonical form of the
+//! request rather than its full JSON:
+//!
+//! - The fields in [`EXCLUDED_FIELDS`] are dropped: `user` (end-user id for
+//!   abuse monitoring), `seed`, `stream` and `stream_options` (how the answer
+//!   is delivered), and `prompt_cache_key` and `prompt_cache_retention`
+//!   (provider-side prompt caching hints).
+//! - Message text is normalized with [`normalize_whitespace`].
+//!
+//! Everything else, including the model, sampling parameters, tools and
+//! response format, is part of the key.
+
+use hqe_core::persistence::LocalDb;
+use serde_json::Value;
+
+use crate::ChatRequest;
+
+/// Request fields left out of the cache key
+pub const EXCLUDED_FIELDS: [&str; 6] = [
+    "user",
+    "seed",
+    "stream",
+    "stream_options",
+    "prompt_cache_key",
+    "prompt_cache_retention",
+];
+
+/// Hash identifying `request` in the response cache
+pub fn request_cache_key(request: &ChatRequest) -> Result<String, serde_json::Error> {
+    let mut params = serde_json::to_value(request)?;
+    let mut messages = Value::Null;
+    if let Some(object) = params.as_object_mut() {
+        for field in EXCLUDED_FIELDS {
+            object.remove(field);
+        }
+        object.remove("model");
+        messages = object.remove("messages").unwrap_or_default();
+    }
+    for message in messages.as_array_mut().into_iter().flatten() {
+        normalize_content(message);
+    }
+
+    // Objects serialize with sorted keys, so equal requests give equal JSON
+    Ok(LocalDb::calculate_hash(
+        &request.model,
+        &messages.to_string(),
+        &params.to_string(),
+    ))
+}
+
+/// Normalize the text of a serialized message, plain or in content parts
+fn normalize_content(message: &mut Value) {
+    match message.get_mut("content") {
+        Some(Value::String(text)) => *text = normalize_whitespace(text),
+        Some(Value::Array(parts)) => {
+            for part in parts {
+                if let Some(Value::String(text)) = part.get_mut("text") {
+                    *text = normalize_whitespace(text);
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Use `\n` line endings, drop trailing whitespace from every line and trim
+/// blank space around the whole text. Indentation inside the text is kept.
+pub fn normalize_whitespace(text: &str) -> String {
+    text.lines()
+        .map(str::trim_end)
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Message, MessageContent, Role};
+
+    fn request(content: MessageContent) -> ChatRequest {
+        ChatRequest::new(
+            "m",
+            vec![Message {
+                role: Role::User,
+                content: Some(content),
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        )
+    }
+
+    #[test]
+    fn test_cache_key_ignores_volatile_fields_and_whitespace() -> anyhow::Result<()> {
+        let base = request(MessageContent::Text("fn main() {\n    run();\n}".into()));
+        let key = request_cache_key(&base)?;
+
+        let mut noisy = request(MessageContent::Text(
+            "\r\nfn main() {  \r\n    run();\r\n}\n\n".into(),
+        ));
+        noisy.user = Some("alice".into());
+        noisy.seed = Some(42);
+        assert_eq!(request_cache_key(&noisy)?, key);
+
+        let parts = request(MessageContent::Parts(vec![serde_json::json!({
+            "type": "text",
+            "text": "  hello \n"
+        })]));
+        let plain_parts = request(MessageContent::Parts(vec![serde_json::json!({
+            "type": "text",
+            "text": "hello"
+        })]));
+        assert_eq!(request_cache_key(&parts)?, request_cache_key(&plain_parts)?);
+
+        let mut warmer = base.clone();
+        warmer.temperature = Some(0.9);
+        assert_ne!(request_cache_key(&warmer)?, key);
+        let mut other_model = base.clone();
+        other_model.model = "m2".into();
+        assert_ne!(request_cache_key(&other_model)?, key);
+        // Indentation is content
+        let flat = request(MessageContent::Text("fn main() {\nrun();\n}".into()));
+        assert_ne!(request_cache_key(&flat)?, key);
+        Ok(())
+    }
+}
@@ -0,0 +1,320 @@
+//! Per-model capability cache.
+//!
+//! Discovery metadata says what a model *should* accept; many OpenAI-compatible
+//! gateways still reject `tools` or `response_format` for individual models.
+//! [`OpenAIClient::model_capabilities`] starts from the discovered traits, probes
+//! whatever is still unknown with one-token requests and stores the result in
+//! the [`DiskCache`](crate::provider_discovery::DiskCache), keyed by base URL and
+//! model, so later scans do not probe again.
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::provider_discovery::ProviderKind;
+use crate::{
+    token_count, ChatRequest, ChatResponse, Message, OpenAIClient, OpenAIError, ResponseFormat,
+    Role,
+};
+
+/// What a model accepts; `None` means not known yet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ModelCapabilities {
+    /// Whether requests may carry `tools` / `parallel_tool_calls`
+    pub supports_tools: Option<bool>,
+    /// Whether `response_format: json_schema` is accepted
+    pub supports_json_schema: Option<bool>,
+    /// Whether `stream: true` is accepted
+    pub supports_streaming: Option<bool>,
+    /// Context window in tokens
+    pub max_context: Option<u32>,
+}
+
+impl ModelCapabilities {
+    /// Whether every probed capability is known.
+    pub fn is_complete(&self) -> bool {
+        self.supports_tools.is_some()
+            && self.supports_json_schema.is_some()
+            && self.supports_streaming.is_some()
+    }
+}
+
+impl OpenAIClient {
+    /// Capabilities of `model`, probing and caching whatever is unknown.
+    ///
+    /// Without a disk cache (response caching disabled) nothing is probed and
+    /// only what discovery knows about the model is returned. Probe failures
+    /// that do not name the probed feature (auth, network, unknown model) leave
+    /// the capability unknown; that result is cached like any other, so the
+    /// model is probed again only once the cache entry expires. Probes refused
+    /// by the daily budget or the scan's spend limit are not cached.
+    pub async fn model_capabilities(&self, model: &str) -> ModelCapabilities {
+        let Some(cache) = &self.disk_cache else {
+            return self.known_capabilities(model);
+        };
+        match cache.get_capabilities(&self.base_url, model) {
+            Ok(Some(cached)) => return cached,
+            Ok(None) => {}
+            Err(e) => warn!("Failed to read model capability cache: {}", e),
+        }
+        let mut capabilities = self.known_capabilities(model);
+        if capabilities.is_complete() {
+            return capabilities;
+        }
+
+        if let Err(e) = self.probe_capabilities(model, &mut capabilities).await {
+            warn!("Capability probes for {} stopped: {}", model, e);
+            return capabilities;
+        }
+        if let Err(e) = cache.set_capabilities(&self.base_url, model, &capabilities) {
+            warn!("Failed to store model capabilities: {}", e);
+        }
+        capabilities
+    }
+
+    /// Capabilities derivable without a request.
+    fn known_capabilities(&self, model: &str) -> ModelCapabilities {
+        let traits = self.model_traits(model);
+        let max_context = traits
+            .as_ref()
+            .and_then(|t| t.context_window)
+            .or_else(|| token_count::context_window(model).map(|n| n as u32));
+        if self.provider_kind == ProviderKind::Anthropic {
+            // The Messages adapter has no response_format equivalent
+            return ModelCapabilities {
+                supports_tools: Some(true),
+                supports_json_schema: Some(false),
+                supports_streaming: Some(true),
+                max_context,
+            };
+        }
+        ModelCapabilities {
+            supports_tools: traits.as_ref().map(|t| t.supports_tools),
+            supports_json_schema: traits.as_ref().map(|t| t.supports_response_schema),
+            supports_streaming: None,
+            max_context,
+        }
+    }
+
+    /// Fill in unknown capabilities with one-token requests.
+    async fn probe_capabilities(
+        &self,
+        model: &str,
+        capabilities: &mut ModelCapabilities,
+    ) -> Result<(), OpenAIError> {
+        if capabilities.supports_tools.is_none() {
+            let request = ChatRequest {
+                tools: Some(vec![serde_json::json!({
+                    "type": "function",
+                    "function": {
+                        "name": "noop",
+                        "description": "Does nothing",
+                        "parameters": {"type": "object", "properties": {}}
+                    }
+                })]),
+                ..probe_request(model)
+            };
+            capabilities.supports_tools = self.probe(request, &["tool", "function"]).await?;
+        }
+        if capabilities.supports_json_schema.is_none() {
+            let request = ChatRequest {
+                response_format: Some(ResponseFormat::JsonSchema {
+                    json_schema: serde_json::json!({
+                        "name": "probe",
+                        "schema": {"type": "object", "properties": {}}
+                    }),
+                }),
+                ..probe_request(model)
+            };
+            capabilities.supports_json_schema = self
+                .probe(request, &["response_format", "json_schema"])
+                .await?;
+        }
+        if capabilities.supports_streaming.is_none() {
+            let request = ChatRequest {
+                stream: Some(true),
+                ..probe_request(model)
+            };
+            capabilities.supports_streaming = self.probe(request, &["stream"]).await?;
+        }
+        debug!(model, ?capabilities, "Probed model capabilities");
+        Ok(())
+    }
+
+    /// `Some(true)` if accepted, `Some(false)` if rejected naming the feature.
+    ///
+    /// Probes are paid requests: they are checked against the daily budget and
+    /// the spend limit and their usage goes into the cost ledger, but they
+    /// bypass the response cache so a cached reply cannot hide a rejection.
+    async fn probe(
+        &self,
+        request: ChatRequest,
+        markers: &[&str],
+    ) -> Result<Option<bool>, OpenAIError> {
+        self.check_daily_budget()?;
+        self.check_spend_limit()?;
+        self.acquire_rate_limit(&request).await;
+        let url = self.endpoint_url("chat/completions")?;
+        match self.send_with_retry(&url, &request, None).await {
+            Ok(resp) => {
+                // A streamed reply has no usage object to record
+                let body = resp.text().await.unwrap_or_default();
+                if let Ok(ChatResponse {
+                    usage: Some(usage), ..
+                }) = serde_json::from_str::<ChatResponse>(&body)
+                {
+                    self.record_usage(&request.model, &usage);
+                }
+                Ok(Some(true))
+            }
+            Err(OpenAIError::InvalidRequest(message)) => {
+                let message = message.to_lowercase();
+                Ok(markers
+                    .iter()
+                    .any(|marker| message.contains(marker))
+                    .then_some(false))
+            }
+            Err(e) => {
+                warn!("Capability probe for {} failed: {}", request.model, e);
+                Ok(None)
+            }
+        }
+    }
+}
+
+/// A one-token request; `max_completion_tokens` because reasoning models
+/// reject `max_tokens`
+fn probe_request(model: &str) -> ChatRequest {
+    ChatRequest {
+        max_completion_tokens: Some(1),
+        ..ChatRequest::new(
+            model,
+            vec![Message {
+                role: Role::User,
+                content: Some("Reply with {}".into()),
+                tool_calls: None,
+                tool_call_id: None,
+            }],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cost::SpendLimit;
+    use crate::provider_discovery::DiskCache;
+    use crate::test_support::mock_client;
+    use std::path::Path;
+    use std::time::Duration;
+
+    /// Mock-server client whose capability cache lives in `dir`
+    fn cached_client(server: &mockito::Server, dir: &Path) -> anyhow::Result<OpenAIClient> {
+        let mut client = mock_client(server)?;
+        client.disk_cache = Some(DiskCache {
+            dir: dir.to_path_buf(),
+            fresh_ttl: Duration::from_secs(60),
+            stale_ttl: Duration::from_secs(60),
+        });
+        Ok(client)
+    }
+
+    #[tokio::test]
+    async fn test_model_capabilities_probes_once_and_caches() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let mut server = mockito::Server::new_async().await;
+        let tools = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("\"tools\"".to_string()))
+            .with_status(400)
+            .with_body(r#"{"error":{"message":"tools are not supported for this model","type":"invalid_request_error"}}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let others = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(
+                r#"{"id":"1","object":"chat.completion","created":0,"model":"m","choices":[]}"#,
+            )
+            .expect(2)
+            .create_async()
+            .await;
+
+        let client = cached_client(&server, temp.path())?;
+
+        let first = client.model_capabilities("local-llm").await;
+        assert_eq!(
+            first,
+            ModelCapabilities {
+                supports_tools: Some(false),
+                supports_json_schema: Some(true),
+                supports_streaming: Some(true),
+                max_context: None,
+            }
+        );
+        let second = client.model_capabilities("local-llm").await;
+        assert_eq!(second, first);
+
+        tools.assert_async().await;
+        others.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_failed_probes_are_cached_until_expiry() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let mut server = mockito::Server::new_async().await;
+        let refused = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(403)
+            .with_body(r#"{"error":{"message":"model not enabled for this key"}}"#)
+            .expect(3)
+            .create_async()
+            .await;
+        let client = cached_client(&server, temp.path())?;
+
+        for _ in 0..2 {
+            let capabilities = client.model_capabilities("local-llm").await;
+            assert_eq!(capabilities.supports_tools, None);
+            assert_eq!(capabilities.supports_json_schema, None);
+            assert_eq!(capabilities.supports_streaming, None);
+        }
+        refused.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_probes_respect_spend_limit_and_record_usage() -> anyhow::Result<()> {
+        let temp = tempfile::TempDir::new()?;
+        let mut server = mockito::Server::new_async().await;
+        let accepted = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex(
+                "\"max_completion_tokens\":1".to_string(),
+            ))
+            .with_status(200)
+            .with_body(
+                r#"{"id":"1","object":"chat.completion","created":0,"model":"m","choices":[],"usage":{"prompt_tokens":40,"completion_tokens":20,"total_tokens":60}}"#,
+            )
+            .expect(1)
+            .create_async()
+            .await;
+        let limit = SpendLimit::new(hqe_core::ScanBudget {
+            tokens: Some(50),
+            ..hqe_core::ScanBudget::default()
+        });
+        let client = cached_client(&server, temp.path())?.with_spend_limit(limit);
+
+        let capabilities = client.model_capabilities("local-llm").await;
+        assert_eq!(capabilities.supports_tools, Some(true));
+        assert_eq!(capabilities.supports_json_schema, None);
+        assert_eq!(client.cost_so_far().prompt_tokens, 40);
+        // Refused probes are not cached, and no further request is sent
+        client.model_capabilities("local-llm").await;
+        let base_url = client.base_url.clone();
+        let cache = client.disk_cache.as_ref().expect("cache configured");
+        assert!(cache.get_capabilities(&base_url, "local-llm")?.is_none());
+        accepted.assert_async().await;
+        Ok(())
+    }
+}
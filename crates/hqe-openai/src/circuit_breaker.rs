@@ -0,0 +1,210 @@
+//! Circuit breaker for unreachable providers.
+//!
+//! Every request to a provider that is hard down still spends `max_retries`
+//! attempts and their timeouts. The [`CircuitBreaker`] counts consecutive
+//! transport failures (and 5xx responses) across all clones of a client; at
+//! the threshold it opens and requests fail immediately with
+//! [`OpenAIError::CircuitOpen`](crate::OpenAIError::CircuitOpen). After the
+//! cooldown one probe request is let through (half-open): success closes the
+//! circuit, failure opens it for another cooldown. A probe that never reports
+//! back (cancelled or dropped) does not wedge the circuit: once another
+//! cooldown has passed, the next request becomes the probe.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+use tracing::{info, warn};
+
+/// Failure threshold and cooldown of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CircuitBreakerConfig {
+    /// Consecutive failures that open the circuit
+    pub failure_threshold: u32,
+    /// How long the circuit stays open before a probe request is allowed
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: 5,
+            cooldown: Duration::from_secs(30),
+        }
+    }
+}
+
+/// State of a [`CircuitBreaker`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Requests flow normally; holds the consecutive failure count
+    Closed {
+        /// Consecutive failures so far
+        failures: u32,
+    },
+    /// Requests fail fast until the cooldown ends
+    Open {
+        /// When a probe request will be allowed
+        until: Instant,
+    },
+    /// The cooldown ended and one probe request is in flight
+    HalfOpen {
+        /// When the probe was let through
+        since: Instant,
+    },
+}
+
+/// Shared closed/open/half-open breaker; clones observe the same state
+#[derive(Debug, Clone)]
+pub struct CircuitBreaker {
+    config: CircuitBreakerConfig,
+    state: Arc<Mutex<CircuitState>>,
+}
+
+impl Default for CircuitBreaker {
+    fn default() -> Self {
+        Self::new(CircuitBreakerConfig::default())
+    }
+}
+
+impl CircuitBreaker {
+    /// Create a closed breaker
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            state: Arc::new(Mutex::new(CircuitState::Closed { failures: 0 })),
+        }
+    }
+
+    /// Current state
+    pub fn state(&self) -> CircuitState {
+        *self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Check whether a request may be sent.
+    ///
+    /// Returns the time left until the next probe when the circuit is open or
+    /// a probe is already in flight. The first caller after the cooldown moves
+    /// the circuit to half-open and becomes the probe; if that probe has not
+    /// reported back within another cooldown, the next caller replaces it.
+    pub fn check(&self) -> Result<(), Duration> {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        let next_probe = match *state {
+            CircuitState::Closed { .. } => return Ok(()),
+            CircuitState::Open { until } => until,
+            CircuitState::HalfOpen { since } => since + self.config.cooldown,
+        };
+        if now >= next_probe {
+            info!("Provider circuit half-open, sending probe request");
+            *state = CircuitState::HalfOpen { since: now };
+            Ok(())
+        } else {
+            Err(next_probe - now)
+        }
+    }
+
+    /// The provider answered; close the circuit
+    pub fn record_success(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        if matches!(*state, CircuitState::HalfOpen { .. }) {
+            info!("Provider circuit closed after successful probe");
+        }
+        *state = CircuitState::Closed { failures: 0 };
+    }
+
+    /// The provider could not be reached; open the circuit at the threshold
+    pub fn record_failure(&self) {
+        let mut state = self.state.lock().unwrap_or_else(PoisonError::into_inner);
+        let failures = match *state {
+            CircuitState::Closed { failures } => failures.saturating_add(1),
+            // A failed probe reopens immediately
+            CircuitState::HalfOpen { .. } => self.config.failure_threshold,
+            CircuitState::Open { .. } => return,
+        };
+        *state = if failures >= self.config.failure_threshold.max(1) {
+            warn!(
+                "Provider circuit open after {} consecutive failure(s); failing fast for {:?}",
+                failures, self.config.cooldown
+            );
+            CircuitState::Open {
+                until: Instant::now() + self.config.cooldown,
+            }
+        } else {
+            CircuitState::Closed { failures }
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_opens_at_threshold_and_fails_fast() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 3,
+            cooldown: Duration::from_secs(60),
+        });
+
+        breaker.record_failure();
+        breaker.record_failure();
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 2 });
+        assert!(breaker.check().is_ok());
+
+        // A success resets the count
+        breaker.record_success();
+        for _ in 0..3 {
+            breaker.record_failure();
+        }
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+        let retry_in = breaker.check().unwrap_err();
+        assert!(retry_in > Duration::from_secs(55));
+
+        // Clones share the state
+        assert!(breaker.clone().check().is_err());
+    }
+
+    #[test]
+    fn test_half_open_probe_closes_or_reopens() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 1,
+            cooldown: Duration::from_millis(20),
+        });
+
+        breaker.record_failure();
+        assert!(breaker.check().is_err());
+        std::thread::sleep(Duration::from_millis(30));
+
+        // One probe goes through, concurrent callers still fail fast
+        assert!(breaker.check().is_ok());
+        assert!(matches!(breaker.state(), CircuitState::HalfOpen { .. }));
+        assert!(breaker.check().is_err());
+
+        breaker.record_failure();
+        assert!(matches!(breaker.state(), CircuitState::Open { .. }));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.check().is_ok());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+
+    #[test]
+    fn test_abandoned_probe_is_replaced_after_cooldown() {
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 1,
+            cooldown: Duration::from_millis(20),
+        });
+        breaker.record_failure();
+        std::thread::sleep(Duration::from_millis(30));
+
+        // The probe is let through but its request is dropped before it reports
+        assert!(breaker.check().is_ok());
+        assert!(breaker.clone().check().is_err());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.clone().check().is_ok());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed { failures: 0 });
+    }
+}
@@ -0,0 +1,336 @@
+//! Per-run token and cost accounting.
+//!
+//! Every [`crate::OpenAIClient`] carries a [`CostLedger`] that accumulates the
+//! `usage` reported by each chat completion, keyed by model. A model's USD
+//! rates come from the provider's discovered pricing when the model list has
+//! them, and from a [`PricingTable`] (normally `pricing.json` in the data
+//! directory) otherwise. Usage of a model with no known rates is still counted
+//! in tokens, but makes the total cost unknown rather than zero.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use hqe_core::{CostEstimate, ScanBudget};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::provider_discovery::ProviderModelPricing;
+use crate::Usage;
+
+/// Cost in USD of a single request, if both input and output prices are known.
+pub fn usage_cost(pricing: &ProviderModelPricing, usage: &Usage) -> Option<f64> {
+    let input = pricing.input_usd_per_million?;
+    let output = pricing.output_usd_per_million?;
+    let prompt = usage.prompt_tokens.max(0) as f64;
+    let completion = usage.completion_tokens.max(0) as f64;
+    Some((prompt * input + completion * output) / 1_000_000.0)
+}
+
+/// Whether `pricing` has both an input and an output rate
+fn is_complete(pricing: &ProviderModelPricing) -> bool {
+    pricing.input_usd_per_million.is_some() && pricing.output_usd_per_million.is_some()
+}
+
+/// Configured USD rates per model, used when discovery has no pricing.
+///
+/// Stored as a JSON object keyed by model id:
+///
+/// ```json
+/// { "gpt-4o": { "input_usd_per_million": 2.5, "output_usd_per_million": 10.0 } }
+/// ```
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PricingTable {
+    models: BTreeMap<String, ProviderModelPricing>,
+}
+
+impl PricingTable {
+    /// Set the rates for `model`
+    pub fn with_model(
+        mut self,
+        model: impl Into<String>,
+        input_usd_per_million: f64,
+        output_usd_per_million: f64,
+    ) -> Self {
+        self.models.insert(
+            model.into(),
+            ProviderModelPricing {
+                input_usd_per_million: Some(input_usd_per_million),
+                output_usd_per_million: Some(output_usd_per_million),
+            },
+        );
+        self
+    }
+
+    /// Rates configured for `model`
+    pub fn get(&self, model: &str) -> Option<&ProviderModelPricing> {
+        self.models.get(model)
+    }
+
+    /// Read a table from a JSON file
+    pub fn load(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+        let content = std::fs::read_to_string(path.as_ref())?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// `pricing.json` next to the stored provider profiles
+    pub fn default_path() -> PathBuf {
+        let mut base = dirs::data_local_dir().unwrap_or_else(|| PathBuf::from("."));
+        base.push("hqe-workbench");
+        base.push("pricing.json");
+        base
+    }
+
+    /// Read the table at [`PricingTable::default_path`].
+    ///
+    /// A missing file gives an empty table; an unreadable one is logged and
+    /// ignored, so a typo in the table never stops a scan.
+    pub fn load_default() -> Self {
+        let path = Self::default_path();
+        if !path.exists() {
+            return Self::default();
+        }
+        Self::load(&path).unwrap_or_else(|e| {
+            warn!("Ignoring unreadable pricing table {:?}: {}", path, e);
+            Self::default()
+        })
+    }
+}
+
+/// Accumulates token usage and cost per model across the requests of one run.
+///
+/// Clones share the same totals, so a ledger can be handed to a client and
+/// read back from elsewhere.
+#[derive(Debug, Clone, Default)]
+pub struct CostLedger {
+    table: Arc<PricingTable>,
+    state: Arc<Mutex<LedgerState>>,
+}
+
+#[derive(Debug, Default)]
+struct LedgerState {
+    /// Resolved rates per model; `None` when no complete rates are known
+    rates: HashMap<String, Option<ProviderModelPricing>>,
+    /// Usage per model
+    models: BTreeMap<String, CostEstimate>,
+}
+
+impl CostLedger {
+    /// Create a ledger falling back to `table` for models without discovered pricing
+    pub fn new(table: PricingTable) -> Self {
+        Self {
+            table: Arc::new(table),
+            state: Arc::default(),
+        }
+    }
+
+    /// Whether rates for `model` were already resolved
+    pub fn has_rate(&self, model: &str) -> bool {
+        self.lock().rates.contains_key(model)
+    }
+
+    /// Settle the rates used for `model`.
+    ///
+    /// `discovered` wins when it has both rates; otherwise the pricing table is
+    /// used, and without either the model's cost is unknown.
+    pub fn resolve_rate(&self, model: &str, discovered: Option<ProviderModelPricing>) {
+        let rate = discovered
+            .filter(is_complete)
+            .or_else(|| self.table.get(model).filter(|p| is_complete(p)).cloned());
+        self.lock().rates.insert(model.to_string(), rate);
+    }
+
+    /// Add one response's usage for `model` and return its cost, if priced.
+    ///
+    /// Models whose rates were never resolved are priced from the table.
+    pub fn record(&self, model: &str, usage: &Usage) -> Option<f64> {
+        let mut state = self.lock();
+        let rate = state
+            .rates
+            .entry(model.to_string())
+            .or_insert_with(|| self.table.get(model).filter(|p| is_complete(p)).cloned());
+        let cost = rate.as_ref().and_then(|pricing| usage_cost(pricing, usage));
+
+        let totals = state.models.entry(model.to_string()).or_default();
+        totals.requests += 1;
+        totals.prompt_tokens += usage.prompt_tokens.max(0) as u64;
+        totals.completion_tokens += usage.completion_tokens.max(0) as u64;
+        match cost {
+            Some(cost) => *totals.total_usd.get_or_insert(0.0) += cost,
+            None => totals.unpriced_models = vec![model.to_string()],
+        }
+        cost
+    }
+
+    /// Usage recorded so far, per model
+    pub fn by_model(&self) -> BTreeMap<String, CostEstimate> {
+        self.lock().models.clone()
+    }
+
+    /// Totals over every model recorded so far.
+    ///
+    /// `total_usd` is `None` as soon as one used model has no known rates;
+    /// those models are listed in `unpriced_models`.
+    pub fn estimate(&self) -> CostEstimate {
+        total_cost(self.by_model().into_values()).unwrap_or(CostEstimate {
+            total_usd: Some(0.0),
+            ..CostEstimate::default()
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, LedgerState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether `other` is a clone sharing this ledger's totals
+    fn shares_totals_with(&self, other: &CostLedger) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
+
+/// A [`ScanBudget`] shared by several clients, e.g. a primary and its fallbacks.
+///
+/// A client with a spend limit refuses every request, including tool rounds
+/// and repair retries, once the usage recorded by all watched ledgers has
+/// used the budget up. Clones share the watched ledgers.
+#[derive(Debug, Clone, Default)]
+pub struct SpendLimit {
+    budget: ScanBudget,
+    ledgers: Arc<Mutex<Vec<CostLedger>>>,
+}
+
+impl SpendLimit {
+    /// A limit enforcing `budget`
+    pub fn new(budget: ScanBudget) -> Self {
+        Self {
+            budget,
+            ledgers: Arc::default(),
+        }
+    }
+
+    /// Count the usage recorded in `ledger` (and its clones) against the budget
+    pub fn watch(&self, ledger: &CostLedger) {
+        let mut ledgers = self.ledgers.lock().unwrap_or_else(PoisonError::into_inner);
+        if !ledgers.iter().any(|l| l.shares_totals_with(ledger)) {
+            ledgers.push(ledger.clone());
+        }
+    }
+
+    /// Why the budget is used up, if it is
+    pub fn exhausted(&self) -> Option<String> {
+        let spent = total_cost(
+            self.ledgers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .iter()
+                .map(CostLedger::estimate),
+        )?;
+        self.budget.exhausted_by(&spent)
+    }
+}
+
+/// Sum several estimates; the cost is unknown if any of them is
+pub fn total_cost(estimates: impl IntoIterator<Item = CostEstimate>) -> Option<CostEstimate> {
+    let mut unpriced = BTreeSet::new();
+    let total = estimates.into_iter().reduce(|mut sum, estimate| {
+        sum.requests += estimate.requests;
+        sum.prompt_tokens += estimate.prompt_tokens;
+        sum.completion_tokens += estimate.completion_tokens;
+        sum.total_usd = sum.total_usd.zip(estimate.total_usd).map(|(a, b)| a + b);
+        unpriced.extend(estimate.unpriced_models);
+        sum
+    });
+    total.map(|mut total| {
+        unpriced.extend(std::mem::take(&mut total.unpriced_models));
+        total.unpriced_models = unpriced.into_iter().collect();
+        total
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn usage(prompt: i32, completion: i32) -> Usage {
+        Usage {
+            prompt_tokens: prompt,
+            completion_tokens: completion,
+            total_tokens: prompt + completion,
+        }
+    }
+
+    #[test]
+    fn test_ledger_accumulates_priced_usage() {
+        let ledger = CostLedger::default();
+        ledger.resolve_rate(
+            "m",
+            Some(ProviderModelPricing {
+                input_usd_per_million: Some(2.0),
+                output_usd_per_million: Some(8.0),
+            }),
+        );
+        let shared = ledger.clone();
+
+        assert_eq!(ledger.record("m", &usage(1_000_000, 0)), Some(2.0));
+        shared.record("m", &usage(500_000, 250_000));
+
+        let estimate = ledger.estimate();
+        assert_eq!(estimate.requests, 2);
+        assert_eq!(estimate.prompt_tokens, 1_500_000);
+        assert_eq!(estimate.completion_tokens, 250_000);
+        assert_eq!(estimate.total_usd, Some(5.0));
+        assert!(estimate.unpriced_models.is_empty());
+    }
+
+    #[test]
+    fn test_ledger_falls_back_to_pricing_table() {
+        let ledger = CostLedger::new(PricingTable::default().with_model("table-model", 1.0, 1.0));
+        // Incomplete discovered pricing does not shadow the table
+        ledger.resolve_rate(
+            "table-model",
+            Some(ProviderModelPricing {
+                input_usd_per_million: Some(5.0),
+                output_usd_per_million: None,
+            }),
+        );
+
+        assert_eq!(
+            ledger.record("table-model", &usage(1_000_000, 0)),
+            Some(1.0)
+        );
+        assert_eq!(ledger.estimate().total_usd, Some(1.0));
+    }
+
+    #[test]
+    fn test_unknown_pricing_makes_total_unknown() {
+        let ledger = CostLedger::new(PricingTable::default().with_model("known", 1.0, 1.0));
+        assert_eq!(CostLedger::default().estimate().total_usd, Some(0.0));
+
+        ledger.record("known", &usage(1_000_000, 0));
+        assert_eq!(ledger.record("mystery", &usage(100, 50)), None);
+
+        let estimate = ledger.estimate();
+        assert_eq!(estimate.requests, 2);
+        assert_eq!(estimate.prompt_tokens, 1_000_100);
+        assert_eq!(estimate.total_usd, None);
+        assert_eq!(estimate.unpriced_models, vec!["mystery".to_string()]);
+        assert_eq!(ledger.by_model()["known"].total_usd, Some(1.0));
+    }
+
+    #[test]
+    fn test_pricing_table_round_trip() -> anyhow::Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("pricing.json");
+        std::fs::write(
+            &path,
+            r#"{ "gpt-4o": { "input_usd_per_million": 2.5, "output_usd_per_million": 10.0 } }"#,
+        )?;
+
+        let table = PricingTable::load(&path)?;
+        let pricing = table.get("gpt-4o").map(|p| p.output_usd_per_million);
+        assert_eq!(pricing, Some(Some(10.0)));
+        assert!(table.get("gpt-4o-mini").is_none());
+        Ok(())
+    }
+}
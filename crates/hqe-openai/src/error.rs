@@ -0,0 +1,240 @@
+//! Typed errors returned by [`crate::OpenAIClient`].
+
+use std::time::Duration;
+
+use hqe_core::HqeError;
+use thiserror::Error;
+
+use crate::ApiError;
+
+/// Errors that can occur while talking to an OpenAI-compatible provider
+#[derive(Debug, Error)]
+pub enum OpenAIError {
+    /// The provider rejected the credentials (HTTP 401/403)
+    #[error("Authentication failed: {0}")]
+    AuthFailed(String),
+
+    /// The provider is throttling requests (HTTP 429)
+    #[error("Rate limited by provider{}", format_retry_after(.retry_after))]
+    RateLimited {
+        /// Delay requested by the provider via `Retry-After`, if any, as sent;
+        /// how long the client itself waits before retrying is decided separately
+        retry_after: Option<Duration>,
+    },
+
+    /// The prompt does not fit in the model's context window
+    #[error("Context length exceeded: {0}")]
+    ContextLengthExceeded(String),
+
+    /// The request was malformed or used unsupported parameters
+    #[error("Invalid request: {0}")]
+    InvalidRequest(String),
+
+    /// Network, TLS or timeout failure before a response was received
+    #[error("Transport error: {0}")]
+    Transport(#[source] reqwest::Error),
+
+    /// The response body could not be decoded
+    #[error("Failed to parse provider response: {0}")]
+    Deserialization(String),
+
+    /// Any other non-success response from the provider
+    #[error("Provider error {status}: {message}")]
+    Provider {
+        /// HTTP status code
+        status: u16,
+        /// Sanitized provider message
+        message: String,
+    },
+
+    /// The provider rejected the `json_schema` response format
+    #[error("Provider does not support structured output: {0}")]
+    SchemaUnsupported(String),
+
+    /// The response could not be parsed into the requested type, even after a repair attempt
+    #[error("Unrecoverable structured output: {0}")]
+    UnrecoverableJson(String),
+
+    /// The model kept calling tools past the round limit
+    #[error("Model still requested tool calls after {0} rounds")]
+    ToolRoundLimit(usize),
+
+    /// The client configuration is invalid (base URL, headers, API key characters)
+    #[error("Invalid client configuration: {0}")]
+    Config(String),
+
+    /// The configured daily budget has been spent
+    #[error("Daily API budget exceeded (${limit:.2}). Please increase limit in config if needed.")]
+    BudgetExceeded {
+        /// Configured daily limit in USD
+        limit: f64,
+    },
+
+    /// The scan's token or cost budget (see [`crate::SpendLimit`]) is used up
+    #[error("Scan budget exhausted: {0}")]
+    SpendLimitReached(String),
+
+    /// The provider failed repeatedly and the circuit breaker is failing fast
+    #[error("Provider circuit open after repeated failures (retry in {}s)", .retry_in.as_secs())]
+    CircuitOpen {
+        /// Time until the breaker lets a probe request through
+        retry_in: Duration,
+    },
+
+    /// The request was abandoned because the caller cancelled it
+    #[error("Request cancelled")]
+    Cancelled,
+}
+
+impl OpenAIError {
+    /// Whether retrying the same request later could succeed
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            OpenAIError::RateLimited { .. } => true,
+            OpenAIError::Transport(e) => e.is_timeout() || e.is_connect(),
+            OpenAIError::Provider { status, .. } => *status >= 500,
+            _ => false,
+        }
+    }
+
+    /// Classify a non-success HTTP response into a typed error.
+    ///
+    /// `body` is the raw response text; provider messages are sanitized before
+    /// being embedded in the error.
+    pub(crate) fn from_response(
+        status: reqwest::StatusCode,
+        body: &str,
+        retry_after: Option<Duration>,
+    ) -> Self {
+        let api_error = serde_json::from_str::<ApiError>(body).ok();
+        let message = match &api_error {
+            Some(api_error) => format!(
+                "{} ({})",
+                crate::sanitize_error_message(&api_error.error.message),
+                api_error.error.error_type
+            ),
+            None => status
+                .canonical_reason()
+                .unwrap_or("Unknown error")
+                .to_string(),
+        };
+
+        let code = api_error
+            .as_ref()
+            .and_then(|e| e.error.code.as_deref())
+            .unwrap_or_default();
+        let lower = api_error
+            .as_ref()
+            .map(|e| e.error.message.to_lowercase())
+            .unwrap_or_default();
+
+        match status.as_u16() {
+            401 | 403 => OpenAIError::AuthFailed(message),
+            429 => OpenAIError::RateLimited { retry_after },
+            400 | 413 | 422
+                if code == "context_length_exceeded"
+                    || lower.contains("context length")
+                    || lower.contains("context window")
+                    || lower.contains("maximum context")
+                    || lower.contains("prompt is too long") =>
+            {
+                OpenAIError::ContextLengthExceeded(message)
+            }
+            400 | 404 | 413 | 422 => OpenAIError::InvalidRequest(message),
+            status => OpenAIError::Provider { status, message },
+        }
+    }
+}
+
+fn format_retry_after(retry_after: &Option<Duration>) -> String {
+    retry_after
+        .map(|d| format!(" (retry after {}s)", d.as_secs()))
+        .unwrap_or_default()
+}
+
+impl From<reqwest::Error> for OpenAIError {
+    /// The request URL is dropped: it may carry the API key as a query parameter.
+    fn from(err: reqwest::Error) -> Self {
+        OpenAIError::Transport(err.without_url())
+    }
+}
+
+impl From<serde_json::Error> for OpenAIError {
+    fn from(err: serde_json::Error) -> Self {
+        OpenAIError::Deserialization(err.to_string())
+    }
+}
+
+impl From<url::ParseError> for OpenAIError {
+    fn from(err: url::ParseError) -> Self {
+        OpenAIError::Config(format!("Invalid URL: {err}"))
+    }
+}
+
+impl From<OpenAIError> for HqeError {
+    fn from(err: OpenAIError) -> Self {
+        match err {
+            OpenAIError::CircuitOpen { .. } => HqeError::ProviderUnavailable(err.to_string()),
+            OpenAIError::Cancelled => HqeError::Cancelled,
+            OpenAIError::SpendLimitReached(reason) => HqeError::BudgetExceeded(reason),
+            err => HqeError::Provider(err.to_string()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::StatusCode;
+
+    #[test]
+    fn test_classifies_auth_and_rate_limits() {
+        let err = OpenAIError::from_response(StatusCode::UNAUTHORIZED, "", None);
+        assert!(matches!(err, OpenAIError::AuthFailed(_)));
+
+        let err = OpenAIError::from_response(
+            StatusCode::TOO_MANY_REQUESTS,
+            "",
+            Some(Duration::from_secs(7)),
+        );
+        assert!(matches!(
+            err,
+            OpenAIError::RateLimited {
+                retry_after: Some(d)
+            } if d.as_secs() == 7
+        ));
+        assert!(err.is_retryable());
+        assert!(err.to_string().contains("retry after 7s"));
+    }
+
+    #[test]
+    fn test_classifies_context_length() {
+        let body = r#"{"error":{"message":"This model's maximum context length is 8192 tokens","type":"invalid_request_error","code":"context_length_exceeded"}}"#;
+        let err = OpenAIError::from_response(StatusCode::BAD_REQUEST, body, None);
+        assert!(matches!(err, OpenAIError::ContextLengthExceeded(_)));
+        assert!(!err.is_retryable());
+    }
+
+    #[test]
+    fn test_classifies_invalid_request_and_provider() {
+        let body = r#"{"error":{"message":"response_format is not supported","type":"invalid_request_error"}}"#;
+        let err = OpenAIError::from_response(StatusCode::BAD_REQUEST, body, None);
+        assert!(matches!(err, OpenAIError::InvalidRequest(ref m) if m.contains("response_format")));
+
+        let err = OpenAIError::from_response(StatusCode::BAD_GATEWAY, "<html>", None);
+        assert!(matches!(err, OpenAIError::Provider { status: 502, .. }));
+        assert!(err.is_retryable());
+    }
+
+    #[test]
+    fn test_converts_into_hqe_error() {
+        let err: HqeError = OpenAIError::InvalidRequest("bad".to_string()).into();
+        assert!(matches!(err, HqeError::Provider(ref m) if m.contains("bad")));
+
+        let err: HqeError = OpenAIError::CircuitOpen {
+            retry_in: Duration::from_secs(12),
+        }
+        .into();
+        assert!(matches!(err, HqeError::ProviderUnavailable(ref m) if m.contains("retry in 12s")));
+    }
+}
@@ -0,0 +1,234 @@
+//! Lossy JSON extraction from model replies.
+//!
+//! Even with a JSON response format, models wrap their output in ```json
+//! fences, put prose before or after it, leave trailing commas, or emit raw
+//! line breaks inside string literals. [`extract_json_lossy`] undoes those
+//! mistakes before parsing, so a reply that is "almost JSON" does not cost a
+//! repair round-trip or fail the file it was analyzing.
+
+use serde_json::Value;
+
+/// Parse the JSON document in a model reply, repairing common damage.
+///
+/// Tries the reply as-is first. Otherwise the content of the first code fence
+/// (if any) is searched for balanced `{...}` objects outside of string
+/// literals; each is tried in order after removing trailing commas and
+/// escaping raw control characters inside strings. The error of the last
+/// failed attempt is returned if nothing parses.
+pub fn extract_json_lossy(content: &str) -> serde_json::Result<Value> {
+    let trimmed = content.trim().trim_start_matches('\u{feff}');
+    let direct = match serde_json::from_str(trimmed) {
+        Ok(value) => return Ok(value),
+        Err(e) => e,
+    };
+
+    let body = strip_code_fence(trimmed).unwrap_or(trimmed);
+    let mut last_error = None;
+    let mut candidates = balanced_objects(body);
+    if candidates.is_empty() {
+        // No object at all (e.g. an array in a fence); repair what there is
+        candidates.push(body);
+    }
+    for candidate in candidates {
+        match serde_json::from_str(&repair(candidate)) {
+            Ok(value) => return Ok(value),
+            Err(e) => last_error = Some(e),
+        }
+    }
+    Err(last_error.unwrap_or(direct))
+}
+
+/// Content of the first ``` fence, with or without a language tag.
+///
+/// An unterminated fence (truncated reply) runs to the end of the input.
+fn strip_code_fence(input: &str) -> Option<&str> {
+    let start = input.find("```")?;
+    let after = &input[start + 3..];
+    // Skip the language tag, e.g. ```json or ```JSON
+    let body_start = after
+        .find(|c: char| !c.is_ascii_alphanumeric())
+        .unwrap_or(after.len());
+    let after = &after[body_start..];
+    let body = match after.find("```") {
+        Some(end) => &after[..end],
+        None => after,
+    };
+    let body = body.trim();
+    (!body.is_empty()).then_some(body)
+}
+
+/// Top-level balanced `{...}` spans, in order, ignoring braces in strings.
+fn balanced_objects(input: &str) -> Vec<&str> {
+    let mut objects = Vec::new();
+    let mut in_string = false;
+    let mut escape = false;
+    let mut depth = 0usize;
+    let mut start_idx = 0usize;
+
+    for (idx, ch) in input.char_indices() {
+        if in_string {
+            if escape {
+                escape = false;
+                continue;
+            }
+            match ch {
+                '\\' => escape = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match ch {
+            // Quotes only open strings inside an object; prose apostrophes
+            // and quotes before the JSON must not hide its braces
+            '"' if depth > 0 => in_string = true,
+            '{' => {
+                if depth == 0 {
+                    start_idx = idx;
+                }
+                depth += 1;
+            }
+            '}' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    objects.push(&input[start_idx..=idx]);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    objects
+}
+
+/// Drop trailing commas and escape control characters inside strings.
+fn repair(candidate: &str) -> String {
+    let mut out = String::with_capacity(candidate.len());
+    let mut in_string = false;
+    let mut escape = false;
+    let mut chars = candidate.chars();
+
+    while let Some(ch) = chars.next() {
+        if in_string {
+            if escape {
+                escape = false;
+                out.push(ch);
+                continue;
+            }
+            match ch {
+                '\\' => {
+                    escape = true;
+                    out.push(ch);
+                }
+                '"' => {
+                    in_string = false;
+                    out.push(ch);
+                }
+                '\n' => out.push_str("\\n"),
+                '\r' => out.push_str("\\r"),
+                '\t' => out.push_str("\\t"),
+                c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+                c => out.push(c),
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => {
+                in_string = true;
+                out.push(ch);
+            }
+            ',' => {
+                let rest = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(rest, Some('}' | ']')) {
+                    out.push(ch);
+                }
+            }
+            c => out.push(c),
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_extract_json_lossy_corpus() -> anyhow::Result<()> {
+        let expected =
+            json!({"findings": [{"id": "SEC-001", "title": "Hardcoded key"}], "is_partial": false});
+        let corpus = [
+            // Already valid
+            r#"{"findings": [{"id": "SEC-001", "title": "Hardcoded key"}], "is_partial": false}"#,
+            // Fenced with a language tag
+            "```json\n{\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}], \"is_partial\": false}\n```",
+            // Fenced with an upper-case tag and CRLF line endings
+            "```JSON\r\n{\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}],\r\n \"is_partial\": false}\r\n```",
+            // Fenced without a tag, prose around it
+            "Here is the analysis:\n```\n{\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}], \"is_partial\": false}\n```\nLet me know if you need more.",
+            // Unterminated fence (reply cut after the object)
+            "```json\n{\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}], \"is_partial\": false}",
+            // Prose before and after, no fence
+            "Sure! I found one issue. {\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}], \"is_partial\": false} Hope this helps.",
+            // Trailing commas in objects and arrays
+            "{\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\",},], \"is_partial\": false,}",
+            // Trailing comma followed by a newline
+            "{\n  \"findings\": [\n    {\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"},\n  ],\n  \"is_partial\": false,\n}",
+            // Leading byte order mark
+            "\u{feff}{\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}], \"is_partial\": false}",
+            // Braces in prose before the real object
+            "Using the {findings} format you asked for: {\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}], \"is_partial\": false}",
+            // Apostrophes and quotes in the prose
+            "Here's the \"JSON\" you wanted: {\"findings\": [{\"id\": \"SEC-001\", \"title\": \"Hardcoded key\"}], \"is_partial\": false}",
+        ];
+
+        for (i, reply) in corpus.iter().enumerate() {
+            let value = extract_json_lossy(reply)
+                .map_err(|e| anyhow::anyhow!("corpus entry {i} failed: {e}"))?;
+            assert_eq!(value, expected, "corpus entry {i}");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_json_lossy_escapes_control_characters_in_strings() -> anyhow::Result<()> {
+        let reply = "{\"recommendation\": \"Rotate the key.\nThen\trevoke it.\r\n\u{0007}\"}";
+        let value = extract_json_lossy(reply)?;
+        assert_eq!(
+            value["recommendation"],
+            "Rotate the key.\nThen\trevoke it.\r\n\u{0007}"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_json_lossy_leaves_string_content_alone() -> anyhow::Result<()> {
+        // Commas before braces, braces and escaped quotes inside strings
+        let reply = r#"Result: {"title": "Use {a,}, not [b,]", "quote": "say \"hi,\" }", "n": 1,}"#;
+        let value = extract_json_lossy(reply)?;
+        assert_eq!(value["title"], "Use {a,}, not [b,]");
+        assert_eq!(value["quote"], "say \"hi,\" }");
+        assert_eq!(value["n"], 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_json_lossy_repairs_fenced_arrays() -> anyhow::Result<()> {
+        let value = extract_json_lossy("```json\n[1, 2, 3,]\n```")?;
+        assert_eq!(value, json!([1, 2, 3]));
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_json_lossy_rejects_non_json() {
+        assert!(extract_json_lossy("").is_err());
+        assert!(extract_json_lossy("I could not analyze this file.").is_err());
+        // Truncated mid-object: nothing balanced to recover
+        assert!(extract_json_lossy("{\"findings\": [{\"id\": \"SEC-001\"").is_err());
+        assert!(extract_json_lossy("```json\n{\"a\": }\n```").is_err());
+    }
+}
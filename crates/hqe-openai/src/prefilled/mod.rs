@@ -4,34 +4,11 @@
 //! Each spec includes base URL, auth scheme, default headers, discovery behavior,
 //! and documented quirks.
 
-use hqe_protocol::models::ProviderKind;
+use hqe_protocol::models::{ProviderKind, ProviderProfile};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Authentication scheme for a provider
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-#[serde(rename_all = "snake_case")]
-pub enum AuthScheme {
-    /// Bearer token in Authorization header (standard OpenAI)
-    Bearer,
-    /// API key in x-api-key header
-    ApiKeyHeader,
-    /// API key in query parameter
-    ApiKeyQuery,
-    /// Custom header
-    CustomHeader,
-}
-
-impl std::fmt::Display for AuthScheme {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            AuthScheme::Bearer => write!(f, "Bearer"),
-            AuthScheme::ApiKeyHeader => write!(f, "X-API-Key"),
-            AuthScheme::ApiKeyQuery => write!(f, "Query Param"),
-            AuthScheme::CustomHeader => write!(f, "Custom"),
-        }
-    }
-}
+pub use hqe_protocol::models::AuthScheme;
 
 /// A prefilled provider specification
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -66,6 +43,9 @@ pub struct ProviderSpec {
     pub supports_tools: bool,
     /// Rate limit notes
     pub rate_limit_notes: Option<String>,
+    /// Request parameters the provider rejects; stripped before sending
+    #[serde(default)]
+    pub unsupported_params: Vec<String>,
 }
 
 impl ProviderSpec {
@@ -80,10 +60,28 @@ impl ProviderSpec {
             AuthScheme::Bearer => "Authorization",
             AuthScheme::ApiKeyHeader => "x-api-key",
             AuthScheme::ApiKeyQuery => "api_key",
+            AuthScheme::CustomHeader if self.kind == ProviderKind::Azure => "api-key",
             AuthScheme::CustomHeader => "Authorization",
         }
     }
 
+    /// Build a provider profile from this spec.
+    ///
+    /// Copies the base URL, default headers, default model, recommended timeout,
+    /// auth scheme and unsupported parameters; the API key itself is stored separately.
+    pub fn to_profile(&self, name: impl Into<String>) -> ProviderProfile {
+        let mut profile = ProviderProfile::new(name, self.base_url.clone())
+            .with_model(self.default_model.clone())
+            .with_provider_kind(self.kind)
+            .with_timeout(self.recommended_timeout_s)
+            .with_auth_scheme(self.auth_scheme);
+        if !self.default_headers.is_empty() {
+            profile.headers = Some(self.default_headers.clone());
+        }
+        profile.unsupported_params = self.unsupported_params.clone();
+        profile
+    }
+
     /// Format an API key according to the auth scheme
     pub fn format_api_key(&self, key: &str) -> String {
         match self.auth_scheme {
@@ -112,6 +110,7 @@ pub struct ProviderSpecBuilder {
     supports_streaming: bool,
     supports_tools: bool,
     rate_limit_notes: Option<String>,
+    unsupported_params: Vec<String>,
 }
 
 impl ProviderSpecBuilder {
@@ -132,6 +131,7 @@ impl ProviderSpecBuilder {
             supports_streaming: true,
             supports_tools: true,
             rate_limit_notes: None,
+            unsupported_params: Vec::new(),
         }
     }
 
@@ -220,6 +220,13 @@ impl ProviderSpecBuilder {
     }
 
     /// Build the final ProviderSpec.
+    /// Mark a request parameter as rejected by the provider.
+    pub fn unsupported_param(mut self, param: impl Into<String>) -> Self {
+        self.unsupported_params.push(param.into());
+        self
+    }
+
+    /// Build the provider spec.
     pub fn build(self) -> ProviderSpec {
         ProviderSpec {
             id: self.id,
@@ -243,6 +250,7 @@ impl ProviderSpecBuilder {
             supports_streaming: self.supports_streaming,
             supports_tools: self.supports_tools,
             rate_limit_notes: self.rate_limit_notes,
+            unsupported_params: self.unsupported_params,
         }
     }
 }
@@ -256,6 +264,10 @@ pub fn all_specs() -> Vec<ProviderSpec> {
         openrouter(),
         xai_grok(),
         kimi(),
+        azure_openai(),
+        gemini(),
+        ollama(),
+        lmstudio(),
     ]
 }
 
@@ -285,9 +297,9 @@ pub fn anthropic() -> ProviderSpec {
 
     ProviderSpec::builder("anthropic")
         .display_name("Anthropic")
-        .kind(ProviderKind::Generic) // Using Generic until we add Anthropic variant
+        .kind(ProviderKind::Anthropic)
         .base_url("https://api.anthropic.com/v1")
-        .auth_scheme(AuthScheme::Bearer)
+        .auth_scheme(AuthScheme::ApiKeyHeader)
         .default_header("Content-Type", "application/json")
         .default_header("anthropic-version", "2023-06-01")
         .default_model("claude-3-5-sonnet-latest")
@@ -387,6 +399,93 @@ pub fn kimi() -> ProviderSpec {
         .build()
 }
 
+/// Azure OpenAI spec
+pub fn azure_openai() -> ProviderSpec {
+    ProviderSpec::builder("azure")
+        .display_name("Azure OpenAI")
+        .kind(ProviderKind::Azure)
+        .base_url("https://YOUR-RESOURCE.openai.azure.com")
+        .auth_scheme(AuthScheme::CustomHeader)
+        .default_header("Content-Type", "application/json")
+        .default_model("gpt-4o-mini")
+        .timeout(60)
+        .website("https://azure.microsoft.com/products/ai-services/openai-service")
+        .docs("https://learn.microsoft.com/azure/ai-services/openai/reference")
+        .streaming(true)
+        .tools(true)
+        .quirk("Replace YOUR-RESOURCE in the base URL with your resource name")
+        .quirk("Requests are routed by deployment name, not model (--azure-deployment)")
+        .quirk("Requires an api-version query parameter (--azure-api-version)")
+        .quirk("API key goes in the 'api-key' header, not Authorization")
+        .quirk("Model discovery is not supported; the model is fixed by the deployment")
+        .rate_limit_notes("Quota is assigned per deployment in tokens per minute")
+        .build()
+}
+
+/// Google Gemini (OpenAI compatibility layer) spec
+pub fn gemini() -> ProviderSpec {
+    ProviderSpec::builder("gemini")
+        .display_name("Google Gemini")
+        .kind(ProviderKind::Generic)
+        .base_url("https://generativelanguage.googleapis.com/v1beta/openai")
+        .auth_scheme(AuthScheme::Bearer)
+        .default_header("Content-Type", "application/json")
+        .default_model("gemini-2.0-flash")
+        .timeout(60)
+        .website("https://ai.google.dev")
+        .docs("https://ai.google.dev/gemini-api/docs/openai")
+        .streaming(true)
+        .tools(true)
+        .unsupported_param("frequency_penalty")
+        .unsupported_param("presence_penalty")
+        .quirk("OpenAI compatibility layer lives under /v1beta/openai")
+        .quirk("Rejects frequency_penalty/presence_penalty on some models; they are not sent")
+        .quirk("Returns finish_reason in uppercase (e.g. 'STOP')")
+        .rate_limit_notes("Rate limits: https://ai.google.dev/gemini-api/docs/rate-limits")
+        .build()
+}
+
+/// Ollama (local) spec
+pub fn ollama() -> ProviderSpec {
+    ProviderSpec::builder("ollama")
+        .display_name("Ollama")
+        .kind(ProviderKind::Ollama)
+        .base_url("http://localhost:11434/v1")
+        .auth_scheme(AuthScheme::Bearer)
+        .default_header("Content-Type", "application/json")
+        .default_model("llama3.1")
+        .timeout(300)
+        .website("https://ollama.com")
+        .docs("https://github.com/ollama/ollama/blob/main/docs/openai.md")
+        .streaming(true)
+        .tools(true)
+        .quirk("No API key needed; any key is ignored")
+        .quirk("Models must be pulled first (`ollama pull <model>`)")
+        .quirk("Model discovery reads the native /api/tags listing")
+        .quirk("First request after idle is slow while the model loads")
+        .build()
+}
+
+/// LM Studio (local server) spec
+pub fn lmstudio() -> ProviderSpec {
+    ProviderSpec::builder("lmstudio")
+        .display_name("LM Studio")
+        .kind(ProviderKind::LmStudio)
+        .base_url("http://localhost:1234/v1")
+        .auth_scheme(AuthScheme::Bearer)
+        .default_header("Content-Type", "application/json")
+        .default_model("local-model")
+        .timeout(300)
+        .website("https://lmstudio.ai")
+        .docs("https://lmstudio.ai/docs/app/api/endpoints/openai")
+        .streaming(true)
+        .tools(true)
+        .quirk("No API key needed; start the server from the Developer tab")
+        .quirk("Use the model identifier shown by LM Studio (see /v1/models)")
+        .quirk("Context length is set when the model is loaded, not per request")
+        .build()
+}
+
 /// Get a spec by ID
 pub fn get_spec(id: &str) -> Option<ProviderSpec> {
     all_specs().into_iter().find(|s| s.id == id)
@@ -418,6 +517,8 @@ mod tests {
     fn test_anthropic_spec() {
         let spec = anthropic();
         assert_eq!(spec.id, "anthropic");
+        assert_eq!(spec.kind, ProviderKind::Anthropic);
+        assert_eq!(spec.auth_header_name(), "x-api-key");
         assert!(spec.default_headers.contains_key("anthropic-version"));
         assert!(!spec.quirks.is_empty());
     }
@@ -451,10 +552,42 @@ mod tests {
         assert_eq!(spec.base_url, "https://api.moonshot.cn/v1");
     }
 
+    #[test]
+    fn test_azure_spec() {
+        let spec = azure_openai();
+        assert_eq!(spec.kind, ProviderKind::Azure);
+        assert_eq!(spec.auth_header_name(), "api-key");
+        assert_eq!(spec.format_api_key("test123"), "test123");
+    }
+
+    #[test]
+    fn test_gemini_spec() {
+        let spec = gemini();
+        assert_eq!(
+            spec.base_url,
+            "https://generativelanguage.googleapis.com/v1beta/openai"
+        );
+        assert_eq!(
+            spec.to_profile("gemini").unsupported_params,
+            ["frequency_penalty", "presence_penalty"]
+        );
+    }
+
+    #[test]
+    fn test_local_specs() {
+        let spec = ollama();
+        assert_eq!(spec.kind, ProviderKind::Ollama);
+        assert_eq!(spec.base_url, "http://localhost:11434/v1");
+
+        let spec = lmstudio();
+        assert_eq!(spec.kind, ProviderKind::LmStudio);
+        assert_eq!(spec.base_url, "http://localhost:1234/v1");
+    }
+
     #[test]
     fn test_all_specs_count() {
         let specs = all_specs();
-        assert_eq!(specs.len(), 6);
+        assert_eq!(specs.len(), 10);
     }
 
     #[test]
@@ -473,10 +606,28 @@ mod tests {
     #[test]
     fn test_spec_list() {
         let list = spec_list();
-        assert_eq!(list.len(), 6);
+        assert_eq!(list.len(), 10);
         assert!(list.iter().any(|(id, _)| id == "openai"));
     }
 
+    #[test]
+    fn test_to_profile_copies_spec() {
+        let spec = openrouter();
+        let profile = spec.to_profile("router");
+
+        assert_eq!(profile.name, "router");
+        assert_eq!(profile.api_key_id, "api_key:router");
+        assert_eq!(profile.base_url, spec.base_url);
+        assert_eq!(profile.default_model, "openai/gpt-4o-mini");
+        assert_eq!(profile.timeout_s, 120);
+        assert_eq!(profile.provider_kind, Some(ProviderKind::OpenRouter));
+        assert_eq!(profile.auth_scheme, Some(AuthScheme::Bearer));
+        assert_eq!(profile.headers, Some(spec.default_headers));
+
+        let anthropic = anthropic().to_profile("claude");
+        assert_eq!(anthropic.auth_scheme, Some(AuthScheme::ApiKeyHeader));
+    }
+
     #[test]
     fn test_format_api_key_bearer() {
         let spec = openai();
@@ -4,6 +4,7 @@
 //! - Profile configuration (base_url, headers, timeouts)
 //! - Secure API key storage via macOS Keychain
 //! - Persistent profile storage in ~/.local/share/hqe-workbench/
+//! - Versioned import/export bundles for sharing profiles (without secrets)
 
 use std::{
     collections::{BTreeMap, HashMap},
@@ -11,7 +12,9 @@ use std::{
     path::PathBuf,
 };
 
+use anyhow::{bail, Context};
 use secrecy::SecretString;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{debug, info, instrument, warn};
 use url::Url;
@@ -19,6 +22,7 @@ use url::Url;
 use crate::provider_discovery::{
     sanitize_base_url, sanitize_headers, DiscoveryError, ProviderKind, ProviderKindExt,
 };
+use crate::{AuthPlacement, AuthScheme};
 // Re-export ProviderProfile from hqe-protocol
 pub use hqe_protocol::models::ProviderProfile;
 
@@ -30,6 +34,8 @@ pub trait ProviderProfileExt {
     fn sanitized_headers(&self) -> Result<HashMap<String, String>, ProfileError>;
     /// Get the effective provider kind (auto-detected if not overridden)
     fn effective_kind(&self) -> Result<ProviderKind, ProfileError>;
+    /// Get where the API key is sent (Bearer if not configured)
+    fn auth_placement(&self) -> AuthPlacement;
 }
 
 impl ProviderProfileExt for ProviderProfile {
@@ -49,6 +55,16 @@ impl ProviderProfileExt for ProviderProfile {
         let url = self.normalized_base_url()?;
         Ok(ProviderKind::detect(&url))
     }
+
+    fn auth_placement(&self) -> AuthPlacement {
+        match self.auth_scheme {
+            // Azure reads the raw key from its own header
+            Some(AuthScheme::CustomHeader) if self.provider_kind == Some(ProviderKind::Azure) => {
+                AuthPlacement::CustomHeader("api-key".to_string())
+            }
+            scheme => scheme.map(AuthPlacement::from).unwrap_or_default(),
+        }
+    }
 }
 
 /// Errors that can occur during profile operations
@@ -317,6 +333,59 @@ impl ApiKeyStore for MemoryKeyStore {
     }
 }
 
+/// Current version of the [`ProfileBundle`] format
+pub const PROFILE_BUNDLE_VERSION: u32 = 1;
+
+/// Shareable set of provider profiles.
+///
+/// Bundles never carry API key material: profiles reference their key only by
+/// `api_key_id`, and credential-looking headers are dropped on export.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileBundle {
+    /// Bundle format version
+    pub version: u32,
+    /// Exported profiles
+    pub profiles: Vec<ProviderProfile>,
+}
+
+/// Outcome of [`ProfileManager::import_bundle`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BundleImport {
+    /// Profiles that were saved
+    pub imported: Vec<String>,
+    /// Profiles left alone because one with the same name already exists
+    pub skipped: Vec<String>,
+    /// Imported profiles with no API key in the local key store yet
+    pub missing_keys: Vec<String>,
+}
+
+/// Prefix of the keychain account that holds a profile's API key
+const API_KEY_ID_PREFIX: &str = "api_key:";
+
+/// Key store entry referenced by a profile's `api_key_id`
+fn key_owner(profile: &ProviderProfile) -> &str {
+    profile
+        .api_key_id
+        .strip_prefix(API_KEY_ID_PREFIX)
+        .filter(|owner| !owner.is_empty())
+        .unwrap_or(&profile.name)
+}
+
+/// Headers that may carry credentials and must not leave the machine
+fn is_credential_header(name: &str) -> bool {
+    let name = name.to_ascii_lowercase();
+    [
+        "authorization",
+        "cookie",
+        "key",
+        "token",
+        "secret",
+        "password",
+    ]
+    .iter()
+    .any(|needle| name.contains(needle))
+}
+
 /// Complete profile manager combining storage and key management
 #[derive(Debug)]
 pub struct ProfileManager<S: ProfilesStore, K: ApiKeyStore> {
@@ -346,7 +415,7 @@ impl<S: ProfilesStore, K: ApiKeyStore> ProfileManager<S, K> {
             Some(p) => {
                 let key = self
                     .key_store
-                    .get_api_key(name)
+                    .get_api_key(key_owner(&p))
                     .map_err(ProfileError::KeyStore)?;
                 Ok(Some((p, key)))
             }
@@ -388,6 +457,117 @@ impl<S: ProfilesStore, K: ApiKeyStore> ProfileManager<S, K> {
         }
         Ok(deleted)
     }
+
+    /// Export profiles as a versioned JSON bundle for sharing.
+    ///
+    /// An empty `names` slice exports every profile. API keys are never
+    /// included, and headers that look like credentials are dropped.
+    #[instrument(skip(self))]
+    pub fn export_bundle(&self, names: &[String]) -> anyhow::Result<String> {
+        let mut profiles = self.store.load_profiles()?;
+        if !names.is_empty() {
+            if let Some(missing) = names
+                .iter()
+                .find(|n| !profiles.iter().any(|p| &p.name == *n))
+            {
+                bail!("profile not found: {missing}");
+            }
+            profiles.retain(|p| names.contains(&p.name));
+        }
+
+        for profile in &mut profiles {
+            if let Some(headers) = profile.headers.as_mut() {
+                headers.retain(|name, _| {
+                    let keep = !is_credential_header(name);
+                    if !keep {
+                        warn!(profile = %profile.name, header = %name, "Dropping credential header from export");
+                    }
+                    keep
+                });
+            }
+            if profile.headers.as_ref().is_some_and(HashMap::is_empty) {
+                profile.headers = None;
+            }
+        }
+
+        let bundle = ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            profiles,
+        };
+        info!(count = bundle.profiles.len(), "Exported profile bundle");
+        Ok(serde_json::to_string_pretty(&bundle)?)
+    }
+
+    /// Import a bundle produced by [`export_bundle`](Self::export_bundle).
+    ///
+    /// Every profile's base URL and headers are validated before anything is
+    /// persisted, so an invalid bundle leaves the store untouched. Existing
+    /// profiles are only replaced when `overwrite` is set. A profile keeps its
+    /// `api_key_id` when the local key store has that entry, so teammates who
+    /// already stored the key are linked to it; otherwise it points at the
+    /// profile's own entry and is listed in [`BundleImport::missing_keys`].
+    #[instrument(skip(self, json))]
+    pub fn import_bundle(&self, json: &str, overwrite: bool) -> anyhow::Result<BundleImport> {
+        let bundle: ProfileBundle = serde_json::from_str(json).context("invalid profile bundle")?;
+        if bundle.version == 0 || bundle.version > PROFILE_BUNDLE_VERSION {
+            bail!(
+                "unsupported profile bundle version {} (expected {})",
+                bundle.version,
+                PROFILE_BUNDLE_VERSION
+            );
+        }
+
+        let mut seen = std::collections::HashSet::new();
+        for profile in &bundle.profiles {
+            if profile.name.trim().is_empty() {
+                bail!("profile bundle contains a profile without a name");
+            }
+            if !seen.insert(profile.name.as_str()) {
+                bail!("profile bundle contains '{}' twice", profile.name);
+            }
+            profile
+                .normalized_base_url()
+                .with_context(|| format!("profile '{}'", profile.name))?;
+            profile
+                .sanitized_headers()
+                .with_context(|| format!("profile '{}'", profile.name))?;
+        }
+
+        let mut stored = self.store.load_profiles()?;
+        let mut outcome = BundleImport::default();
+        for mut profile in bundle.profiles {
+            if !overwrite && stored.iter().any(|p| p.name == profile.name) {
+                outcome.skipped.push(profile.name);
+                continue;
+            }
+
+            let has_key = self
+                .key_store
+                .get_api_key(key_owner(&profile))
+                .map_err(ProfileError::KeyStore)?
+                .is_some();
+            if !has_key || !profile.api_key_id.starts_with(API_KEY_ID_PREFIX) {
+                profile.api_key_id = format!("{API_KEY_ID_PREFIX}{}", profile.name);
+            }
+            if !has_key {
+                outcome.missing_keys.push(profile.name.clone());
+            }
+
+            stored.retain(|p| p.name != profile.name);
+            outcome.imported.push(profile.name.clone());
+            stored.push(profile);
+        }
+
+        if !outcome.imported.is_empty() {
+            self.store.save_profiles(&stored)?;
+        }
+        info!(
+            imported = outcome.imported.len(),
+            skipped = outcome.skipped.len(),
+            "Imported profile bundle"
+        );
+        Ok(outcome)
+    }
 }
 
 impl Default for ProfileManager<DefaultProfilesStore, KeychainStore> {
@@ -469,6 +649,84 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn bundle_export_omits_secrets_and_round_trips() -> anyhow::Result<()> {
+        let key_store = MemoryKeyStore::default();
+        let source = ProfileManager::new(MemoryProfilesStore::default(), key_store.clone());
+        let profile = ProviderProfile::new("team", "https://api.example.com/v1")
+            .with_header("X-Api-Key", "header-secret")
+            .with_header("X-Region", "eu");
+        source.save_profile(profile, Some("sk-secret"))?;
+        source.save_profile(
+            ProviderProfile::new("other", "https://api.other.com/v1"),
+            None,
+        )?;
+
+        let json = source.export_bundle(&["team".to_string()])?;
+        assert!(!json.contains("sk-secret"));
+        assert!(!json.contains("header-secret"));
+        let bundle: ProfileBundle = serde_json::from_str(&json)?;
+        assert_eq!(bundle.version, PROFILE_BUNDLE_VERSION);
+        assert_eq!(bundle.profiles.len(), 1);
+        assert!(source.export_bundle(&["nope".to_string()]).is_err());
+
+        // A teammate sharing the key store is linked to the existing key
+        let target = ProfileManager::new(MemoryProfilesStore::default(), key_store);
+        let outcome = target.import_bundle(&json, false)?;
+        assert_eq!(outcome.imported, vec!["team".to_string()]);
+        assert!(outcome.missing_keys.is_empty());
+        let (loaded, key) = target.get_profile_with_key("team")?.unwrap();
+        assert_eq!(key.unwrap().expose_secret(), "sk-secret");
+        assert_eq!(
+            loaded.headers.as_ref().and_then(|h| h.get("X-Region")),
+            Some(&"eu".to_string())
+        );
+
+        // Without a key locally the profile is flagged
+        let fresh = ProfileManager::new(MemoryProfilesStore::default(), MemoryKeyStore::default());
+        let outcome = fresh.import_bundle(&json, false)?;
+        assert_eq!(outcome.missing_keys, vec!["team".to_string()]);
+        Ok(())
+    }
+
+    #[test]
+    fn bundle_import_respects_overwrite_and_validates() -> anyhow::Result<()> {
+        let manager =
+            ProfileManager::new(MemoryProfilesStore::default(), MemoryKeyStore::default());
+        manager.save_profile(
+            ProviderProfile::new("team", "https://api.example.com/v1").with_model("old"),
+            None,
+        )?;
+        let json = serde_json::to_string(&ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            profiles: vec![
+                ProviderProfile::new("team", "https://api.example.com/v1").with_model("new")
+            ],
+        })?;
+
+        let outcome = manager.import_bundle(&json, false)?;
+        assert_eq!(outcome.skipped, vec!["team".to_string()]);
+        assert_eq!(manager.load_profiles()?[0].default_model, "old");
+
+        manager.import_bundle(&json, true)?;
+        assert_eq!(manager.load_profiles()?[0].default_model, "new");
+
+        // One bad profile rejects the whole bundle
+        let bad = serde_json::to_string(&ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            profiles: vec![
+                ProviderProfile::new("ok", "https://api.ok.com/v1"),
+                ProviderProfile::new("bad", "http://remote.example.com/v1"),
+            ],
+        })?;
+        assert!(manager.import_bundle(&bad, true).is_err());
+        assert!(manager.store.get_profile("ok")?.is_none());
+
+        let future = r#"{"version": 99, "profiles": []}"#;
+        assert!(manager.import_bundle(future, true).is_err());
+        Ok(())
+    }
+
     #[test]
     fn default_profiles_store_path() {
         let store = DefaultProfilesStore;
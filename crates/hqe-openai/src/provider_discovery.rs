@@ -4,6 +4,7 @@
 //! - Venice.ai
 //! - xAI / Grok
 //! - OpenRouter
+//! - Ollama / LM Studio (local, no API key required)
 //! - Generic OpenAI-compatible endpoints
 
 use std::{
@@ -13,9 +14,13 @@ use std::{
     time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION};
+use reqwest::header::{
+    HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH,
+    LAST_MODIFIED,
+};
+use reqwest::StatusCode;
 use secrecy::{ExposeSecret, SecretString};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use serde_json::Value;
 use thiserror::Error;
 use tracing::{debug, info, instrument, warn};
@@ -24,6 +29,13 @@ use url::Url;
 // Re-export ProviderKind from hqe-protocol for consistency
 pub use hqe_protocol::models::ProviderKind;
 
+use crate::capabilities::ModelCapabilities;
+
+/// Default port of a local Ollama server
+pub const OLLAMA_DEFAULT_PORT: u16 = 11434;
+/// Default port of the LM Studio local server
+pub const LMSTUDIO_DEFAULT_PORT: u16 = 1234;
+
 /// Extension trait for ProviderKind with detection logic
 pub trait ProviderKindExt {
     /// Auto-detect provider kind from base URL hostname
@@ -48,6 +60,17 @@ impl ProviderKindExt for ProviderKind {
         if host.ends_with(".openai.azure.com") {
             return ProviderKind::Azure;
         }
+        if host == "api.anthropic.com" || host.ends_with(".anthropic.com") {
+            return ProviderKind::Anthropic;
+        }
+        // Local servers are recognized by their default ports
+        if is_local_or_private_url(base_url) {
+            match base_url.port() {
+                Some(OLLAMA_DEFAULT_PORT) => return ProviderKind::Ollama,
+                Some(LMSTUDIO_DEFAULT_PORT) => return ProviderKind::LmStudio,
+                _ => {}
+            }
+        }
         ProviderKind::Generic
     }
 }
@@ -78,6 +101,51 @@ pub struct ProviderModelTraits {
     pub supports_logprobs: bool,
     /// Whether the model is optimized for code generation
     pub code_optimized: bool,
+    /// Whether the model supports `response_format: json_object`
+    #[serde(default)]
+    pub supports_json_mode: bool,
+    /// Context window size in tokens, if known
+    #[serde(default)]
+    pub context_window: Option<u32>,
+    /// Maximum completion tokens per response, if known
+    #[serde(default)]
+    pub max_output_tokens: Option<u32>,
+}
+
+impl ProviderModelTraits {
+    /// Capabilities of well-known OpenAI models, keyed by model id prefix.
+    ///
+    /// Used when the provider's model listing carries no capability metadata.
+    pub fn for_known_model(model_id: &str) -> Option<Self> {
+        let id = model_id
+            .rsplit('/')
+            .next()
+            .unwrap_or(model_id)
+            .to_lowercase();
+        // (prefix, json mode, json schema, max output tokens); longer prefixes first
+        const KNOWN: &[(&str, bool, bool, u32)] = &[
+            ("gpt-4o-mini", true, true, 16_384),
+            ("gpt-4o", true, true, 16_384),
+            ("chatgpt-4o", true, false, 16_384),
+            ("gpt-4.1", true, true, 32_768),
+            ("o4-mini", true, true, 100_000),
+            ("o3", true, true, 100_000),
+            ("o1", true, true, 100_000),
+            ("gpt-4-turbo", true, false, 4_096),
+            ("gpt-4", false, false, 8_192),
+            ("gpt-3.5-turbo", true, false, 4_096),
+        ];
+        let (_, json_mode, json_schema, max_output) =
+            KNOWN.iter().find(|(prefix, ..)| id.starts_with(prefix))?;
+        Some(Self {
+            supports_tools: true,
+            supports_json_mode: *json_mode,
+            supports_response_schema: *json_schema,
+            context_window: crate::token_count::context_window(&id).map(|n| n as u32),
+            max_output_tokens: Some(*max_output),
+            ..Self::default()
+        })
+    }
 }
 
 /// A discovered model from the provider's /models endpoint
@@ -110,6 +178,12 @@ pub struct ProviderModelList {
     pub fetched_at_unix_s: u64,
     /// List of discovered models
     pub models: Vec<DiscoveredModel>,
+    /// `ETag` of the listing response, sent back as `If-None-Match`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// `Last-Modified` of the listing response, sent back as `If-Modified-Since`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<String>,
 }
 
 /// Client for discovering models from OpenAI-compatible providers
@@ -130,7 +204,8 @@ impl ProviderDiscoveryClient {
     /// # Arguments
     /// * `base_url_raw` - The provider's base URL
     /// * `headers_raw` - Additional headers (excluding Authorization)
-    /// * `api_key` - Optional API key (will be wrapped in Bearer auth)
+    /// * `api_key` - API key (will be wrapped in Bearer auth); may be omitted
+    ///   only for local or private-network providers
     /// * `timeout` - Request timeout
     /// * `cache` - Optional disk cache for model lists
     #[instrument(skip(api_key, headers_raw))]
@@ -145,6 +220,14 @@ impl ProviderDiscoveryClient {
         let provider_kind = ProviderKind::detect(&base_url);
         info!(%provider_kind, %base_url, "Detected provider kind");
 
+        // Local servers (Ollama, LM Studio, ...) usually run without auth
+        let api_key = api_key.filter(|key| !key.expose_secret().trim().is_empty());
+        if api_key.is_none() && !is_local_or_private_url(&base_url) {
+            return Err(DiscoveryError::InvalidApiKey(
+                "API key is required for non-local providers".to_string(),
+            ));
+        }
+
         let mut headers = HeaderMap::new();
         let headers_hash: HashMap<String, String> = headers_raw
             .iter()
@@ -158,13 +241,27 @@ impl ProviderDiscoveryClient {
             headers.insert(name, value);
         }
 
-        // Prefer bearer auth for OpenAI-compat providers
+        // Prefer bearer auth for OpenAI-compat providers; Anthropic uses x-api-key
         if let Some(ref key) = api_key {
             let key_str = key.expose_secret();
-            let hv = HeaderValue::from_str(&format!("Bearer {key_str}")).map_err(|_| {
+            let invalid_key = |_| {
                 DiscoveryError::InvalidApiKey("api key contains illegal characters".to_string())
-            })?;
-            headers.insert(AUTHORIZATION, hv);
+            };
+            if provider_kind == ProviderKind::Anthropic {
+                headers.insert(
+                    HeaderName::from_static("x-api-key"),
+                    HeaderValue::from_str(key_str).map_err(invalid_key)?,
+                );
+                headers
+                    .entry(HeaderName::from_static("anthropic-version"))
+                    .or_insert(HeaderValue::from_static(
+                        crate::anthropic::ANTHROPIC_VERSION,
+                    ));
+            } else {
+                let hv =
+                    HeaderValue::from_str(&format!("Bearer {key_str}")).map_err(invalid_key)?;
+                headers.insert(AUTHORIZATION, hv);
+            }
         }
 
         let http = reqwest::Client::builder()
@@ -212,18 +309,33 @@ impl ProviderDiscoveryClient {
     /// Discover chat models from the provider
     ///
     /// Returns only chat models (filters out embeddings, audio, etc.)
+    ///
+    /// Once the cached list is no longer fresh, it is revalidated with the
+    /// validators the provider sent (`If-None-Match` / `If-Modified-Since`); a
+    /// `304 Not Modified` refreshes the cache entry without downloading the
+    /// catalog again. Without validators the list is fetched in full.
     #[instrument(skip(self))]
     pub async fn discover_chat_models(&self) -> Result<ProviderModelList, DiscoveryError> {
         // Check cache first
+        let mut revalidate = None;
         if let Some(cache) = &self.cache {
             if let Some(cached) = cache.get_fresh(&self.cache_key())? {
                 debug!("Returning cached model list");
                 return Ok(cached);
             }
+            revalidate = cache
+                .get_stale(&self.cache_key())?
+                .filter(|cached| cached.etag.is_some() || cached.last_modified.is_some());
         }
 
-        let mut url = join_path(&self.base_url, "models")
-            .map_err(|e| DiscoveryError::InvalidBaseUrl(e.to_string()))?;
+        // Ollama's own listing carries model families, which catch embedding models
+        let ollama_native = self.provider_kind == ProviderKind::Ollama;
+        let mut url = if ollama_native {
+            self.ollama_tags_url()
+        } else {
+            join_path(&self.base_url, "models")
+                .map_err(|e| DiscoveryError::InvalidBaseUrl(e.to_string()))?
+        };
         if self.provider_kind == ProviderKind::Venice {
             // Fetch all model types, then filter to text/code locally.
             url.query_pairs_mut().append_pair("type", "all");
@@ -237,29 +349,39 @@ impl ProviderDiscoveryClient {
 
         info!(%url, "Fetching models from provider");
 
-        let resp = self
-            .http
-            .get(url)
-            .send()
-            .await
-            .map_err(|e| DiscoveryError::Http(e.to_string()))?;
-        let status = resp.status();
-        let body = resp
-            .text()
-            .await
-            .map_err(|e| DiscoveryError::Http(e.to_string()))?;
-
-        if !status.is_success() {
-            return Err(DiscoveryError::Provider(
-                status.as_u16(),
-                truncate(&body, 400),
-            ));
-        }
-
-        let json: Value =
-            serde_json::from_str(&body).map_err(|e| DiscoveryError::Json(e.to_string()))?;
-
-        let mut models = parse_models_response(self.provider_kind, &json)?;
+        let mut etag = None;
+        let mut last_modified = None;
+        let mut models = match self.get_listing(url, revalidate).await {
+            Ok(Listing::NotModified(mut cached)) => {
+                debug!("Model list not modified, refreshing cache entry");
+                cached.fetched_at_unix_s = unix_now();
+                if let Some(cache) = &self.cache {
+                    cache.set(&self.cache_key(), &cached)?;
+                }
+                return Ok(cached);
+            }
+            Ok(Listing::Body {
+                json,
+                etag: response_etag,
+                last_modified: response_last_modified,
+            }) => {
+                etag = response_etag;
+                last_modified = response_last_modified;
+                if ollama_native {
+                    parse_ollama_tags(&json)?
+                } else {
+                    parse_models_response(self.provider_kind, &json)?
+                }
+            }
+            // An Ollama server on a non-default port may lack the OpenAI-style listing
+            Err(DiscoveryError::Provider(404, _)) if self.is_ollama_candidate() => {
+                let tags_url = self.ollama_tags_url();
+                info!(url = %tags_url, "Falling back to Ollama model listing");
+                let json = self.get_json(tags_url).await?;
+                parse_ollama_tags(&json)?
+            }
+            Err(e) => return Err(e),
+        };
 
         // Text-model-only filter: use explicit model type when present, otherwise fallback to id heuristic.
         let original_count = models.len();
@@ -283,6 +405,8 @@ impl ProviderDiscoveryClient {
             base_url: self.base_url.to_string(),
             fetched_at_unix_s: unix_now(),
             models,
+            etag,
+            last_modified,
         };
 
         // Save to cache
@@ -300,6 +424,104 @@ impl ProviderDiscoveryClient {
     fn cache_key(&self) -> String {
         generate_cache_key(self.provider_kind, &self.base_url)
     }
+
+    /// Fetch a URL and parse the body as JSON, mapping non-2xx to `Provider`.
+    async fn get_json(&self, url: Url) -> Result<Value, DiscoveryError> {
+        let resp = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| DiscoveryError::Http(e.to_string()))?;
+        read_json(resp).await
+    }
+
+    /// Fetch the model listing, revalidating `cached` when it has validators.
+    async fn get_listing(
+        &self,
+        url: Url,
+        cached: Option<ProviderModelList>,
+    ) -> Result<Listing, DiscoveryError> {
+        let mut request = self.http.get(url);
+        if let Some(cached) = &cached {
+            if let Some(etag) = &cached.etag {
+                request = request.header(IF_NONE_MATCH, etag);
+            }
+            if let Some(last_modified) = &cached.last_modified {
+                request = request.header(IF_MODIFIED_SINCE, last_modified);
+            }
+        }
+        let resp = request
+            .send()
+            .await
+            .map_err(|e| DiscoveryError::Http(e.to_string()))?;
+        if resp.status() == StatusCode::NOT_MODIFIED {
+            if let Some(cached) = cached {
+                return Ok(Listing::NotModified(cached));
+            }
+        }
+
+        let validator = |name| {
+            resp.headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .map(str::to_string)
+        };
+        let etag = validator(ETAG);
+        let last_modified = validator(LAST_MODIFIED);
+        Ok(Listing::Body {
+            json: read_json(resp).await?,
+            etag,
+            last_modified,
+        })
+    }
+
+    /// Whether a missing `/models` endpoint may be an Ollama server.
+    fn is_ollama_candidate(&self) -> bool {
+        self.provider_kind == ProviderKind::Generic && is_local_or_private_url(&self.base_url)
+    }
+
+    /// Ollama's native `/api/tags` listing, next to the `/v1` compatibility API
+    fn ollama_tags_url(&self) -> Url {
+        let mut url = self.base_url.clone();
+        let prefix = url
+            .path()
+            .trim_end_matches('/')
+            .trim_end_matches("/v1")
+            .to_string();
+        url.set_path(&format!("{prefix}/api/tags"));
+        url
+    }
+}
+
+/// Response to a (possibly conditional) model listing request
+enum Listing {
+    /// The cached list is still current (HTTP 304)
+    NotModified(ProviderModelList),
+    /// Full listing with the validators the provider sent, if any
+    Body {
+        json: Value,
+        etag: Option<String>,
+        last_modified: Option<String>,
+    },
+}
+
+/// Read a response body as JSON, mapping non-2xx to `Provider`.
+async fn read_json(resp: reqwest::Response) -> Result<Value, DiscoveryError> {
+    let status = resp.status();
+    let body = resp
+        .text()
+        .await
+        .map_err(|e| DiscoveryError::Http(e.to_string()))?;
+
+    if !status.is_success() {
+        return Err(DiscoveryError::Provider(
+            status.as_u16(),
+            truncate(&body, 400),
+        ));
+    }
+
+    serde_json::from_str(&body).map_err(|e| DiscoveryError::Json(e.to_string()))
 }
 
 /// Generate a consistent cache key for a provider and base URL
@@ -359,12 +581,9 @@ pub fn sanitize_base_url(input: &str) -> Result<Url, DiscoveryError> {
     url.set_fragment(None);
 
     let host = url.host_str().unwrap_or_default().to_lowercase();
-    let is_local = host == "localhost" || host == "127.0.0.1" || host == "::1";
-    let is_private = is_private_host(&host);
-
     match url.scheme() {
         "https" => {}
-        "http" if is_local || is_private => {}
+        "http" if is_local_or_private_url(&url) => {}
         other => {
             return Err(DiscoveryError::InvalidBaseUrl(format!(
                 "unsupported scheme: {other}"
@@ -390,6 +609,9 @@ pub fn sanitize_base_url(input: &str) -> Result<Url, DiscoveryError> {
     } else if path.contains("/openai/deployments/") {
         // Azure uses a different layout; keep it as-is
         path
+    } else if host == "generativelanguage.googleapis.com" {
+        // Gemini's OpenAI compatibility layer lives under /v1beta/openai
+        path
     } else if host.ends_with("venice.ai") && path == "/v1" {
         "/api/v1".to_string()
     } else if path.ends_with("/api/v1") || path.ends_with("/api/v1/") {
@@ -405,10 +627,18 @@ pub fn sanitize_base_url(input: &str) -> Result<Url, DiscoveryError> {
 }
 
 /// Returns true when the URL points to localhost or a private IP range.
+///
+/// Covers `localhost`, the IPv4 loopback (`127.0.0.0/8`) and private ranges,
+/// and the IPv6 loopback and unique local addresses (`fc00::/7`).
 pub fn is_local_or_private_url(url: &Url) -> bool {
-    let host = url.host_str().unwrap_or_default().to_lowercase();
-    let is_local = host == "localhost" || host == "127.0.0.1" || host == "::1";
-    is_local || is_private_host(&host)
+    match url.host() {
+        Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+        Some(url::Host::Ipv4(ipv4)) => ipv4.is_loopback() || ipv4.is_private(),
+        // Unique local addresses are fc00::/7. We avoid `Ipv6Addr::is_unique_local()`
+        // to keep MSRV at Rust 1.75.
+        Some(url::Host::Ipv6(ipv6)) => ipv6.is_loopback() || (ipv6.octets()[0] & 0xfe) == 0xfc,
+        None => false,
+    }
 }
 
 /// Parses and checks whether a base URL is local/private (useful for optional API keys).
@@ -417,35 +647,6 @@ pub fn is_local_or_private_base_url(input: &str) -> Result<bool, DiscoveryError>
     Ok(is_local_or_private_url(&url))
 }
 
-fn is_private_host(host: &str) -> bool {
-    let ip: std::net::IpAddr = match host.parse() {
-        Ok(ip) => ip,
-        Err(_) => return false,
-    };
-
-    match ip {
-        std::net::IpAddr::V4(ipv4) => {
-            let octets = ipv4.octets();
-            match octets {
-                [10, _, _, _] => true,
-                [172, b, _, _] if (16..=31).contains(&b) => true,
-                [192, 168, _, _] => true,
-                _ => false,
-            }
-        }
-        std::net::IpAddr::V6(ipv6) => {
-            if ipv6.is_loopback() {
-                return true;
-            }
-
-            // Unique local addresses are fc00::/7. We avoid `Ipv6Addr::is_unique_local()`
-            // to keep MSRV at Rust 1.75.
-            let first = ipv6.octets()[0];
-            (first & 0xfe) == 0xfc
-        }
-    }
-}
-
 /// Sanitize user-configured headers (excluding secrets)
 ///
 /// - Header names must be token-like
@@ -536,6 +737,54 @@ fn parse_models_response(
     Ok(out)
 }
 
+/// Map Ollama's native `/api/tags` listing into discovered models.
+fn parse_ollama_tags(v: &Value) -> Result<Vec<DiscoveredModel>, DiscoveryError> {
+    let tags = v
+        .get("models")
+        .and_then(|m| m.as_array())
+        .ok_or_else(|| DiscoveryError::Json("missing or invalid 'models' array".to_string()))?;
+
+    let mut out = Vec::new();
+    for tag in tags {
+        let id = tag
+            .get("model")
+            .or_else(|| tag.get("name"))
+            .and_then(|x| x.as_str());
+        let Some(id) = id else {
+            continue;
+        };
+        let name = tag
+            .get("name")
+            .and_then(|x| x.as_str())
+            .unwrap_or(id)
+            .to_string();
+        let details = tag.get("details").unwrap_or(&Value::Null);
+        let family = details
+            .get("family")
+            .and_then(|x| x.as_str())
+            .unwrap_or_default()
+            .to_lowercase();
+
+        out.push(DiscoveredModel {
+            id: id.to_string(),
+            name,
+            provider_kind: ProviderKind::Ollama,
+            // Embedding models are filtered later by id; the family catches the rest
+            model_type: family.contains("bert").then(|| "embedding".to_string()),
+            context_length: None,
+            traits: ProviderModelTraits {
+                code_optimized: id.to_lowercase().contains("code"),
+                ..ProviderModelTraits::default()
+            },
+            pricing: ProviderModelPricing {
+                input_usd_per_million: None,
+                output_usd_per_million: None,
+            },
+        });
+    }
+    Ok(out)
+}
+
 fn parse_model_item(
     kind: ProviderKind,
     item: &Value,
@@ -610,6 +859,16 @@ fn parse_model_item(
                 .get("optimizedForCode")
                 .and_then(|x| x.as_bool())
                 .unwrap_or(false),
+            // Schema support implies plain JSON mode
+            supports_json_mode: caps
+                .get("supportsResponseSchema")
+                .and_then(|x| x.as_bool())
+                .unwrap_or(false),
+            context_window: ctx,
+            max_output_tokens: model_spec
+                .get("maxCompletionTokens")
+                .and_then(|x| x.as_u64())
+                .map(|n| n as u32),
         };
 
         let pricing = extract_venice_pricing(model_spec);
@@ -639,6 +898,25 @@ fn parse_model_item(
         let pricing = extract_openrouter_pricing(item);
 
         let model_type = extract_model_type(item);
+        let params: Vec<&str> = item
+            .get("supported_parameters")
+            .and_then(|x| x.as_array())
+            .map(|a| a.iter().filter_map(|p| p.as_str()).collect())
+            .unwrap_or_default();
+        let traits = ProviderModelTraits {
+            supports_tools: params.contains(&"tools"),
+            supports_reasoning: params.contains(&"reasoning"),
+            supports_response_schema: params.contains(&"structured_outputs"),
+            supports_json_mode: params.contains(&"response_format"),
+            supports_logprobs: params.contains(&"logprobs"),
+            context_window: ctx,
+            max_output_tokens: item
+                .get("top_provider")
+                .and_then(|p| p.get("max_completion_tokens"))
+                .and_then(|x| x.as_u64())
+                .map(|n| n as u32),
+            ..ProviderModelTraits::default()
+        };
 
         return Ok(Some(DiscoveredModel {
             id,
@@ -646,20 +924,21 @@ fn parse_model_item(
             provider_kind: ProviderKind::OpenRouter,
             model_type,
             context_length: ctx,
-            traits: ProviderModelTraits::default(),
+            traits,
             pricing,
         }));
     }
 
     // OpenAI/xAI generic schema: only id is reliable
     let model_type = extract_model_type(item);
+    let traits = ProviderModelTraits::for_known_model(&id).unwrap_or_default();
     Ok(Some(DiscoveredModel {
         name: id.clone(),
         id,
         provider_kind: kind,
         model_type,
-        context_length: None,
-        traits: ProviderModelTraits::default(),
+        context_length: traits.context_window,
+        traits,
         pricing: ProviderModelPricing {
             input_usd_per_million: None,
             output_usd_per_million: None,
@@ -814,11 +1093,11 @@ impl DiskCache {
         self.get_within(key, self.stale_ttl)
     }
 
-    fn get_within(
+    fn get_within<T: DeserializeOwned>(
         &self,
         key: &str,
         ttl: Duration,
-    ) -> Result<Option<ProviderModelList>, DiscoveryError> {
+    ) -> Result<Option<T>, DiscoveryError> {
         let p = self.path(key);
         if !p.exists() {
             return Ok(None);
@@ -834,13 +1113,35 @@ impl DiskCache {
             return Ok(None);
         }
         let s = fs::read_to_string(&p).map_err(|e| DiscoveryError::Cache(e.to_string()))?;
-        let v: ProviderModelList =
-            serde_json::from_str(&s).map_err(|e| DiscoveryError::Cache(e.to_string()))?;
+        let v = serde_json::from_str(&s).map_err(|e| DiscoveryError::Cache(e.to_string()))?;
         Ok(Some(v))
     }
 
     /// Store a model list in the cache
     pub fn set(&self, key: &str, value: &ProviderModelList) -> Result<(), DiscoveryError> {
+        self.write(key, value)
+    }
+
+    /// Get the probed capabilities of a model (within stale_ttl)
+    pub fn get_capabilities(
+        &self,
+        base_url: &Url,
+        model: &str,
+    ) -> Result<Option<ModelCapabilities>, DiscoveryError> {
+        self.get_within(&capabilities_cache_key(base_url, model), self.stale_ttl)
+    }
+
+    /// Store the probed capabilities of a model
+    pub fn set_capabilities(
+        &self,
+        base_url: &Url,
+        model: &str,
+        value: &ModelCapabilities,
+    ) -> Result<(), DiscoveryError> {
+        self.write(&capabilities_cache_key(base_url, model), value)
+    }
+
+    fn write<T: Serialize>(&self, key: &str, value: &T) -> Result<(), DiscoveryError> {
         fs::create_dir_all(&self.dir).map_err(|e| DiscoveryError::Cache(e.to_string()))?;
         let p = self.path(key);
         let s = serde_json::to_string(value).map_err(|e| DiscoveryError::Cache(e.to_string()))?;
@@ -849,6 +1150,16 @@ impl DiskCache {
     }
 }
 
+/// Cache key for the capabilities of one model at one base URL
+fn capabilities_cache_key(base_url: &Url, model: &str) -> String {
+    let slug: String = format!("{base_url}_{model}")
+        .to_ascii_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("capabilities_{slug}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -866,6 +1177,14 @@ mod tests {
 
         let u = sanitize_base_url("https://api.openai.com/v1/models")?;
         assert_eq!(u.as_str(), "https://api.openai.com/v1");
+
+        let u = sanitize_base_url(
+            "https://generativelanguage.googleapis.com/v1beta/openai/chat/completions",
+        )?;
+        assert_eq!(
+            u.as_str(),
+            "https://generativelanguage.googleapis.com/v1beta/openai"
+        );
         Ok(())
     }
 
@@ -892,6 +1211,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn local_base_urls_need_no_api_key() -> anyhow::Result<()> {
+        for local in [
+            "http://localhost:11434",
+            "http://127.0.0.1:11434/v1",
+            "http://127.0.1.1:11434",
+            "http://[::1]:11434",
+            "http://[fd00::5]:11434",
+            "http://192.168.1.10:11434",
+        ] {
+            assert!(is_local_or_private_base_url(local)?, "{local}");
+        }
+        assert!(!is_local_or_private_base_url("https://ollama.example.com")?);
+        assert!(!is_local_or_private_base_url("https://[2001:db8::1]")?);
+        Ok(())
+    }
+
     #[test]
     fn sanitize_headers_rejects_newlines() {
         let mut h = HashMap::new();
@@ -933,8 +1269,154 @@ mod tests {
         let openai = Url::parse("https://api.openai.com/v1")?;
         assert_eq!(ProviderKind::detect(&openai), ProviderKind::OpenAI);
 
+        let anthropic = Url::parse("https://api.anthropic.com/v1")?;
+        assert_eq!(ProviderKind::detect(&anthropic), ProviderKind::Anthropic);
+
         let generic = Url::parse("https://custom.example.com/v1")?;
         assert_eq!(ProviderKind::detect(&generic), ProviderKind::Generic);
+
+        let ollama = Url::parse("http://localhost:11434/v1")?;
+        assert_eq!(ProviderKind::detect(&ollama), ProviderKind::Ollama);
+
+        let lmstudio = Url::parse("http://192.168.1.20:1234/v1")?;
+        assert_eq!(ProviderKind::detect(&lmstudio), ProviderKind::LmStudio);
+
+        // Default ports only identify local servers
+        let remote = Url::parse("https://models.example.com:1234/v1")?;
+        assert_eq!(ProviderKind::detect(&remote), ProviderKind::Generic);
+        Ok(())
+    }
+
+    #[test]
+    fn missing_api_key_rejected_for_remote_only() {
+        let headers = BTreeMap::new();
+        let timeout = Duration::from_secs(5);
+        assert!(matches!(
+            ProviderDiscoveryClient::new(
+                "https://api.openai.com/v1",
+                &headers,
+                None,
+                timeout,
+                None
+            ),
+            Err(DiscoveryError::InvalidApiKey(_))
+        ));
+        let empty = Some(SecretString::new("".into()));
+        assert!(ProviderDiscoveryClient::new(
+            "http://localhost:11434/v1",
+            &headers,
+            empty,
+            timeout,
+            None
+        )
+        .is_ok_and(|client| !client.has_api_key()));
+    }
+
+    #[tokio::test]
+    async fn discovery_falls_back_to_ollama_tags() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let models = server
+            .mock("GET", "/v1/models")
+            .with_status(404)
+            .create_async()
+            .await;
+        let tags = server
+            .mock("GET", "/api/tags")
+            .match_header("authorization", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(
+                r#"{"models":[
+                    {"name":"qwen2.5-coder:7b","model":"qwen2.5-coder:7b","details":{"family":"qwen2"}},
+                    {"name":"nomic-embed-text:latest","model":"nomic-embed-text:latest","details":{"family":"nomic-bert"}},
+                    {"name":"llama3.1:latest","model":"llama3.1:latest","details":{"family":"llama"}}
+                ]}"#,
+            )
+            .create_async()
+            .await;
+
+        let client = ProviderDiscoveryClient::new(
+            &format!("{}/v1", server.url()),
+            &BTreeMap::new(),
+            None,
+            Duration::from_secs(5),
+            None,
+        )?;
+        let list = client.discover_chat_models().await?;
+
+        models.assert_async().await;
+        tags.assert_async().await;
+        let ids: Vec<&str> = list.models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["llama3.1:latest", "qwen2.5-coder:7b"]);
+        assert!(list.models[1].traits.code_optimized);
+        assert_eq!(list.models[0].provider_kind, ProviderKind::Ollama);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn discovery_revalidates_cached_list() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let temp = tempfile::TempDir::new()?;
+        let cache = DiskCache {
+            dir: temp.path().to_path_buf(),
+            // Always revalidate
+            fresh_ttl: Duration::ZERO,
+            stale_ttl: Duration::from_secs(3600),
+        };
+        let client = ProviderDiscoveryClient::new(
+            &format!("{}/v1", server.url()),
+            &BTreeMap::new(),
+            None,
+            Duration::from_secs(5),
+            Some(cache.clone()),
+        )?;
+
+        let full = server
+            .mock("GET", "/v1/models")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_header("etag", "\"v1\"")
+            .with_header("last-modified", "Wed, 21 Oct 2026 07:28:00 GMT")
+            .with_body(r#"{"data":[{"id":"gpt-4o"}]}"#)
+            .expect(1)
+            .create_async()
+            .await;
+        let first = client.discover_chat_models().await?;
+        full.assert_async().await;
+        assert_eq!(first.etag.as_deref(), Some("\"v1\""));
+
+        let not_modified = server
+            .mock("GET", "/v1/models")
+            .match_header("if-none-match", "\"v1\"")
+            .match_header("if-modified-since", "Wed, 21 Oct 2026 07:28:00 GMT")
+            .with_status(304)
+            .expect(1)
+            .create_async()
+            .await;
+        let second = client.discover_chat_models().await?;
+        not_modified.assert_async().await;
+        let ids: Vec<&str> = second.models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, ["gpt-4o"]);
+        assert!(second.fetched_at_unix_s >= first.fetched_at_unix_s);
+
+        // Without validators the next refresh downloads the list again
+        let mut cached = cache
+            .get_stale(&client.cache_key())?
+            .ok_or_else(|| anyhow::anyhow!("expected a cached list"))?;
+        cached.etag = None;
+        cached.last_modified = None;
+        cache.set(&client.cache_key(), &cached)?;
+        let refetch = server
+            .mock("GET", "/v1/models")
+            .match_header("if-none-match", mockito::Matcher::Missing)
+            .match_header("if-modified-since", mockito::Matcher::Missing)
+            .with_status(200)
+            .with_body(r#"{"data":[{"id":"gpt-4o"},{"id":"gpt-4.1"}]}"#)
+            .create_async()
+            .await;
+        let third = client.discover_chat_models().await?;
+        refetch.assert_async().await;
+        assert_eq!(third.models.len(), 2);
+        assert!(third.etag.is_none());
         Ok(())
     }
 
@@ -979,6 +1461,8 @@ mod tests {
         assert!(model.traits.supports_vision);
         assert!(model.traits.supports_tools);
         assert!(!model.traits.supports_reasoning);
+        assert!(model.traits.supports_json_mode);
+        assert_eq!(model.traits.context_window, Some(8192));
         // Venice pricing is already in USD per million tokens
         let input_val = model.pricing.input_usd_per_million.unwrap();
         let output_val = model.pricing.output_usd_per_million.unwrap();
@@ -1005,7 +1489,9 @@ mod tests {
             "pricing": {
                 "prompt": "0.000001",
                 "completion": "0.000003"
-            }
+            },
+            "top_provider": {"max_completion_tokens": 1024},
+            "supported_parameters": ["tools", "response_format", "temperature"]
         });
 
         let result = parse_model_item(ProviderKind::Generic, &json)?;
@@ -1016,6 +1502,11 @@ mod tests {
         assert_eq!(model.provider_kind, ProviderKind::OpenRouter);
         assert_eq!(model.model_type.as_deref(), Some("text"));
         assert_eq!(model.context_length, Some(4096));
+        assert!(model.traits.supports_tools);
+        assert!(model.traits.supports_json_mode);
+        assert!(!model.traits.supports_response_schema);
+        assert_eq!(model.traits.context_window, Some(4096));
+        assert_eq!(model.traits.max_output_tokens, Some(1024));
         // Compare with tolerance for floating point precision
         let input_val = model.pricing.input_usd_per_million.unwrap();
         let output_val = model.pricing.output_usd_per_million.unwrap();
@@ -1050,6 +1541,69 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_known_model_traits() {
+        let traits =
+            ProviderModelTraits::for_known_model("gpt-4o-mini-2024-07-18").unwrap_or_default();
+        assert!(traits.supports_response_schema);
+        assert_eq!(traits.context_window, Some(128_000));
+        assert_eq!(traits.max_output_tokens, Some(16_384));
+
+        let traits = ProviderModelTraits::for_known_model("openai/gpt-4").unwrap_or_default();
+        assert!(!traits.supports_json_mode);
+        assert!(traits.supports_tools);
+
+        assert!(ProviderModelTraits::for_known_model("llama-3.1-70b").is_none());
+    }
+
+    #[test]
+    fn test_parse_ollama_tags() -> anyhow::Result<()> {
+        let json = serde_json::json!({
+            "models": [
+                {
+                    "name": "llama3.1:8b",
+                    "model": "llama3.1:8b",
+                    "modified_at": "2026-05-02T09:15:41.000Z",
+                    "size": 4920753328u64,
+                    "digest": "46e0c10c039e019119339687c3c1757cc81b9da49709a3b3924863ba87ca666e",
+                    "details": {
+                        "format": "gguf",
+                        "family": "llama",
+                        "families": ["llama"],
+                        "parameter_size": "8.0B",
+                        "quantization_level": "Q4_K_M"
+                    }
+                },
+                {
+                    "name": "codellama:latest",
+                    "details": { "family": "llama" }
+                },
+                {
+                    "name": "nomic-embed-text:latest",
+                    "model": "nomic-embed-text:latest",
+                    "details": { "family": "nomic-bert" }
+                },
+                { "size": 1 }
+            ]
+        });
+
+        let models = parse_ollama_tags(&json)?;
+        let ids: Vec<&str> = models.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            ["llama3.1:8b", "codellama:latest", "nomic-embed-text:latest"]
+        );
+        assert!(models
+            .iter()
+            .all(|m| m.provider_kind == ProviderKind::Ollama));
+        assert_eq!(models[0].model_type, None);
+        assert!(models[1].traits.code_optimized);
+        assert_eq!(models[2].model_type.as_deref(), Some("embedding"));
+
+        assert!(parse_ollama_tags(&serde_json::json!({ "data": [] })).is_err());
+        Ok(())
+    }
+
     #[test]
     fn test_parse_model_item_invalid() -> anyhow::Result<()> {
         let json = serde_json::json!({
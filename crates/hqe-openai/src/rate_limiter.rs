@@ -4,6 +4,8 @@
 //! Uses a token bucket algorithm that supports:
 //! - Requests per minute (RPM) limiting
 //! - Tokens per minute (TPM) limiting
+//! - Separate buckets per model on top of the provider-wide ones
+//! - Self-throttling from the provider's `x-ratelimit-*` response headers
 //!
 //! # Example
 //! ```
@@ -13,29 +15,88 @@
 //! let config = RateLimitConfig {
 //!     requests_per_minute: 60,
 //!     tokens_per_minute: Some(10000),
-//! };
+//!     ..RateLimitConfig::default()
+//! }
+//! .with_model_limit("gpt-4o", 10, Some(30000));
 //! let limiter = RateLimiter::new(config);
 //! ```
 
+use reqwest::header::HeaderMap;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::{Duration, Instant};
 use tokio::sync::Mutex as TokioMutex;
 use tracing::{debug, trace};
 
+use crate::token_count::{heuristic_tokens, MESSAGE_OVERHEAD_TOKENS, REPLY_PRIMING_TOKENS};
+use crate::{Message, MessageContent};
+
+/// Estimate how many prompt tokens a list of messages will consume.
+///
+/// Uses a `chars / 4` heuristic plus a small per-message overhead. For
+/// [`MessageContent::Parts`] only text parts are counted; images and other
+/// attachments are ignored. The result is intentionally conservative enough to
+/// pre-check a request against a tokens-per-minute budget.
+pub fn estimate_prompt_tokens(messages: &[Message]) -> u32 {
+    let total = messages.iter().fold(REPLY_PRIMING_TOKENS, |acc, m| {
+        let tokens = match &m.content {
+            Some(MessageContent::Text(text)) => heuristic_tokens(text),
+            Some(MessageContent::Parts(parts)) => parts
+                .iter()
+                .filter(|part| {
+                    part.get("type")
+                        .and_then(|t| t.as_str())
+                        .map_or(true, |t| t == "text")
+                })
+                .filter_map(|part| part.get("text").and_then(|t| t.as_str()))
+                .map(heuristic_tokens)
+                .sum(),
+            None => 0,
+        };
+        acc.saturating_add(tokens)
+            .saturating_add(MESSAGE_OVERHEAD_TOKENS)
+    });
+
+    u32::try_from(total).unwrap_or(u32::MAX)
+}
+
+/// Requests-per-minute and tokens-per-minute ceilings for one bucket
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelRateLimit {
+    /// Maximum requests allowed per minute
+    pub requests_per_minute: u32,
+    /// Maximum tokens allowed per minute (optional)
+    pub tokens_per_minute: Option<u32>,
+}
+
 /// Configuration for rate limiting
-#[derive(Debug, Clone, Copy)]
+///
+/// The top-level limits apply to every request sent through the client (the
+/// provider-wide ceiling); `models` adds independent buckets for individual
+/// model ids, which must also have capacity before a request to that model
+/// is sent.
+#[derive(Debug, Clone)]
 pub struct RateLimitConfig {
     /// Maximum requests allowed per minute
     pub requests_per_minute: u32,
     /// Maximum tokens allowed per minute (optional)
     pub tokens_per_minute: Option<u32>,
+    /// Per-model limits keyed by model id
+    pub models: HashMap<String, ModelRateLimit>,
+    /// Longest pause taken on a provider's `x-ratelimit-reset-*` header
+    pub max_reset_wait: Duration,
 }
 
+/// Default upper bound on how long an exhausted provider limit pauses requests
+pub const DEFAULT_MAX_RESET_WAIT: Duration = Duration::from_secs(60);
+
 impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
             requests_per_minute: 60,
             tokens_per_minute: None,
+            models: HashMap::new(),
+            max_reset_wait: DEFAULT_MAX_RESET_WAIT,
         }
     }
 }
@@ -46,6 +107,8 @@ impl RateLimitConfig {
         Self {
             requests_per_minute: 60,
             tokens_per_minute: Some(60000),
+            models: HashMap::new(),
+            max_reset_wait: DEFAULT_MAX_RESET_WAIT,
         }
     }
 
@@ -54,6 +117,8 @@ impl RateLimitConfig {
         Self {
             requests_per_minute: 3000,
             tokens_per_minute: Some(250000),
+            models: HashMap::new(),
+            max_reset_wait: DEFAULT_MAX_RESET_WAIT,
         }
     }
 
@@ -62,8 +127,104 @@ impl RateLimitConfig {
         Self {
             requests_per_minute: u32::MAX,
             tokens_per_minute: None,
+            models: HashMap::new(),
+            max_reset_wait: DEFAULT_MAX_RESET_WAIT,
         }
     }
+
+    /// Add a separate bucket for one model
+    pub fn with_model_limit(
+        mut self,
+        model: impl Into<String>,
+        requests_per_minute: u32,
+        tokens_per_minute: Option<u32>,
+    ) -> Self {
+        self.models.insert(
+            model.into(),
+            ModelRateLimit {
+                requests_per_minute,
+                tokens_per_minute,
+            },
+        );
+        self
+    }
+}
+
+/// Rate limit state reported by the provider in `x-ratelimit-*` response headers
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct RateLimitHeaders {
+    /// `x-ratelimit-limit-requests`
+    pub limit_requests: Option<u32>,
+    /// `x-ratelimit-limit-tokens`
+    pub limit_tokens: Option<u32>,
+    /// `x-ratelimit-remaining-requests`
+    pub remaining_requests: Option<u32>,
+    /// `x-ratelimit-remaining-tokens`
+    pub remaining_tokens: Option<u32>,
+    /// `x-ratelimit-reset-requests`: time until the request limit is replenished
+    pub reset_requests: Option<Duration>,
+    /// `x-ratelimit-reset-tokens`: time until the token limit is replenished
+    pub reset_tokens: Option<Duration>,
+}
+
+impl RateLimitHeaders {
+    /// Parse the headers, or `None` if the response has no rate limit headers
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let text = |name: &str| headers.get(name).and_then(|v| v.to_str().ok());
+        let number = |name: &str| text(name).and_then(|v| v.trim().parse::<u32>().ok());
+        let duration = |name: &str| text(name).and_then(parse_reset_duration);
+
+        let parsed = Self {
+            limit_requests: number("x-ratelimit-limit-requests"),
+            limit_tokens: number("x-ratelimit-limit-tokens"),
+            remaining_requests: number("x-ratelimit-remaining-requests"),
+            remaining_tokens: number("x-ratelimit-remaining-tokens"),
+            reset_requests: duration("x-ratelimit-reset-requests"),
+            reset_tokens: duration("x-ratelimit-reset-tokens"),
+        };
+        (parsed != Self::default()).then_some(parsed)
+    }
+}
+
+/// Parse a reset duration such as `1s`, `6m0s`, `20ms` or `1h2m3.5s`.
+///
+/// A bare number is taken as seconds. Negative, non-finite and out-of-range
+/// values are rejected.
+fn parse_reset_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse::<f64>() {
+        return seconds_to_duration(secs);
+    }
+
+    let mut total = 0.0;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let split = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .filter(|&i| i > 0)?;
+        let amount: f64 = rest[..split].parse().ok()?;
+        rest = &rest[split..];
+        let unit_len = rest
+            .find(|c: char| c.is_ascii_digit())
+            .unwrap_or(rest.len());
+        let seconds_per_unit = match &rest[..unit_len] {
+            "ms" => 0.001,
+            "s" => 1.0,
+            "m" => 60.0,
+            "h" => 3600.0,
+            _ => return None,
+        };
+        total += amount * seconds_per_unit;
+        rest = &rest[unit_len..];
+    }
+    seconds_to_duration(total)
+}
+
+fn seconds_to_duration(secs: f64) -> Option<Duration> {
+    if !secs.is_finite() || secs < 0.0 {
+        return None;
+    }
+    Duration::try_from_secs_f64(secs).ok()
 }
 
 /// Internal state of the token bucket
@@ -90,6 +251,11 @@ impl TokenBucket {
         }
     }
 
+    /// Create a bucket holding `per_minute` tokens, refilled over a minute
+    fn per_minute(per_minute: u32) -> Self {
+        Self::new(per_minute as f64, per_minute as f64 / 60.0)
+    }
+
     /// Refill tokens based on elapsed time
     fn refill(&mut self) {
         let now = Instant::now();
@@ -130,15 +296,106 @@ impl TokenBucket {
             Duration::from_secs_f64(seconds_needed)
         }
     }
+
+    /// Lower the level to what the provider says is left
+    fn sync_remaining(&mut self, remaining: u32) {
+        self.refill();
+        self.tokens = self.tokens.min(remaining as f64);
+    }
+}
+
+/// Request and token buckets that must both have capacity
+#[derive(Debug)]
+struct Buckets {
+    requests: TokenBucket,
+    tokens: Option<TokenBucket>,
+    /// Set when the provider reported an exhausted limit, until its reset
+    blocked_until: Option<Instant>,
+}
+
+impl Buckets {
+    fn new(requests_per_minute: u32, tokens_per_minute: Option<u32>) -> Self {
+        Self {
+            requests: TokenBucket::per_minute(requests_per_minute),
+            tokens: tokens_per_minute.map(TokenBucket::per_minute),
+            blocked_until: None,
+        }
+    }
+
+    /// A request larger than the whole token bucket can never fit; cap it
+    /// so oversized prompts wait for a full bucket instead of forever.
+    fn token_amount(&self, token_count: Option<u32>) -> Option<f64> {
+        match (&self.tokens, token_count) {
+            (Some(bucket), Some(tokens)) => Some((tokens as f64).min(bucket.max_tokens)),
+            _ => None,
+        }
+    }
+
+    /// How long until a request of `token_count` tokens fits
+    fn wait_time(&mut self, token_count: Option<u32>) -> Duration {
+        let blocked = self
+            .blocked_until
+            .map(|until| until.saturating_duration_since(Instant::now()))
+            .unwrap_or(Duration::ZERO);
+        self.requests.refill();
+        let mut wait = blocked.max(self.requests.time_until_available(1.0));
+        if let Some(amount) = self.token_amount(token_count) {
+            if let Some(bucket) = self.tokens.as_mut() {
+                bucket.refill();
+                wait = wait.max(bucket.time_until_available(amount));
+            }
+        }
+        wait
+    }
+
+    /// Take capacity for one request; call only after a zero [`Buckets::wait_time`]
+    fn consume(&mut self, token_count: Option<u32>) {
+        self.requests.try_consume(1.0);
+        if let Some(amount) = self.token_amount(token_count) {
+            if let Some(bucket) = self.tokens.as_mut() {
+                bucket.try_consume(amount);
+            }
+        }
+    }
+
+    /// Apply the provider's view of the remaining capacity, pausing for at
+    /// most `max_reset_wait` on an exhausted limit
+    fn observe(&mut self, headers: &RateLimitHeaders, max_reset_wait: Duration) {
+        if let Some(remaining) = headers.remaining_requests {
+            self.requests.sync_remaining(remaining);
+        }
+        if let (Some(bucket), Some(remaining)) = (self.tokens.as_mut(), headers.remaining_tokens) {
+            bucket.sync_remaining(remaining);
+        }
+
+        let exhausted = [
+            (headers.remaining_requests, headers.reset_requests),
+            (headers.remaining_tokens, headers.reset_tokens),
+        ]
+        .into_iter()
+        .filter_map(|(remaining, reset)| (remaining == Some(0)).then_some(reset).flatten())
+        .max();
+        if let Some(reset) = exhausted {
+            let reset = reset.min(max_reset_wait);
+            let Some(until) = Instant::now().checked_add(reset) else {
+                return;
+            };
+            debug!("Provider rate limit exhausted, pausing for {:?}", reset);
+            self.blocked_until = Some(self.blocked_until.map_or(until, |b| b.max(until)));
+        }
+    }
 }
 
 /// Rate limiter using token bucket algorithm
+///
+/// Every request draws from the provider-wide buckets and, when configured or
+/// learned from response headers, from the buckets of its model.
 #[derive(Debug, Clone)]
 pub struct RateLimiter {
-    /// Token bucket for request rate limiting
-    request_bucket: Arc<TokioMutex<TokenBucket>>,
-    /// Token bucket for token rate limiting (optional)
-    token_bucket: Option<Arc<TokioMutex<TokenBucket>>>,
+    /// Provider-wide request and token buckets
+    provider: Arc<TokioMutex<Buckets>>,
+    /// Per-model buckets keyed by model id
+    models: Arc<TokioMutex<HashMap<String, Arc<TokioMutex<Buckets>>>>>,
     /// Configuration
     config: RateLimitConfig,
 }
@@ -146,28 +403,34 @@ pub struct RateLimiter {
 impl RateLimiter {
     /// Create a new rate limiter with the given configuration
     pub fn new(config: RateLimitConfig) -> Self {
-        // Convert RPM to tokens per second
-        let request_refill_rate = config.requests_per_minute as f64 / 60.0;
-        let request_bucket =
-            TokenBucket::new(config.requests_per_minute as f64, request_refill_rate);
-
-        // Optional token bucket for TPM limiting
-        let token_bucket = config.tokens_per_minute.map(|tpm| {
-            let token_refill_rate = tpm as f64 / 60.0;
-            TokenBucket::new(tpm as f64, token_refill_rate)
-        });
+        let models = config
+            .models
+            .iter()
+            .map(|(model, limit)| {
+                let buckets = Buckets::new(limit.requests_per_minute, limit.tokens_per_minute);
+                (model.clone(), Arc::new(TokioMutex::new(buckets)))
+            })
+            .collect();
 
         Self {
-            request_bucket: Arc::new(TokioMutex::new(request_bucket)),
-            token_bucket: token_bucket.map(|tb| Arc::new(TokioMutex::new(tb))),
+            provider: Arc::new(TokioMutex::new(Buckets::new(
+                config.requests_per_minute,
+                config.tokens_per_minute,
+            ))),
+            models: Arc::new(TokioMutex::new(models)),
             config,
         }
     }
 
+    async fn model_buckets(&self, model: &str) -> Option<Arc<TokioMutex<Buckets>>> {
+        self.models.lock().await.get(model).cloned()
+    }
+
     /// Acquire permission to make a request
     /// Waits if necessary until rate limits allow the request
     ///
     /// # Arguments
+    /// * `model` - Model the request is for; selects the per-model bucket
     /// * `token_count` - Optional number of tokens this request will consume
     ///   (for TPM limiting)
     ///
@@ -175,70 +438,104 @@ impl RateLimiter {
     /// ```rust
     /// # async fn example() {
     /// # use hqe_openai::rate_limiter::{RateLimiter, RateLimitConfig};
-    /// let limiter = RateLimiter::new(RateLimitConfig::default());
+    /// let limiter = RateLimiter::new(
+    ///     RateLimitConfig::default().with_model_limit("gpt-4o", 10, Some(30_000)),
+    /// );
     ///
     /// // Acquire permission for a request
-    /// limiter.acquire(None).await;
+    /// limiter.acquire("gpt-4o-mini", None).await;
     ///
     /// // Make your API call here
     ///
     /// // Or with token count for TPM limiting
-    /// limiter.acquire(Some(1000)).await;
+    /// limiter.acquire("gpt-4o", Some(1000)).await;
     /// # }
     /// ```
-    pub async fn acquire(&self, token_count: Option<u32>) {
-        let mut request_bucket = self.request_bucket.lock().await;
+    pub async fn acquire(&self, model: &str, token_count: Option<u32>) {
+        let model_buckets = self.model_buckets(model).await;
 
         loop {
-            // Try to consume a request token
-            if request_bucket.try_consume(1.0) {
-                // If we have token-based limiting and a token count was provided
-                if let (Some(bucket), Some(tokens)) = (&self.token_bucket, token_count) {
-                    let mut token_bucket = bucket.lock().await;
-                    let tokens_f64 = tokens as f64;
-
-                    if token_bucket.try_consume(tokens_f64) {
-                        return; // Success!
+            // Lock order: provider, then model
+            let mut provider = self.provider.lock().await;
+            let mut wait_time = provider.wait_time(token_count);
+            match &model_buckets {
+                Some(buckets) => {
+                    let mut buckets = buckets.lock().await;
+                    wait_time = wait_time.max(buckets.wait_time(token_count));
+                    if wait_time.is_zero() {
+                        provider.consume(token_count);
+                        buckets.consume(token_count);
+                        return;
                     }
-                    // Rollback request token if token bucket fails
-                    request_bucket.tokens += 1.0;
-                } else {
-                    return; // Success!
                 }
+                None if wait_time.is_zero() => {
+                    provider.consume(token_count);
+                    return;
+                }
+                None => {}
             }
+            drop(provider);
 
-            let wait_time = request_bucket.time_until_available(1.0);
-            drop(request_bucket);
-
-            debug!("Rate limit hit, waiting {:?} for request bucket", wait_time);
+            debug!(model, "Rate limit hit, waiting {:?}", wait_time);
             tokio::time::sleep(wait_time).await;
-
-            request_bucket = self.request_bucket.lock().await;
         }
     }
 
     /// Try to acquire permission without waiting
     /// Returns true if successful, false if rate limited
-    pub async fn try_acquire(&self, token_count: Option<u32>) -> bool {
-        let mut request_bucket = self.request_bucket.lock().await;
+    pub async fn try_acquire(&self, model: &str, token_count: Option<u32>) -> bool {
+        let model_buckets = self.model_buckets(model).await;
 
-        if !request_bucket.try_consume(1.0) {
+        let mut provider = self.provider.lock().await;
+        if !provider.wait_time(token_count).is_zero() {
             return false;
         }
-
-        if let (Some(bucket), Some(tokens)) = (&self.token_bucket, token_count) {
-            let mut token_bucket = bucket.lock().await;
-            let tokens_f64 = tokens as f64;
-            if !token_bucket.try_consume(tokens_f64) {
-                // Rollback request token
-                request_bucket.tokens += 1.0;
+        if let Some(buckets) = &model_buckets {
+            let mut buckets = buckets.lock().await;
+            if !buckets.wait_time(token_count).is_zero() {
                 return false;
             }
+            buckets.consume(token_count);
         }
-
+        provider.consume(token_count);
         true
     }
 
+    /// Feed the provider's rate limit headers for a `model` response back in.
+    ///
+    /// Lowers the model's buckets to the reported remaining capacity and pauses
+    /// them until the reset when a limit is exhausted. A model without its own
+    /// buckets gets them from the reported `x-ratelimit-limit-*` values; without
+    /// those, the provider-wide buckets are adjusted instead.
+    pub async fn observe(&self, model: &str, headers: &RateLimitHeaders) {
+        let buckets = {
+            let mut models = self.models.lock().await;
+            match (models.get(model), headers.limit_requests) {
+                (Some(buckets), _) => Some(Arc::clone(buckets)),
+                (None, Some(limit_requests)) => {
+                    let buckets = Arc::new(TokioMutex::new(Buckets::new(
+                        limit_requests,
+                        headers.limit_tokens,
+                    )));
+                    models.insert(model.to_string(), Arc::clone(&buckets));
+                    Some(buckets)
+                }
+                (None, None) => None,
+            }
+        };
+        match buckets {
+            Some(buckets) => buckets
+                .lock()
+                .await
+                .observe(headers, self.config.max_reset_wait),
+            None => self
+                .provider
+                .lock()
+                .await
+                .observe(headers, self.config.max_reset_wait),
+        }
+    }
+
     /// Get current configuration
     pub fn config(&self) -> &RateLimitConfig {
         &self.config
@@ -273,16 +570,17 @@ mod tests {
         let limiter = RateLimiter::new(RateLimitConfig {
             requests_per_minute: 60, // 1 per second
             tokens_per_minute: None,
+            ..RateLimitConfig::default()
         });
 
         // First acquire should succeed immediately
         let start = Instant::now();
-        limiter.acquire(None).await;
+        limiter.acquire("m", None).await;
         let elapsed = start.elapsed();
         assert!(elapsed < Duration::from_millis(100));
 
         // Rapid successive acquires should be rate limited
-        limiter.acquire(None).await;
+        limiter.acquire("m", None).await;
         // Should have waited approximately 1 second
     }
 
@@ -291,9 +589,208 @@ mod tests {
         let limiter = RateLimiter::new(RateLimitConfig {
             requests_per_minute: 1, // Very restrictive
             tokens_per_minute: None,
+            ..RateLimitConfig::default()
         });
 
-        assert!(limiter.try_acquire(None).await);
-        assert!(!limiter.try_acquire(None).await); // Should fail immediately
+        assert!(limiter.try_acquire("m", None).await);
+        assert!(!limiter.try_acquire("m", None).await); // Should fail immediately
+    }
+
+    fn message(content: Option<MessageContent>) -> Message {
+        Message {
+            role: crate::Role::User,
+            content,
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    #[test]
+    fn test_estimate_prompt_tokens_text() {
+        let empty = estimate_prompt_tokens(&[]);
+        assert_eq!(empty as usize, REPLY_PRIMING_TOKENS);
+
+        // 8 chars -> 2 tokens, plus per-message overhead
+        let tokens = estimate_prompt_tokens(&[message(Some("abcdefgh".into()))]);
+        assert_eq!(
+            tokens as usize,
+            REPLY_PRIMING_TOKENS + MESSAGE_OVERHEAD_TOKENS + 2
+        );
+
+        // Partial tokens round up
+        let tokens = estimate_prompt_tokens(&[message(Some("abcde".into())), message(None)]);
+        assert_eq!(
+            tokens as usize,
+            REPLY_PRIMING_TOKENS + 2 * MESSAGE_OVERHEAD_TOKENS + 2
+        );
+    }
+
+    #[test]
+    fn test_estimate_prompt_tokens_counts_text_parts_only() {
+        let parts = MessageContent::Parts(vec![
+            serde_json::json!({ "type": "text", "text": "abcd" }),
+            serde_json::json!({ "type": "image_url", "image_url": { "url": "data:image/png;base64,AAAAAAAAAAAAAAAA" } }),
+            serde_json::json!({ "text": "efgh" }),
+        ]);
+        let tokens = estimate_prompt_tokens(&[message(Some(parts))]);
+        assert_eq!(
+            tokens as usize,
+            REPLY_PRIMING_TOKENS + MESSAGE_OVERHEAD_TOKENS + 2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_oversized_request_does_not_block_forever() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            requests_per_minute: 60,
+            tokens_per_minute: Some(100),
+            ..RateLimitConfig::default()
+        });
+
+        // Larger than the whole token bucket; should be capped and granted
+        let result = tokio::time::timeout(
+            Duration::from_millis(500),
+            limiter.acquire("m", Some(10_000)),
+        )
+        .await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_model_buckets_are_independent() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig::unlimited()
+                .with_model_limit("gpt-4o", 1, None)
+                .with_model_limit("gpt-4o-mini", 60, Some(1_000)),
+        );
+
+        assert!(limiter.try_acquire("gpt-4o", None).await);
+        assert!(!limiter.try_acquire("gpt-4o", None).await);
+
+        // Other models are unaffected by the exhausted gpt-4o bucket
+        assert!(limiter.try_acquire("gpt-4o-mini", Some(600)).await);
+        assert!(!limiter.try_acquire("gpt-4o-mini", Some(600)).await);
+        assert!(limiter.try_acquire("unlisted-model", Some(600)).await);
+    }
+
+    #[tokio::test]
+    async fn test_provider_bucket_limits_all_models() {
+        let limiter = RateLimiter::new(
+            RateLimitConfig {
+                requests_per_minute: 1,
+                ..RateLimitConfig::default()
+            }
+            .with_model_limit("gpt-4o", 60, None),
+        );
+
+        assert!(limiter.try_acquire("gpt-4o", None).await);
+        // The model bucket has room, but the provider-wide one is empty
+        assert!(!limiter.try_acquire("gpt-4o", None).await);
+        assert!(!limiter.try_acquire("other", None).await);
+    }
+
+    #[test]
+    fn test_rate_limit_headers_parse() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-limit-requests", "500".parse().unwrap());
+        headers.insert("x-ratelimit-limit-tokens", "30000".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-requests", "0".parse().unwrap());
+        headers.insert("x-ratelimit-remaining-tokens", "29000".parse().unwrap());
+        headers.insert("x-ratelimit-reset-requests", "1m30.5s".parse().unwrap());
+        headers.insert("x-ratelimit-reset-tokens", "20ms".parse().unwrap());
+
+        let parsed = RateLimitHeaders::from_headers(&headers).unwrap();
+        assert_eq!(parsed.limit_requests, Some(500));
+        assert_eq!(parsed.limit_tokens, Some(30_000));
+        assert_eq!(parsed.remaining_requests, Some(0));
+        assert_eq!(parsed.remaining_tokens, Some(29_000));
+        assert_eq!(parsed.reset_requests, Some(Duration::from_millis(90_500)));
+        assert_eq!(parsed.reset_tokens, Some(Duration::from_millis(20)));
+
+        assert_eq!(RateLimitHeaders::from_headers(&HeaderMap::new()), None);
+        assert_eq!(parse_reset_duration("6m0s"), Some(Duration::from_secs(360)));
+        assert_eq!(parse_reset_duration("1h"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_reset_duration("2"), Some(Duration::from_secs(2)));
+        assert_eq!(parse_reset_duration("soon"), None);
+        assert_eq!(parse_reset_duration("5x"), None);
+    }
+
+    #[test]
+    fn test_reset_duration_rejects_unrepresentable_values() {
+        for value in [
+            "inf",
+            "-inf",
+            "NaN",
+            "-1",
+            "1e30",
+            "99999999999999999999h",
+            "99999999999999999999m",
+        ] {
+            assert_eq!(parse_reset_duration(value), None, "{value}");
+        }
+
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-remaining-requests", "0".parse().unwrap());
+        headers.insert("x-ratelimit-reset-requests", "inf".parse().unwrap());
+        let parsed = RateLimitHeaders::from_headers(&headers).unwrap();
+        assert_eq!(parsed.reset_requests, None);
+    }
+
+    #[tokio::test]
+    async fn test_observe_caps_reset_wait() {
+        let limiter = RateLimiter::new(RateLimitConfig {
+            max_reset_wait: Duration::from_millis(200),
+            ..RateLimitConfig::unlimited()
+        });
+        let exhausted = RateLimitHeaders {
+            limit_requests: Some(500),
+            remaining_requests: Some(0),
+            // Parses, but would pause the model for over a century
+            reset_requests: parse_reset_duration("999999h"),
+            ..RateLimitHeaders::default()
+        };
+        limiter.observe("gpt-4o", &exhausted).await;
+        assert!(!limiter.try_acquire("gpt-4o", None).await);
+
+        let waited =
+            tokio::time::timeout(Duration::from_secs(5), limiter.acquire("gpt-4o", None)).await;
+        assert!(waited.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_observe_pauses_until_reset() {
+        let limiter = RateLimiter::new(RateLimitConfig::unlimited());
+        let exhausted = RateLimitHeaders {
+            limit_requests: Some(500),
+            remaining_requests: Some(0),
+            reset_requests: Some(Duration::from_millis(300)),
+            ..RateLimitHeaders::default()
+        };
+        limiter.observe("gpt-4o", &exhausted).await;
+
+        // Learned a bucket for gpt-4o; other models are not paused
+        assert!(!limiter.try_acquire("gpt-4o", None).await);
+        assert!(limiter.try_acquire("gpt-4o-mini", None).await);
+
+        let start = Instant::now();
+        limiter.acquire("gpt-4o", None).await;
+        assert!(start.elapsed() >= Duration::from_millis(250));
+    }
+
+    #[tokio::test]
+    async fn test_observe_lowers_remaining_tokens() {
+        let limiter = RateLimiter::new(RateLimitConfig::unlimited().with_model_limit(
+            "gpt-4o",
+            600,
+            Some(10_000),
+        ));
+        let headers = RateLimitHeaders {
+            remaining_tokens: Some(100),
+            ..RateLimitHeaders::default()
+        };
+        limiter.observe("gpt-4o", &headers).await;
+
+        assert!(!limiter.try_acquire("gpt-4o", Some(5_000)).await);
+        assert!(limiter.try_acquire("gpt-4o", Some(50)).await);
     }
 }
@@ -0,0 +1,312 @@
+//! Failover and round-robin routing across several providers.
+//!
+//! A [`FailoverClient`] holds an ordered list of [`OpenAIClient`]s, typically
+//! built from different provider profiles. Each request goes to one client,
+//! which spends its own retry budget first; if it still fails with a retryable
+//! error (429, 408, 5xx, timeout, connection failure or an open circuit
+//! breaker) the request moves on to the next client. Errors that another
+//! provider would return just the same (bad request, auth) are returned as is.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use tokio_util::sync::CancellationToken;
+use tracing::{debug, warn};
+
+use crate::{is_retryable_status, ChatRequest, ChatResponse, OpenAIClient, OpenAIError};
+
+/// Order in which a [`FailoverClient`] tries its clients
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RoutingStrategy {
+    /// Always start with the first client; later clients are fallbacks
+    #[default]
+    Failover,
+    /// Start each request with the next client in turn, failing over from there
+    RoundRobin,
+}
+
+/// A value together with the backend that produced it
+#[derive(Debug, Clone)]
+pub struct Routed<T> {
+    /// [`OpenAIClient::backend_label`] of the client that answered
+    pub backend: String,
+    /// The client's result
+    pub value: T,
+}
+
+/// Routes requests over an ordered list of clients
+#[derive(Debug, Clone)]
+pub struct FailoverClient {
+    clients: Vec<OpenAIClient>,
+    strategy: RoutingStrategy,
+    /// Shared by clones so round-robin rotation is global
+    next: Arc<AtomicUsize>,
+}
+
+impl FailoverClient {
+    /// Create a router over `clients`, tried in order
+    pub fn new(clients: Vec<OpenAIClient>) -> Result<Self, OpenAIError> {
+        if clients.is_empty() {
+            return Err(OpenAIError::Config(
+                "failover routing needs at least one client".to_string(),
+            ));
+        }
+        Ok(Self {
+            clients,
+            strategy: RoutingStrategy::default(),
+            next: Arc::new(AtomicUsize::new(0)),
+        })
+    }
+
+    /// Set the routing strategy
+    pub fn with_strategy(mut self, strategy: RoutingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Abandon requests on every client once `token` is cancelled
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.clients = self
+            .clients
+            .into_iter()
+            .map(|client| client.with_cancellation(token.clone()))
+            .collect();
+        self
+    }
+
+    /// Apply `f` to every client, e.g. to attach a per-client cost tracker
+    pub fn map_clients(mut self, f: impl FnMut(OpenAIClient) -> OpenAIClient) -> Self {
+        self.clients = self.clients.into_iter().map(f).collect();
+        self
+    }
+
+    /// Routing strategy in use
+    pub fn strategy(&self) -> RoutingStrategy {
+        self.strategy
+    }
+
+    /// Clients in configuration order; the first one is the primary
+    pub fn clients(&self) -> &[OpenAIClient] {
+        &self.clients
+    }
+
+    /// The first configured client
+    pub fn primary(&self) -> &OpenAIClient {
+        &self.clients[0]
+    }
+
+    /// Run `op` against one client after another until one succeeds.
+    ///
+    /// `op` gets its own clone of the client (clones share the HTTP pool,
+    /// rate limiter and circuit breaker), so the future it returns can own it.
+    ///
+    /// Moves to the next client only when the error is worth retrying
+    /// elsewhere (see [`should_fail_over`]); other errors, and the error of the
+    /// last client tried, are returned.
+    pub async fn route<T, F, Fut>(&self, mut op: F) -> Result<Routed<T>, OpenAIError>
+    where
+        F: FnMut(OpenAIClient) -> Fut,
+        Fut: Future<Output = Result<T, OpenAIError>>,
+    {
+        let start = match self.strategy {
+            RoutingStrategy::Failover => 0,
+            RoutingStrategy::RoundRobin => {
+                self.next.fetch_add(1, Ordering::Relaxed) % self.clients.len()
+            }
+        };
+
+        let mut tried = 0;
+        loop {
+            let client = &self.clients[(start + tried) % self.clients.len()];
+            tried += 1;
+            match op(client.clone()).await {
+                Ok(value) => {
+                    let backend = client.backend_label();
+                    debug!(backend = %backend, "Request answered");
+                    return Ok(Routed { backend, value });
+                }
+                Err(err) if tried < self.clients.len() && should_fail_over(&err) => {
+                    warn!(
+                        "{} failed, failing over to the next provider: {}",
+                        client.backend_label(),
+                        err
+                    );
+                }
+                Err(err) => return Err(err),
+            }
+        }
+    }
+
+    /// Send a chat completion, using each client's default model
+    pub async fn chat(&self, request: ChatRequest) -> Result<Routed<ChatResponse>, OpenAIError> {
+        self.route(|client| {
+            let mut request = request.clone();
+            request.model = client.default_model().to_string();
+            async move { client.chat(request).await }
+        })
+        .await
+    }
+}
+
+impl From<OpenAIClient> for FailoverClient {
+    fn from(client: OpenAIClient) -> Self {
+        Self {
+            clients: vec![client],
+            strategy: RoutingStrategy::default(),
+            next: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+}
+
+/// Whether another provider might answer a request that failed with `err`
+pub fn should_fail_over(err: &OpenAIError) -> bool {
+    match err {
+        OpenAIError::RateLimited { .. } | OpenAIError::CircuitOpen { .. } => true,
+        OpenAIError::Transport(e) => e.is_timeout() || e.is_connect(),
+        OpenAIError::Provider { status, .. } => reqwest::StatusCode::from_u16(*status)
+            .map(is_retryable_status)
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{completion, user_request};
+    use crate::{AuthPlacement, ClientConfig};
+    use secrecy::SecretString;
+
+    fn client(base_url: &str, model: &str) -> anyhow::Result<OpenAIClient> {
+        Ok(OpenAIClient::new(ClientConfig {
+            base_url: base_url.to_string(),
+            api_key: SecretString::new("sk-test".into()),
+            default_model: model.to_string(),
+            disable_system_proxy: true,
+            max_retries: 1,
+            cache_enabled: false,
+            auth: AuthPlacement::Bearer,
+            ..ClientConfig::default()
+        })?)
+    }
+
+    fn request() -> ChatRequest {
+        user_request("ignored", "hi")
+    }
+
+    #[tokio::test]
+    async fn test_fails_over_after_retry_budget() -> anyhow::Result<()> {
+        let mut primary = mockito::Server::new_async().await;
+        let mut backup = mockito::Server::new_async().await;
+        // max_retries = 1, so the primary is tried twice before moving on
+        let down = primary
+            .mock("POST", "/v1/chat/completions")
+            .with_status(503)
+            .expect(2)
+            .create_async()
+            .await;
+        let up = backup
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(
+                serde_json::json!({ "model": "backup-model" }),
+            ))
+            .with_status(200)
+            .with_body(completion("from backup"))
+            .create_async()
+            .await;
+
+        let router = FailoverClient::new(vec![
+            client(&format!("{}/v1", primary.url()), "primary-model")?,
+            client(&format!("{}/v1", backup.url()), "backup-model")?,
+        ])?;
+        let routed = router.chat(request()).await?;
+
+        down.assert_async().await;
+        up.assert_async().await;
+        assert!(routed.backend.contains(&backup.host_with_port()));
+        assert!(routed.backend.contains("backup-model"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_does_not_fail_over_on_client_errors() -> anyhow::Result<()> {
+        let mut primary = mockito::Server::new_async().await;
+        let mut backup = mockito::Server::new_async().await;
+        primary
+            .mock("POST", "/v1/chat/completions")
+            .with_status(401)
+            .create_async()
+            .await;
+        let untouched = backup
+            .mock("POST", "/v1/chat/completions")
+            .expect(0)
+            .create_async()
+            .await;
+
+        let router = FailoverClient::new(vec![
+            client(&format!("{}/v1", primary.url()), "m")?,
+            client(&format!("{}/v1", backup.url()), "m")?,
+        ])?;
+        let err = router.chat(request()).await.unwrap_err();
+
+        assert!(matches!(err, OpenAIError::AuthFailed(_)));
+        untouched.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_rotates_start() -> anyhow::Result<()> {
+        let mut first = mockito::Server::new_async().await;
+        let mut second = mockito::Server::new_async().await;
+        let a = first
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(completion("a"))
+            .expect(2)
+            .create_async()
+            .await;
+        let b = second
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(completion("b"))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let router = FailoverClient::new(vec![
+            client(&format!("{}/v1", first.url()), "m")?,
+            client(&format!("{}/v1", second.url()), "m")?,
+        ])?
+        .with_strategy(RoutingStrategy::RoundRobin);
+        let mut backends = Vec::new();
+        for _ in 0..4 {
+            backends.push(router.chat(request()).await?.backend);
+        }
+
+        a.assert_async().await;
+        b.assert_async().await;
+        assert_ne!(backends[0], backends[1]);
+        assert_eq!(backends[0], backends[2]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_failover_classification() {
+        assert!(should_fail_over(&OpenAIError::RateLimited {
+            retry_after: None
+        }));
+        assert!(should_fail_over(&OpenAIError::Provider {
+            status: 502,
+            message: String::new(),
+        }));
+        assert!(should_fail_over(&OpenAIError::Provider {
+            status: 408,
+            message: String::new(),
+        }));
+        assert!(!should_fail_over(&OpenAIError::InvalidRequest(
+            String::new()
+        )));
+        assert!(FailoverClient::new(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,499 @@
+//! Schema-validated JSON responses.
+//!
+//! [`OpenAIClient::chat_json`] asks the provider for output matching the JSON
+//! schema derived from the target type. Providers with weak JSON adherence still
+//! return prose, fenced blocks, truncated objects or objects with missing or
+//! mistyped fields, so every reply is checked against the schema before it is
+//! deserialized. A reply that fails either step gets a single repair round-trip
+//! in which the parse or validation errors are fed back to the model.
+//!
+//! The `*_with_tools` variants additionally let the model call tools before it
+//! answers; tool rounds do not count as repairs.
+
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use tracing::warn;
+
+use crate::json_repair::extract_json_lossy;
+use crate::tools::{execute_tool_calls, ToolExecutor};
+use crate::{ChatRequest, ChatResponse, Message, OpenAIClient, OpenAIError, ResponseFormat, Role};
+
+/// Number of repair round-trips attempted when a response fails to parse or validate.
+pub const MAX_JSON_REPAIR_ATTEMPTS: usize = 1;
+
+/// Maximum number of schema violations quoted back to the model.
+const MAX_REPORTED_VIOLATIONS: usize = 5;
+
+/// A structured reply together with the repair it needed, if any.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructuredReply<T> {
+    /// The deserialized reply
+    pub value: T,
+    /// Parse or validation error of the first reply, if a repair round-trip
+    /// was needed to obtain `value`
+    pub repair: Option<String>,
+    /// Total tokens the provider reported across every round, if any
+    pub tokens: Option<u64>,
+}
+
+/// Tools a structured request may call before answering
+#[derive(Clone, Copy)]
+pub struct ToolAccess<'a> {
+    /// Runs the calls and supplies the tool definitions when the request has none
+    pub executor: &'a dyn ToolExecutor,
+    /// Tool-calling rounds allowed before [`OpenAIError::ToolRoundLimit`]
+    pub max_rounds: usize,
+}
+
+impl OpenAIClient {
+    /// Send a chat request and deserialize the reply into `T`.
+    ///
+    /// The request's `response_format` is replaced with the JSON schema derived
+    /// from `T`. If the reply cannot be parsed or does not match the schema, the
+    /// errors are sent back to the model once and the corrected reply is used
+    /// instead.
+    ///
+    /// Returns [`OpenAIError::SchemaUnsupported`] when the provider rejects the
+    /// `json_schema` response format, and [`OpenAIError::UnrecoverableJson`] when
+    /// the reply is still invalid after the repair attempt.
+    pub async fn chat_json<T>(&self, request: ChatRequest) -> Result<T, OpenAIError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        self.chat_json_reply(request).await.map(|reply| reply.value)
+    }
+
+    /// Like [`OpenAIClient::chat_json`], but also reports the repair round-trip.
+    pub async fn chat_json_reply<T>(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StructuredReply<T>, OpenAIError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        self.json_schema_reply(request, None).await
+    }
+
+    /// Like [`OpenAIClient::chat_json_reply`], executing the model's tool calls
+    /// until it answers.
+    pub async fn chat_json_reply_with_tools<T>(
+        &self,
+        request: ChatRequest,
+        tools: ToolAccess<'_>,
+    ) -> Result<StructuredReply<T>, OpenAIError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        self.json_schema_reply(request, Some(tools)).await
+    }
+
+    async fn json_schema_reply<T>(
+        &self,
+        mut request: ChatRequest,
+        tools: Option<ToolAccess<'_>>,
+    ) -> Result<StructuredReply<T>, OpenAIError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        request.response_format = Some(json_schema_format::<T>()?);
+
+        match self.structured_reply(request, tools).await {
+            Err(err @ (OpenAIError::InvalidRequest(_) | OpenAIError::Provider { .. }))
+                if is_response_format_unsupported(&err.to_string()) =>
+            {
+                Err(OpenAIError::SchemaUnsupported(err.to_string()))
+            }
+            other => other,
+        }
+    }
+
+    /// Send a chat request as-is and validate and deserialize the reply into
+    /// `T`, with the same single repair round-trip as [`OpenAIClient::chat_json`].
+    pub async fn complete_json<T>(
+        &self,
+        request: ChatRequest,
+    ) -> Result<StructuredReply<T>, OpenAIError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        self.structured_reply(request, None).await
+    }
+
+    /// Like [`OpenAIClient::complete_json`], executing the model's tool calls
+    /// until it answers.
+    ///
+    /// Returns [`OpenAIError::ToolRoundLimit`] if the model still calls tools
+    /// after `tools.max_rounds` rounds.
+    pub async fn complete_json_with_tools<T>(
+        &self,
+        request: ChatRequest,
+        tools: ToolAccess<'_>,
+    ) -> Result<StructuredReply<T>, OpenAIError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        self.structured_reply(request, Some(tools)).await
+    }
+
+    async fn structured_reply<T>(
+        &self,
+        mut request: ChatRequest,
+        tools: Option<ToolAccess<'_>>,
+    ) -> Result<StructuredReply<T>, OpenAIError>
+    where
+        T: DeserializeOwned + JsonSchema,
+    {
+        if let Some(tools) = tools.filter(|_| request.tools.is_none()) {
+            let definitions = tools.executor.definitions().await;
+            if !definitions.is_empty() {
+                request.tools = Some(definitions);
+            }
+        }
+
+        let schema = schema_for::<T>()?;
+        let validator = jsonschema::validator_for(&schema)
+            .map_err(|e| OpenAIError::InvalidRequest(format!("invalid response schema: {e}")))?;
+
+        let mut repairs = 0;
+        let mut tool_rounds = 0;
+        let mut repair = None;
+        let mut tokens: Option<u64> = None;
+        loop {
+            let response = self.chat(request.clone()).await?;
+            if let Some(usage) = &response.usage {
+                let used = u64::try_from(usage.total_tokens).unwrap_or(0);
+                tokens = Some(tokens.unwrap_or(0) + used);
+            }
+            if let Some(tools) = tools {
+                let message = response.choices.first().map(|c| c.message.clone());
+                let calls = message
+                    .as_ref()
+                    .and_then(|m| m.tool_calls.clone())
+                    .unwrap_or_default();
+                if let Some(message) = message.filter(|_| !calls.is_empty()) {
+                    if tool_rounds >= tools.max_rounds {
+                        return Err(OpenAIError::ToolRoundLimit(tools.max_rounds));
+                    }
+                    tool_rounds += 1;
+                    request.messages.push(message);
+                    request
+                        .messages
+                        .extend(execute_tool_calls(tools.executor, &calls).await);
+                    continue;
+                }
+            }
+            let content = response_text(&response);
+
+            let problem = match parse_json_response::<Value>(&content) {
+                Ok(value) => {
+                    let violations: Vec<String> = validator
+                        .iter_errors(&value)
+                        .take(MAX_REPORTED_VIOLATIONS)
+                        .map(|e| format!("{} at '{}'", e, e.instance_path()))
+                        .collect();
+                    if violations.is_empty() {
+                        match serde_json::from_value::<T>(value) {
+                            Ok(value) => {
+                                return Ok(StructuredReply {
+                                    value,
+                                    repair,
+                                    tokens,
+                                })
+                            }
+                            Err(e) => Problem::Parse(e.to_string()),
+                        }
+                    } else {
+                        Problem::Schema(violations.join("; "))
+                    }
+                }
+                Err(e) => Problem::Parse(e),
+            };
+            if repairs >= MAX_JSON_REPAIR_ATTEMPTS {
+                return Err(OpenAIError::UnrecoverableJson(problem.to_string()));
+            }
+            repairs += 1;
+
+            warn!(
+                "Structured response invalid, requesting repair: {}",
+                problem
+            );
+            request.messages.push(Message {
+                role: Role::Assistant,
+                content: Some(content.into()),
+                tool_calls: None,
+                tool_call_id: None,
+            });
+            request.messages.push(Message {
+                role: Role::User,
+                content: Some(problem.repair_prompt().into()),
+                tool_calls: None,
+                tool_call_id: None,
+            });
+            repair.get_or_insert(problem.to_string());
+        }
+    }
+}
+
+/// Why a structured reply was rejected.
+enum Problem {
+    /// Not JSON, or JSON that does not deserialize
+    Parse(String),
+    /// JSON that violates the schema
+    Schema(String),
+}
+
+impl Problem {
+    fn repair_prompt(&self) -> String {
+        let reason = match self {
+            Problem::Parse(error) => format!("Your previous reply could not be parsed: {error}."),
+            Problem::Schema(violations) => {
+                format!("Your previous reply did not match the required schema: {violations}.")
+            }
+        };
+        format!(
+            "{reason} Reply again with only the corrected JSON document that matches the required schema, \
+             without markdown fences or commentary."
+        )
+    }
+}
+
+impl std::fmt::Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Problem::Parse(error) => write!(f, "{error}"),
+            Problem::Schema(violations) => write!(f, "schema violations: {violations}"),
+        }
+    }
+}
+
+/// JSON schema for `T`, as sent in `response_format` and used for validation.
+///
+/// Works for report sections such as `ExecutiveSummary`, `DeepScanResults` or
+/// `TodoItem` as well as analyzer payloads.
+pub fn schema_for<T: JsonSchema>() -> Result<Value, OpenAIError> {
+    Ok(serde_json::to_value(schemars::schema_for!(T))?)
+}
+
+/// Build a `json_schema` response format for `T`.
+pub fn json_schema_format<T: JsonSchema>() -> Result<ResponseFormat, OpenAIError> {
+    Ok(ResponseFormat::JsonSchema {
+        json_schema: serde_json::json!({
+            "name": schema_name(&T::schema_name()),
+            "schema": schema_for::<T>()?,
+        }),
+    })
+}
+
+/// Schema names must match `^[a-zA-Z0-9_-]{1,64}$`.
+fn schema_name(raw: &str) -> String {
+    let name: String = raw
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(64)
+        .collect();
+    if name.is_empty() {
+        "response".to_string()
+    } else {
+        name
+    }
+}
+
+fn response_text(response: &ChatResponse) -> String {
+    response
+        .choices
+        .first()
+        .and_then(|c| c.message.content.as_ref().and_then(|c| c.to_text_lossy()))
+        .unwrap_or_default()
+}
+
+/// Deserialize a model reply, repairing fences, prose and other damage first.
+///
+/// See [`extract_json_lossy`] for what is tolerated.
+pub(crate) fn parse_json_response<T: DeserializeOwned>(content: &str) -> Result<T, String> {
+    if content.trim().is_empty() {
+        return Err("response was empty".to_string());
+    }
+    let value = extract_json_lossy(content).map_err(|e| e.to_string())?;
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Whether a provider error indicates that `response_format` is not supported.
+pub(crate) fn is_response_format_unsupported(error: &str) -> bool {
+    let msg = error.to_lowercase();
+    msg.contains("response_format")
+        || msg.contains("json_schema")
+        || msg.contains("json object")
+        || msg.contains("json_object")
+        || msg.contains("unsupported")
+        || msg.contains("not supported")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{completion, mock_client, user_request};
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize, JsonSchema, PartialEq)]
+    struct Verdict {
+        ok: bool,
+        reason: String,
+    }
+
+    fn request() -> ChatRequest {
+        user_request("test-model", "Judge this")
+    }
+
+    #[test]
+    fn test_parse_json_response_tolerates_wrapping() -> anyhow::Result<()> {
+        let fenced = "Sure:\n```json\n{\"ok\": true, \"reason\": \"fine\"}\n```";
+        let verdict: Verdict = parse_json_response(fenced).map_err(anyhow::Error::msg)?;
+        assert!(verdict.ok);
+
+        let prose = "Result: {\"ok\": false, \"reason\": \"has } brace\"} done";
+        let verdict: Verdict = parse_json_response(prose).map_err(anyhow::Error::msg)?;
+        assert_eq!(verdict.reason, "has } brace");
+
+        assert!(parse_json_response::<Verdict>("{\"ok\": true}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_schema_format_names_schema() -> anyhow::Result<()> {
+        let format = serde_json::to_value(json_schema_format::<Vec<Verdict>>()?)?;
+        assert_eq!(format["type"], "json_schema");
+        assert_eq!(format["json_schema"]["name"], "Array_of_Verdict");
+        assert!(format["json_schema"]["schema"].is_object());
+        Ok(())
+    }
+
+    #[test]
+    fn test_report_section_schemas_accept_own_output() -> anyhow::Result<()> {
+        use hqe_core::models::{DeepScanResults, ExecutiveSummary};
+
+        let summary = serde_json::to_value(ExecutiveSummary::default())?;
+        let validator = jsonschema::validator_for(&schema_for::<ExecutiveSummary>()?)?;
+        assert!(validator.is_valid(&summary));
+        assert!(!validator.is_valid(&serde_json::json!({"health_score": "high"})));
+
+        let results = serde_json::to_value(DeepScanResults::default())?;
+        let validator = jsonschema::validator_for(&schema_for::<DeepScanResults>()?)?;
+        assert!(validator.is_valid(&results));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_json_repairs_once() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let broken = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "response_format": {"type": "json_schema"}
+            })))
+            .with_status(200)
+            .with_body(completion("{\"ok\": true"))
+            .expect(1)
+            .create_async()
+            .await;
+        let repaired = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex("could not be parsed".to_string()))
+            .with_status(200)
+            .with_body(completion("{\"ok\": true, \"reason\": \"fixed\"}"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let verdict: Verdict = mock_client(&server)?.chat_json(request()).await?;
+
+        assert_eq!(
+            verdict,
+            Verdict {
+                ok: true,
+                reason: "fixed".to_string()
+            }
+        );
+        repaired.assert_async().await;
+        broken.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_json_repairs_schema_violations() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let invalid = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "response_format": {"type": "json_schema"}
+            })))
+            .with_status(200)
+            .with_body(completion("{\"ok\": \"yes\", \"reason\": \"fine\"}"))
+            .expect(1)
+            .create_async()
+            .await;
+        let repaired = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex(
+                "did not match the required schema".to_string(),
+            ))
+            .with_status(200)
+            .with_body(completion("{\"ok\": true, \"reason\": \"fine\"}"))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let reply: StructuredReply<Verdict> =
+            mock_client(&server)?.chat_json_reply(request()).await?;
+
+        assert!(reply.value.ok);
+        let repair = reply.repair.unwrap_or_default();
+        assert!(repair.contains("schema violations"), "{repair}");
+        assert!(repair.contains("/ok"), "{repair}");
+        repaired.assert_async().await;
+        invalid.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_json_gives_up_after_one_repair() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(completion("not json"))
+            .expect(2)
+            .create_async()
+            .await;
+
+        let result = mock_client(&server)?.chat_json::<Verdict>(request()).await;
+
+        assert!(matches!(result, Err(OpenAIError::UnrecoverableJson(_))));
+        mock.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_json_reports_unsupported_schema() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(400)
+            .with_body(
+                r#"{"error":{"message":"response_format json_schema is not supported","type":"invalid_request_error"}}"#,
+            )
+            .create_async()
+            .await;
+
+        let result = mock_client(&server)?.chat_json::<Verdict>(request()).await;
+
+        assert!(matches!(result, Err(OpenAIError::SchemaUnsupported(_))));
+        Ok(())
+    }
+}
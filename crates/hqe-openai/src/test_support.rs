@@ -0,0 +1,49 @@
+//! Shared fixtures for the crate's unit tests.
+
+use crate::{ChatRequest, ClientConfig, Message, OpenAIClient, Role};
+use secrecy::SecretString;
+
+/// A user message with plain text content
+pub(crate) fn user_message(text: &str) -> Message {
+    Message {
+        role: Role::User,
+        content: Some(text.into()),
+        tool_calls: None,
+        tool_call_id: None,
+    }
+}
+
+/// A request for `model` holding one user message
+pub(crate) fn user_request(model: &str, text: &str) -> ChatRequest {
+    ChatRequest::new(model, vec![user_message(text)])
+}
+
+/// Chat completion response body with a single choice holding `message`
+pub(crate) fn completion_message(message: serde_json::Value) -> String {
+    serde_json::json!({
+        "id": "chatcmpl-1",
+        "object": "chat.completion",
+        "created": 0,
+        "model": "test-model",
+        "choices": [{"index": 0, "message": message, "finish_reason": "stop"}]
+    })
+    .to_string()
+}
+
+/// Chat completion response body whose assistant reply is `content`
+pub(crate) fn completion(content: &str) -> String {
+    completion_message(serde_json::json!({"role": "assistant", "content": content}))
+}
+
+/// Client for a mock server: no retries, no cache, no system proxy
+pub(crate) fn mock_client(server: &mockito::Server) -> anyhow::Result<OpenAIClient> {
+    Ok(OpenAIClient::new(ClientConfig {
+        base_url: format!("{}/v1", server.url()),
+        api_key: SecretString::new("test".into()),
+        default_model: "test-model".to_string(),
+        disable_system_proxy: true,
+        max_retries: 0,
+        cache_enabled: false,
+        ..ClientConfig::default()
+    })?)
+}
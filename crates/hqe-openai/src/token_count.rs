@@ -0,0 +1,353 @@
+//! Token estimation and prompt truncation for chat requests.
+//!
+//! OpenAI models are counted with their tiktoken BPE encoding; any other model
+//! falls back to a `chars / 4` heuristic. The counts are estimates meant to keep
+//! requests under the context window before the provider rejects them.
+
+use tiktoken_rs::tokenizer::{get_tokenizer, Tokenizer};
+use tiktoken_rs::CoreBPE;
+
+use crate::{ChatRequest, Message, MessageContent, Role};
+
+/// Tokens added per message for role and formatting markers
+pub const MESSAGE_OVERHEAD_TOKENS: usize = 4;
+
+/// Tokens added once per request to prime the assistant reply
+pub const REPLY_PRIMING_TOKENS: usize = 3;
+
+/// Context window assumed for models we know nothing about
+pub const DEFAULT_CONTEXT_WINDOW: usize = 32_768;
+
+/// Marker appended to text that was cut to fit the context window
+pub const TRUNCATION_MARKER: &str = "\n\n[... truncated to fit the model context window ...]";
+
+/// Outcome of [`ChatRequest::truncate_to_fit`] when content had to be removed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Truncation {
+    /// Estimated prompt tokens before trimming
+    pub original_tokens: usize,
+    /// Estimated prompt tokens after trimming
+    pub final_tokens: usize,
+    /// Whole messages or content parts that were dropped
+    pub removed_blocks: usize,
+}
+
+impl Truncation {
+    /// Number of prompt tokens removed
+    pub fn trimmed_tokens(&self) -> usize {
+        self.original_tokens.saturating_sub(self.final_tokens)
+    }
+}
+
+/// Count the tokens in `text` for `model`.
+pub fn count_tokens(model: &str, text: &str) -> usize {
+    match bpe_for_model(model) {
+        Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+        None => heuristic_tokens(text),
+    }
+}
+
+/// `chars / 4`, rounded up.
+pub fn heuristic_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+/// Estimate the prompt tokens a request will consume.
+///
+/// Only text content is counted; images and other attachments are ignored.
+pub fn estimate_request_tokens(request: &ChatRequest) -> usize {
+    request
+        .messages
+        .iter()
+        .map(|m| message_tokens(&request.model, m))
+        .fold(REPLY_PRIMING_TOKENS, usize::saturating_add)
+}
+
+/// Known context window for `model`, ignoring any `vendor/` prefix.
+pub fn context_window(model: &str) -> Option<usize> {
+    let name = base_model(model);
+    let size = if name.starts_with("gpt-4.1") {
+        1_047_576
+    } else if name.starts_with("gpt-4o")
+        || name.starts_with("chatgpt-4o")
+        || name.starts_with("gpt-4-turbo")
+        || name.starts_with("gpt-4-1106")
+        || name.starts_with("gpt-4-0125")
+        || name.starts_with("o1")
+    {
+        128_000
+    } else if name.starts_with("o3") || name.starts_with("o4") {
+        200_000
+    } else if name.starts_with("gpt-4-32k") {
+        32_768
+    } else if name.starts_with("gpt-4") {
+        8_192
+    } else if name.starts_with("gpt-3.5-turbo") {
+        16_385
+    } else if name.starts_with("claude") {
+        200_000
+    } else {
+        return None;
+    };
+    Some(size)
+}
+
+impl ChatRequest {
+    /// Trim user content until the prompt fits in `max_context - reserve_output` tokens.
+    ///
+    /// Older user messages are dropped first, then the oldest text parts of the
+    /// final user message, and finally the tail of its remaining text is cut and
+    /// marked with [`TRUNCATION_MARKER`]. System and assistant messages are never
+    /// touched. Returns `None` if the request already fit.
+    pub fn truncate_to_fit(
+        &mut self,
+        max_context: usize,
+        reserve_output: usize,
+    ) -> Option<Truncation> {
+        let budget = max_context.saturating_sub(reserve_output);
+        let original_tokens = estimate_request_tokens(self);
+        if original_tokens <= budget {
+            return None;
+        }
+
+        let mut total = original_tokens;
+        let mut removed_blocks = 0;
+        let last_user = self
+            .messages
+            .iter()
+            .rposition(|m| matches!(m.role, Role::User));
+
+        // 1. Drop whole user messages, oldest first, keeping the final one
+        if let Some(last_user) = last_user {
+            let mut idx = 0;
+            let mut last = last_user;
+            while total > budget && idx < last {
+                if matches!(self.messages[idx].role, Role::User) {
+                    total -= message_tokens(&self.model, &self.messages[idx]);
+                    self.messages.remove(idx);
+                    removed_blocks += 1;
+                    last -= 1;
+                } else {
+                    idx += 1;
+                }
+            }
+
+            // 2. Drop the oldest text parts of the final user message
+            let model = self.model.clone();
+            let message = &mut self.messages[last];
+            if let Some(MessageContent::Parts(parts)) = &mut message.content {
+                while total > budget && parts.len() > 1 {
+                    total -= part_tokens(&model, &parts[0]);
+                    parts.remove(0);
+                    removed_blocks += 1;
+                }
+            }
+
+            // 3. Cut the tail of whatever text remains
+            if total > budget {
+                let current = message_tokens(&model, message);
+                let others = total - current;
+                let allowed = budget
+                    .saturating_sub(others)
+                    .saturating_sub(MESSAGE_OVERHEAD_TOKENS);
+                truncate_message_text(&model, message, allowed);
+                total = others + message_tokens(&model, message);
+            }
+        }
+
+        Some(Truncation {
+            original_tokens,
+            final_tokens: total,
+            removed_blocks,
+        })
+    }
+}
+
+fn message_tokens(model: &str, message: &Message) -> usize {
+    let content = match &message.content {
+        Some(MessageContent::Text(text)) => count_tokens(model, text),
+        Some(MessageContent::Parts(parts)) => {
+            parts.iter().map(|part| part_tokens(model, part)).sum()
+        }
+        None => 0,
+    };
+    content.saturating_add(MESSAGE_OVERHEAD_TOKENS)
+}
+
+fn part_tokens(model: &str, part: &serde_json::Value) -> usize {
+    part_text(part).map_or(0, |text| count_tokens(model, text))
+}
+
+fn part_text(part: &serde_json::Value) -> Option<&str> {
+    let is_text = part
+        .get("type")
+        .and_then(|t| t.as_str())
+        .map_or(true, |t| t == "text");
+    if is_text {
+        part.get("text").and_then(|t| t.as_str())
+    } else {
+        None
+    }
+}
+
+/// Shrink the message's (last) text so it uses at most `allowed` tokens.
+fn truncate_message_text(model: &str, message: &mut Message, allowed: usize) {
+    let text = match &mut message.content {
+        Some(MessageContent::Text(text)) => text,
+        Some(MessageContent::Parts(parts)) => {
+            match parts.iter_mut().rev().find_map(|part| part.get_mut("text")) {
+                Some(serde_json::Value::String(text)) => text,
+                _ => return,
+            }
+        }
+        None => return,
+    };
+
+    truncate_text(model, text, allowed);
+}
+
+/// Cut the tail of `text` so it uses at most `allowed` tokens, including the
+/// appended [`TRUNCATION_MARKER`].
+pub fn truncate_text(model: &str, text: &mut String, allowed: usize) {
+    let marker_tokens = count_tokens(model, TRUNCATION_MARKER);
+    let target = allowed.saturating_sub(marker_tokens);
+    let mut current = count_tokens(model, text);
+    let mut keep_chars = text.chars().count();
+
+    while current > target && keep_chars > 0 {
+        // Scale down proportionally, always making progress
+        let scaled = keep_chars * target / current.max(1);
+        keep_chars = scaled.min(keep_chars.saturating_sub(1));
+        let cut = text
+            .char_indices()
+            .nth(keep_chars)
+            .map_or(text.len(), |(i, _)| i);
+        text.truncate(cut);
+        current = count_tokens(model, text);
+    }
+    text.push_str(TRUNCATION_MARKER);
+}
+
+fn base_model(model: &str) -> &str {
+    model.rsplit('/').next().unwrap_or(model)
+}
+
+fn bpe_for_model(model: &str) -> Option<&'static CoreBPE> {
+    Some(match get_tokenizer(base_model(model))? {
+        Tokenizer::O200kBase => tiktoken_rs::o200k_base_singleton(),
+        Tokenizer::Cl100kBase => tiktoken_rs::cl100k_base_singleton(),
+        Tokenizer::P50kBase => tiktoken_rs::p50k_base_singleton(),
+        Tokenizer::P50kEdit => tiktoken_rs::p50k_edit_singleton(),
+        Tokenizer::R50kBase | Tokenizer::Gpt2 => tiktoken_rs::r50k_base_singleton(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn message(role: Role, content: MessageContent) -> Message {
+        Message {
+            role,
+            content: Some(content),
+            tool_calls: None,
+            tool_call_id: None,
+        }
+    }
+
+    #[test]
+    fn test_count_tokens_uses_bpe_for_openai_models() {
+        assert_eq!(count_tokens("gpt-4o", "hello world"), 2);
+        assert_eq!(count_tokens("openai/gpt-4o-mini", "hello world"), 2);
+        // Unknown models fall back to chars / 4
+        assert_eq!(count_tokens("llama3", "hello world"), 3);
+    }
+
+    #[test]
+    fn test_context_window_lookup() {
+        assert_eq!(context_window("gpt-4o-mini"), Some(128_000));
+        assert_eq!(context_window("anthropic/claude-3-5-sonnet"), Some(200_000));
+        assert_eq!(context_window("gpt-4"), Some(8_192));
+        assert_eq!(context_window("mystery-model"), None);
+    }
+
+    #[test]
+    fn test_truncate_noop_when_fitting() {
+        let mut req = ChatRequest::new(
+            "llama3",
+            vec![message(Role::User, MessageContent::Text("hi".into()))],
+        );
+        assert_eq!(req.truncate_to_fit(1_000, 100), None);
+    }
+
+    #[test]
+    fn test_truncate_drops_oldest_user_messages_first() {
+        let mut req = ChatRequest::new(
+            "llama3",
+            vec![
+                message(Role::System, MessageContent::Text("system".into())),
+                message(Role::User, MessageContent::Text("a".repeat(400))),
+                message(Role::Assistant, MessageContent::Text("ok".into())),
+                message(Role::User, MessageContent::Text("latest".into())),
+            ],
+        );
+
+        let truncation = req.truncate_to_fit(100, 50);
+
+        assert_eq!(truncation.map(|t| t.removed_blocks), Some(1));
+        assert_eq!(req.messages.len(), 3);
+        assert!(matches!(req.messages[0].role, Role::System));
+        assert!(estimate_request_tokens(&req) <= 50);
+    }
+
+    #[test]
+    fn test_truncate_cuts_final_message_text() -> anyhow::Result<()> {
+        let mut req = ChatRequest::new(
+            "gpt-4o",
+            vec![
+                message(Role::System, MessageContent::Text("Be brief.".into())),
+                message(Role::User, MessageContent::Text("word ".repeat(5_000))),
+            ],
+        );
+        let original = estimate_request_tokens(&req);
+
+        let truncation = req
+            .truncate_to_fit(1_000, 200)
+            .ok_or_else(|| anyhow::anyhow!("expected truncation"))?;
+
+        assert_eq!(truncation.original_tokens, original);
+        assert!(truncation.final_tokens <= 800);
+        assert_eq!(truncation.final_tokens, estimate_request_tokens(&req));
+        let text = req.messages[1]
+            .content
+            .as_ref()
+            .and_then(|c| c.to_text_lossy())
+            .unwrap_or_default();
+        assert!(text.starts_with("word word"));
+        assert!(text.ends_with(TRUNCATION_MARKER));
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncate_drops_oldest_parts() {
+        let parts = vec![
+            serde_json::json!({"type": "text", "text": "x".repeat(800)}),
+            serde_json::json!({"type": "text", "text": "keep me"}),
+        ];
+        let mut req = ChatRequest::new(
+            "llama3",
+            vec![message(Role::User, MessageContent::Parts(parts))],
+        );
+
+        let truncation = req.truncate_to_fit(100, 10);
+
+        assert_eq!(truncation.map(|t| t.removed_blocks), Some(1));
+        assert_eq!(
+            req.messages[0]
+                .content
+                .as_ref()
+                .and_then(|c| c.to_text_lossy()),
+            Some("keep me".to_string())
+        );
+    }
+}
@@ -0,0 +1,379 @@
+//! Tool calling.
+//!
+//! [`OpenAIClient::chat_with_tools`] runs a conversation in which the model may
+//! call tools: every tool call in a reply is handed to a [`ToolExecutor`], the
+//! results are appended as [`Role::Tool`] messages and the request is sent
+//! again, until the model answers without calling a tool.
+//!
+//! The MCP [`ToolRegistry`] implements [`ToolExecutor`], so the tools registered
+//! there (e.g. prompt tools) can be offered to the model directly.
+
+use async_trait::async_trait;
+use futures::future::join_all;
+use hqe_mcp::ToolRegistry;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use tracing::{debug, warn};
+
+use crate::{ChatRequest, ChatResponse, Message, OpenAIClient, OpenAIError, Role};
+
+/// Number of tool-calling rounds before [`OpenAIClient::chat_with_tools`] gives up.
+pub const MAX_TOOL_ROUNDS: usize = 8;
+
+/// A tool call requested by the model
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ToolCall {
+    /// Call ID, echoed back in the tool result
+    #[serde(default)]
+    pub id: String,
+    /// Call type; always `function` today
+    #[serde(rename = "type", default = "function_type")]
+    pub call_type: String,
+    /// The function to call
+    pub function: FunctionCall,
+}
+
+/// Function name and arguments of a [`ToolCall`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FunctionCall {
+    /// Name of the tool
+    pub name: String,
+    /// JSON-encoded arguments
+    #[serde(default, deserialize_with = "arguments_string")]
+    pub arguments: String,
+}
+
+impl ToolCall {
+    /// The decoded arguments; missing arguments decode to an empty object.
+    pub fn arguments(&self) -> Result<Value, serde_json::Error> {
+        if self.function.arguments.trim().is_empty() {
+            return Ok(Value::Object(serde_json::Map::new()));
+        }
+        serde_json::from_str(&self.function.arguments)
+    }
+}
+
+fn function_type() -> String {
+    "function".to_string()
+}
+
+/// OpenAI sends arguments as a JSON string; Ollama and some gateways send an object.
+fn arguments_string<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Value::deserialize(deserializer)? {
+        Value::String(s) => s,
+        Value::Null => String::new(),
+        other => other.to_string(),
+    })
+}
+
+/// Outcome of executing a [`ToolCall`]
+#[derive(Debug, Clone, PartialEq)]
+pub struct ToolResult {
+    /// ID of the call this answers
+    pub tool_call_id: String,
+    /// Output shown to the model
+    pub content: String,
+    /// Whether the tool failed; the error is still reported to the model
+    pub is_error: bool,
+}
+
+impl ToolResult {
+    /// A successful result
+    pub fn success(call: &ToolCall, content: impl Into<String>) -> Self {
+        Self {
+            tool_call_id: call.id.clone(),
+            content: content.into(),
+            is_error: false,
+        }
+    }
+
+    /// A failed call; the model sees the message and can recover
+    pub fn error(call: &ToolCall, message: impl std::fmt::Display) -> Self {
+        Self {
+            tool_call_id: call.id.clone(),
+            content: format!("Error: {message}"),
+            is_error: true,
+        }
+    }
+
+    /// The [`Role::Tool`] message carrying this result
+    pub fn into_message(self) -> Message {
+        Message {
+            role: Role::Tool,
+            content: Some(self.content.into()),
+            tool_calls: None,
+            tool_call_id: Some(self.tool_call_id),
+        }
+    }
+}
+
+/// Runs the tools a model calls
+#[async_trait]
+pub trait ToolExecutor: Send + Sync {
+    /// Tool definitions offered to the model when the request sets no `tools`
+    async fn definitions(&self) -> Vec<Value> {
+        Vec::new()
+    }
+
+    /// Execute one tool call; failures are reported through [`ToolResult::error`]
+    async fn execute(&self, call: &ToolCall) -> ToolResult;
+}
+
+/// `tools` entry for a function tool
+pub fn function_tool(name: &str, description: &str, parameters: Value) -> Value {
+    serde_json::json!({
+        "type": "function",
+        "function": {
+            "name": name,
+            "description": description,
+            "parameters": parameters,
+        }
+    })
+}
+
+#[async_trait]
+impl ToolExecutor for ToolRegistry {
+    async fn definitions(&self) -> Vec<Value> {
+        self.to_openai_tools(None).await
+    }
+
+    async fn execute(&self, call: &ToolCall) -> ToolResult {
+        let payload = match serde_json::to_value(call) {
+            Ok(payload) => payload,
+            Err(e) => return ToolResult::error(call, e),
+        };
+        match self.dispatch_tool_call(&payload).await {
+            Ok(Value::String(text)) => ToolResult::success(call, text),
+            Ok(value) => ToolResult::success(call, value.to_string()),
+            Err(e) => ToolResult::error(call, e),
+        }
+    }
+}
+
+/// Result of [`OpenAIClient::chat_with_tools`]
+#[derive(Debug, Clone)]
+pub struct ToolConversation {
+    /// The final response, without tool calls
+    pub response: ChatResponse,
+    /// Every message sent and received, ending with the final answer
+    pub messages: Vec<Message>,
+    /// Number of tool-calling rounds before the final answer
+    pub rounds: usize,
+}
+
+impl OpenAIClient {
+    /// Send a chat request, executing tool calls until the model answers.
+    ///
+    /// If the request has no `tools`, the executor's
+    /// [`definitions`](ToolExecutor::definitions) are offered. The calls of one
+    /// reply run concurrently and their results are appended in call order.
+    ///
+    /// Returns [`OpenAIError::ToolRoundLimit`] if the model still calls tools
+    /// after [`MAX_TOOL_ROUNDS`] rounds.
+    pub async fn chat_with_tools(
+        &self,
+        mut request: ChatRequest,
+        executor: impl ToolExecutor,
+    ) -> Result<ToolConversation, OpenAIError> {
+        if request.tools.is_none() {
+            let definitions = executor.definitions().await;
+            if !definitions.is_empty() {
+                request.tools = Some(definitions);
+            }
+        }
+
+        for rounds in 0..=MAX_TOOL_ROUNDS {
+            let response = self.chat(request.clone()).await?;
+            let Some(message) = response.choices.first().map(|c| c.message.clone()) else {
+                return Err(OpenAIError::Deserialization(
+                    "response contained no choices".to_string(),
+                ));
+            };
+            let calls = message.tool_calls.clone().unwrap_or_default();
+            request.messages.push(message);
+            if calls.is_empty() {
+                return Ok(ToolConversation {
+                    response,
+                    messages: request.messages,
+                    rounds,
+                });
+            }
+            if rounds == MAX_TOOL_ROUNDS {
+                break;
+            }
+
+            debug!(
+                round = rounds + 1,
+                calls = calls.len(),
+                "Executing tool calls"
+            );
+            request
+                .messages
+                .extend(execute_tool_calls(&executor, &calls).await);
+        }
+
+        Err(OpenAIError::ToolRoundLimit(MAX_TOOL_ROUNDS))
+    }
+}
+
+/// Runs the calls of one reply concurrently, returning their [`Role::Tool`]
+/// messages in call order
+pub(crate) async fn execute_tool_calls(
+    executor: &(impl ToolExecutor + ?Sized),
+    calls: &[ToolCall],
+) -> Vec<Message> {
+    let results = join_all(calls.iter().map(|call| executor.execute(call))).await;
+    results
+        .into_iter()
+        .map(|result| {
+            if result.is_error {
+                warn!(
+                    "Tool call {} failed: {}",
+                    result.tool_call_id, result.content
+                );
+            }
+            result.into_message()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{completion_message, mock_client, user_request};
+    use hqe_protocol::models::MCPToolDefinition;
+
+    fn request() -> ChatRequest {
+        user_request("test-model", "What is 2 + 3?")
+    }
+
+    async fn registry() -> anyhow::Result<ToolRegistry> {
+        let registry = ToolRegistry::new();
+        registry
+            .register_tool(
+                "math",
+                MCPToolDefinition {
+                    name: "add".to_string(),
+                    description: "Add two numbers".to_string(),
+                    input_schema: serde_json::json!({
+                        "type": "object",
+                        "properties": {"a": {"type": "number"}, "b": {"type": "number"}},
+                        "required": ["a", "b"]
+                    }),
+                },
+                Box::new(|args| {
+                    Box::pin(async move {
+                        let sum = args["a"].as_f64().unwrap_or_default()
+                            + args["b"].as_f64().unwrap_or_default();
+                        Ok(serde_json::json!(sum))
+                    })
+                }),
+            )
+            .await?;
+        Ok(registry)
+    }
+
+    #[test]
+    fn test_tool_call_accepts_object_arguments() -> anyhow::Result<()> {
+        let call: ToolCall = serde_json::from_value(serde_json::json!({
+            "function": {"name": "add", "arguments": {"a": 1, "b": 2}}
+        }))?;
+        assert_eq!(call.call_type, "function");
+        assert_eq!(call.arguments()?, serde_json::json!({"a": 1, "b": 2}));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_tools_runs_registry_tools() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let final_answer = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::Regex(
+                r#""content":"Error: Tool not found: subtract","role":"tool","tool_call_id":"call_2""#
+                    .to_string(),
+            ))
+            .with_status(200)
+            .with_body(completion_message(serde_json::json!({
+                "role": "assistant", "content": "2 + 3 = 5"
+            })))
+            .expect(1)
+            .create_async()
+            .await;
+        let tool_round = server
+            .mock("POST", "/v1/chat/completions")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "tools": [{"type": "function", "function": {"name": "math__add"}}]
+            })))
+            .with_status(200)
+            .with_body(completion_message(serde_json::json!({
+                "role": "assistant",
+                "content": null,
+                "tool_calls": [
+                    {"id": "call_1", "type": "function", "function": {"name": "math__add", "arguments": "{\"a\": 2, \"b\": 3}"}},
+                    {"id": "call_2", "type": "function", "function": {"name": "subtract", "arguments": "{}"}}
+                ]
+            })))
+            .expect(1)
+            .create_async()
+            .await;
+
+        let conversation = mock_client(&server)?
+            .chat_with_tools(request(), registry().await?)
+            .await?;
+
+        assert_eq!(conversation.rounds, 1);
+        let roles: Vec<String> = conversation
+            .messages
+            .iter()
+            .map(|m| serde_json::to_value(&m.role).map(|r| r.to_string()))
+            .collect::<Result<_, _>>()?;
+        assert_eq!(
+            roles,
+            [
+                "\"user\"",
+                "\"assistant\"",
+                "\"tool\"",
+                "\"tool\"",
+                "\"assistant\""
+            ]
+        );
+        let sum = conversation.messages[2]
+            .content
+            .as_ref()
+            .and_then(|c| c.to_text_lossy());
+        assert_eq!(sum.as_deref(), Some("5.0"));
+        assert_eq!(
+            conversation.messages[2].tool_call_id.as_deref(),
+            Some("call_1")
+        );
+        tool_round.assert_async().await;
+        final_answer.assert_async().await;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_chat_with_tools_stops_after_round_limit() -> anyhow::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/chat/completions")
+            .with_status(200)
+            .with_body(completion_message(serde_json::json!({
+                "role": "assistant",
+                "tool_calls": [{"id": "call_1", "type": "function", "function": {"name": "math__add", "arguments": "{\"a\": 1, \"b\": 1}"}}]
+            })))
+            .expect(MAX_TOOL_ROUNDS + 1)
+            .create_async()
+            .await;
+
+        let result = mock_client(&server)?
+            .chat_with_tools(request(), registry().await?)
+            .await;
+
+        assert!(matches!(result, Err(OpenAIError::ToolRoundLimit(_))));
+        mock.assert_async().await;
+        Ok(())
+    }
+}
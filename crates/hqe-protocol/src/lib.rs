@@ -9,6 +9,7 @@
 //! - [`ProviderProfile`] - Configuration for LLM providers
 //! - [`TopicManifest`] - Definition of topic capabilities and schemas
 //! - [`TopicCapabilities`] - Tools, prompts, and workflows provided by a topic
+//! - [`VectorIndex`] - Cosine-similarity search over entity embeddings
 //!
 //! # Provider Support
 //!
@@ -47,7 +48,11 @@
 /// Protocol models module
 pub mod models;
 
+/// Entity similarity search module
+pub mod vector;
+
 pub use models::*;
+pub use vector::*;
 
 /// Initialize the protocol crate
 ///
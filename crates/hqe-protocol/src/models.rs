@@ -20,6 +20,12 @@ pub enum ProviderKind {
     Generic,
     /// Azure OpenAI provider
     Azure,
+    /// Anthropic Messages API provider (api.anthropic.com)
+    Anthropic,
+    /// Local Ollama server (port 11434)
+    Ollama,
+    /// Local LM Studio server (port 1234)
+    LmStudio,
 }
 
 impl std::fmt::Display for ProviderKind {
@@ -31,6 +37,9 @@ impl std::fmt::Display for ProviderKind {
             ProviderKind::XAI => write!(f, "xai"),
             ProviderKind::Generic => write!(f, "generic"),
             ProviderKind::Azure => write!(f, "azure"),
+            ProviderKind::Anthropic => write!(f, "anthropic"),
+            ProviderKind::Ollama => write!(f, "ollama"),
+            ProviderKind::LmStudio => write!(f, "lmstudio"),
         }
     }
 }
@@ -123,6 +132,31 @@ impl PromptCategory {
     }
 }
 
+/// Authentication scheme for a provider
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthScheme {
+    /// Bearer token in Authorization header (standard OpenAI)
+    Bearer,
+    /// API key in x-api-key header
+    ApiKeyHeader,
+    /// API key in query parameter
+    ApiKeyQuery,
+    /// Custom header
+    CustomHeader,
+}
+
+impl std::fmt::Display for AuthScheme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthScheme::Bearer => write!(f, "Bearer"),
+            AuthScheme::ApiKeyHeader => write!(f, "X-API-Key"),
+            AuthScheme::ApiKeyQuery => write!(f, "Query Param"),
+            AuthScheme::CustomHeader => write!(f, "Custom"),
+        }
+    }
+}
+
 /// Unified Provider Profile definition
 ///
 /// This is the single source of truth for provider configuration across all crates.
@@ -152,6 +186,27 @@ pub struct ProviderProfile {
     /// HTTP timeout in seconds
     #[serde(default = "default_timeout_s")]
     pub timeout_s: u64,
+    /// How the API key is sent (Bearer token if not specified)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auth_scheme: Option<AuthScheme>,
+    /// Azure OpenAI deployment, for `{resource}.openai.azure.com` endpoints
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub azure: Option<AzureDeployment>,
+    /// Request parameters the provider rejects (e.g. "presence_penalty")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unsupported_params: Vec<String>,
+}
+
+/// Azure OpenAI deployment addressing.
+///
+/// Azure routes requests by deployment rather than by model, e.g.
+/// `/openai/deployments/{deployment}/chat/completions?api-version={api_version}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+pub struct AzureDeployment {
+    /// Deployment name chosen when the model was deployed
+    pub deployment: String,
+    /// Value of the `api-version` query parameter (e.g. "2024-10-21")
+    pub api_version: String,
 }
 
 fn default_timeout_s() -> u64 {
@@ -172,6 +227,9 @@ impl ProviderProfile {
             project: None,
             provider_kind: None,
             timeout_s: default_timeout_s(),
+            auth_scheme: None,
+            azure: None,
+            unsupported_params: Vec::new(),
         }
     }
 
@@ -201,6 +259,25 @@ impl ProviderProfile {
         self
     }
 
+    /// Set the auth scheme
+    pub fn with_auth_scheme(mut self, scheme: AuthScheme) -> Self {
+        self.auth_scheme = Some(scheme);
+        self
+    }
+
+    /// Address an Azure OpenAI deployment
+    pub fn with_azure_deployment(
+        mut self,
+        deployment: impl Into<String>,
+        api_version: impl Into<String>,
+    ) -> Self {
+        self.azure = Some(AzureDeployment {
+            deployment: deployment.into(),
+            api_version: api_version.into(),
+        });
+        self
+    }
+
     /// Set the API key ID
     pub fn with_api_key_id(mut self, api_key_id: impl Into<String>) -> Self {
         self.api_key_id = api_key_id.into();
@@ -0,0 +1,136 @@
+//! Similarity search over entity embeddings
+
+use crate::models::Entity;
+use std::collections::HashMap;
+
+/// Cosine similarity of two vectors, in `[-1, 1]`.
+///
+/// `None` when the lengths differ or either vector has zero magnitude.
+pub fn cosine_similarity(a: &[f32], b: &[f32]) -> Option<f32> {
+    if a.len() != b.len() {
+        return None;
+    }
+    let (mut dot, mut norm_a, mut norm_b) = (0.0f32, 0.0f32, 0.0f32);
+    for (x, y) in a.iter().zip(b) {
+        dot += x * y;
+        norm_a += x * x;
+        norm_b += y * y;
+    }
+    if norm_a == 0.0 || norm_b == 0.0 {
+        return None;
+    }
+    Some(dot / (norm_a.sqrt() * norm_b.sqrt()))
+}
+
+/// An index of entities searchable by their [`Entity::vector_embedding`]
+pub trait VectorIndex {
+    /// Insert an entity, replacing any entity with the same ID.
+    ///
+    /// An entity without an embedding cannot be searched; it is not stored
+    /// and removes an earlier version of itself.
+    fn upsert(&mut self, entity: Entity);
+
+    /// Remove an entity by ID
+    fn remove(&mut self, id: &str) -> Option<Entity>;
+
+    /// The `top_k` entities most similar to `embedding` by cosine
+    /// similarity, best first. Entities whose embedding has a different
+    /// length, or no magnitude, are never returned.
+    fn query(&self, embedding: &[f32], top_k: usize) -> Vec<(Entity, f32)>;
+}
+
+/// Brute-force [`VectorIndex`] held in memory; every query scores every entity
+#[derive(Debug, Clone, Default)]
+pub struct InMemoryVectorIndex {
+    entities: HashMap<String, Entity>,
+}
+
+impl InMemoryVectorIndex {
+    /// Create an empty index
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of indexed entities
+    pub fn len(&self) -> usize {
+        self.entities.len()
+    }
+
+    /// Whether the index holds no entities
+    pub fn is_empty(&self) -> bool {
+        self.entities.is_empty()
+    }
+}
+
+impl VectorIndex for InMemoryVectorIndex {
+    fn upsert(&mut self, entity: Entity) {
+        if entity.vector_embedding.is_some() {
+            self.entities.insert(entity.id.clone(), entity);
+        } else {
+            self.entities.remove(&entity.id);
+        }
+    }
+
+    fn remove(&mut self, id: &str) -> Option<Entity> {
+        self.entities.remove(id)
+    }
+
+    fn query(&self, embedding: &[f32], top_k: usize) -> Vec<(Entity, f32)> {
+        let mut scored: Vec<(&Entity, f32)> = self
+            .entities
+            .values()
+            .filter_map(|entity| {
+                let score = cosine_similarity(entity.vector_embedding.as_deref()?, embedding)?;
+                Some((entity, score))
+            })
+            .collect();
+        // Ties in ID order so results do not depend on hash order
+        scored.sort_by(|(a, x), (b, y)| y.total_cmp(x).then_with(|| a.id.cmp(&b.id)));
+        scored
+            .into_iter()
+            .take(top_k)
+            .map(|(entity, score)| (entity.clone(), score))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn entity(id: &str, embedding: Option<Vec<f32>>) -> Entity {
+        Entity {
+            id: id.to_string(),
+            topic_id: "code_audit".to_string(),
+            kind: "Note".to_string(),
+            data: json!({}),
+            vector_embedding: embedding,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_nearest_entity_is_returned_first() {
+        let mut index = InMemoryVectorIndex::new();
+        index.upsert(entity("east", Some(vec![1.0, 0.0])));
+        index.upsert(entity("north", Some(vec![0.0, 1.0])));
+        index.upsert(entity("north-east", Some(vec![0.7, 0.7])));
+        index.upsert(entity("unembedded", None));
+        index.upsert(entity("other-model", Some(vec![1.0, 0.0, 0.0])));
+        assert_eq!(index.len(), 4);
+
+        let hits = index.query(&[0.9, 0.1], 3);
+        let ids: Vec<&str> = hits.iter().map(|(e, _)| e.id.as_str()).collect();
+        assert_eq!(ids, ["east", "north-east", "north"]);
+        assert!(hits[0].1 > 0.99 && hits[0].1 <= 1.0);
+
+        assert_eq!(index.query(&[0.9, 0.1], 1).len(), 1);
+        assert!(index.query(&[0.0, 0.0], 3).is_empty());
+
+        // Losing the embedding takes the entity out of the index
+        index.upsert(entity("east", None));
+        assert_eq!(index.query(&[0.9, 0.1], 1)[0].0.id, "north-east");
+    }
+}
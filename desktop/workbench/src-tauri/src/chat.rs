@@ -708,7 +708,6 @@ pub async fn apply_provider_spec(
 ) -> Result<serde_json::Value, String> {
     use hqe_openai::prefilled::get_spec;
     use hqe_openai::profile::ProfileManager;
-    use hqe_protocol::models::ProviderProfile;
 
     info!(spec_id = %spec_id, "Applying provider spec");
 
@@ -716,17 +715,7 @@ pub async fn apply_provider_spec(
 
     let profile_name = profile_name.unwrap_or_else(|| spec.display_name.clone());
 
-    let profile = ProviderProfile {
-        name: profile_name.clone(),
-        base_url: spec.base_url,
-        api_key_id: format!("api_key:{}", profile_name),
-        default_model: spec.default_model,
-        headers: Some(spec.default_headers),
-        organization: None,
-        project: None,
-        provider_kind: Some(spec.kind),
-        timeout_s: spec.recommended_timeout_s,
-    };
+    let profile = spec.to_profile(profile_name.clone());
 
     let manager = ProfileManager::default();
     manager
@@ -2,6 +2,7 @@
 
 use crate::{log_and_wrap_error, AppState};
 use hqe_artifacts::ArtifactWriter;
+use hqe_core::events::ScanEvent;
 use hqe_core::models::*;
 use hqe_core::scan::ScanPipeline;
 use hqe_openai::profile::{
@@ -13,8 +14,9 @@ use hqe_openai::ProviderKindExt;
 use secrecy::SecretString;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use tauri::{command, Manager, State};
+use tauri::{command, Emitter, Manager, State};
 use tauri_plugin_dialog::DialogExt;
+use tokio_util::sync::CancellationToken;
 use url::Url;
 
 /// Select a folder using native dialog
@@ -29,7 +31,13 @@ pub async fn select_folder(app: tauri::AppHandle) -> Result<Option<String>, Stri
     Ok(path.map(|p| p.to_string()))
 }
 
+/// Window event carrying each [`ScanEvent`] of a running scan
+pub const SCAN_PROGRESS_EVENT: &str = "scan-progress";
+
 /// Scan a repository
+///
+/// Progress is emitted to the window as [`SCAN_PROGRESS_EVENT`] events while
+/// the scan runs.
 #[command]
 pub async fn scan_repo(
     app: tauri::AppHandle,
@@ -55,8 +63,10 @@ pub async fn scan_repo(
     }
 
     // Run scan
+    let cancel = CancellationToken::new();
     let mut pipeline = ScanPipeline::new(&path, config.clone())
-        .map_err(|e| log_and_wrap_error("Failed to initialize scan pipeline", e))?;
+        .map_err(|e| log_and_wrap_error("Failed to initialize scan pipeline", e))?
+        .with_cancellation(cancel.clone());
     if config.llm_enabled && !config.local_only {
         let profile_name = config
             .provider_profile
@@ -82,20 +92,37 @@ pub async fn scan_repo(
             llm_enabled: true,
         });
 
-        pipeline = pipeline.with_llm_analyzer(Arc::new(analyzer));
+        pipeline = pipeline.with_llm_analyzer(Arc::new(analyzer.with_cancellation(cancel.clone())));
+
+        match hqe_core::persistence::LocalDb::init() {
+            Ok(db) => pipeline = pipeline.with_analysis_cache(db),
+            Err(e) => tracing::warn!("File analysis cache unavailable: {}", e),
+        }
     }
 
-    let result = pipeline
-        .run()
-        .await
-        .map_err(|e| log_and_wrap_error("Scan failed", e))?;
+    // Forward progress to the window as `scan-progress` events
+    let (tx, mut rx) = tokio::sync::mpsc::channel::<ScanEvent>(64);
+    let emitter = app.clone();
+    let progress = tokio::spawn(async move {
+        while let Some(event) = rx.recv().await {
+            if let Err(e) = emitter.emit(SCAN_PROGRESS_EVENT, &event) {
+                tracing::warn!("Failed to emit scan progress: {}", e);
+            }
+        }
+    });
+
+    *state.scan_cancel.lock().await = Some(cancel);
+    let result = pipeline.run_with_events(tx).await;
+    *state.scan_cancel.lock().await = None;
+    let _ = progress.await;
+    let result = result.map_err(|e| log_and_wrap_error("Scan failed", e))?;
 
     let output_root = get_output_root(&app)?;
     std::fs::create_dir_all(&output_root)
         .map_err(|e| log_and_wrap_error("Failed to create output directory", e))?;
 
     let run_dir = output_root.join(format!("hqe_run_{}", result.manifest.run_id));
-    let writer = ArtifactWriter::new(&run_dir);
+    let writer = ArtifactWriter::new(&run_dir).with_html(true);
     writer
         .write_all(&result)
         .await
@@ -104,6 +131,21 @@ pub async fn scan_repo(
     Ok(result.report)
 }
 
+/// Cancel the scan in progress.
+///
+/// The running `scan_repo` call then returns a partial report. Returns `false`
+/// if no scan was running.
+#[command]
+pub async fn cancel_scan(state: State<'_, AppState>) -> Result<bool, String> {
+    match state.scan_cancel.lock().await.as_ref() {
+        Some(token) => {
+            token.cancel();
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
 /// Validate repository path to prevent security issues
 fn validate_repo_path(path: &Path) -> Result<(), String> {
     // Check for path traversal attempts
@@ -281,6 +323,20 @@ pub async fn export_artifacts(
             .map_err(|e| log_and_wrap_error("Failed to copy artifact", e))?;
     }
 
+    // Runs from before the HTML report existed get one rendered from report.json
+    let report_json = canonical_source.join("report.json");
+    if !target.join("report.html").exists() && report_json.exists() {
+        let content = tokio::fs::read_to_string(&report_json)
+            .await
+            .map_err(|e| log_and_wrap_error("Failed to read report", e))?;
+        let report: HqeReport = serde_json::from_str(&content)
+            .map_err(|e| log_and_wrap_error("Failed to parse report", e))?;
+        ArtifactWriter::new(&target)
+            .write_report_html(&report)
+            .await
+            .map_err(|e| log_and_wrap_error("Failed to write HTML report", e))?;
+    }
+
     Ok(())
 }
 
@@ -341,6 +397,8 @@ pub async fn discover_models(
                 },
             })
             .collect(),
+        etag: None,
+        last_modified: None,
     })
 }
 
@@ -485,6 +543,9 @@ pub async fn save_provider_config(
         project: profile.project,
         provider_kind: None,
         timeout_s: 60,
+        auth_scheme: None,
+        azure: None,
+        unsupported_params: Vec::new(),
     };
 
     // Store using new manager
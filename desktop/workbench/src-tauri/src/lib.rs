@@ -14,6 +14,7 @@ use secrecy::SecretString;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::Mutex;
+use tokio_util::sync::CancellationToken;
 use tracing::error;
 
 /// Log an internal error and return a generic user-facing message.
@@ -32,6 +33,8 @@ pub struct AppState {
     pub db: Arc<Mutex<EncryptedDb>>,
     /// Session-only API keys (not persisted)
     pub session_keys: Arc<Mutex<HashMap<String, SecretString>>>,
+    /// Cancels the scan in progress, if any
+    pub scan_cancel: Arc<Mutex<Option<CancellationToken>>>,
 }
 
 /// Run the Tauri application
@@ -48,10 +51,12 @@ pub fn run() {
             current_repo: Arc::new(Mutex::new(None)),
             db: Arc::new(Mutex::new(db)),
             session_keys: Arc::new(Mutex::new(HashMap::new())),
+            scan_cancel: Arc::new(Mutex::new(None)),
         })
         .invoke_handler(tauri::generate_handler![
             select_folder,
             scan_repo,
+            cancel_scan,
             get_repo_info,
             load_report,
             export_artifacts,
@@ -60,11 +60,18 @@ pub async fn run_llm(
         organization: profile.organization.clone(),
         project: profile.project.clone(),
         disable_system_proxy: false,
+        proxy: None,
         timeout_seconds: profile.timeout_s,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
         max_retries: 1,
         rate_limit_config: None,
         cache_enabled: true,
         daily_budget: 1.0,
+        provider_kind: profile.provider_kind,
+        auth: profile.auth_placement(),
+        azure: profile.azure.clone(),
+        unsupported_params: profile.unsupported_params.clone(),
     };
 
     let client = OpenAIClient::new(config).map_err(|e| {
@@ -74,40 +81,16 @@ pub async fn run_llm(
 
     let response = client
         .chat(ChatRequest {
-            model,
-            messages: vec![Message {
-                role: Role::User,
-                content: Some(MessageContent::Text(prompt)),
-                tool_calls: None,
-            }],
-            frequency_penalty: None,
-            presence_penalty: None,
-            repetition_penalty: None,
-            logprobs: None,
-            top_logprobs: None,
             temperature: Some(0.2),
-            min_temp: None,
-            max_temp: None,
-            top_p: None,
-            top_k: None,
-            max_tokens: None,
-            max_completion_tokens: None,
-            n: None,
-            stop: None,
-            stop_token_ids: None,
-            seed: None,
-            user: None,
-            prompt_cache_key: None,
-            prompt_cache_retention: None,
-            reasoning_effort: None,
-            reasoning: None,
-            stream: None,
-            stream_options: None,
-            tool_choice: None,
-            tools: None,
-            venice_parameters: None,
-            parallel_tool_calls: None,
-            response_format: None,
+            ..ChatRequest::new(
+                model,
+                vec![Message {
+                    role: Role::User,
+                    content: Some(MessageContent::Text(prompt)),
+                    tool_calls: None,
+                    tool_call_id: None,
+                }],
+            )
         })
         .await
         .map_err(|e| {
@@ -161,11 +144,18 @@ pub async fn test_connection(
         organization: profile.organization.clone(),
         project: profile.project.clone(),
         disable_system_proxy: false,
+        proxy: None,
         timeout_seconds: profile.timeout_s,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
         max_retries: 1,
         rate_limit_config: None,
         cache_enabled: true,
         daily_budget: 1.0,
+        provider_kind: profile.provider_kind,
+        auth: profile.auth_placement(),
+        azure: profile.azure.clone(),
+        unsupported_params: profile.unsupported_params.clone(),
     };
 
     let client = OpenAIClient::new(config).map_err(|e| {
@@ -184,10 +174,6 @@ pub async fn discover_models(
 ) -> Result<Vec<ModelInfo>, String> {
     let (profile, api_key) = resolve_profile(Some(profile_name.to_string()), session_key)?;
     let allow_missing_key = is_local_or_private_base_url(&profile.base_url).unwrap_or(false);
-    if api_key.is_none() && !allow_missing_key {
-        return Err("No API key stored for profile".to_string());
-    }
-    let allow_missing_key = is_local_or_private_base_url(&profile.base_url).unwrap_or(false);
     let api_key = match api_key {
         Some(key) => Some(key),
         None if allow_missing_key => None,
@@ -257,17 +243,28 @@ pub fn build_scan_analyzer(
         organization: profile.organization.clone(),
         project: profile.project.clone(),
         disable_system_proxy: false,
+        proxy: None,
         timeout_seconds: profile.timeout_s,
+        connect_timeout_secs: None,
+        read_timeout_secs: None,
         max_retries: 1,
         rate_limit_config: None,
         cache_enabled: true,
         daily_budget: 1.0,
+        provider_kind: profile.provider_kind,
+        auth: profile.auth_placement(),
+        azure: profile.azure.clone(),
+        unsupported_params: profile.unsupported_params.clone(),
     };
 
-    let client = OpenAIClient::new(config).map_err(|e| {
-        error!(error = %e, "Failed to create OpenAI client");
-        "Failed to initialize AI client".to_string()
-    })?;
+    let client = OpenAIClient::new(config)
+        .map_err(|e| {
+            error!(error = %e, "Failed to create OpenAI client");
+            "Failed to initialize AI client".to_string()
+        })?
+        .with_cost_ledger(hqe_openai::CostLedger::new(
+            hqe_openai::PricingTable::load_default(),
+        ));
     let analyzer = hqe_openai::OpenAIAnalyzer::new(client)
         .with_venice_parameters(venice_parameters)
         .with_parallel_tool_calls(parallel_tool_calls);
@@ -1,10 +1,18 @@
 import { useEffect, useState } from 'react'
 import { useNavigate } from 'react-router-dom'
 import { invoke } from '@tauri-apps/api/core'
+import { listen } from '@tauri-apps/api/event'
 import { useRepoStore, useScanStore, useReportStore } from '../store'
-import { HqeReport, ProviderProfile } from '../types'
+import { HqeReport, ProviderProfile, ScanProgressEvent } from '../types'
 import { useToast } from '../context/ToastContext'
 
+/** One glob per line or comma-separated */
+const parseGlobs = (raw: string) =>
+  raw
+    .split(/[\n,]/)
+    .map((glob) => glob.trim())
+    .filter((glob) => glob !== '')
+
 export function ScanScreen() {
   const navigate = useNavigate()
   const { path, name } = useRepoStore()
@@ -14,11 +22,14 @@ export function ScanScreen() {
 
   const [localOnly, setLocalOnly] = useState(true)
   const [maxFiles, setMaxFiles] = useState(40)
+  const [includeGlobs, setIncludeGlobs] = useState('')
+  const [excludeGlobs, setExcludeGlobs] = useState('')
   const [profiles, setProfiles] = useState<ProviderProfile[]>([])
   const [selectedProfile, setSelectedProfile] = useState<string>('')
   const [loadingProfiles, setLoadingProfiles] = useState(false)
   const [veniceParameters, setVeniceParameters] = useState('')
   const [parallelToolCalls, setParallelToolCalls] = useState<'default' | 'true' | 'false'>('default')
+  const [scanLog, setScanLog] = useState<Array<{ text: string; warning: boolean }>>([])
 
   const selectedProfileInfo = profiles.find((p) => p.name === selectedProfile)
   const isVeniceProfile = Boolean(
@@ -51,10 +62,37 @@ export function ScanScreen() {
 
     setScanning(true)
     reset()
+    setScanLog([])
+
+    const queued = new Set<string>()
+    const analyzed = new Set<string>()
+    const log = (text: string, warning = false) =>
+      setScanLog((lines) => [...lines.slice(-199), { text, warning }])
+    const unlisten = await listen<ScanProgressEvent>('scan-progress', ({ payload }) => {
+      switch (payload.event) {
+        case 'phase_started':
+          setPhase(`${payload.phase.replace(/_/g, ' ')}...`)
+          log(`▸ ${payload.phase}`)
+          break
+        case 'file_queued':
+          queued.add(payload.path)
+          break
+        case 'file_analyzed':
+          analyzed.add(payload.path)
+          setProgress(Math.round((analyzed.size / Math.max(queued.size, 1)) * 100))
+          log(`  ${payload.path}: ${payload.findings} finding(s)${payload.cached ? ' (cached)' : ''}`)
+          break
+        case 'warning':
+          log(`⚠ ${payload.message}`, true)
+          break
+        default:
+          break
+      }
+    })
 
     try {
       setPhase('ingesting repository...')
-      setProgress(10)
+      setProgress(0)
 
       let veniceParamsValue: Record<string, unknown> | null = null
       if (!localOnly && isVeniceProfile && veniceParameters.trim() !== '') {
@@ -80,6 +118,8 @@ export function ScanScreen() {
           max_files_sent: maxFiles,
           max_total_chars_sent: 250000,
           snippet_chars: 4000,
+          include_globs: parseGlobs(includeGlobs),
+          exclude_globs: parseGlobs(excludeGlobs),
         },
         local_only: localOnly,
         timeout_seconds: 120,
@@ -90,17 +130,11 @@ export function ScanScreen() {
             : null,
       }
 
-      setPhase('analyzing code with local heuristics...')
-      setProgress(40)
-
       const report = await invoke<HqeReport>('scan_repo', {
         repo_path: path,
         config,
       })
 
-      setPhase('generating artifacts...')
-      setProgress(80)
-
       setReport(report)
 
       setProgress(100)
@@ -113,6 +147,18 @@ export function ScanScreen() {
       console.error('Scan failed:', error)
       toast.error('Scan failed. Please check logs for details.')
       setScanning(false)
+    } finally {
+      unlisten()
+    }
+  }
+
+  const handleCancel = async () => {
+    try {
+      setPhase('cancelling scan...')
+      await invoke<boolean>('cancel_scan')
+    } catch (error) {
+      console.error('Failed to cancel scan:', error)
+      toast.error('Failed to cancel scan')
     }
   }
 
@@ -223,6 +269,39 @@ export function ScanScreen() {
               />
             </div>
 
+            {/* Path Filters */}
+            <div className="p-3 space-y-3" style={{ background: 'var(--dracula-bg)' }}>
+              <div>
+                <label className="text-terminal-cyan font-mono text-sm block mb-1">
+                  --include
+                </label>
+                <input
+                  type="text"
+                  value={includeGlobs}
+                  onChange={(e) => setIncludeGlobs(e.target.value)}
+                  placeholder="src/**, *.toml"
+                  className="input text-xs"
+                  disabled={isScanning}
+                />
+              </div>
+              <div>
+                <label className="text-terminal-cyan font-mono text-sm block mb-1">
+                  --exclude
+                </label>
+                <input
+                  type="text"
+                  value={excludeGlobs}
+                  onChange={(e) => setExcludeGlobs(e.target.value)}
+                  placeholder="vendor/, **/*.min.js"
+                  className="input text-xs"
+                  disabled={isScanning}
+                />
+              </div>
+              <p className="text-xs" style={{ color: 'var(--dracula-comment)' }}>
+                Comma-separated globs. Excludes win over includes; no includes means every file.
+              </p>
+            </div>
+
             {/* Provider Profile */}
             {!localOnly && (
               <div className="p-3" style={{ background: 'var(--dracula-bg)' }}>
@@ -312,6 +391,9 @@ export function ScanScreen() {
                 </p>
               </div>
               <span className="font-mono text-sm text-terminal-green">{progress}%</span>
+              <button onClick={handleCancel} className="btn text-xs">
+                <span className="text-terminal-red">✗</span> cancel_scan
+              </button>
             </div>
 
             {/* Progress Bar */}
@@ -328,20 +410,19 @@ export function ScanScreen() {
               />
             </div>
 
-            {/* Terminal Output Lines */}
-            <div className="mt-4 font-mono text-xs space-y-1" style={{ color: 'var(--dracula-comment)' }}>
-              <div className="flex gap-2">
-                <span>[{progress >= 10 ? '✓' : ' '}]</span>
-                <span style={{ color: progress >= 10 ? 'var(--dracula-green)' : undefined }}>ingest repository</span>
-              </div>
-              <div className="flex gap-2">
-                <span>[{progress >= 40 ? '✓' : ' '}]</span>
-                <span style={{ color: progress >= 40 ? 'var(--dracula-green)' : undefined }}>local heuristics</span>
-              </div>
-              <div className="flex gap-2">
-                <span>[{progress >= 80 ? '✓' : ' '}]</span>
-                <span style={{ color: progress >= 80 ? 'var(--dracula-green)' : undefined }}>generate artifacts</span>
-              </div>
+            {/* Live Scan Log */}
+            <div
+              className="mt-4 font-mono text-xs space-y-1 max-h-48 overflow-auto"
+              style={{ color: 'var(--dracula-comment)' }}
+            >
+              {scanLog.map((line, i) => (
+                <div
+                  key={i}
+                  style={{ color: line.warning ? 'var(--dracula-orange)' : undefined }}
+                >
+                  {line.text}
+                </div>
+              ))}
             </div>
           </div>
         ) : (
@@ -66,6 +66,15 @@ export interface HqeReport {
   master_todo_backlog: TodoItem[]
 }
 
+// Payload of the `scan-progress` window event emitted by scan_repo
+export type ScanProgressEvent =
+  | { event: 'phase_started'; phase: string }
+  | { event: 'file_queued'; path: string }
+  | { event: 'file_analyzed'; path: string; findings: number; cached: boolean }
+  | { event: 'llm_call_completed'; path: string; tokens?: number | null }
+  | { event: 'phase_completed'; phase: string }
+  | { event: 'warning'; message: string }
+
 export interface ProviderProfile {
   name: string
   base_url: string
@@ -76,6 +85,14 @@ export interface ProviderProfile {
   project?: string | null
   provider_kind?: string | null
   timeout_s: number
+  auth_scheme?: string | null
+  azure?: AzureDeployment | null
+  unsupported_params?: string[]
+}
+
+export interface AzureDeployment {
+  deployment: string
+  api_version: string
 }
 
 export interface ProviderModel {
@@ -90,6 +107,9 @@ export interface ProviderModel {
     supports_response_schema?: boolean
     supports_logprobs?: boolean
     code_optimized?: boolean
+    supports_json_mode?: boolean
+    context_window?: number
+    max_output_tokens?: number
   }
 }
 